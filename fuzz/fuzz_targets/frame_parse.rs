@@ -0,0 +1,20 @@
+#![no_main]
+
+use std::io::Cursor;
+
+use libfuzzer_sys::fuzz_target;
+use redis_starter_rust::Frame;
+
+// Any panic while checking, parsing or encoding a frame is a bug:
+// the bytes come straight from the clients and the master
+fuzz_target!(|data: &[u8]| {
+    let mut src = Cursor::new(data);
+    if Frame::check(&mut src).is_err() {
+        return;
+    }
+
+    src.set_position(0);
+    if let Ok(frame) = Frame::parse(&mut src) {
+        frame.encode();
+    }
+});
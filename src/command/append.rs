@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug, Default)]
+pub struct Append {
+    key: String,
+    value: Bytes,
+}
+
+impl Append {
+    pub fn new(key: impl ToString, value: Bytes) -> Append {
+        Append {
+            key: key.to_string(),
+            value,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.append(self.key.clone(), self.value.clone()) {
+            Ok(len) => Frame::Integer(len as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Append> {
+        let key = frames.next_string()?;
+        let value = frames.next_bytes()?;
+
+        Ok(Append::new(key, value))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk("APPEND".into()),
+            Frame::Bulk(self.key.clone().into()),
+            Frame::Bulk(self.value.clone()),
+        ])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Append {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Append::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn missing_key_is_created() {
+        let db = Db::new();
+
+        let reply = Append::new("key", Bytes::from("hello")).execute(&db);
+
+        assert_eq!(reply, Frame::Integer(5));
+        assert_eq!(db.getrange("key", 0, -1).unwrap(), Bytes::from("hello"));
+    }
+
+    #[tokio::test]
+    async fn value_is_appended() {
+        let db = Db::new();
+        db.set("key".to_string(), Bytes::from("hello"), None);
+
+        let reply = Append::new("key", Bytes::from(" world")).execute(&db);
+
+        assert_eq!(reply, Frame::Integer(11));
+        assert_eq!(
+            db.getrange("key", 0, -1).unwrap(),
+            Bytes::from("hello world")
+        );
+    }
+
+    #[tokio::test]
+    async fn wrong_type_is_rejected() {
+        let db = Db::new();
+        db.sadd("set".to_string(), vec![Bytes::from("a")]).unwrap();
+
+        assert_eq!(
+            Append::new("set", Bytes::from("a")).execute(&db),
+            Frame::Error(crate::db::WRONGTYPE_ERR.to_string())
+        );
+    }
+}
@@ -0,0 +1,362 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// Integer type of a bit field, like `u8` or `i16`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BitFieldType {
+    pub signed: bool,
+    pub bits: u8,
+}
+
+impl BitFieldType {
+    /// Parse the type, signed fields can be up to 64 bits wide
+    /// and unsigned ones up to 63 bits, so any value fits in an i64
+    pub fn parse(ty: &str) -> crate::Result<BitFieldType> {
+        const ERROR_MSG: &str = "ERR Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is.";
+
+        let signed = match ty.chars().next() {
+            Some('i') | Some('I') => true,
+            Some('u') | Some('U') => false,
+            _ => return Err(ERROR_MSG.into()),
+        };
+        let bits = ty[1..].parse::<u8>().map_err(|_| ERROR_MSG)?;
+
+        match (signed, bits) {
+            (true, 1..=64) | (false, 1..=63) => Ok(BitFieldType { signed, bits }),
+            _ => Err(ERROR_MSG.into()),
+        }
+    }
+
+    /// Range of the values that fit in the field
+    pub fn bounds(&self) -> (i128, i128) {
+        if self.signed {
+            (-(1i128 << (self.bits - 1)), (1i128 << (self.bits - 1)) - 1)
+        } else {
+            (0, (1i128 << self.bits) - 1)
+        }
+    }
+}
+
+impl std::fmt::Display for BitFieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sign = if self.signed { 'i' } else { 'u' };
+        write!(f, "{}{}", sign, self.bits)
+    }
+}
+
+/// Behavior of SET and INCRBY when the value does not fit in the field
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum BitFieldOverflow {
+    /// Wrap around, like the C integer arithmetic
+    #[default]
+    Wrap,
+    /// Saturate to the min or max value of the field
+    Sat,
+    /// Do nothing and return null
+    Fail,
+}
+
+impl BitFieldOverflow {
+    /// Fit the value in the field bounds according to the overflow behavior
+    /// Returns `None` if the value does not fit and the behavior is FAIL
+    pub fn apply(&self, ty: BitFieldType, value: i128) -> Option<i64> {
+        let (min, max) = ty.bounds();
+
+        if (min..=max).contains(&value) {
+            return Some(value as i64);
+        }
+
+        match self {
+            BitFieldOverflow::Wrap => {
+                let modulo = 1i128 << ty.bits;
+                let wrapped = value.rem_euclid(modulo);
+
+                if ty.signed && wrapped > max {
+                    Some((wrapped - modulo) as i64)
+                } else {
+                    Some(wrapped as i64)
+                }
+            }
+            BitFieldOverflow::Sat => Some(value.clamp(min, max) as i64),
+            BitFieldOverflow::Fail => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum BitFieldOp {
+    /// GET \<type\> \<offset\>
+    Get(BitFieldType, u64),
+    /// SET \<type\> \<offset\> \<value\>
+    Set(BitFieldType, u64, i64),
+    /// INCRBY \<type\> \<offset\> \<increment\>
+    IncrBy(BitFieldType, u64, i64),
+    /// OVERFLOW WRAP|SAT|FAIL
+    Overflow(BitFieldOverflow),
+}
+
+#[derive(Debug)]
+pub struct BitField {
+    key: String,
+    ops: Vec<BitFieldOp>,
+}
+
+impl BitField {
+    pub fn new(key: String, ops: Vec<BitFieldOp>) -> BitField {
+        BitField { key, ops }
+    }
+
+    /// Returns true if the command modifies the value, so it has to be propagated
+    pub fn is_write(&self) -> bool {
+        self.ops
+            .iter()
+            .any(|op| matches!(op, BitFieldOp::Set(..) | BitFieldOp::IncrBy(..)))
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        let mut overflow = BitFieldOverflow::default();
+        let mut results = Vec::new();
+
+        for op in &self.ops {
+            let result = match op {
+                BitFieldOp::Get(ty, offset) => db.bitfield_get(&self.key, *ty, *offset).map(Some),
+                BitFieldOp::Set(ty, offset, value) => {
+                    db.bitfield_set(&self.key, *ty, *offset, *value, overflow)
+                }
+                BitFieldOp::IncrBy(ty, offset, increment) => {
+                    db.bitfield_incrby(&self.key, *ty, *offset, *increment, overflow)
+                }
+                BitFieldOp::Overflow(behavior) => {
+                    overflow = *behavior;
+                    continue;
+                }
+            };
+
+            match result {
+                Ok(Some(value)) => results.push(Frame::Integer(value)),
+                Ok(None) => results.push(Frame::Null),
+                Err(err) => return Frame::Error(err.to_string()),
+            }
+        }
+
+        Frame::Array(results)
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<BitField> {
+        let key = frames.next_string()?;
+        let mut ops = Vec::new();
+
+        while let Ok(op) = frames.next_string() {
+            let op = match op.to_uppercase().as_str() {
+                "GET" => {
+                    let ty = BitFieldType::parse(&frames.next_string()?)?;
+                    let offset = BitField::parse_offset(&frames.next_string()?, ty)?;
+                    BitFieldOp::Get(ty, offset)
+                }
+                "SET" => {
+                    let ty = BitFieldType::parse(&frames.next_string()?)?;
+                    let offset = BitField::parse_offset(&frames.next_string()?, ty)?;
+                    BitFieldOp::Set(ty, offset, frames.next_int()?)
+                }
+                "INCRBY" => {
+                    let ty = BitFieldType::parse(&frames.next_string()?)?;
+                    let offset = BitField::parse_offset(&frames.next_string()?, ty)?;
+                    BitFieldOp::IncrBy(ty, offset, frames.next_int()?)
+                }
+                "OVERFLOW" => {
+                    let behavior = match frames.next_string()?.to_uppercase().as_str() {
+                        "WRAP" => BitFieldOverflow::Wrap,
+                        "SAT" => BitFieldOverflow::Sat,
+                        "FAIL" => BitFieldOverflow::Fail,
+                        _ => return Err("ERR Invalid OVERFLOW type specified".into()),
+                    };
+                    BitFieldOp::Overflow(behavior)
+                }
+                _ => return Err("ERR syntax error".into()),
+            };
+
+            ops.push(op);
+        }
+
+        Ok(BitField::new(key, ops))
+    }
+
+    /// Parse the bit offset, prefixed with `#` it is multiplied by the type width
+    fn parse_offset(offset: &str, ty: BitFieldType) -> crate::Result<u64> {
+        const ERROR_MSG: &str = "ERR bit offset is not an integer or out of range";
+        // The value is limited to 512MB, like in Redis
+        const MAX_BITS: u64 = 512 * 1024 * 1024 * 8;
+
+        let offset = match offset.strip_prefix('#') {
+            Some(index) => index
+                .parse::<u64>()
+                .ok()
+                .and_then(|index| index.checked_mul(ty.bits as u64)),
+            None => offset.parse::<u64>().ok(),
+        };
+
+        match offset {
+            Some(offset) if offset + ty.bits as u64 <= MAX_BITS => Ok(offset),
+            _ => Err(ERROR_MSG.into()),
+        }
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("BITFIELD".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ];
+
+        for op in &self.ops {
+            let args = match op {
+                BitFieldOp::Get(ty, offset) => {
+                    vec!["GET".into(), ty.to_string(), offset.to_string()]
+                }
+                BitFieldOp::Set(ty, offset, value) => vec![
+                    "SET".into(),
+                    ty.to_string(),
+                    offset.to_string(),
+                    value.to_string(),
+                ],
+                BitFieldOp::IncrBy(ty, offset, increment) => vec![
+                    "INCRBY".into(),
+                    ty.to_string(),
+                    offset.to_string(),
+                    increment.to_string(),
+                ],
+                BitFieldOp::Overflow(behavior) => {
+                    let behavior = match behavior {
+                        BitFieldOverflow::Wrap => "WRAP",
+                        BitFieldOverflow::Sat => "SAT",
+                        BitFieldOverflow::Fail => "FAIL",
+                    };
+                    vec!["OVERFLOW".into(), behavior.into()]
+                }
+            };
+
+            frames.extend(args.into_iter().map(|arg| Frame::Bulk(arg.into())));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for BitField {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(BitField::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    const U8: BitFieldType = BitFieldType {
+        signed: false,
+        bits: 8,
+    };
+
+    fn bitfield(db: &Db, ops: Vec<BitFieldOp>) -> Frame {
+        BitField::new("key".to_string(), ops).execute(db)
+    }
+
+    fn integers(values: &[i64]) -> Frame {
+        Frame::Array(values.iter().copied().map(Frame::Integer).collect())
+    }
+
+    #[tokio::test]
+    async fn get_set_and_incrby_u8() {
+        let db = Db::new();
+
+        assert_eq!(bitfield(&db, vec![BitFieldOp::Get(U8, 0)]), integers(&[0]));
+        // SET replies with the previous value
+        assert_eq!(
+            bitfield(&db, vec![BitFieldOp::Set(U8, 0, 255)]),
+            integers(&[0])
+        );
+        assert_eq!(
+            bitfield(&db, vec![BitFieldOp::Get(U8, 0)]),
+            integers(&[255])
+        );
+        assert_eq!(
+            db.getrange("key", 0, -1).unwrap(),
+            Bytes::from_static(b"\xff")
+        );
+
+        // The increment wraps around by default
+        assert_eq!(
+            bitfield(&db, vec![BitFieldOp::IncrBy(U8, 0, 10)]),
+            integers(&[9])
+        );
+    }
+
+    #[tokio::test]
+    async fn incrby_u8_overflow_behaviors() {
+        let db = Db::new();
+        bitfield(&db, vec![BitFieldOp::Set(U8, 0, 250)]);
+
+        assert_eq!(
+            bitfield(
+                &db,
+                vec![
+                    BitFieldOp::Overflow(BitFieldOverflow::Sat),
+                    BitFieldOp::IncrBy(U8, 0, 10),
+                ]
+            ),
+            integers(&[255])
+        );
+        assert_eq!(
+            bitfield(
+                &db,
+                vec![
+                    BitFieldOp::Overflow(BitFieldOverflow::Fail),
+                    BitFieldOp::IncrBy(U8, 0, 1),
+                ]
+            ),
+            Frame::Array(vec![Frame::Null])
+        );
+        assert_eq!(
+            bitfield(&db, vec![BitFieldOp::Get(U8, 0)]),
+            integers(&[255])
+        );
+    }
+
+    #[tokio::test]
+    async fn unaligned_offset_spans_two_bytes() {
+        let db = Db::new();
+
+        bitfield(&db, vec![BitFieldOp::Set(U8, 4, 255)]);
+
+        assert_eq!(
+            db.getrange("key", 0, -1).unwrap(),
+            Bytes::from_static(b"\x0f\xf0")
+        );
+        assert_eq!(
+            bitfield(&db, vec![BitFieldOp::Get(U8, 4)]),
+            integers(&[255])
+        );
+    }
+}
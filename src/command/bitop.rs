@@ -0,0 +1,208 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// Bitwise operation of BITOP
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum BitOperation {
+    #[default]
+    And,
+    Or,
+    Xor,
+    Not,
+}
+
+impl BitOperation {
+    pub fn parse(op: &str) -> crate::Result<BitOperation> {
+        match op.to_uppercase().as_str() {
+            "AND" => Ok(BitOperation::And),
+            "OR" => Ok(BitOperation::Or),
+            "XOR" => Ok(BitOperation::Xor),
+            "NOT" => Ok(BitOperation::Not),
+            _ => Err("ERR syntax error".into()),
+        }
+    }
+
+    /// Applies the operation to the values, shorter values are zero padded
+    pub fn apply(&self, values: &[Bytes]) -> Vec<u8> {
+        let len = values.iter().map(|value| value.len()).max().unwrap_or(0);
+        let byte = |value: &Bytes, i: usize| value.get(i).copied().unwrap_or(0);
+
+        (0..len)
+            .map(|i| {
+                let mut bytes = values.iter().map(|value| byte(value, i));
+                let first = bytes.next().unwrap_or(0);
+
+                match self {
+                    BitOperation::And => bytes.fold(first, |acc, b| acc & b),
+                    BitOperation::Or => bytes.fold(first, |acc, b| acc | b),
+                    BitOperation::Xor => bytes.fold(first, |acc, b| acc ^ b),
+                    BitOperation::Not => !first,
+                }
+            })
+            .collect()
+    }
+}
+
+impl std::fmt::Display for BitOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BitOperation::And => write!(f, "AND"),
+            BitOperation::Or => write!(f, "OR"),
+            BitOperation::Xor => write!(f, "XOR"),
+            BitOperation::Not => write!(f, "NOT"),
+        }
+    }
+}
+
+/// BITOP AND | OR | XOR | NOT destkey key [key ...]
+#[derive(Debug, Default)]
+pub struct BitOp {
+    op: BitOperation,
+    dest: String,
+    keys: Vec<String>,
+}
+
+impl BitOp {
+    pub fn new(op: BitOperation, dest: impl ToString, keys: Vec<String>) -> BitOp {
+        BitOp {
+            op,
+            dest: dest.to_string(),
+            keys,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.bitop(self.op, self.dest.clone(), &self.keys) {
+            Ok(len) => Frame::Integer(len as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<BitOp> {
+        let op = BitOperation::parse(&frames.next_string()?)?;
+        let dest = frames.next_string()?;
+
+        let mut keys = vec![frames.next_string()?];
+        while let Ok(key) = frames.next_string() {
+            keys.push(key);
+        }
+
+        if op == BitOperation::Not && keys.len() != 1 {
+            return Err("ERR BITOP NOT must be called with a single source key.".into());
+        }
+
+        Ok(BitOp::new(op, dest, keys))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("BITOP".into()),
+            Frame::Bulk(self.op.to_string().into()),
+            Frame::Bulk(self.dest.clone().into()),
+        ];
+
+        for key in &self.keys {
+            frames.push(Frame::Bulk(key.clone().into()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for BitOp {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(BitOp::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bitop(db: &Db, op: BitOperation, keys: &[&str]) -> Frame {
+        let keys = keys.iter().map(|key| key.to_string()).collect();
+
+        BitOp::new(op, "dest", keys).execute(db)
+    }
+
+    #[tokio::test]
+    async fn and_zero_pads_the_shorter_value() {
+        let db = Db::new();
+        db.set("a".to_string(), Bytes::from_static(b"\xff\x0f\xff"), None);
+        db.set("b".to_string(), Bytes::from_static(b"\xf0\xff"), None);
+
+        assert_eq!(
+            bitop(&db, BitOperation::And, &["a", "b"]),
+            Frame::Integer(3)
+        );
+        assert_eq!(
+            db.getrange("dest", 0, -1).unwrap(),
+            Bytes::from_static(b"\xf0\x0f\x00")
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_key_is_an_empty_string() {
+        let db = Db::new();
+        db.set("a".to_string(), Bytes::from_static(b"\x0f"), None);
+
+        assert_eq!(
+            bitop(&db, BitOperation::Or, &["a", "missing"]),
+            Frame::Integer(1)
+        );
+        assert_eq!(
+            db.getrange("dest", 0, -1).unwrap(),
+            Bytes::from_static(b"\x0f")
+        );
+    }
+
+    #[tokio::test]
+    async fn not_inverts_every_bit() {
+        let db = Db::new();
+        db.set("a".to_string(), Bytes::from_static(b"\x0f\xaa"), None);
+
+        assert_eq!(bitop(&db, BitOperation::Not, &["a"]), Frame::Integer(2));
+        assert_eq!(
+            db.getrange("dest", 0, -1).unwrap(),
+            Bytes::from_static(b"\xf0\x55")
+        );
+    }
+
+    #[test]
+    fn not_takes_a_single_key() {
+        let frame = Frame::Array(
+            ["NOT", "dest", "a", "b"]
+                .iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+
+        let err = BitOp::parse_frames(&mut Parse::new(frame).unwrap()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR BITOP NOT must be called with a single source key."
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, parse, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// Unit of the start and end of a bit range
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum BitUnit {
+    #[default]
+    Byte,
+    Bit,
+}
+
+impl BitUnit {
+    pub fn parse(unit: &str) -> crate::Result<BitUnit> {
+        match unit.to_uppercase().as_str() {
+            "BYTE" => Ok(BitUnit::Byte),
+            "BIT" => Ok(BitUnit::Bit),
+            _ => Err("ERR syntax error".into()),
+        }
+    }
+}
+
+impl std::fmt::Display for BitUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BitUnit::Byte => write!(f, "BYTE"),
+            BitUnit::Bit => write!(f, "BIT"),
+        }
+    }
+}
+
+/// BITPOS key bit [start [end [BYTE | BIT]]]
+#[derive(Debug, Default)]
+pub struct BitPos {
+    key: String,
+    bit: bool,
+    start: Option<i64>,
+    end: Option<i64>,
+    unit: BitUnit,
+}
+
+impl BitPos {
+    pub fn new(
+        key: impl ToString,
+        bit: bool,
+        start: Option<i64>,
+        end: Option<i64>,
+        unit: BitUnit,
+    ) -> BitPos {
+        BitPos {
+            key: key.to_string(),
+            bit,
+            start,
+            end,
+            unit,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.bitpos(&self.key, self.bit, self.start, self.end, self.unit) {
+            Ok(pos) => Frame::Integer(pos),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<BitPos> {
+        let key = frames.next_string()?;
+        let bit = match frames.next_string()?.as_str() {
+            "0" => false,
+            "1" => true,
+            _ => return Err("ERR The bit argument must be 1 or 0.".into()),
+        };
+
+        let start = match frames.next_int() {
+            Ok(start) => Some(start),
+            Err(parse::Error::EndOfStream) => None,
+            Err(err) => return Err(err.into()),
+        };
+        let end = match start.map(|_| frames.next_int()) {
+            Some(Ok(end)) => Some(end),
+            Some(Err(parse::Error::EndOfStream)) | None => None,
+            Some(Err(err)) => return Err(err.into()),
+        };
+        let unit = match end.map(|_| frames.next_string()) {
+            Some(Ok(unit)) => BitUnit::parse(&unit)?,
+            Some(Err(parse::Error::EndOfStream)) | None => BitUnit::Byte,
+            Some(Err(err)) => return Err(err.into()),
+        };
+
+        Ok(BitPos::new(key, bit, start, end, unit))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk(Bytes::from("BITPOS")),
+            Frame::Bulk(Bytes::from(self.key.clone())),
+            Frame::Bulk(Bytes::from(if self.bit { "1" } else { "0" })),
+        ];
+
+        if let Some(start) = self.start {
+            frames.push(Frame::Bulk(Bytes::from(start.to_string())));
+        }
+        if let Some(end) = self.end {
+            frames.push(Frame::Bulk(Bytes::from(end.to_string())));
+            frames.push(Frame::Bulk(Bytes::from(self.unit.to_string())));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for BitPos {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(BitPos::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db_with(value: &'static [u8]) -> Db {
+        let db = Db::new();
+        db.set("key".to_string(), Bytes::from_static(value), None);
+
+        db
+    }
+
+    fn bitpos(db: &Db, bit: bool, start: Option<i64>, end: Option<i64>, unit: BitUnit) -> i64 {
+        db.bitpos("key", bit, start, end, unit).unwrap()
+    }
+
+    #[tokio::test]
+    async fn first_set_bit() {
+        let db = db_with(b"\x00\xff\xf0");
+
+        assert_eq!(bitpos(&db, true, None, None, BitUnit::Byte), 8);
+        assert_eq!(bitpos(&db, true, Some(2), Some(-1), BitUnit::Byte), 16);
+        assert_eq!(bitpos(&db, true, Some(7), Some(15), BitUnit::Bit), 8);
+
+        let db = db_with(b"\x00\x00\x00");
+        assert_eq!(bitpos(&db, true, None, None, BitUnit::Byte), -1);
+    }
+
+    #[tokio::test]
+    async fn first_clear_bit() {
+        let db = db_with(b"\xff\xf0\x00");
+
+        assert_eq!(bitpos(&db, false, None, None, BitUnit::Byte), 12);
+        assert_eq!(bitpos(&db, false, Some(2), None, BitUnit::Byte), 16);
+        assert_eq!(bitpos(&db, false, Some(1), Some(12), BitUnit::Bit), 12);
+        assert_eq!(bitpos(&db, false, Some(1), Some(9), BitUnit::Bit), -1);
+    }
+
+    #[tokio::test]
+    async fn clear_bit_past_the_end() {
+        let db = db_with(b"\xff\xff\xff");
+
+        // Without an explicit end the string is zero padded on the right
+        assert_eq!(bitpos(&db, false, None, None, BitUnit::Byte), 24);
+        assert_eq!(bitpos(&db, false, Some(1), None, BitUnit::Byte), 24);
+        // An explicit end limits the search to the string
+        assert_eq!(bitpos(&db, false, Some(0), Some(-1), BitUnit::Byte), -1);
+        assert_eq!(bitpos(&db, false, Some(0), Some(23), BitUnit::Bit), -1);
+    }
+
+    #[tokio::test]
+    async fn missing_key_is_all_clear() {
+        let db = Db::new();
+
+        assert_eq!(bitpos(&db, false, None, None, BitUnit::Byte), 0);
+        assert_eq!(bitpos(&db, true, None, None, BitUnit::Byte), -1);
+    }
+}
@@ -0,0 +1,87 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// Cluster mode is not supported, the commands only report
+/// a standalone node so clients can detect it
+#[derive(Debug)]
+pub enum Cluster {
+    /// CLUSTER INFO
+    Info,
+    /// CLUSTER MYID
+    MyId,
+}
+
+impl Cluster {
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Cluster> {
+        let section = frames.next_string()?;
+
+        match section.to_lowercase().as_str() {
+            "info" => Ok(Cluster::Info),
+            "myid" => Ok(Cluster::MyId),
+            _ => Err(format!("Protocol error: unsupported CLUSTER section: {}", section).into()),
+        }
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let section = match self {
+            Cluster::Info => "INFO",
+            Cluster::MyId => "MYID",
+        };
+
+        Frame::Array(vec![
+            Frame::Bulk("CLUSTER".into()),
+            Frame::Bulk(section.into()),
+        ])
+    }
+
+    pub fn execute(&self, server_info: &Info) -> Frame {
+        match self {
+            Cluster::Info => {
+                let info = [
+                    "cluster_enabled:0",
+                    "cluster_state:ok",
+                    "cluster_slots_assigned:0",
+                    "cluster_slots_ok:0",
+                    "cluster_slots_pfail:0",
+                    "cluster_slots_fail:0",
+                    "cluster_known_nodes:1",
+                    "cluster_size:0",
+                    "cluster_current_epoch:0",
+                    "cluster_my_epoch:0",
+                ]
+                .iter()
+                .map(|line| format!("{}\r\n", line))
+                .collect::<String>();
+
+                Frame::Bulk(info.into())
+            }
+            Cluster::MyId => Frame::Bulk(server_info.node_id().to_string().into()),
+        }
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Cluster {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Cluster::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, _db: &Db, server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(server_info)
+    }
+
+    fn execute_replica(&self, _db: &Db, server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(server_info)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,231 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::{
+    spec::{CommandSpec, COMMAND_SPECS},
+    Command, CommandTrait,
+};
+
+#[derive(Debug)]
+pub enum CommandInfo {
+    /// COMMAND GETKEYS command [arg [arg ...]]
+    GetKeys(Vec<Bytes>),
+    /// COMMAND INFO [command-name [command-name ...]]
+    Info(Vec<String>),
+    /// COMMAND COUNT
+    Count,
+}
+
+impl CommandInfo {
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<CommandInfo> {
+        let subcommand = frames.next_string()?;
+
+        match subcommand.to_uppercase().as_str() {
+            "GETKEYS" => {
+                let mut args = vec![frames.next_bytes()?];
+                while let Ok(arg) = frames.next_bytes() {
+                    args.push(arg);
+                }
+
+                Ok(CommandInfo::GetKeys(args))
+            }
+            "INFO" => {
+                let mut names = Vec::new();
+                while let Ok(name) = frames.next_string() {
+                    names.push(name);
+                }
+
+                Ok(CommandInfo::Info(names))
+            }
+            "COUNT" => Ok(CommandInfo::Count),
+            _ => Err(format!("ERR unknown subcommand '{}'. Try COMMAND HELP.", subcommand).into()),
+        }
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        match self {
+            CommandInfo::GetKeys(args) => {
+                let mut frames = vec![Frame::Bulk("COMMAND".into()), Frame::Bulk("GETKEYS".into())];
+                frames.extend(args.iter().cloned().map(Frame::Bulk));
+
+                Frame::Array(frames)
+            }
+            CommandInfo::Info(names) => {
+                let mut frames = vec![Frame::Bulk("COMMAND".into()), Frame::Bulk("INFO".into())];
+                frames.extend(
+                    names
+                        .iter()
+                        .map(|name| Frame::Bulk(Bytes::from(name.clone()))),
+                );
+
+                Frame::Array(frames)
+            }
+            CommandInfo::Count => Frame::Array(vec![
+                Frame::Bulk("COMMAND".into()),
+                Frame::Bulk("COUNT".into()),
+            ]),
+        }
+    }
+
+    pub fn execute(&self) -> Frame {
+        match self {
+            CommandInfo::GetKeys(args) => Self::get_keys(args),
+            CommandInfo::Info(names) => Self::info(names),
+            // Every supported command has a spec
+            CommandInfo::Count => Frame::Integer(COMMAND_SPECS.len() as i64),
+        }
+    }
+
+    /// Returns the details of the commands, a null for unknown commands,
+    /// without names the details of all the commands are returned
+    fn info(names: &[String]) -> Frame {
+        if names.is_empty() {
+            return Frame::Array(COMMAND_SPECS.iter().map(Self::spec_frame).collect());
+        }
+
+        Frame::Array(
+            names
+                .iter()
+                .map(|name| match CommandSpec::lookup(name) {
+                    Some(spec) => Self::spec_frame(spec),
+                    None => Frame::Null,
+                })
+                .collect(),
+        )
+    }
+
+    /// The name, arity, flags, first key, last key and step of the command
+    fn spec_frame(spec: &CommandSpec) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk(Bytes::from(spec.name.to_lowercase())),
+            Frame::Integer(spec.arity),
+            Frame::Array(
+                spec.flags
+                    .iter()
+                    .map(|flag| Frame::Simple(flag.to_string()))
+                    .collect(),
+            ),
+            Frame::Integer(spec.first_key as i64),
+            Frame::Integer(spec.last_key),
+            Frame::Integer(spec.step as i64),
+        ])
+    }
+
+    /// Returns the keys of the command, see [`Command::get_keys`]
+    fn get_keys(args: &[Bytes]) -> Frame {
+        let frame = Frame::Array(args.iter().cloned().map(Frame::Bulk).collect());
+
+        match Command::get_keys(frame) {
+            Ok(keys) => Frame::Array(
+                keys.into_iter()
+                    .map(|key| Frame::Bulk(Bytes::from(key)))
+                    .collect(),
+            ),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl CommandTrait for CommandInfo {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(CommandInfo::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute()
+    }
+
+    fn execute_replica(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute()
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_keys(args: &[&str]) -> Frame {
+        let args = args
+            .iter()
+            .map(|arg| Bytes::from(arg.to_string()))
+            .collect();
+
+        CommandInfo::GetKeys(args).execute()
+    }
+
+    fn keys(keys: &[&str]) -> Frame {
+        Frame::Array(
+            keys.iter()
+                .map(|key| Frame::Bulk(Bytes::from(key.to_string())))
+                .collect(),
+        )
+    }
+
+    fn info(name: &str, arity: i64, flag: &str) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk(Bytes::from(name.to_string())),
+            Frame::Integer(arity),
+            Frame::Array(vec![Frame::Simple(flag.to_string())]),
+            Frame::Integer(1),
+            Frame::Integer(1),
+            Frame::Integer(1),
+        ])
+    }
+
+    #[test]
+    fn info_returns_arity_and_flags() {
+        let names = vec!["get".to_string(), "SET".to_string(), "nosuch".to_string()];
+
+        assert_eq!(
+            CommandInfo::Info(names).execute(),
+            Frame::Array(vec![
+                info("get", 2, "readonly"),
+                info("set", -3, "write"),
+                Frame::Null,
+            ])
+        );
+    }
+
+    #[test]
+    fn set_key_is_the_first_argument() {
+        assert_eq!(get_keys(&["SET", "foo", "bar"]), keys(&["foo"]));
+        assert_eq!(get_keys(&["SET", "foo", "bar", "EX", "10"]), keys(&["foo"]));
+    }
+
+    #[test]
+    fn get_key_is_the_only_argument() {
+        assert_eq!(get_keys(&["GET", "foo"]), keys(&["foo"]));
+        assert_eq!(
+            get_keys(&["GET"]),
+            Frame::Error("ERR Invalid number of arguments specified for command".to_string())
+        );
+    }
+
+    #[test]
+    fn mget_keys_are_all_the_arguments() {
+        assert_eq!(get_keys(&["MGET", "a", "b", "c"]), keys(&["a", "b", "c"]));
+    }
+
+    #[test]
+    fn commands_without_keys_are_rejected() {
+        assert_eq!(
+            get_keys(&["ECHO", "hi"]),
+            Frame::Error("ERR The command has no key arguments".to_string())
+        );
+        assert_eq!(
+            get_keys(&["UNKNOWN", "hi"]),
+            Frame::Error("ERR Invalid command specified".to_string())
+        );
+    }
+}
@@ -1,13 +1,21 @@
 use async_trait::async_trait;
 
-use crate::{connection::Connection, Db, Frame, Info as ServerInfo, Parse};
+use crate::{
+    connection::Connection,
+    error::{redis_error, RedisErrorKind},
+    glob, Db, Frame, Info as ServerInfo, Parse,
+};
 
 use super::CommandTrait;
 
 #[derive(Debug)]
 pub enum Config {
-    Dir,
-    DbFilename,
+    /// CONFIG GET parameter [parameter ...]
+    /// Each parameter is a glob-style pattern, like `*` for all the parameters
+    Get(Vec<String>),
+    /// CONFIG SET parameter value [parameter value ...]
+    /// Only the encoding limits can be changed at runtime
+    Set(Vec<(String, String)>),
 }
 
 impl Config {
@@ -15,6 +23,7 @@ impl Config {
         match frames.next_string() {
             Ok(section) => match section.as_str().to_lowercase().as_str() {
                 "get" => Config::parse_get(frames),
+                "set" => Config::parse_set(frames),
                 _ => Err(format!("Protocol error: unsupported Config section: {}", section).into()),
             },
             Err(err) => Err(err.into()),
@@ -22,26 +31,85 @@ impl Config {
     }
 
     fn parse_get(frames: &mut Parse) -> crate::Result<Config> {
-        let get_arg = frames.next_string()?.as_str().to_lowercase();
+        let mut patterns = vec![frames.next_string()?.to_lowercase()];
+        while let Ok(pattern) = frames.next_string() {
+            patterns.push(pattern.to_lowercase());
+        }
+
+        Ok(Config::Get(patterns))
+    }
 
-        match get_arg.as_str() {
-            "dir" => Ok(Config::Dir),
-            "dbfilename" => Ok(Config::DbFilename),
-            _ => Err("Protocol error: expected command: Config get".into()),
+    fn parse_set(frames: &mut Parse) -> crate::Result<Config> {
+        let mut params = vec![(frames.next_string()?.to_lowercase(), frames.next_string()?)];
+        while let Ok(name) = frames.next_string() {
+            params.push((name.to_lowercase(), frames.next_string()?));
         }
+
+        Ok(Config::Set(params))
     }
 
     pub fn to_frame(&self) -> Frame {
-        Frame::Simple("Config".into())
+        match self {
+            Config::Get(patterns) => {
+                let mut frames = vec![Frame::Bulk("CONFIG".into()), Frame::Bulk("GET".into())];
+                for pattern in patterns {
+                    frames.push(Frame::Bulk(pattern.clone().into()));
+                }
+
+                Frame::Array(frames)
+            }
+            Config::Set(params) => {
+                let mut frames = vec![Frame::Bulk("CONFIG".into()), Frame::Bulk("SET".into())];
+                for (name, value) in params {
+                    frames.push(Frame::Bulk(name.clone().into()));
+                    frames.push(Frame::Bulk(value.clone().into()));
+                }
+
+                Frame::Array(frames)
+            }
+        }
     }
 
-    pub fn execute(&self, server_info: &mut ServerInfo) -> Frame {
-        let (key, value) = match self {
-            Config::Dir => ("dir", server_info.dir().to_string()),
-            Config::DbFilename => ("dbfilename", server_info.dbfilename().to_string()),
-        };
+    /// Replies with a flat list of the names and values of the matching parameters,
+    /// a parameter matching several patterns is listed once
+    pub fn execute(&self, db: &Db, server_info: &mut ServerInfo) -> Frame {
+        match self {
+            Config::Get(patterns) => {
+                let mut params = server_info.all_config_params();
+                params.extend(db.encoding_config().params());
+
+                let frames = params
+                    .into_iter()
+                    .filter(|(name, _)| patterns.iter().any(|pattern| glob::matches(pattern, name)))
+                    .flat_map(|(name, value)| [Frame::Bulk(name.into()), Frame::Bulk(value.into())])
+                    .collect();
+
+                Frame::Array(frames)
+            }
+            // All the parameters are applied or none
+            Config::Set(params) => {
+                let mut encoding = db.encoding_config();
 
-        Frame::Array(vec![Frame::Bulk(key.into()), Frame::Bulk(value.into())])
+                for (name, value) in params {
+                    match encoding.set(name, value) {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            return redis_error(
+                                RedisErrorKind::Err,
+                                &format!(
+                                    "Unknown option or number of arguments for CONFIG SET - '{}'",
+                                    name
+                                ),
+                            )
+                        }
+                        Err(err) => return Frame::Error(err.to_string()),
+                    }
+                }
+
+                db.set_encoding_config(encoding);
+                Frame::Simple("OK".into())
+            }
+        }
     }
 }
 
@@ -53,20 +121,20 @@ impl CommandTrait for Config {
 
     async fn execute(
         &self,
-        _db: &Db,
+        db: &Db,
         server_info: &mut ServerInfo,
         _connection: Connection,
     ) -> Frame {
-        self.execute(server_info)
+        self.execute(db, server_info)
     }
 
     fn execute_replica(
         &self,
-        _db: &Db,
+        db: &Db,
         server_info: &mut ServerInfo,
         _connection: Connection,
     ) -> Frame {
-        self.execute(server_info)
+        self.execute(db, server_info)
     }
 
     fn to_frame(&self) -> Frame {
@@ -77,3 +145,64 @@ impl CommandTrait for Config {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        server::tests::{connect, send, start_server},
+        Frame,
+    };
+
+    #[tokio::test]
+    async fn lowering_hash_max_listpack_entries_flips_the_encoding() {
+        let client = connect(start_server(&[]).await).await;
+        let encoding = || send(&client, &["OBJECT", "ENCODING", "hash"]);
+
+        send(&client, &["HSET", "hash", "a", "1", "b", "2", "c", "3"]).await;
+        assert_eq!(encoding().await, Frame::Bulk("listpack".into()));
+
+        assert_eq!(
+            send(
+                &client,
+                &["CONFIG", "SET", "hash-max-listpack-entries", "2"]
+            )
+            .await,
+            Frame::Simple("OK".into())
+        );
+        assert_eq!(
+            send(&client, &["CONFIG", "GET", "hash-max-listpack-entries"]).await,
+            Frame::Array(vec![
+                Frame::Bulk("hash-max-listpack-entries".into()),
+                Frame::Bulk("2".into()),
+            ])
+        );
+
+        // The hash is converted on its next write, like in Redis
+        send(&client, &["HSET", "hash", "d", "4"]).await;
+        assert_eq!(encoding().await, Frame::Bulk("hashtable".into()));
+    }
+
+    #[tokio::test]
+    async fn any_list_max_listpack_size_is_accepted() {
+        let client = connect(start_server(&[]).await).await;
+        let encoding = || send(&client, &["OBJECT", "ENCODING", "list"]);
+
+        for size in ["0", "-6", "-1", "3"] {
+            assert_eq!(
+                send(&client, &["CONFIG", "SET", "list-max-listpack-size", size]).await,
+                Frame::Simple("OK".into())
+            );
+        }
+        assert!(matches!(
+            send(&client, &["CONFIG", "SET", "list-max-listpack-size", "one"]).await,
+            Frame::Error(_)
+        ));
+
+        // 0 keeps a single entry in a listpack, like 1
+        send(&client, &["CONFIG", "SET", "list-max-listpack-size", "0"]).await;
+        send(&client, &["RPUSH", "list", "a"]).await;
+        assert_eq!(encoding().await, Frame::Bulk("listpack".into()));
+        send(&client, &["RPUSH", "list", "b"]).await;
+        assert_eq!(encoding().await, Frame::Bulk("quicklist".into()));
+    }
+}
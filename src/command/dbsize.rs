@@ -0,0 +1,45 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug, Default)]
+pub struct DbSize {}
+
+impl DbSize {
+    pub fn execute(&self, db: &Db) -> Frame {
+        Frame::Integer(db.dbsize() as i64)
+    }
+
+    pub fn parse_frames(_frames: &mut Parse) -> crate::Result<DbSize> {
+        Ok(DbSize {})
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![Frame::Bulk("DBSIZE".into())])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for DbSize {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(DbSize::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,529 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{
+    connection::Connection,
+    db::{self, Entry},
+    error::{redis_error, RedisErrorKind},
+    glob, Db, Frame, Info, Parse, RedisDB,
+};
+
+use super::CommandTrait;
+
+#[derive(Debug)]
+pub enum Debug {
+    /// DEBUG OBJECT \<key\>
+    Object(String),
+    /// DEBUG RELOAD
+    /// Saves the dataset to the RDB file and loads it back
+    Reload,
+    /// DEBUG JMAP
+    /// Does nothing, kept for compatibility
+    JMap,
+    /// DEBUG SET-ACTIVE-EXPIRE \<0|1\>
+    /// Enables or disables the removal of the expired keys in the background
+    SetActiveExpire(bool),
+    /// DEBUG CHANGE-REPL-ID
+    /// Starts a new replication history, the replicas of the previous one
+    /// can only continue up to where it ended
+    ChangeReplId,
+    /// DEBUG PROTOCOL \<true|false\>
+    /// Replies with a value of the given RESP type, only the booleans are supported
+    Protocol(bool),
+    /// DEBUG DUMP-KEYSPACE
+    /// Non standard, replies with a JSON object of every key with its type and TTL in ms,
+    /// only allowed with `--enable-debug-commands`
+    DumpKeyspace,
+    /// DEBUG STRINGMATCH-LEN \<pattern\> \<string\>
+    /// Replies 1 if the string matches the glob-style pattern, like KEYS does
+    StringMatchLen { pattern: String, string: String },
+    /// DEBUG SLEEP \<seconds\> [GLOBAL]
+    /// Sleeps before replying. The connections are served by separate tasks,
+    /// so only the calling connection is blocked, unlike in the single threaded Redis.
+    /// Non standard GLOBAL holds the keyspace lock meanwhile, stalling every command on keys
+    Sleep { seconds: f64, global: bool },
+}
+
+impl Debug {
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Debug> {
+        let subcommand = frames.next_string()?;
+
+        match subcommand.to_lowercase().as_str() {
+            "object" => Ok(Debug::Object(frames.next_string()?)),
+            "reload" => Ok(Debug::Reload),
+            "jmap" => Ok(Debug::JMap),
+            "change-repl-id" => Ok(Debug::ChangeReplId),
+            "dump-keyspace" => Ok(Debug::DumpKeyspace),
+            "stringmatch-len" => Ok(Debug::StringMatchLen {
+                pattern: frames.next_string()?,
+                string: frames.next_string()?,
+            }),
+            "sleep" => {
+                let seconds = frames
+                    .next_string()?
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+                    .ok_or("ERR value is not a valid float")?;
+                let global = match frames.next_string() {
+                    Ok(mode) if mode.to_uppercase() == "GLOBAL" => true,
+                    Ok(_) => return Err("ERR syntax error".into()),
+                    Err(_) => false,
+                };
+
+                Ok(Debug::Sleep { seconds, global })
+            }
+            "set-active-expire" => match frames.next_string()?.as_str() {
+                "0" => Ok(Debug::SetActiveExpire(false)),
+                "1" => Ok(Debug::SetActiveExpire(true)),
+                _ => Err("ERR value is out of range, must be 0 or 1".into()),
+            },
+            "protocol" => match frames.next_string()?.to_lowercase().as_str() {
+                "true" => Ok(Debug::Protocol(true)),
+                "false" => Ok(Debug::Protocol(false)),
+                _ => Err(
+                    "ERR Wrong protocol type name. Please use one of the following: true|false"
+                        .into(),
+                ),
+            },
+            _ => Err(format!(
+                "Protocol error: unsupported DEBUG subcommand: {}",
+                subcommand
+            )
+            .into()),
+        }
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        match self {
+            Debug::Object(key) => Frame::Array(vec![
+                Frame::Bulk("DEBUG".into()),
+                Frame::Bulk("OBJECT".into()),
+                Frame::Bulk(key.clone().into()),
+            ]),
+            Debug::Reload => Frame::Array(vec![
+                Frame::Bulk("DEBUG".into()),
+                Frame::Bulk("RELOAD".into()),
+            ]),
+            Debug::JMap => Frame::Array(vec![
+                Frame::Bulk("DEBUG".into()),
+                Frame::Bulk("JMAP".into()),
+            ]),
+            Debug::SetActiveExpire(enabled) => Frame::Array(vec![
+                Frame::Bulk("DEBUG".into()),
+                Frame::Bulk("SET-ACTIVE-EXPIRE".into()),
+                Frame::Bulk(if *enabled { "1" } else { "0" }.into()),
+            ]),
+            Debug::ChangeReplId => Frame::Array(vec![
+                Frame::Bulk("DEBUG".into()),
+                Frame::Bulk("CHANGE-REPL-ID".into()),
+            ]),
+            Debug::Protocol(value) => Frame::Array(vec![
+                Frame::Bulk("DEBUG".into()),
+                Frame::Bulk("PROTOCOL".into()),
+                Frame::Bulk(if *value { "true" } else { "false" }.into()),
+            ]),
+            Debug::DumpKeyspace => Frame::Array(vec![
+                Frame::Bulk("DEBUG".into()),
+                Frame::Bulk("DUMP-KEYSPACE".into()),
+            ]),
+            Debug::StringMatchLen { pattern, string } => Frame::Array(vec![
+                Frame::Bulk("DEBUG".into()),
+                Frame::Bulk("STRINGMATCH-LEN".into()),
+                Frame::Bulk(pattern.clone().into()),
+                Frame::Bulk(string.clone().into()),
+            ]),
+            Debug::Sleep { seconds, global } => {
+                let mut frames = vec![
+                    Frame::Bulk("DEBUG".into()),
+                    Frame::Bulk("SLEEP".into()),
+                    Frame::Bulk(seconds.to_string().into()),
+                ];
+                if *global {
+                    frames.push(Frame::Bulk("GLOBAL".into()));
+                }
+
+                Frame::Array(frames)
+            }
+        }
+    }
+
+    pub async fn execute(&self, db: &Db, server_info: &mut Info, connection: Connection) -> Frame {
+        match self {
+            Debug::Object(key) => Debug::object(db, key),
+            Debug::Reload => match Debug::reload(db, server_info).await {
+                Ok(()) => Frame::Simple("OK".into()),
+                Err(err) => redis_error(
+                    RedisErrorKind::Err,
+                    &format!("Error trying to reload the RDB: {}", err),
+                ),
+            },
+            Debug::JMap => Frame::Simple("OK".into()),
+            Debug::SetActiveExpire(enabled) => {
+                db.set_active_expire(*enabled);
+                Frame::Simple("OK".into())
+            }
+            Debug::ChangeReplId => {
+                server_info.change_replid();
+                Frame::Simple("OK".into())
+            }
+            Debug::Protocol(value) => connection.session().boolean(*value),
+            Debug::DumpKeyspace => match server_info.enable_debug_commands() {
+                true => Frame::Bulk(Debug::dump_keyspace(db).into()),
+                false => redis_error(
+                    RedisErrorKind::Err,
+                    "DEBUG DUMP-KEYSPACE is not allowed, start the server with --enable-debug-commands",
+                ),
+            },
+            Debug::StringMatchLen { pattern, string } => {
+                Frame::Integer(glob::matches(pattern, string) as i64)
+            }
+            Debug::Sleep { seconds, global } => {
+                let duration = Duration::from_secs_f64(*seconds);
+
+                match global {
+                    true => {
+                        let db = db.clone();
+                        // The lock is held on a blocking thread, not to stall the runtime workers
+                        let _ = tokio::task::spawn_blocking(move || db.stall(duration)).await;
+                    }
+                    false => tokio::time::sleep(duration).await,
+                }
+
+                Frame::Simple("OK".into())
+            }
+        }
+    }
+
+    /// Serializes the keyspace as `{"key":{"type":"string","ttl":-1},...}`,
+    /// the TTL is in milliseconds, -1 for the keys without expiry
+    fn dump_keyspace(db: &Db) -> String {
+        let keys: Vec<String> = db
+            .keyspace()
+            .into_iter()
+            .map(|(key, type_name, ttl)| {
+                let ttl = ttl.map_or(-1, |ttl| ttl.as_millis() as i64);
+
+                format!(
+                    "{}:{{\"type\":\"{}\",\"ttl\":{}}}",
+                    json_string(&key),
+                    type_name,
+                    ttl
+                )
+            })
+            .collect();
+
+        format!("{{{}}}", keys.join(","))
+    }
+
+    fn object(db: &Db, key: &str) -> Frame {
+        match db.get_no_touch(key) {
+            Some(entry) => Frame::Simple(Debug::describe_object(&entry)),
+            None => redis_error(RedisErrorKind::Err, "no such key"),
+        }
+    }
+
+    /// Round trips the dataset through the RDB file
+    async fn reload(db: &Db, server_info: &Info) -> crate::Result<()> {
+        let mut rdb = RedisDB::new(server_info.rdb_path());
+        rdb.set_checksum(server_info.rdbchecksum());
+
+        let functions = db
+            .function_libraries()
+            .into_iter()
+            .map(|(_, code)| code)
+            .collect::<Vec<_>>();
+
+        rdb.write_rdb(&db.to_rdb(), &functions).await?;
+        db.load_rdb(rdb.read_rdb().await?);
+        db.load_functions(rdb.functions());
+
+        Ok(())
+    }
+
+    /// Builds the DEBUG OBJECT line for the entry
+    fn describe_object(entry: &Entry) -> String {
+        let (addr, serialized_len) = match entry {
+            Entry::String(entry) => (entry.value().as_ptr(), entry.value().len()),
+            Entry::Stream(stream) => {
+                // Estimate: id (timestamp + sequence) and key-value pairs of each entry
+                let len = stream
+                    .entries()
+                    .iter()
+                    .map(|entry| {
+                        16 + entry
+                            .key_value()
+                            .iter()
+                            .map(|(key, value)| key.len() + value.len())
+                            .sum::<usize>()
+                    })
+                    .sum();
+
+                (stream.entries().as_ptr() as *const u8, len)
+            }
+            Entry::List(list) => (
+                list as *const _ as *const u8,
+                list.iter().map(|value| value.len()).sum(),
+            ),
+            Entry::Set(set) => (
+                set as *const _ as *const u8,
+                set.iter().map(|member| member.len()).sum(),
+            ),
+            Entry::Hash(hash) => (
+                hash as *const _ as *const u8,
+                hash.iter()
+                    .map(|(field, value)| field.len() + value.len())
+                    .sum(),
+            ),
+            Entry::ZSet(zset) => (
+                zset as *const _ as *const u8,
+                // Member and the 8 bytes of the score
+                zset.iter().map(|(member, _)| member.len() + 8).sum(),
+            ),
+        };
+
+        format!(
+            "Value at:{:p} refcount:1 encoding:{} serializedlength:{} lru:{} lru_seconds_idle:{} type:{}",
+            addr,
+            entry.encoding(),
+            serialized_len,
+            entry.lru(),
+            db::lru_idle_secs(entry.lru()),
+            entry.type_name(),
+        )
+    }
+}
+
+/// Quotes the string for JSON, escaping the quotes, backslashes and control characters
+fn json_string(string: &str) -> String {
+    let mut quoted = String::with_capacity(string.len() + 2);
+    quoted.push('"');
+
+    for c in string.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+#[async_trait]
+impl CommandTrait for Debug {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Debug::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, server_info: &mut Info, connection: Connection) -> Frame {
+        self.execute(db, server_info, connection).await
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        match self {
+            Debug::Object(key) => Debug::object(db, key),
+            // Not propagated, the master does not reload the replicas
+            Debug::Reload
+            | Debug::JMap
+            | Debug::SetActiveExpire(_)
+            | Debug::ChangeReplId
+            | Debug::Protocol(_)
+            | Debug::DumpKeyspace
+            | Debug::StringMatchLen { .. }
+            | Debug::Sleep { .. } => Frame::Null,
+        }
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::server::tests::{connect, send, start_server};
+
+    #[tokio::test]
+    async fn expired_key_stays_until_accessed_without_active_expire() {
+        let client = connect(start_server(&["--enable-debug-commands"]).await).await;
+
+        assert_eq!(
+            send(&client, &["DEBUG", "SET-ACTIVE-EXPIRE", "0"]).await,
+            Frame::Simple("OK".into())
+        );
+        send(&client, &["SET", "key", "value", "PX", "50"]).await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        assert_eq!(send(&client, &["DBSIZE"]).await, Frame::Integer(1));
+        assert_eq!(send(&client, &["GET", "key"]).await, Frame::Null);
+        assert_eq!(send(&client, &["DBSIZE"]).await, Frame::Integer(0));
+    }
+
+    #[tokio::test]
+    async fn expired_key_is_removed_with_active_expire() {
+        let client = connect(start_server(&["--enable-debug-commands"]).await).await;
+
+        send(&client, &["SET", "key", "value", "PX", "50"]).await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        assert_eq!(send(&client, &["DBSIZE"]).await, Frame::Integer(0));
+    }
+
+    #[tokio::test]
+    async fn stringmatch_len_uses_glob_matcher() {
+        let client = connect(start_server(&[]).await).await;
+        let cases = [
+            // Nested brackets, `[` is a plain char inside a class
+            ("[[a]", "[", 1),
+            ("[[a]", "a", 1),
+            ("[a[]]", "[]", 1),
+            ("[a[]]", "a", 0),
+            // Escapes
+            ("a\\*b", "a*b", 1),
+            ("a\\*b", "axb", 0),
+            ("[\\]]", "]", 1),
+            // Consecutive stars collapse into one
+            ("a***b", "ab", 1),
+            ("a***b", "aXYZb", 1),
+            ("***", "", 1),
+            ("a**c", "abd", 0),
+        ];
+
+        for (pattern, string, expected) in cases {
+            assert_eq!(
+                send(&client, &["DEBUG", "STRINGMATCH-LEN", pattern, string]).await,
+                Frame::Integer(expected),
+                "{} against {}",
+                pattern,
+                string
+            );
+        }
+    }
+
+    /// Time taken by a GET on another connection while the first one sleeps
+    async fn get_during_sleep(sleep: &'static [&'static str]) -> Duration {
+        let addr = start_server(&[]).await;
+        let sleeping = connect(addr).await;
+        let client = connect(addr).await;
+
+        let sleep = tokio::spawn(async move { send(&sleeping, sleep).await });
+        // Lets the sleep start first
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let started = std::time::Instant::now();
+        send(&client, &["GET", "key"]).await;
+        let elapsed = started.elapsed();
+
+        assert_eq!(sleep.await.unwrap(), Frame::Simple("OK".into()));
+        elapsed
+    }
+
+    #[tokio::test]
+    async fn global_sleep_delays_other_connections() {
+        let elapsed = get_during_sleep(&["DEBUG", "SLEEP", "0.5", "GLOBAL"]).await;
+
+        assert!(elapsed >= Duration::from_millis(300), "{:?}", elapsed);
+    }
+
+    #[tokio::test]
+    async fn sleep_only_blocks_its_connection() {
+        let elapsed = get_during_sleep(&["DEBUG", "SLEEP", "0.5"]).await;
+
+        assert!(elapsed < Duration::from_millis(300), "{:?}", elapsed);
+    }
+
+    #[tokio::test]
+    async fn reload_keeps_strings_and_streams() {
+        let dir = std::env::temp_dir().join(format!("debug-reload-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let client = connect(start_server(&["--dir", dir.to_str().unwrap()]).await).await;
+
+        send(&client, &["SET", "string", "value"]).await;
+        send(&client, &["SET", "number", "123"]).await;
+        send(&client, &["SET", "volatile", "value", "EX", "100"]).await;
+        send(&client, &["XADD", "stream", "1-1", "a", "1"]).await;
+        send(&client, &["XADD", "stream", "2-1", "b", "2", "c", "3"]).await;
+        let range = send(&client, &["XRANGE", "stream", "-", "+"]).await;
+
+        assert_eq!(
+            send(&client, &["DEBUG", "RELOAD"]).await,
+            Frame::Simple("OK".into())
+        );
+
+        assert_eq!(send(&client, &["DBSIZE"]).await, Frame::Integer(4));
+        assert_eq!(
+            send(&client, &["GET", "string"]).await,
+            Frame::Bulk("value".into())
+        );
+        assert_eq!(
+            send(&client, &["GET", "number"]).await,
+            Frame::Bulk("123".into())
+        );
+        match send(&client, &["TTL", "volatile"]).await {
+            Frame::Integer(ttl) => assert!(ttl > 90 && ttl <= 100),
+            frame => panic!("expected the TTL, got {frame:?}"),
+        }
+        assert_eq!(send(&client, &["XRANGE", "stream", "-", "+"]).await, range);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn dump(frame: Frame) -> String {
+        match frame {
+            Frame::Bulk(dump) => String::from_utf8(dump.to_vec()).unwrap(),
+            frame => panic!("expected the dump, got {frame:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn dump_keyspace_lists_the_keys_after_writes() {
+        let client = connect(start_server(&["--enable-debug-commands"]).await).await;
+
+        assert_eq!(dump(send(&client, &["DEBUG", "DUMP-KEYSPACE"]).await), "{}");
+
+        send(&client, &["SET", "string", "value"]).await;
+        send(&client, &["RPUSH", "list", "a", "b"]).await;
+        send(&client, &["HSET", "hash", "field", "value"]).await;
+        assert_eq!(
+            dump(send(&client, &["DEBUG", "DUMP-KEYSPACE"]).await),
+            concat!(
+                r#"{"hash":{"type":"hash","ttl":-1},"#,
+                r#""list":{"type":"list","ttl":-1},"#,
+                r#""string":{"type":"string","ttl":-1}}"#
+            )
+        );
+
+        send(&client, &["SET", "volatile", "value", "PX", "100000"]).await;
+        let dump = dump(send(&client, &["DEBUG", "DUMP-KEYSPACE"]).await);
+        let ttl = dump
+            .split_once(r#""volatile":{"type":"string","ttl":"#)
+            .and_then(|(_, rest)| rest.split_once('}'))
+            .map(|(ttl, _)| ttl.parse::<i64>().unwrap())
+            .unwrap_or_else(|| panic!("no volatile key in {dump}"));
+        assert!(ttl > 99_000 && ttl <= 100_000);
+    }
+
+    #[tokio::test]
+    async fn dump_keyspace_needs_debug_commands() {
+        let client = connect(start_server(&[]).await).await;
+
+        assert!(matches!(
+            send(&client, &["DEBUG", "DUMP-KEYSPACE"]).await,
+            Frame::Error(_)
+        ));
+    }
+}
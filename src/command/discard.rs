@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{
+    connection::Connection,
+    error::{redis_error, RedisErrorKind},
+    Db, Frame, Info, Parse,
+};
+
+use super::CommandTrait;
+
+/// DISCARD, drops the commands queued since MULTI
+#[derive(Debug, Default)]
+pub struct Discard {}
+
+impl Discard {
+    pub fn execute(&self, connection: Connection) -> Frame {
+        match connection.session().take_transaction() {
+            Some(_) => Frame::Simple("OK".into()),
+            None => redis_error(RedisErrorKind::Err, "DISCARD without MULTI"),
+        }
+    }
+
+    pub fn parse_frames(_frames: &mut Parse) -> crate::Result<Discard> {
+        Ok(Discard {})
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![Frame::Bulk(Bytes::from("DISCARD"))])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Discard {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Discard::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, _db: &Db, _server_info: &mut Info, connection: Connection) -> Frame {
+        self.execute(connection)
+    }
+
+    fn execute_replica(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,62 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// DUMP key, serializes the value of the key to be restored with RESTORE
+#[derive(Debug, Default)]
+pub struct Dump {
+    key: String,
+}
+
+impl Dump {
+    pub fn new(key: impl ToString) -> Dump {
+        Dump {
+            key: key.to_string(),
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.dump(&self.key) {
+            Some(payload) => Frame::Bulk(payload),
+            None => Frame::Null,
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Dump> {
+        let key = frames.next_string()?;
+
+        Ok(Dump::new(key))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk("DUMP".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Dump {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Dump::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,48 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// EXEC, executes the commands queued since MULTI,
+/// the commands are executed by [`Command::execute`](super::Command::execute)
+#[derive(Debug, Default)]
+pub struct Exec {}
+
+impl Exec {
+    pub fn execute(&self) -> Frame {
+        Frame::Null
+    }
+
+    pub fn parse_frames(_frames: &mut Parse) -> crate::Result<Exec> {
+        Ok(Exec {})
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![Frame::Bulk(Bytes::from("EXEC"))])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Exec {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Exec::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute()
+    }
+
+    fn execute_replica(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
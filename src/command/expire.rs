@@ -0,0 +1,328 @@
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+
+use crate::{
+    connection::Connection,
+    error::{redis_error, RedisErrorKind},
+    parse, Db, Frame, Info, Parse,
+};
+
+use super::CommandTrait;
+
+/// Condition on the current expiry of the key for EXPIRE,
+/// a key without expiry has an infinite TTL for GT and LT
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ExpireCondition {
+    #[default]
+    Always,
+    /// NX, only set the expiry if the key has none
+    IfNoExpiry,
+    /// XX, only set the expiry if the key has one
+    IfExpiry,
+    /// GT, only set the expiry if it is later than the current one
+    IfGreater,
+    /// LT, only set the expiry if it is earlier than the current one
+    IfLess,
+}
+
+impl ExpireCondition {
+    fn parse(condition: &str) -> crate::Result<ExpireCondition> {
+        match condition.to_uppercase().as_str() {
+            "NX" => Ok(ExpireCondition::IfNoExpiry),
+            "XX" => Ok(ExpireCondition::IfExpiry),
+            "GT" => Ok(ExpireCondition::IfGreater),
+            "LT" => Ok(ExpireCondition::IfLess),
+            _ => Err(format!("ERR Unsupported option {}", condition).into()),
+        }
+    }
+
+    fn name(&self) -> Option<&'static str> {
+        match self {
+            ExpireCondition::Always => None,
+            ExpireCondition::IfNoExpiry => Some("NX"),
+            ExpireCondition::IfExpiry => Some("XX"),
+            ExpireCondition::IfGreater => Some("GT"),
+            ExpireCondition::IfLess => Some("LT"),
+        }
+    }
+}
+
+/// EXPIRE, PEXPIRE, EXPIREAT and PEXPIREAT, with NX | XX | GT | LT
+#[derive(Debug)]
+pub struct Expire {
+    key: String,
+    time: i64,
+    condition: ExpireCondition,
+    /// The time is in milliseconds instead of seconds
+    millis: bool,
+    /// The time is a unix timestamp instead of a duration from now
+    absolute: bool,
+    /// Time the command was received, a duration counts from it
+    /// so the expiry propagated to the replicas is the one applied
+    received_at: SystemTime,
+}
+
+impl Expire {
+    pub fn new(
+        key: impl ToString,
+        time: i64,
+        condition: ExpireCondition,
+        millis: bool,
+        absolute: bool,
+    ) -> Expire {
+        Expire {
+            key: key.to_string(),
+            time,
+            condition,
+            millis,
+            absolute,
+            received_at: SystemTime::now(),
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        let when = match self.expires_at() {
+            Some(when) => when,
+            None => {
+                return redis_error(
+                    RedisErrorKind::Err,
+                    &format!(
+                        "invalid expire time in '{}' command",
+                        self.name().to_lowercase()
+                    ),
+                )
+            }
+        };
+
+        Frame::Integer(db.expire_at(&self.key, when, self.condition) as i64)
+    }
+
+    /// Returns the time at which the key expires,
+    /// or `None` if the time overflows
+    fn expires_at(&self) -> Option<SystemTime> {
+        let millis = if self.millis {
+            self.time
+        } else {
+            self.time.checked_mul(1000)?
+        };
+
+        let start = if self.absolute {
+            SystemTime::UNIX_EPOCH
+        } else {
+            self.received_at
+        };
+
+        let duration = Duration::from_millis(millis.unsigned_abs());
+        if millis >= 0 {
+            start.checked_add(duration)
+        } else {
+            // A time in the past, the key is removed right away
+            Some(
+                start
+                    .checked_sub(duration)
+                    .unwrap_or(SystemTime::UNIX_EPOCH),
+            )
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match (self.millis, self.absolute) {
+            (false, false) => "EXPIRE",
+            (true, false) => "PEXPIRE",
+            (false, true) => "EXPIREAT",
+            (true, true) => "PEXPIREAT",
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse, millis: bool, absolute: bool) -> crate::Result<Expire> {
+        let key = frames.next_string()?;
+        let time = frames
+            .next_string()?
+            .parse::<i64>()
+            .map_err(|_| "ERR value is not an integer or out of range")?;
+
+        let condition = match frames.next_string() {
+            Ok(condition) => ExpireCondition::parse(&condition)?,
+            Err(parse::Error::EndOfStream) => ExpireCondition::Always,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Expire::new(key, time, condition, millis, absolute))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk(self.name().into()),
+            Frame::Bulk(self.key.clone().into()),
+            Frame::Bulk(self.time.to_string().into()),
+        ];
+
+        if let Some(condition) = self.condition.name() {
+            frames.push(Frame::Bulk(condition.into()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Expire {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Expire::parse_frames(
+            frames,
+            self.millis,
+            self.absolute,
+        )?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    /// Propagated as PEXPIREAT with the expiry applied,
+    /// so the replicas don't count a duration from when they receive the command
+    fn propagated_frame(&self, frame: Frame, _response: &Frame) -> Frame {
+        let when = match self.expires_at() {
+            Some(when) => when,
+            None => return frame,
+        };
+
+        let millis = when
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or_default();
+
+        Expire::new(&self.key, millis, self.condition, true, true).to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::{connect, send, start_server};
+
+    #[test]
+    fn relative_expiry_is_propagated_as_pexpireat() {
+        let expire = Expire::new("key", 100, ExpireCondition::Always, false, false);
+        let millis = expire
+            .received_at
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            + 100_000;
+
+        let frame = expire.propagated_frame(expire.to_frame(), &Frame::Integer(1));
+
+        assert_eq!(
+            frame,
+            Frame::Array(vec![
+                Frame::Bulk("PEXPIREAT".into()),
+                Frame::Bulk("key".into()),
+                Frame::Bulk(millis.to_string().into()),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn replica_ttl_matches_master() {
+        let master = start_server(&[]).await;
+        let master_port = master.port().to_string();
+        let replica = start_server(&["--replicaof", "127.0.0.1", &master_port]).await;
+
+        let master = connect(master).await;
+        send(&master, &["SET", "key", "value"]).await;
+        send(&master, &["EXPIRE", "key", "100"]).await;
+        assert_eq!(
+            send(&master, &["WAIT", "1", "1000"]).await,
+            Frame::Integer(1)
+        );
+
+        let replica = connect(replica).await;
+        let master_ttl = send(&master, &["PTTL", "key"]).await;
+        let replica_ttl = send(&replica, &["PTTL", "key"]).await;
+
+        match (master_ttl, replica_ttl) {
+            (Frame::Integer(master_ttl), Frame::Integer(replica_ttl)) => {
+                assert!(master_ttl > 99_000 && master_ttl <= 100_000);
+                assert!(replica_ttl <= master_ttl && master_ttl - replica_ttl < 100);
+            }
+            ttls => panic!("expected the TTLs, got {ttls:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn collections_can_expire() {
+        let dir = std::env::temp_dir().join(format!("expire-collections-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let client = connect(start_server(&["--dir", dir.to_str().unwrap()]).await).await;
+
+        send(&client, &["RPUSH", "list", "a"]).await;
+        send(&client, &["SADD", "set", "a"]).await;
+        send(&client, &["HSET", "hash", "a", "1"]).await;
+        send(&client, &["ZADD", "zset", "1", "a"]).await;
+
+        let keys = ["list", "set", "hash", "zset"];
+        for key in keys {
+            assert_eq!(
+                send(&client, &["EXPIRE", key, "100"]).await,
+                Frame::Integer(1),
+                "{key}"
+            );
+        }
+
+        // The TTLs are saved and loaded back
+        send(&client, &["DEBUG", "RELOAD"]).await;
+        for key in keys {
+            match send(&client, &["TTL", key]).await {
+                Frame::Integer(ttl) => assert!(ttl > 90 && ttl <= 100, "{key}: {ttl}"),
+                frame => panic!("expected the TTL of {key}, got {frame:?}"),
+            }
+        }
+
+        let payload = match send(&client, &["DUMP", "list"]).await {
+            Frame::Bulk(payload) => payload,
+            frame => panic!("expected the payload, got {frame:?}"),
+        };
+        let restore = Frame::Array(vec![
+            Frame::Bulk("RESTORE".into()),
+            Frame::Bulk("copy".into()),
+            Frame::Bulk("100000".into()),
+            Frame::Bulk(payload),
+        ]);
+        client.write_frame(restore).await.unwrap();
+        assert_eq!(
+            client.read_frame().await.unwrap().unwrap(),
+            Frame::Simple("OK".into())
+        );
+        match send(&client, &["TTL", "copy"]).await {
+            Frame::Integer(ttl) => assert!(ttl > 90 && ttl <= 100),
+            frame => panic!("expected the TTL, got {frame:?}"),
+        }
+
+        // An expiry in the past removes the key
+        assert_eq!(
+            send(&client, &["PEXPIRE", "hash", "-1"]).await,
+            Frame::Integer(1)
+        );
+        assert_eq!(
+            send(&client, &["TYPE", "hash"]).await,
+            Frame::Simple("none".into())
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
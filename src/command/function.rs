@@ -0,0 +1,317 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, glob, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// Scripting engines reported by FUNCTION STATS.
+/// The code of the libraries is stored but not evaluated, so their functions are unknown.
+const ENGINES: [&str; 1] = ["LUA"];
+
+/// What FUNCTION RESTORE does with the existing libraries
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum FunctionRestorePolicy {
+    /// Delete all the existing libraries first
+    Flush,
+    /// Fail if a library already exists
+    #[default]
+    Append,
+    /// Overwrite the existing libraries with the same name
+    Replace,
+}
+
+#[derive(Debug)]
+pub enum Function {
+    /// FUNCTION STATS
+    Stats,
+    /// FUNCTION LIST [LIBRARYNAME pattern] [WITHCODE]
+    List {
+        library_name: Option<String>,
+        with_code: bool,
+    },
+    /// FUNCTION DUMP
+    Dump,
+    /// FUNCTION RESTORE payload [FLUSH | APPEND | REPLACE]
+    Restore {
+        payload: Bytes,
+        policy: FunctionRestorePolicy,
+    },
+}
+
+impl Function {
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Function> {
+        let subcommand = frames.next_string()?;
+
+        match subcommand.to_uppercase().as_str() {
+            "STATS" => Ok(Function::Stats),
+            "LIST" => {
+                let mut library_name = None;
+                let mut with_code = false;
+
+                while let Ok(option) = frames.next_string() {
+                    match option.to_uppercase().as_str() {
+                        "LIBRARYNAME" if library_name.is_none() => {
+                            library_name = Some(frames.next_string()?)
+                        }
+                        "WITHCODE" if !with_code => with_code = true,
+                        _ => return Err("ERR Unknown argument or argument given twice".into()),
+                    }
+                }
+
+                Ok(Function::List {
+                    library_name,
+                    with_code,
+                })
+            }
+            "DUMP" => Ok(Function::Dump),
+            "RESTORE" => {
+                let payload = frames.next_bytes()?;
+                let policy = match frames.next_string() {
+                    Ok(policy) => match policy.to_uppercase().as_str() {
+                        "FLUSH" => FunctionRestorePolicy::Flush,
+                        "APPEND" => FunctionRestorePolicy::Append,
+                        "REPLACE" => FunctionRestorePolicy::Replace,
+                        _ => return Err("ERR Wrong restore policy given, value should be either FLUSH, APPEND or REPLACE.".into()),
+                    },
+                    Err(_) => FunctionRestorePolicy::default(),
+                };
+
+                Ok(Function::Restore { payload, policy })
+            }
+            _ => Err(format!(
+                "ERR unknown subcommand '{}'. Try FUNCTION HELP.",
+                subcommand
+            )
+            .into()),
+        }
+    }
+
+    /// Returns true if the command modifies the libraries, so it has to be propagated
+    pub fn is_write(&self) -> bool {
+        matches!(self, Function::Restore { .. })
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![Frame::Bulk("FUNCTION".into())];
+
+        match self {
+            Function::Stats => frames.push(Frame::Bulk("STATS".into())),
+            Function::List {
+                library_name,
+                with_code,
+            } => {
+                frames.push(Frame::Bulk("LIST".into()));
+                if let Some(library_name) = library_name {
+                    frames.push(Frame::Bulk("LIBRARYNAME".into()));
+                    frames.push(Frame::Bulk(library_name.clone().into()));
+                }
+                if *with_code {
+                    frames.push(Frame::Bulk("WITHCODE".into()));
+                }
+            }
+            Function::Dump => frames.push(Frame::Bulk("DUMP".into())),
+            Function::Restore { payload, policy } => {
+                let policy = match policy {
+                    FunctionRestorePolicy::Flush => "FLUSH",
+                    FunctionRestorePolicy::Append => "APPEND",
+                    FunctionRestorePolicy::Replace => "REPLACE",
+                };
+
+                frames.push(Frame::Bulk("RESTORE".into()));
+                frames.push(Frame::Bulk(payload.clone()));
+                frames.push(Frame::Bulk(policy.into()));
+            }
+        }
+
+        Frame::Array(frames)
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match self {
+            // No script is ever running
+            Function::Stats => {
+                let libraries_count = db.function_libraries().len();
+
+                Frame::Array(vec![
+                    Frame::Bulk("running_script".into()),
+                    Frame::Null,
+                    Frame::Bulk("engines".into()),
+                    Frame::Array(
+                        ENGINES
+                            .iter()
+                            .flat_map(|engine| {
+                                [
+                                    Frame::Bulk((*engine).into()),
+                                    Frame::Array(vec![
+                                        Frame::Bulk("libraries_count".into()),
+                                        Frame::Integer(libraries_count as i64),
+                                        Frame::Bulk("functions_count".into()),
+                                        Frame::Integer(0),
+                                    ]),
+                                ]
+                            })
+                            .collect(),
+                    ),
+                ])
+            }
+            Function::List {
+                library_name,
+                with_code,
+            } => Frame::Array(
+                db.function_libraries()
+                    .into_iter()
+                    .filter(|(name, _)| {
+                        library_name
+                            .as_ref()
+                            .is_none_or(|pattern| glob::matches(pattern, name))
+                    })
+                    .map(|(name, code)| {
+                        let mut library = vec![
+                            Frame::Bulk("library_name".into()),
+                            Frame::Bulk(name.into()),
+                            Frame::Bulk("engine".into()),
+                            Frame::Bulk(ENGINES[0].into()),
+                            Frame::Bulk("functions".into()),
+                            Frame::Array(Vec::new()),
+                        ];
+                        if *with_code {
+                            library.push(Frame::Bulk("library_code".into()));
+                            library.push(Frame::Bulk(code));
+                        }
+
+                        Frame::Array(library)
+                    })
+                    .collect(),
+            ),
+            Function::Dump => Frame::Bulk(db.function_dump()),
+            Function::Restore { payload, policy } => match db.function_restore(payload, *policy) {
+                Ok(()) => Frame::Simple("OK".into()),
+                Err(err) => Frame::Error(err.to_string()),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Function {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Function::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::*;
+    use crate::{
+        server::tests::{connect, send, start_server},
+        Config, RedisDB, Server,
+    };
+
+    const LIBRARY: &str = "#!lua name=mylib\nredis.register_function('f', function() return 1 end)";
+
+    /// Sends FUNCTION RESTORE with a binary payload, which can't go through `send`
+    async fn restore(connection: &Connection, payload: Bytes, policy: &str) -> Frame {
+        let frame = Frame::Array(vec![
+            Frame::Bulk("FUNCTION".into()),
+            Frame::Bulk("RESTORE".into()),
+            Frame::Bulk(payload),
+            Frame::Bulk(Bytes::from(policy.to_string())),
+        ]);
+
+        connection.write_frame(frame).await.unwrap();
+        connection.read_frame().await.unwrap().unwrap()
+    }
+
+    /// Starts a server with the dataset and the libraries of the RDB file in the directory,
+    /// like a restarted one
+    async fn restart_server(dir: &str) -> SocketAddr {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let addr = SocketAddr::from(([127, 0, 0, 1], port));
+        let config = Config::new(
+            ["--port", &port.to_string(), "--dir", dir]
+                .into_iter()
+                .map(String::from),
+        )
+        .unwrap();
+
+        let db = Db::new();
+        let mut rdb = RedisDB::new(format!("{}/dump.rdb", dir));
+        db.load_rdb(rdb.read_rdb().await.unwrap());
+        db.load_functions(rdb.functions());
+
+        tokio::spawn(Server::new(addr, db, config).await.run());
+
+        addr
+    }
+
+    #[tokio::test]
+    async fn dump_and_restore_across_restarts() {
+        let dir = std::env::temp_dir().join(format!("function-restore-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.to_str().unwrap().to_string();
+
+        let client = connect(start_server(&["--dir", &dir]).await).await;
+        let payload = Bytes::from(RedisDB::dump_functions(&[Bytes::from(LIBRARY)]));
+        assert_eq!(
+            restore(&client, payload.clone(), "APPEND").await,
+            Frame::Simple("OK".into())
+        );
+        // Saves the RDB file
+        assert_eq!(
+            send(&client, &["DEBUG", "RELOAD"]).await,
+            Frame::Simple("OK".into())
+        );
+        let dump = send(&client, &["FUNCTION", "DUMP"]).await;
+
+        let client = connect(restart_server(&dir).await).await;
+        assert_eq!(
+            send(&client, &["FUNCTION", "LIST", "WITHCODE"]).await,
+            Frame::Array(vec![Frame::Array(vec![
+                Frame::Bulk("library_name".into()),
+                Frame::Bulk("mylib".into()),
+                Frame::Bulk("engine".into()),
+                Frame::Bulk("LUA".into()),
+                Frame::Bulk("functions".into()),
+                Frame::Array(Vec::new()),
+                Frame::Bulk("library_code".into()),
+                Frame::Bulk(LIBRARY.into()),
+            ])])
+        );
+        assert_eq!(send(&client, &["FUNCTION", "DUMP"]).await, dump);
+
+        assert_eq!(
+            restore(&client, payload.clone(), "APPEND").await,
+            Frame::Error("ERR Library mylib already exists".into())
+        );
+        assert_eq!(
+            restore(&client, payload, "REPLACE").await,
+            Frame::Simple("OK".into())
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -16,7 +16,7 @@ impl Get {
 
     pub fn execute(&self, db: &Db) -> Frame {
         match db.get(&self.key) {
-            Some(Entry::String(entry)) => Frame::Bulk(entry.value().clone()),
+            Some(Entry::String(entry)) => Frame::Bulk(entry.value()),
             Some(_) => Frame::Null,
             None => Frame::Null,
         }
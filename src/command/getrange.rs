@@ -0,0 +1,126 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug, Default)]
+pub struct GetRange {
+    key: String,
+    start: i64,
+    end: i64,
+}
+
+impl GetRange {
+    pub fn new(key: impl ToString, start: i64, end: i64) -> GetRange {
+        GetRange {
+            key: key.to_string(),
+            start,
+            end,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.getrange(&self.key, self.start, self.end) {
+            Ok(value) => Frame::Bulk(value),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<GetRange> {
+        const ERROR_MSG: &str = "ERR value is not an integer or out of range";
+
+        let key = frames.next_string()?;
+        let start = frames.next_int().map_err(|_| ERROR_MSG)?;
+        let end = frames.next_int().map_err(|_| ERROR_MSG)?;
+
+        Ok(GetRange::new(key, start, end))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk("GETRANGE".into()),
+            Frame::Bulk(self.key.clone().into()),
+            Frame::Bulk(self.start.to_string().into()),
+            Frame::Bulk(self.end.to_string().into()),
+        ])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for GetRange {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(GetRange::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    fn db_with_hello() -> Db {
+        let db = Db::new();
+        db.set("key".to_string(), Bytes::from("hello"), None);
+        db
+    }
+
+    fn getrange(db: &Db, start: i64, end: i64) -> Frame {
+        GetRange::new("key", start, end).execute(db)
+    }
+
+    #[tokio::test]
+    async fn start_past_end_of_string_is_empty() {
+        let db = db_with_hello();
+
+        assert_eq!(getrange(&db, 10, 20), Frame::Bulk(Bytes::new()));
+    }
+
+    #[tokio::test]
+    async fn negative_indexes_count_from_end() {
+        let db = db_with_hello();
+
+        assert_eq!(getrange(&db, -3, -1), Frame::Bulk("llo".into()));
+        assert_eq!(getrange(&db, 0, -1), Frame::Bulk("hello".into()));
+    }
+
+    #[tokio::test]
+    async fn out_of_range_indexes_are_clamped() {
+        let db = db_with_hello();
+
+        assert_eq!(getrange(&db, -100, 100), Frame::Bulk("hello".into()));
+        assert_eq!(getrange(&db, 1, 100), Frame::Bulk("ello".into()));
+    }
+
+    #[tokio::test]
+    async fn start_after_end_is_empty() {
+        let db = db_with_hello();
+
+        assert_eq!(getrange(&db, 3, 1), Frame::Bulk(Bytes::new()));
+        assert_eq!(getrange(&db, -1, -3), Frame::Bulk(Bytes::new()));
+    }
+
+    #[tokio::test]
+    async fn missing_key_is_empty() {
+        let db = Db::new();
+
+        assert_eq!(getrange(&db, 0, -1), Frame::Bulk(Bytes::new()));
+    }
+}
@@ -0,0 +1,68 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// GETSET key value, deprecated in favor of SET key value GET
+#[derive(Debug, Default)]
+pub struct GetSet {
+    key: String,
+    value: Bytes,
+}
+
+impl GetSet {
+    pub fn new(key: impl ToString, value: Bytes) -> GetSet {
+        GetSet {
+            key: key.to_string(),
+            value,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.getset(self.key.clone(), self.value.clone()) {
+            Some(value) => Frame::Bulk(value),
+            None => Frame::Null,
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<GetSet> {
+        let key = frames.next_string()?;
+        let value = frames.next_bytes()?;
+
+        Ok(GetSet::new(key, value))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk("GETSET".into()),
+            Frame::Bulk(self.key.clone().into()),
+            Frame::Bulk(self.value.clone()),
+        ])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for GetSet {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(GetSet::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
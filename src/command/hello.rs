@@ -0,0 +1,95 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, parse, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// HELLO [protover], switches the protocol of the connection
+/// AUTH and SETNAME are not supported
+#[derive(Debug, Default)]
+pub struct Hello {
+    protocol: Option<u8>,
+}
+
+impl Hello {
+    pub fn new(protocol: Option<u8>) -> Hello {
+        Hello { protocol }
+    }
+
+    /// Replies with the server properties, as a flat list of names and values
+    pub fn execute(&self, server_info: &Info, connection: Connection) -> Frame {
+        let mut session = connection.session();
+
+        if let Some(protocol) = self.protocol {
+            session.set_protocol(protocol);
+        }
+
+        let role = match server_info.role().is_master() {
+            true => "master",
+            false => "replica",
+        };
+
+        Frame::Array(vec![
+            Frame::Bulk(Bytes::from("server")),
+            Frame::Bulk(Bytes::from("redis")),
+            Frame::Bulk(Bytes::from("version")),
+            Frame::Bulk(Bytes::from("7.2.0")),
+            Frame::Bulk(Bytes::from("proto")),
+            Frame::Integer(session.protocol() as i64),
+            Frame::Bulk(Bytes::from("mode")),
+            Frame::Bulk(Bytes::from("standalone")),
+            Frame::Bulk(Bytes::from("role")),
+            Frame::Bulk(Bytes::from(role)),
+            Frame::Bulk(Bytes::from("modules")),
+            Frame::Array(Vec::new()),
+        ])
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Hello> {
+        let protocol = match frames.next_string() {
+            Ok(protocol) => match protocol.as_str() {
+                "2" => Some(2),
+                "3" => Some(3),
+                _ => return Err("NOPROTO unsupported protocol version".into()),
+            },
+            Err(parse::Error::EndOfStream) => None,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Hello::new(protocol))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![Frame::Bulk(Bytes::from("HELLO"))];
+
+        if let Some(protocol) = self.protocol {
+            frames.push(Frame::Bulk(Bytes::from(protocol.to_string())));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Hello {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Hello::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, _db: &Db, server_info: &mut Info, connection: Connection) -> Frame {
+        self.execute(server_info, connection)
+    }
+
+    fn execute_replica(&self, _db: &Db, server_info: &mut Info, connection: Connection) -> Frame {
+        self.execute(server_info, connection)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
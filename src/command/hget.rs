@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug, Default)]
+pub struct HGet {
+    key: String,
+    field: String,
+}
+
+impl HGet {
+    pub fn new(key: impl ToString, field: impl ToString) -> HGet {
+        HGet {
+            key: key.to_string(),
+            field: field.to_string(),
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.hget(&self.key, &self.field) {
+            Ok(Some(value)) => Frame::Bulk(value),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<HGet> {
+        let key = frames.next_string()?;
+        let field = frames.next_string()?;
+
+        Ok(HGet::new(key, field))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk("HGET".into()),
+            Frame::Bulk(self.key.clone().into()),
+            Frame::Bulk(self.field.clone().into()),
+        ])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for HGet {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(HGet::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,147 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, parse, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug, Default)]
+pub struct HRandField {
+    key: String,
+    /// The reply is a single bulk without count and an array with it,
+    /// even for `HRANDFIELD key 1`
+    count: Option<i64>,
+    with_values: bool,
+}
+
+impl HRandField {
+    pub fn new(key: impl ToString, count: Option<i64>, with_values: bool) -> HRandField {
+        HRandField {
+            key: key.to_string(),
+            count,
+            with_values,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        let fields = match db.hrandfield(&self.key, self.count.unwrap_or(1)) {
+            Ok(fields) => fields,
+            Err(err) => return Frame::Error(err.to_string()),
+        };
+
+        if self.count.is_none() {
+            return fields
+                .into_iter()
+                .next()
+                .map(|(field, _)| Frame::Bulk(field.into()))
+                .unwrap_or(Frame::Null);
+        }
+
+        let mut frames = Vec::new();
+        for (field, value) in fields {
+            frames.push(Frame::Bulk(field.into()));
+
+            if self.with_values {
+                frames.push(Frame::Bulk(value));
+            }
+        }
+
+        Frame::Array(frames)
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<HRandField> {
+        let key = frames.next_string()?;
+
+        let count = match frames.next_int() {
+            Ok(count) => Some(count),
+            Err(parse::Error::EndOfStream) => return Ok(HRandField::new(key, None, false)),
+            Err(err) => return Err(err.into()),
+        };
+
+        let with_values = match frames.next_string() {
+            Ok(s) if s.to_uppercase() == "WITHVALUES" => true,
+            Ok(_) => return Err("ERR syntax error".into()),
+            Err(parse::Error::EndOfStream) => false,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(HRandField::new(key, count, with_values))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("HRANDFIELD".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ];
+
+        if let Some(count) = self.count {
+            frames.push(Frame::Bulk(count.to_string().into()));
+        }
+
+        if self.with_values {
+            frames.push(Frame::Bulk("WITHVALUES".into()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for HRandField {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(HRandField::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    fn hash(db: &Db) {
+        db.hset(
+            "myhash".to_string(),
+            vec![("a".to_string(), Bytes::from("1"))],
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn count_presence_selects_reply_type() {
+        let db = Db::new();
+        hash(&db);
+
+        let reply = HRandField::new("myhash", None, false).execute(&db);
+        assert_eq!(reply, Frame::Bulk("a".into()));
+
+        let reply = HRandField::new("myhash", Some(1), false).execute(&db);
+        assert_eq!(reply, Frame::Array(vec![Frame::Bulk("a".into())]));
+    }
+
+    #[tokio::test]
+    async fn too_large_negative_count_is_out_of_range() {
+        let db = Db::new();
+        hash(&db);
+
+        for with_values in [false, true] {
+            let reply = HRandField::new("myhash", Some(i64::MIN), with_values).execute(&db);
+            assert_eq!(reply, Frame::Error("ERR value is out of range".into()));
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::{scan::ScanArgs, CommandTrait};
+
+/// HSCAN key cursor [MATCH pattern] [COUNT count] [NOVALUES]
+#[derive(Debug)]
+pub struct HScan {
+    key: String,
+    args: ScanArgs,
+    /// NOVALUES, only the fields are returned
+    no_values: bool,
+}
+
+impl HScan {
+    pub fn new(key: impl ToString, args: ScanArgs, no_values: bool) -> HScan {
+        HScan {
+            key: key.to_string(),
+            args,
+            no_values,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        let (cursor, fields) = match db.hscan(&self.key, self.args.cursor, self.args.count) {
+            Ok(page) => page,
+            Err(err) => return Frame::Error(err.to_string()),
+        };
+
+        let mut frames = Vec::new();
+        for (field, value) in fields
+            .into_iter()
+            .filter(|(field, _)| self.args.matches(field))
+        {
+            frames.push(Frame::Bulk(field.into()));
+            if !self.no_values {
+                frames.push(Frame::Bulk(value));
+            }
+        }
+
+        Frame::Array(vec![
+            Frame::Bulk(cursor.to_string().into()),
+            Frame::Array(frames),
+        ])
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<HScan> {
+        let key = frames.next_string()?;
+        let mut no_values = false;
+
+        let args = ScanArgs::parse_frames(frames, |option, _| match option {
+            "NOVALUES" => {
+                no_values = true;
+                Ok(true)
+            }
+            _ => Ok(false),
+        })?;
+
+        Ok(HScan::new(key, args, no_values))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("HSCAN".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ];
+        frames.extend(self.args.to_frames());
+
+        if self.no_values {
+            frames.push(Frame::Bulk("NOVALUES".into()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for HScan {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(HScan::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bytes::Bytes;
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> HScan {
+        let frame = Frame::Array(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+
+        HScan::parse_frames(&mut Parse::new(frame).unwrap()).unwrap()
+    }
+
+    /// Returns the next cursor and the items of a reply
+    fn page(frame: Frame) -> (String, Vec<Bytes>) {
+        let mut frames = frame
+            .into_array()
+            .unwrap()
+            .into_iter()
+            .map(|frame| match frame {
+                Frame::Bulk(bytes) => vec![bytes],
+                Frame::Array(items) => items
+                    .into_iter()
+                    .map(|item| match item {
+                        Frame::Bulk(item) => item,
+                        item => panic!("expected a bulk item, got {item:?}"),
+                    })
+                    .collect(),
+                frame => panic!("unexpected frame {frame:?}"),
+            });
+
+        let cursor = frames.next().unwrap().remove(0);
+        let items = frames.next().unwrap();
+
+        (String::from_utf8(cursor.to_vec()).unwrap(), items)
+    }
+
+    fn hash(len: usize) -> Db {
+        let db = Db::new();
+        let fields = (0..len)
+            .map(|i| (format!("field{i}"), Bytes::from(format!("value{i}"))))
+            .collect();
+        db.hset("hash".to_string(), fields).unwrap();
+
+        db
+    }
+
+    #[tokio::test]
+    async fn cursor_iterates_the_whole_hash() {
+        let db = hash(200);
+
+        let mut fields = HashMap::new();
+        let mut cursor = "0".to_string();
+        let mut calls = 0;
+        loop {
+            let (next, items) = page(parse(&["hash", &cursor, "COUNT", "20"]).execute(&db));
+            for pair in items.chunks(2) {
+                fields.insert(pair[0].clone(), pair[1].clone());
+            }
+            calls += 1;
+
+            cursor = next;
+            if cursor == "0" {
+                break;
+            }
+        }
+
+        assert!(calls > 1);
+        assert_eq!(fields.len(), 200);
+        for i in 0..200 {
+            assert_eq!(
+                fields[&Bytes::from(format!("field{i}"))],
+                Bytes::from(format!("value{i}"))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn novalues_returns_only_the_fields() {
+        let db = hash(5);
+
+        let (cursor, items) = page(parse(&["hash", "0"]).execute(&db));
+        let (no_values_cursor, fields) = page(parse(&["hash", "0", "NOVALUES"]).execute(&db));
+
+        assert_eq!(cursor, "0");
+        assert_eq!(no_values_cursor, "0");
+        assert_eq!(items.len(), 10);
+        assert_eq!(
+            fields,
+            items
+                .chunks(2)
+                .map(|pair| pair[0].clone())
+                .collect::<Vec<_>>()
+        );
+    }
+}
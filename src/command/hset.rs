@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug, Default)]
+pub struct HSet {
+    key: String,
+    fields: Vec<(String, Bytes)>,
+}
+
+impl HSet {
+    pub fn new(key: impl ToString, fields: Vec<(String, Bytes)>) -> HSet {
+        HSet {
+            key: key.to_string(),
+            fields,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.hset(self.key.clone(), self.fields.clone()) {
+            Ok(added) => Frame::Integer(added as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<HSet> {
+        let key = frames.next_string()?;
+        let mut fields = vec![(frames.next_string()?, frames.next_bytes()?)];
+
+        while let Ok(field) = frames.next_string() {
+            let value = frames.next_bytes()?;
+            fields.push((field, value));
+        }
+
+        Ok(HSet::new(key, fields))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("HSET".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ];
+
+        for (field, value) in &self.fields {
+            frames.push(Frame::Bulk(field.clone().into()));
+            frames.push(Frame::Bulk(value.clone()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for HSet {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(HSet::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,102 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug, Default)]
+pub struct HSetNx {
+    key: String,
+    field: String,
+    value: Bytes,
+}
+
+impl HSetNx {
+    pub fn new(key: impl ToString, field: impl ToString, value: Bytes) -> HSetNx {
+        HSetNx {
+            key: key.to_string(),
+            field: field.to_string(),
+            value,
+        }
+    }
+
+    /// Replies with 1 if the field was set, 0 if it already existed
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.hsetnx(self.key.clone(), self.field.clone(), self.value.clone()) {
+            Ok(set) => Frame::Integer(set as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<HSetNx> {
+        let key = frames.next_string()?;
+        let field = frames.next_string()?;
+        let value = frames.next_bytes()?;
+
+        Ok(HSetNx::new(key, field, value))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk("HSETNX".into()),
+            Frame::Bulk(self.key.clone().into()),
+            Frame::Bulk(self.field.clone().into()),
+            Frame::Bulk(self.value.clone()),
+        ])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for HSetNx {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(HSetNx::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::WRONGTYPE_ERR;
+
+    #[tokio::test]
+    async fn existing_field_is_not_overwritten() {
+        let db = Db::new();
+
+        let first = HSetNx::new("hash", "field", Bytes::from("first"));
+        assert_eq!(first.execute(&db), Frame::Integer(1));
+
+        let second = HSetNx::new("hash", "field", Bytes::from("second"));
+        assert_eq!(second.execute(&db), Frame::Integer(0));
+        assert_eq!(
+            db.hget("hash", "field").unwrap(),
+            Some(Bytes::from("first"))
+        );
+    }
+
+    #[tokio::test]
+    async fn non_hash_value_is_wrongtype() {
+        let db = Db::new();
+        db.set("key".to_string(), Bytes::from("value"), None);
+
+        let hsetnx = HSetNx::new("key", "field", Bytes::from("value"));
+        assert_eq!(hsetnx.execute(&db), Frame::Error(WRONGTYPE_ERR.to_string()));
+    }
+}
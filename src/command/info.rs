@@ -1,30 +1,125 @@
+use std::collections::BTreeSet;
+
 use async_trait::async_trait;
 
 use crate::{connection::Connection, parse::Error, Db, Frame, Info as ServerInfo, Parse};
 
 use super::CommandTrait;
 
+/// Sections of the INFO reply, in the order they are rendered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum InfoSection {
+    Server,
+    Clients,
+    Replication,
+    CommandStats,
+    LatencyStats,
+}
+
+impl InfoSection {
+    /// Sections rendered without arguments or with `default`
+    const DEFAULT: [InfoSection; 3] = [
+        InfoSection::Server,
+        InfoSection::Clients,
+        InfoSection::Replication,
+    ];
+
+    /// Sections rendered with `all`
+    const ALL: [InfoSection; 4] = [
+        InfoSection::Server,
+        InfoSection::Clients,
+        InfoSection::Replication,
+        InfoSection::CommandStats,
+    ];
+
+    /// All the supported sections, rendered with `everything`
+    const EVERYTHING: [InfoSection; 5] = [
+        InfoSection::Server,
+        InfoSection::Clients,
+        InfoSection::Replication,
+        InfoSection::CommandStats,
+        InfoSection::LatencyStats,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            InfoSection::Server => "server",
+            InfoSection::Clients => "clients",
+            InfoSection::Replication => "replication",
+            InfoSection::CommandStats => "commandstats",
+            InfoSection::LatencyStats => "latencystats",
+        }
+    }
+
+    fn render(&self, server_info: &ServerInfo) -> String {
+        match self {
+            InfoSection::Server => server_info.server_section(),
+            InfoSection::Clients => server_info.clients_section(),
+            InfoSection::Replication => server_info.replication_section(),
+            InfoSection::CommandStats => server_info.command_stats(),
+            InfoSection::LatencyStats => server_info.latency_stats(),
+        }
+    }
+}
+
 #[derive(Debug, Default)]
-pub struct Info {}
+pub struct Info {
+    sections: BTreeSet<InfoSection>,
+}
 
 impl Info {
+    /// Collects the requested sections, each rendered once,
+    /// the unknown sections are ignored like in Redis
     pub fn parse_frames(frames: &mut Parse) -> crate::Result<Info> {
-        match frames.next_string() {
-            Ok(section) => match section.as_str().to_lowercase().as_str() {
-                "replication" => Ok(Info {}),
-                _ => Err(format!("Protocol error: unsupported INFO section: {}", section).into()),
-            },
-            Err(Error::EndOfStream) => Ok(Info {}),
-            Err(err) => Err(err.into()),
+        let mut sections = BTreeSet::new();
+        let mut has_args = false;
+
+        loop {
+            let section = match frames.next_string() {
+                Ok(section) => section.to_lowercase(),
+                Err(Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+            has_args = true;
+
+            match section.as_str() {
+                "default" => sections.extend(InfoSection::DEFAULT),
+                "all" => sections.extend(InfoSection::ALL),
+                "everything" => sections.extend(InfoSection::EVERYTHING),
+                section => sections.extend(
+                    InfoSection::EVERYTHING
+                        .into_iter()
+                        .find(|known| known.name() == section),
+                ),
+            }
+        }
+
+        if !has_args {
+            sections.extend(InfoSection::DEFAULT);
         }
+
+        Ok(Info { sections })
     }
 
     pub fn to_frame(&self) -> Frame {
-        Frame::Simple("INFO".into())
+        let mut frames = vec![Frame::Bulk("INFO".into())];
+        for section in &self.sections {
+            frames.push(Frame::Bulk(section.name().into()));
+        }
+
+        Frame::Array(frames)
     }
 
+    /// Renders the sections separated by an empty line
     pub fn execute(&self, server_info: &mut ServerInfo) -> Frame {
-        Frame::Bulk(bytes::Bytes::from(server_info.to_string()))
+        let info = self
+            .sections
+            .iter()
+            .map(|section| section.render(server_info))
+            .collect::<Vec<_>>()
+            .join("\r\n");
+
+        Frame::Bulk(bytes::Bytes::from(info))
     }
 }
 
@@ -60,3 +155,28 @@ impl CommandTrait for Info {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        server::tests::{connect, send, start_server},
+        Frame,
+    };
+
+    #[tokio::test]
+    async fn replication_and_server_sections_together() {
+        let addr = start_server(&[]).await;
+        let client = connect(addr).await;
+
+        let info = match send(&client, &["INFO", "replication", "server"]).await {
+            Frame::Bulk(info) => String::from_utf8(info.to_vec()).unwrap(),
+            frame => panic!("unexpected frame {:?}", frame),
+        };
+
+        let server = info.find("# Server").expect("server section");
+        let replication = info.find("# Replication").expect("replication section");
+        assert!(server < replication);
+        assert!(info.contains("role:master"));
+        assert!(!info.contains("# Clients"));
+    }
+}
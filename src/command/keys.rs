@@ -1,34 +1,44 @@
 use async_trait::async_trait;
 use bytes::Bytes;
 
-use crate::{connection::Connection, Db, Frame, Info, Parse};
+use crate::{connection::Connection, glob, Db, Frame, Info, Parse};
 
 use super::CommandTrait;
 
+/// KEYS pattern, with the glob-style patterns of [`glob::matches`]
 #[derive(Debug, Default)]
-pub struct Keys {}
+pub struct Keys {
+    pattern: String,
+}
 
 impl Keys {
+    pub fn new(pattern: impl ToString) -> Keys {
+        Keys {
+            pattern: pattern.to_string(),
+        }
+    }
+
     pub fn execute(&self, db: &Db) -> Frame {
         Frame::Array(
             db.keys()
                 .iter()
+                .filter(|k| glob::matches(&self.pattern, k))
                 .map(|k| Frame::Bulk(Bytes::from(k.clone())))
                 .collect(),
         )
     }
 
     pub fn parse_frames(frames: &mut Parse) -> crate::Result<Keys> {
-        let key = frames.next_string()?;
+        let pattern = frames.next_string()?;
 
-        match key.as_str() {
-            "*" => Ok(Keys {}),
-            _ => Err("Protocol error: expected *".into()),
-        }
+        Ok(Keys::new(pattern))
     }
 
     pub fn to_frame(&self) -> Frame {
-        Frame::Array(vec![Frame::Bulk("KEYS".into()), Frame::Bulk("*".into())])
+        Frame::Array(vec![
+            Frame::Bulk("KEYS".into()),
+            Frame::Bulk(self.pattern.clone().into()),
+        ])
     }
 }
 
@@ -0,0 +1,249 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, parse, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// LCS key1 key2 [LEN] [IDX] [MINMATCHLEN min-match-len] [WITHMATCHLEN]
+#[derive(Debug, Default)]
+pub struct Lcs {
+    key1: String,
+    key2: String,
+    /// Reply with the length of the subsequence only
+    len: bool,
+    /// Reply with the ranges of the matches
+    idx: bool,
+    /// Matches shorter than this are not reported with IDX
+    min_match_len: usize,
+    with_match_len: bool,
+}
+
+impl Lcs {
+    pub fn new(
+        key1: impl ToString,
+        key2: impl ToString,
+        len: bool,
+        idx: bool,
+        min_match_len: usize,
+        with_match_len: bool,
+    ) -> Lcs {
+        Lcs {
+            key1: key1.to_string(),
+            key2: key2.to_string(),
+            len,
+            idx,
+            min_match_len,
+            with_match_len,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        let lcs = match db.lcs(&self.key1, &self.key2) {
+            Ok(lcs) => lcs,
+            Err(err) => return Frame::Error(err.to_string()),
+        };
+
+        if self.len {
+            return Frame::Integer(lcs.sequence.len() as i64);
+        }
+        if !self.idx {
+            return Frame::Bulk(lcs.sequence);
+        }
+
+        let range = |(start, end): (usize, usize)| {
+            Frame::Array(vec![
+                Frame::Integer(start as i64),
+                Frame::Integer(end as i64),
+            ])
+        };
+
+        let matches = lcs
+            .matches
+            .into_iter()
+            .filter(|((start, end), _)| end - start + 1 >= self.min_match_len)
+            .map(|(a, b)| {
+                let mut frames = vec![range(a), range(b)];
+                if self.with_match_len {
+                    frames.push(Frame::Integer((a.1 - a.0 + 1) as i64));
+                }
+
+                Frame::Array(frames)
+            })
+            .collect();
+
+        Frame::Array(vec![
+            Frame::Bulk(Bytes::from("matches")),
+            Frame::Array(matches),
+            Frame::Bulk(Bytes::from("len")),
+            Frame::Integer(lcs.sequence.len() as i64),
+        ])
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Lcs> {
+        let key1 = frames.next_string()?;
+        let key2 = frames.next_string()?;
+
+        let mut len = false;
+        let mut idx = false;
+        let mut min_match_len = 0;
+        let mut with_match_len = false;
+
+        loop {
+            let option = match frames.next_string() {
+                Ok(option) => option.to_uppercase(),
+                Err(parse::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            match option.as_str() {
+                "LEN" => len = true,
+                "IDX" => idx = true,
+                "MINMATCHLEN" => {
+                    // Negative lengths match everything
+                    min_match_len = usize::try_from(frames.next_int()?).unwrap_or(0);
+                }
+                "WITHMATCHLEN" => with_match_len = true,
+                _ => return Err("ERR syntax error".into()),
+            }
+        }
+
+        if len && idx {
+            return Err("ERR If you want both the length and indexes, please just use IDX.".into());
+        }
+
+        Ok(Lcs::new(
+            key1,
+            key2,
+            len,
+            idx,
+            min_match_len,
+            with_match_len,
+        ))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("LCS".into()),
+            Frame::Bulk(self.key1.clone().into()),
+            Frame::Bulk(self.key2.clone().into()),
+        ];
+
+        if self.len {
+            frames.push(Frame::Bulk("LEN".into()));
+        }
+        if self.idx {
+            frames.push(Frame::Bulk("IDX".into()));
+        }
+        if self.min_match_len > 0 {
+            frames.push(Frame::Bulk("MINMATCHLEN".into()));
+            frames.push(Frame::Bulk(self.min_match_len.to_string().into()));
+        }
+        if self.with_match_len {
+            frames.push(Frame::Bulk("WITHMATCHLEN".into()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Lcs {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Lcs::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db() -> Db {
+        let db = Db::new();
+        db.set("key1".to_string(), Bytes::from("ohmytext"), None);
+        db.set("key2".to_string(), Bytes::from("mynewtext"), None);
+
+        db
+    }
+
+    fn range(start: i64, end: i64) -> Frame {
+        Frame::Array(vec![Frame::Integer(start), Frame::Integer(end)])
+    }
+
+    #[tokio::test]
+    async fn plain_lcs_is_the_subsequence() {
+        let db = db();
+
+        let lcs = Lcs::new("key1", "key2", false, false, 0, false);
+        assert_eq!(lcs.execute(&db), Frame::Bulk("mytext".into()));
+    }
+
+    #[tokio::test]
+    async fn len_is_the_length_of_the_subsequence() {
+        let db = db();
+
+        let lcs = Lcs::new("key1", "key2", true, false, 0, false);
+        assert_eq!(lcs.execute(&db), Frame::Integer(6));
+    }
+
+    #[tokio::test]
+    async fn idx_reports_the_matches_from_the_end() {
+        let db = db();
+
+        let lcs = Lcs::new("key1", "key2", false, true, 4, true);
+        assert_eq!(
+            lcs.execute(&db),
+            Frame::Array(vec![
+                Frame::Bulk("matches".into()),
+                Frame::Array(vec![Frame::Array(vec![
+                    range(4, 7),
+                    range(5, 8),
+                    Frame::Integer(4),
+                ])]),
+                Frame::Bulk("len".into()),
+                Frame::Integer(6),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_keys_are_empty_strings() {
+        let db = db();
+
+        let lcs = Lcs::new("key1", "missing", false, false, 0, false);
+        assert_eq!(lcs.execute(&db), Frame::Bulk("".into()));
+    }
+
+    #[tokio::test]
+    async fn too_large_table_is_rejected() {
+        let db = Db::new();
+        let value = Bytes::from(vec![b'a'; 20_000]);
+        db.set("key1".to_string(), value.clone(), None);
+        db.set("key2".to_string(), value, None);
+
+        let lcs = Lcs::new("key1", "key2", true, false, 0, false);
+        assert_eq!(
+            lcs.execute(&db),
+            Frame::Error(
+                "ERR Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len"
+                    .into()
+            )
+        );
+    }
+}
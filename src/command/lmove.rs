@@ -0,0 +1,197 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// End of a list
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ListDirection {
+    /// Head of the list
+    #[default]
+    Left,
+    /// Tail of the list
+    Right,
+}
+
+impl ListDirection {
+    pub fn parse(direction: &str) -> crate::Result<ListDirection> {
+        match direction.to_uppercase().as_str() {
+            "LEFT" => Ok(ListDirection::Left),
+            "RIGHT" => Ok(ListDirection::Right),
+            _ => Err("ERR syntax error".into()),
+        }
+    }
+}
+
+impl std::fmt::Display for ListDirection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListDirection::Left => write!(f, "LEFT"),
+            ListDirection::Right => write!(f, "RIGHT"),
+        }
+    }
+}
+
+/// LMOVE source destination LEFT | RIGHT LEFT | RIGHT,
+/// and RPOPLPUSH source destination, the same as LMOVE source destination RIGHT LEFT
+#[derive(Debug, Default)]
+pub struct LMove {
+    source: String,
+    destination: String,
+    from: ListDirection,
+    to: ListDirection,
+}
+
+impl LMove {
+    pub fn new(
+        source: impl ToString,
+        destination: impl ToString,
+        from: ListDirection,
+        to: ListDirection,
+    ) -> LMove {
+        LMove {
+            source: source.to_string(),
+            destination: destination.to_string(),
+            from,
+            to,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.lmove(&self.source, self.destination.clone(), self.from, self.to) {
+            Ok(Some(value)) => Frame::Bulk(value),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<LMove> {
+        let source = frames.next_string()?;
+        let destination = frames.next_string()?;
+        let from = ListDirection::parse(&frames.next_string()?)?;
+        let to = ListDirection::parse(&frames.next_string()?)?;
+
+        Ok(LMove::new(source, destination, from, to))
+    }
+
+    pub fn parse_rpoplpush(frames: &mut Parse) -> crate::Result<LMove> {
+        let source = frames.next_string()?;
+        let destination = frames.next_string()?;
+
+        Ok(LMove::new(
+            source,
+            destination,
+            ListDirection::Right,
+            ListDirection::Left,
+        ))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk("LMOVE".into()),
+            Frame::Bulk(self.source.clone().into()),
+            Frame::Bulk(self.destination.clone().into()),
+            Frame::Bulk(self.from.to_string().into()),
+            Frame::Bulk(self.to.to_string().into()),
+        ])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for LMove {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(LMove::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    /// RPOPLPUSH is propagated as the equivalent LMOVE
+    fn propagated_frame(&self, _frame: Frame, _response: &Frame) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    fn list(db: &Db, key: &str, values: &[&str]) {
+        let values = values
+            .iter()
+            .map(|value| Bytes::from(value.to_string()))
+            .collect();
+
+        db.push(key.to_string(), ListDirection::Right, values)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn single_list_is_rotated() {
+        let db = Db::new();
+        list(&db, "list", &["a", "b", "c"]);
+
+        let lmove = LMove::new("list", "list", ListDirection::Right, ListDirection::Left);
+        assert_eq!(lmove.execute(&db), Frame::Bulk("c".into()));
+        assert_eq!(db.lrange("list", 0, -1).unwrap(), ["c", "a", "b"]);
+
+        let lmove = LMove::new("list", "list", ListDirection::Left, ListDirection::Right);
+        assert_eq!(lmove.execute(&db), Frame::Bulk("c".into()));
+        assert_eq!(db.lrange("list", 0, -1).unwrap(), ["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn element_is_moved_between_lists() {
+        let db = Db::new();
+        list(&db, "source", &["a", "b"]);
+        list(&db, "destination", &["x"]);
+
+        let lmove = LMove::new(
+            "source",
+            "destination",
+            ListDirection::Left,
+            ListDirection::Right,
+        );
+        assert_eq!(lmove.execute(&db), Frame::Bulk("a".into()));
+        assert_eq!(db.lrange("source", 0, -1).unwrap(), ["b"]);
+        assert_eq!(db.lrange("destination", 0, -1).unwrap(), ["x", "a"]);
+
+        // The source is removed with its last element
+        assert_eq!(lmove.execute(&db), Frame::Bulk("b".into()));
+        assert_eq!(db.get_type("source"), "none");
+        assert_eq!(db.lrange("destination", 0, -1).unwrap(), ["x", "a", "b"]);
+
+        assert_eq!(lmove.execute(&db), Frame::Null);
+    }
+
+    #[tokio::test]
+    async fn wrong_type_destination_is_rejected() {
+        let db = Db::new();
+        list(&db, "source", &["a"]);
+        db.set("string".to_string(), Bytes::from("value"), None);
+
+        let lmove = LMove::new("source", "string", ListDirection::Left, ListDirection::Left);
+        assert_eq!(
+            lmove.execute(&db),
+            Frame::Error(crate::db::WRONGTYPE_ERR.to_string())
+        );
+        assert_eq!(db.lrange("source", 0, -1).unwrap(), ["a"]);
+    }
+}
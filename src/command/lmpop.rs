@@ -0,0 +1,192 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, parse, Db, Frame, Info, Parse};
+
+use super::{CommandTrait, ListDirection};
+
+/// LMPOP numkeys key [key ...] LEFT | RIGHT [COUNT count]
+#[derive(Debug, Default)]
+pub struct LMPop {
+    keys: Vec<String>,
+    direction: ListDirection,
+    count: u64,
+}
+
+impl LMPop {
+    pub fn new(keys: Vec<String>, direction: ListDirection, count: u64) -> LMPop {
+        LMPop {
+            keys,
+            direction,
+            count,
+        }
+    }
+
+    /// Replies with the key popped from and its popped elements,
+    /// or null if all the lists are empty
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.lmpop(&self.keys, self.direction, self.count as usize) {
+            Ok(Some((key, values))) => Frame::Array(vec![
+                Frame::Bulk(key.into()),
+                Frame::Array(values.into_iter().map(Frame::Bulk).collect()),
+            ]),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<LMPop> {
+        let numkeys = match frames.next_int()? {
+            numkeys if numkeys <= 0 => return Err("ERR numkeys should be greater than 0".into()),
+            numkeys => numkeys,
+        };
+
+        let mut keys = Vec::with_capacity(numkeys as usize);
+        for _ in 0..numkeys {
+            keys.push(frames.next_string()?);
+        }
+
+        let direction = ListDirection::parse(&frames.next_string()?)?;
+
+        let count = match frames.next_string() {
+            Ok(option) if option.to_uppercase() == "COUNT" => match frames.next_int()? {
+                count if count <= 0 => return Err("ERR count should be greater than 0".into()),
+                count => count as u64,
+            },
+            Ok(_) => return Err("ERR syntax error".into()),
+            Err(parse::Error::EndOfStream) => 1,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(LMPop::new(keys, direction, count))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("LMPOP".into()),
+            Frame::Bulk(self.keys.len().to_string().into()),
+        ];
+
+        for key in &self.keys {
+            frames.push(Frame::Bulk(key.clone().into()));
+        }
+
+        frames.push(Frame::Bulk(self.direction.to_string().into()));
+        frames.push(Frame::Bulk("COUNT".into()));
+        frames.push(Frame::Bulk(self.count.to_string().into()));
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for LMPop {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(LMPop::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::db::WRONGTYPE_ERR;
+
+    fn list(db: &Db, key: &str, values: &[&str]) {
+        let values = values
+            .iter()
+            .map(|value| Bytes::from(value.to_string()))
+            .collect();
+
+        db.push(key.to_string(), ListDirection::Right, values)
+            .unwrap();
+    }
+
+    fn lmpop(keys: &[&str], direction: ListDirection, count: u64) -> LMPop {
+        let keys = keys.iter().map(|key| key.to_string()).collect();
+
+        LMPop::new(keys, direction, count)
+    }
+
+    fn popped(key: &str, values: &[&str]) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk(Bytes::from(key.to_string())),
+            Frame::Array(
+                values
+                    .iter()
+                    .map(|value| Frame::Bulk(Bytes::from(value.to_string())))
+                    .collect(),
+            ),
+        ])
+    }
+
+    #[tokio::test]
+    async fn single_missing_key_is_null() {
+        let db = Db::new();
+
+        assert_eq!(
+            lmpop(&["missing"], ListDirection::Left, 1).execute(&db),
+            Frame::Null
+        );
+    }
+
+    #[tokio::test]
+    async fn emptied_lists_are_null() {
+        let db = Db::new();
+        list(&db, "a", &["1"]);
+        list(&db, "b", &["2"]);
+        let pop = lmpop(&["a", "b"], ListDirection::Left, 10);
+
+        assert_eq!(pop.execute(&db), popped("a", &["1"]));
+        assert_eq!(pop.execute(&db), popped("b", &["2"]));
+        // The lists are deleted with their last element
+        assert_eq!(db.get_type("a"), "none");
+        assert_eq!(db.get_type("b"), "none");
+        assert_eq!(pop.execute(&db), Frame::Null);
+    }
+
+    #[tokio::test]
+    async fn missing_keys_are_skipped() {
+        let db = Db::new();
+        list(&db, "list", &["1", "2", "3"]);
+
+        assert_eq!(
+            lmpop(&["missing", "list", "other"], ListDirection::Right, 2).execute(&db),
+            popped("list", &["3", "2"])
+        );
+        assert_eq!(db.lrange("list", 0, -1).unwrap(), vec![Bytes::from("1")]);
+    }
+
+    #[tokio::test]
+    async fn non_list_before_a_list_is_wrongtype() {
+        let db = Db::new();
+        db.set("string".to_string(), Bytes::from("value"), None);
+        list(&db, "list", &["1"]);
+
+        assert_eq!(
+            lmpop(&["string", "list"], ListDirection::Left, 1).execute(&db),
+            Frame::Error(WRONGTYPE_ERR.to_string())
+        );
+        assert_eq!(
+            lmpop(&["list", "string"], ListDirection::Left, 1).execute(&db),
+            popped("list", &["1"])
+        );
+    }
+}
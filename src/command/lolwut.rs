@@ -0,0 +1,213 @@
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, parse, random::SeededRng, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+const VERSION_LINE: &str = "Redis ver. 7.2.0\n";
+
+/// LOLWUT [VERSION version] [columns] [rows]
+/// Version 5, the default, draws a composition in the style of Piet Mondrian,
+/// other versions only reply with the server version
+#[derive(Debug, Default)]
+pub struct Lolwut {
+    version: Option<u64>,
+    columns: Option<usize>,
+    rows: Option<usize>,
+}
+
+/// Colors of the cells of the composition
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Color {
+    White,
+    Black,
+    Red,
+    Blue,
+    Yellow,
+}
+
+impl Color {
+    /// ANSI escape code setting the foreground color
+    fn ansi(self) -> &'static str {
+        match self {
+            Color::White => "\x1b[97m",
+            Color::Black => "\x1b[30m",
+            Color::Red => "\x1b[31m",
+            Color::Blue => "\x1b[34m",
+            Color::Yellow => "\x1b[33m",
+        }
+    }
+}
+
+impl Lolwut {
+    pub fn new(version: Option<u64>, columns: Option<usize>, rows: Option<usize>) -> Lolwut {
+        Lolwut {
+            version,
+            columns,
+            rows,
+        }
+    }
+
+    /// Draws the composition, the same one for a given server run
+    pub fn execute(&self, server_info: &Info) -> Frame {
+        if self.version.is_some_and(|version| version != 5) {
+            return Frame::Bulk(Bytes::from(VERSION_LINE));
+        }
+
+        let columns = self.columns.unwrap_or(66).clamp(1, 1000);
+        let rows = self.rows.unwrap_or(40).clamp(1, 1000);
+
+        let seed = server_info
+            .started_at()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or_default();
+        let mut rng = SeededRng::new(seed);
+
+        let mut canvas = vec![vec![Color::White; columns]; rows];
+        compose(&mut canvas, &mut rng, (0, 0, columns, rows), 0);
+
+        let mut art = String::new();
+        for row in canvas {
+            let mut current = None;
+            for color in row {
+                if current != Some(color) {
+                    art.push_str(color.ansi());
+                    current = Some(color);
+                }
+                art.push('█');
+            }
+            art.push_str("\x1b[0m\n");
+        }
+
+        art.push_str("\nPiet Mondrian - Composition with Red, Blue and Yellow. ");
+        art.push_str(VERSION_LINE);
+
+        Frame::Bulk(Bytes::from(art))
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Lolwut> {
+        let mut version = None;
+        let mut args = Vec::new();
+
+        loop {
+            let arg = match frames.next_string() {
+                Ok(arg) => arg,
+                Err(parse::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            if args.is_empty() && version.is_none() && arg.eq_ignore_ascii_case("VERSION") {
+                version = Some(frames.next_uint()?);
+                continue;
+            }
+
+            let arg = arg
+                .parse::<usize>()
+                .map_err(|_| "ERR value is not an integer or out of range")?;
+            args.push(arg);
+        }
+
+        if args.len() > 2 {
+            return Err("ERR syntax error".into());
+        }
+
+        Ok(Lolwut::new(
+            version,
+            args.first().copied(),
+            args.get(1).copied(),
+        ))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![Frame::Bulk("LOLWUT".into())];
+
+        if let Some(version) = self.version {
+            frames.push(Frame::Bulk("VERSION".into()));
+            frames.push(Frame::Bulk(version.to_string().into()));
+        }
+        for arg in [self.columns, self.rows].into_iter().flatten() {
+            frames.push(Frame::Bulk(arg.to_string().into()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+/// Splits the area with a black line into two smaller areas, recursively,
+/// and paints the areas that are not split anymore with a random color
+fn compose(
+    canvas: &mut [Vec<Color>],
+    rng: &mut SeededRng,
+    (x, y, width, height): (usize, usize, usize, usize),
+    depth: usize,
+) {
+    const MIN_SIDE: usize = 4;
+
+    let splits_vertically = width >= height * 2;
+    let side = if splits_vertically { width } else { height };
+
+    // Stop on small areas, and sometimes earlier for a mix of large and small blocks
+    if side < MIN_SIDE * 2 + 1 || (depth >= 2 && rng.gen_range(4) == 0) {
+        let color = match rng.gen_range(8) {
+            0 => Color::Red,
+            1 => Color::Blue,
+            2 => Color::Yellow,
+            _ => Color::White,
+        };
+        for row in &mut canvas[y..y + height] {
+            row[x..x + width].fill(color);
+        }
+        return;
+    }
+
+    let split = MIN_SIDE + rng.gen_range(side - MIN_SIDE * 2);
+
+    if splits_vertically {
+        for row in &mut canvas[y..y + height] {
+            row[x + split] = Color::Black;
+        }
+        compose(canvas, rng, (x, y, split, height), depth + 1);
+        compose(
+            canvas,
+            rng,
+            (x + split + 1, y, width - split - 1, height),
+            depth + 1,
+        );
+    } else {
+        canvas[y + split][x..x + width].fill(Color::Black);
+        compose(canvas, rng, (x, y, width, split), depth + 1);
+        compose(
+            canvas,
+            rng,
+            (x, y + split + 1, width, height - split - 1),
+            depth + 1,
+        );
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Lolwut {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Lolwut::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, _db: &Db, server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(server_info)
+    }
+
+    fn execute_replica(&self, _db: &Db, server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(server_info)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,70 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// LRANGE key start stop
+#[derive(Debug, Default)]
+pub struct LRange {
+    key: String,
+    start: i64,
+    stop: i64,
+}
+
+impl LRange {
+    pub fn new(key: impl ToString, start: i64, stop: i64) -> LRange {
+        LRange {
+            key: key.to_string(),
+            start,
+            stop,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.lrange(&self.key, self.start, self.stop) {
+            Ok(values) => Frame::Array(values.into_iter().map(Frame::Bulk).collect()),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<LRange> {
+        let key = frames.next_string()?;
+        let start = frames.next_int()?;
+        let stop = frames.next_int()?;
+
+        Ok(LRange::new(key, start, stop))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk("LRANGE".into()),
+            Frame::Bulk(self.key.clone().into()),
+            Frame::Bulk(self.start.to_string().into()),
+            Frame::Bulk(self.stop.to_string().into()),
+        ])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for LRange {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(LRange::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
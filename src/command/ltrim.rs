@@ -0,0 +1,169 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// LTRIM key start stop
+#[derive(Debug, Default)]
+pub struct LTrim {
+    key: String,
+    start: i64,
+    stop: i64,
+}
+
+impl LTrim {
+    pub fn new(key: impl ToString, start: i64, stop: i64) -> LTrim {
+        LTrim {
+            key: key.to_string(),
+            start,
+            stop,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.ltrim(&self.key, self.start, self.stop) {
+            Ok(()) => Frame::Simple("OK".to_string()),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<LTrim> {
+        let key = frames.next_string()?;
+        let start = frames.next_int()?;
+        let stop = frames.next_int()?;
+
+        Ok(LTrim::new(key, start, stop))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk("LTRIM".into()),
+            Frame::Bulk(self.key.clone().into()),
+            Frame::Bulk(self.start.to_string().into()),
+            Frame::Bulk(self.stop.to_string().into()),
+        ])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for LTrim {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(LTrim::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::{
+        command::ListDirection,
+        server::tests::{connect, send, start_server},
+    };
+
+    fn list(db: &Db, values: &[&str]) {
+        let values = values
+            .iter()
+            .map(|value| Bytes::from(value.to_string()))
+            .collect();
+
+        db.push("list".to_string(), ListDirection::Right, values)
+            .unwrap();
+    }
+
+    fn range(db: &Db) -> Vec<Bytes> {
+        db.lrange("list", 0, -1).unwrap()
+    }
+
+    #[tokio::test]
+    async fn list_is_trimmed_to_the_range() {
+        let db = Db::new();
+        list(&db, &["a", "b", "c", "d", "e"]);
+
+        assert_eq!(
+            LTrim::new("list", 1, -2).execute(&db),
+            Frame::Simple("OK".into())
+        );
+        assert_eq!(range(&db), ["b", "c", "d"]);
+
+        // Indexes past the end are clamped
+        assert_eq!(
+            LTrim::new("list", -100, 100).execute(&db),
+            Frame::Simple("OK".into())
+        );
+        assert_eq!(range(&db), ["b", "c", "d"]);
+    }
+
+    #[tokio::test]
+    async fn start_after_stop_removes_the_key() {
+        let db = Db::new();
+        list(&db, &["a", "b", "c"]);
+
+        assert_eq!(
+            LTrim::new("list", 2, 1).execute(&db),
+            Frame::Simple("OK".into())
+        );
+        assert!(range(&db).is_empty());
+        assert_eq!(db.get_type("list"), "none");
+    }
+
+    #[tokio::test]
+    async fn start_past_the_end_removes_the_key() {
+        let db = Db::new();
+        list(&db, &["a", "b", "c"]);
+
+        assert_eq!(
+            LTrim::new("list", 5, 10).execute(&db),
+            Frame::Simple("OK".into())
+        );
+        assert_eq!(db.get_type("list"), "none");
+    }
+
+    #[tokio::test]
+    async fn missing_key_is_ok() {
+        let db = Db::new();
+
+        assert_eq!(
+            LTrim::new("list", 0, 1).execute(&db),
+            Frame::Simple("OK".into())
+        );
+        assert_eq!(db.get_type("list"), "none");
+    }
+
+    #[tokio::test]
+    async fn encoding_goes_back_to_listpack_at_half_the_limit() {
+        let client = connect(start_server(&[]).await).await;
+        send(&client, &["CONFIG", "SET", "list-max-listpack-size", "4"]).await;
+        let encoding = || send(&client, &["OBJECT", "ENCODING", "list"]);
+
+        send(&client, &["RPUSH", "list", "a", "b", "c", "d"]).await;
+        assert_eq!(encoding().await, Frame::Bulk("listpack".into()));
+        send(&client, &["RPUSH", "list", "e"]).await;
+        assert_eq!(encoding().await, Frame::Bulk("quicklist".into()));
+
+        // Unlike Redis, which never converts back, so the encoding does not flap
+        send(&client, &["LTRIM", "list", "0", "2"]).await;
+        assert_eq!(encoding().await, Frame::Bulk("quicklist".into()));
+        send(&client, &["LTRIM", "list", "0", "1"]).await;
+        assert_eq!(encoding().await, Frame::Bulk("listpack".into()));
+    }
+}
@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// MGET key [key ...]
+#[derive(Debug, Default)]
+pub struct MGet {
+    keys: Vec<String>,
+}
+
+impl MGet {
+    pub fn new(keys: Vec<String>) -> MGet {
+        MGet { keys }
+    }
+
+    /// Replies with the value of each key, a null for the keys that don't hold a string
+    pub fn execute(&self, db: &Db) -> Frame {
+        let values = db
+            .mget(&self.keys)
+            .into_iter()
+            .map(|value| match value {
+                Some(value) => Frame::Bulk(value),
+                None => Frame::Null,
+            })
+            .collect();
+
+        Frame::Array(values)
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<MGet> {
+        let mut keys = vec![frames.next_string()?];
+        while let Ok(key) = frames.next_string() {
+            keys.push(key);
+        }
+
+        Ok(MGet::new(keys))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![Frame::Bulk("MGET".into())];
+        frames.extend(
+            self.keys
+                .iter()
+                .map(|key| Frame::Bulk(Bytes::from(key.clone()))),
+        );
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for MGet {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(MGet::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -1,4 +1,12 @@
-use crate::{connection::Connection, frame::Frame, parse::Parse, Db, Info as ServerInfo};
+use std::time::Instant;
+
+use crate::{
+    connection::Connection,
+    error::{self, redis_error, RedisErrorKind},
+    frame::Frame,
+    parse::Parse,
+    Db, Info as ServerInfo,
+};
 
 mod echo;
 use async_trait::async_trait;
@@ -9,6 +17,7 @@ pub use ping::Ping;
 
 mod set;
 use set::Set;
+pub use set::SetCondition;
 
 mod get;
 use get::Get;
@@ -44,6 +53,201 @@ pub use xrange::XRange;
 mod xread;
 pub use xread::XRead;
 
+mod cluster;
+use cluster::Cluster;
+
+mod strlen;
+use strlen::Strlen;
+
+mod append;
+use append::Append;
+
+mod getset;
+use getset::GetSet;
+
+mod debug;
+use debug::Debug;
+
+mod sadd;
+use sadd::SAdd;
+
+mod smembers;
+use smembers::SMembers;
+
+mod spop;
+use spop::SPop;
+
+mod hset;
+use hset::HSet;
+
+mod hget;
+use hget::HGet;
+
+mod hrandfield;
+use hrandfield::HRandField;
+
+mod srandmember;
+use srandmember::SRandMember;
+
+mod scan;
+use scan::Scan;
+
+mod hscan;
+use hscan::HScan;
+
+mod sscan;
+use sscan::SScan;
+
+mod zscan;
+use zscan::ZScan;
+
+mod expire;
+use expire::Expire;
+pub use expire::ExpireCondition;
+
+mod persist;
+use persist::Persist;
+
+mod zadd;
+use zadd::ZAdd;
+pub use zadd::ZAddFlags;
+
+mod zrandmember;
+use zrandmember::ZRandMember;
+
+mod object;
+use object::Object;
+
+mod bitfield;
+use bitfield::BitField;
+pub use bitfield::{BitFieldOverflow, BitFieldType};
+
+mod hello;
+use hello::Hello;
+
+mod subscribe;
+use subscribe::Subscribe;
+
+mod unsubscribe;
+use unsubscribe::Unsubscribe;
+
+mod publish;
+use publish::Publish;
+
+mod bitop;
+use bitop::BitOp;
+pub use bitop::BitOperation;
+
+mod bitpos;
+use bitpos::BitPos;
+pub use bitpos::BitUnit;
+
+mod rpush;
+use rpush::RPush;
+
+mod lrange;
+use lrange::LRange;
+
+mod ltrim;
+use ltrim::LTrim;
+
+mod lmove;
+use lmove::LMove;
+pub use lmove::ListDirection;
+
+mod sort;
+use sort::Sort;
+pub use sort::SortOpts;
+
+mod spec;
+use spec::CommandSpec;
+
+mod command_info;
+use command_info::CommandInfo;
+
+mod dump;
+use dump::Dump;
+
+mod restore;
+use restore::Restore;
+
+mod dbsize;
+use dbsize::DbSize;
+
+mod wait_aof;
+use wait_aof::WaitAof;
+
+mod reset;
+use reset::Reset;
+
+mod lcs;
+use lcs::Lcs;
+
+mod lolwut;
+use lolwut::Lolwut;
+
+mod pubsub;
+use pubsub::PubSub;
+
+mod xgroup;
+use xgroup::XGroup;
+
+mod setrange;
+use setrange::SetRange;
+
+mod getrange;
+use getrange::GetRange;
+
+mod function;
+use function::Function;
+pub use function::FunctionRestorePolicy;
+
+mod srem;
+use srem::SRem;
+
+mod lpush;
+use lpush::LPush;
+
+mod hsetnx;
+use hsetnx::HSetNx;
+
+mod set_store;
+pub use set_store::SetOperation;
+use set_store::SetStore;
+
+mod lmpop;
+use lmpop::LMPop;
+
+mod zrangebylex;
+pub use zrangebylex::LexBound;
+use zrangebylex::ZRangeByLex;
+
+mod ttl;
+use ttl::Ttl;
+
+mod xinfo;
+use xinfo::XInfoStream;
+
+mod multi;
+use multi::Multi;
+
+mod exec;
+use exec::Exec;
+
+mod discard;
+use discard::Discard;
+
+mod mget;
+use mget::MGet;
+
+/// Commands executed right away inside a transaction, instead of being queued
+const TRANSACTION_COMMANDS: [&str; 3] = ["MULTI", "EXEC", "DISCARD"];
+
+/// Commands sent by the master to the replicas besides the write commands
+pub(crate) const REPLICATION_COMMANDS: [&str; 2] = ["PING", "REPLCONF"];
+
+const UNKNOWN_COMMAND_ERR: &str = "ERR unknown command";
+
 #[derive(Debug)]
 pub struct Command;
 
@@ -55,25 +259,101 @@ impl Command {
     /// This function will return an error if the frame is not a valid command
     pub fn from_frame(frame: Frame) -> crate::Result<Box<dyn CommandTrait + Send>> {
         let mut frames = Parse::new(frame)?;
+        let name = frames.next_string()?;
+
+        let command: Box<dyn CommandTrait + Send> = match name.to_uppercase().as_str() {
+            "ECHO" => Box::new(Echo::parse_frames(&mut frames)?),
+            "PING" => Box::new(Ping::parse_frames(&mut frames)?),
+            "SET" => Box::new(Set::parse_frames(&mut frames)?),
+            "GET" => Box::new(Get::parse_frames(&mut frames)?),
+            "KEYS" => Box::new(Keys::parse_frames(&mut frames)?),
+            "INFO" => Box::new(Info::parse_frames(&mut frames)?),
+            "REPLCONF" => Box::new(ReplConf::parse_frames(&mut frames)?),
+            "PSYNC" => Box::new(Psync::parse_frames(&mut frames)?),
+            "WAIT" => Box::new(Wait::parse_frames(&mut frames)?),
+            "CONFIG" => Box::new(Config::parse_frames(&mut frames)?),
+            "TYPE" => Box::new(Type::parse_frames(&mut frames)?),
+            "XADD" => Box::new(XAdd::parse_frames(&mut frames)?),
+            "XRANGE" => Box::new(XRange::parse_frames(&mut frames)?),
+            "XREAD" => Box::new(XRead::parse_frames(&mut frames)?),
+            "CLUSTER" => Box::new(Cluster::parse_frames(&mut frames)?),
+            "STRLEN" => Box::new(Strlen::parse_frames(&mut frames)?),
+            "APPEND" => Box::new(Append::parse_frames(&mut frames)?),
+            "GETSET" => Box::new(GetSet::parse_frames(&mut frames)?),
+            "DEBUG" => Box::new(Debug::parse_frames(&mut frames)?),
+            "SADD" => Box::new(SAdd::parse_frames(&mut frames)?),
+            "SMEMBERS" => Box::new(SMembers::parse_frames(&mut frames)?),
+            "SPOP" => Box::new(SPop::parse_frames(&mut frames)?),
+            "SINTERSTORE" => Box::new(SetStore::parse_frames(&mut frames, SetOperation::Inter)?),
+            "SUNIONSTORE" => Box::new(SetStore::parse_frames(&mut frames, SetOperation::Union)?),
+            "SDIFFSTORE" => Box::new(SetStore::parse_frames(&mut frames, SetOperation::Diff)?),
+            "HSET" => Box::new(HSet::parse_frames(&mut frames)?),
+            "HSETNX" => Box::new(HSetNx::parse_frames(&mut frames)?),
+            "HGET" => Box::new(HGet::parse_frames(&mut frames)?),
+            "HRANDFIELD" => Box::new(HRandField::parse_frames(&mut frames)?),
+            "BITFIELD" => Box::new(BitField::parse_frames(&mut frames)?),
+            "BITPOS" => Box::new(BitPos::parse_frames(&mut frames)?),
+            "BITOP" => Box::new(BitOp::parse_frames(&mut frames)?),
+            "OBJECT" => Box::new(Object::parse_frames(&mut frames)?),
+            "SRANDMEMBER" => Box::new(SRandMember::parse_frames(&mut frames)?),
+            "SCAN" => Box::new(Scan::parse_frames(&mut frames)?),
+            "ZADD" => Box::new(ZAdd::parse_frames(&mut frames)?),
+            "ZRANDMEMBER" => Box::new(ZRandMember::parse_frames(&mut frames)?),
+            "ZRANGEBYLEX" => Box::new(ZRangeByLex::parse_frames(&mut frames)?),
+            "HSCAN" => Box::new(HScan::parse_frames(&mut frames)?),
+            "SSCAN" => Box::new(SScan::parse_frames(&mut frames)?),
+            "ZSCAN" => Box::new(ZScan::parse_frames(&mut frames)?),
+            "EXPIRE" => Box::new(Expire::parse_frames(&mut frames, false, false)?),
+            "PEXPIRE" => Box::new(Expire::parse_frames(&mut frames, true, false)?),
+            "EXPIREAT" => Box::new(Expire::parse_frames(&mut frames, false, true)?),
+            "PEXPIREAT" => Box::new(Expire::parse_frames(&mut frames, true, true)?),
+            "TTL" => Box::new(Ttl::parse_frames(&mut frames, false)?),
+            "PTTL" => Box::new(Ttl::parse_frames(&mut frames, true)?),
+            "PERSIST" => Box::new(Persist::parse_frames(&mut frames)?),
+            "HELLO" => Box::new(Hello::parse_frames(&mut frames)?),
+            "SUBSCRIBE" => Box::new(Subscribe::parse_frames(&mut frames)?),
+            "UNSUBSCRIBE" => Box::new(Unsubscribe::parse_frames(&mut frames)?),
+            "PUBLISH" => Box::new(Publish::parse_frames(&mut frames)?),
+            "RPUSH" => Box::new(RPush::parse_frames(&mut frames)?),
+            "LPUSH" => Box::new(LPush::parse_frames(&mut frames)?),
+            "LRANGE" => Box::new(LRange::parse_frames(&mut frames)?),
+            "LTRIM" => Box::new(LTrim::parse_frames(&mut frames)?),
+            "LMOVE" => Box::new(LMove::parse_frames(&mut frames)?),
+            "RPOPLPUSH" => Box::new(LMove::parse_rpoplpush(&mut frames)?),
+            "LMPOP" => Box::new(LMPop::parse_frames(&mut frames)?),
+            "SORT" => Box::new(Sort::parse_frames(&mut frames)?),
+            "DUMP" => Box::new(Dump::parse_frames(&mut frames)?),
+            "RESTORE" => Box::new(Restore::parse_frames(&mut frames)?),
+            "COMMAND" => Box::new(CommandInfo::parse_frames(&mut frames)?),
+            "DBSIZE" => Box::new(DbSize::parse_frames(&mut frames)?),
+            "WAITAOF" => Box::new(WaitAof::parse_frames(&mut frames)?),
+            "RESET" => Box::new(Reset::parse_frames(&mut frames)?),
+            "LCS" => Box::new(Lcs::parse_frames(&mut frames)?),
+            "LOLWUT" => Box::new(Lolwut::parse_frames(&mut frames)?),
+            "PUBSUB" => Box::new(PubSub::parse_frames(&mut frames)?),
+            "XGROUP" => Box::new(XGroup::parse_frames(&mut frames)?),
+            "XINFO" => Box::new(XInfoStream::parse_frames(&mut frames)?),
+            "SETRANGE" => Box::new(SetRange::parse_frames(&mut frames)?),
+            "GETRANGE" => Box::new(GetRange::parse_frames(&mut frames)?),
+            "FUNCTION" => Box::new(Function::parse_frames(&mut frames)?),
+            "SREM" => Box::new(SRem::parse_frames(&mut frames)?),
+            "MULTI" => Box::new(Multi::parse_frames(&mut frames)?),
+            "EXEC" => Box::new(Exec::parse_frames(&mut frames)?),
+            "DISCARD" => Box::new(Discard::parse_frames(&mut frames)?),
+            "MGET" => Box::new(MGet::parse_frames(&mut frames)?),
+            _ => {
+                let mut args = String::new();
+                while let Ok(arg) = frames.next_string() {
+                    args.push_str(&format!("'{}' ", arg));
+                }
 
-        let command: Box<dyn CommandTrait + Send> =
-            match frames.next_string()?.to_uppercase().as_str() {
-                "ECHO" => Box::new(Echo::parse_frames(&mut frames)?),
-                "PING" => Box::new(Ping::parse_frames(&mut frames)?),
-                "SET" => Box::new(Set::parse_frames(&mut frames)?),
-                "GET" => Box::new(Get::parse_frames(&mut frames)?),
-                "KEYS" => Box::new(Keys::parse_frames(&mut frames)?),
-                "INFO" => Box::new(Info::parse_frames(&mut frames)?),
-                "REPLCONF" => Box::new(ReplConf::parse_frames(&mut frames)?),
-                "PSYNC" => Box::new(Psync::parse_frames(&mut frames)?),
-                "WAIT" => Box::new(Wait::parse_frames(&mut frames)?),
-                "CONFIG" => Box::new(Config::parse_frames(&mut frames)?),
-                "TYPE" => Box::new(Type::parse_frames(&mut frames)?),
-                "XADD" => Box::new(XAdd::parse_frames(&mut frames)?),
-                "XRANGE" => Box::new(XRange::parse_frames(&mut frames)?),
-                "XREAD" => Box::new(XRead::parse_frames(&mut frames)?),
-                cmd => return Err(format!("Protocol error: unknown command {:?}", cmd).into()),
-            };
+                return Err(format!(
+                    "{} '{}', with args beginning with: {}",
+                    UNKNOWN_COMMAND_ERR, name, args
+                )
+                .into());
+            }
+        };
 
         frames.finish()?;
 
@@ -81,27 +361,22 @@ impl Command {
     }
 
     /// Parse the frame into a command
-    /// Used for replica commands parsing
+    /// Used for replica commands parsing, only the commands flagged `write`
+    /// in [`COMMAND_SPECS`](spec::COMMAND_SPECS) and the replication commands are accepted
     ///
     /// # Errors
     ///
     /// This function will return an error if the frame is not a valid command
     pub fn from_frame_writes(frame: Frame) -> crate::Result<Box<dyn CommandTrait + Send>> {
-        let mut frames = Parse::new(frame)?;
-
-        let command: Box<dyn CommandTrait + Send> =
-            match frames.next_string()?.to_uppercase().as_str() {
-                "SET" => Box::new(Set::parse_frames(&mut frames)?),
-                "REPLCONF" => Box::new(ReplConf::parse_frames(&mut frames)?),
-                "PING" => Box::new(Ping::parse_frames(&mut frames)?),
-                cmd => {
-                    return Err(format!("Protocol error: not a 'write' command {:?}", cmd).into())
-                }
-            };
+        let name = Command::name(&frame).to_uppercase();
 
-        frames.finish()?;
+        // The replication link also carries the pings and acks of the master
+        let is_write = CommandSpec::lookup(&name).is_some_and(|spec| spec.is_write());
+        if !is_write && !REPLICATION_COMMANDS.contains(&name.as_str()) {
+            return Err(format!("Protocol error: not a 'write' command {:?}", name).into());
+        }
 
-        Ok(command)
+        Command::from_frame(frame)
     }
 
     /// Parse the frame into a command
@@ -110,7 +385,8 @@ impl Command {
     /// # Returns
     ///
     /// Returns response to the command as a Frame
-    /// And the byte length of the parsed frame
+    /// And the frames to propagate to the replicas, one per executed write command,
+    /// see [`CommandTrait::propagated_frame`]
     ///
     /// # Errors
     ///
@@ -120,35 +396,191 @@ impl Command {
         db: &Db,
         server_info: &mut ServerInfo,
         connection: Connection,
-    ) -> (Frame, usize) {
-        match Command::from_frame(frame) {
-            Ok(command) => match command.as_any().downcast_ref::<Wait>() {
-                Some(wait_command) => {
-                    let count = server_info
-                        .count_sync_repl(wait_command.replica_count, wait_command.timeout)
-                        .await;
-
-                    (
-                        Frame::Integer(count),
-                        command.to_frame().encode().bytes().len(),
-                    )
+    ) -> (Frame, Vec<Frame>) {
+        Command::execute_in(frame, db, server_info, connection, false).await
+    }
+
+    /// Executes the command, the commands of a transaction run
+    /// under the guard of EXEC and don't block
+    async fn execute_in(
+        frame: Frame,
+        db: &Db,
+        server_info: &mut ServerInfo,
+        connection: Connection,
+        in_transaction: bool,
+    ) -> (Frame, Vec<Frame>) {
+        let name = Command::name(&frame);
+
+        if !connection.session().allows_command(&name) {
+            server_info.record_command_rejected(&name);
+
+            let err = format!(
+                "ERR Can't execute '{}': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context",
+                name.to_lowercase()
+            );
+            return (Frame::Error(err), Vec::new());
+        }
+
+        // Inside a transaction the commands are queued until EXEC
+        if connection.session().is_in_transaction()
+            && !TRANSACTION_COMMANDS.contains(&name.to_uppercase().as_str())
+        {
+            return (
+                Command::queue(frame, &name, server_info, connection),
+                Vec::new(),
+            );
+        }
+
+        match Command::from_frame(frame.clone()) {
+            Ok(command) => {
+                if command.as_any().is::<Exec>() {
+                    let start = Instant::now();
+                    let (response, propagated) = Command::exec(db, server_info, connection).await;
+
+                    let failed = matches!(response, Frame::Error(_));
+                    server_info.record_command_call(&name, start.elapsed(), failed);
+
+                    return (response, propagated);
+                }
+
+                // The commands waiting for replicas or new entries don't hold off the transactions
+                let xread = command.as_any().downcast_ref::<XRead>();
+                let waits = command.as_any().is::<Wait>() || xread.is_some_and(XRead::is_blocking);
+                let _guard = match in_transaction || waits {
+                    true => None,
+                    false => Some(db.lock_command().await),
+                };
+
+                Command::expire_keys(&frame, db);
+
+                let start = Instant::now();
+
+                let response = match command.as_any().downcast_ref::<Wait>() {
+                    Some(wait_command) => {
+                        // The writes acknowledged are the ones made before WAIT
+                        let offset = server_info.offset();
+                        let count = server_info
+                            .count_sync_repl(
+                                offset,
+                                wait_command.replica_count,
+                                wait_command.timeout,
+                            )
+                            .await;
+
+                        Frame::Integer(count as i64)
+                    }
+                    None => match xread {
+                        // Inside a transaction the blocking reads don't wait, like in Redis
+                        Some(xread) if in_transaction => xread.execute_non_blocking(db).await,
+                        _ => error::with_error_prefix(
+                            command.execute(db, server_info, connection).await,
+                        ),
+                    },
+                };
+
+                let failed = matches!(response, Frame::Error(_));
+                server_info.record_command_call(&name, start.elapsed(), failed);
+
+                let propagated = Command::is_propagatable(frame.clone())
+                    .unwrap_or(false)
+                    .then(|| command.propagated_frame(frame, &response));
+
+                (response, propagated.into_iter().collect())
+            }
+            Err(err) => {
+                // Unknown commands have no stats to record
+                if !name.is_empty() && !err.to_string().starts_with(UNKNOWN_COMMAND_ERR) {
+                    server_info.record_command_rejected(&name);
+                }
+
+                let response = error::with_error_prefix(Frame::Error(err.to_string()));
+                (response, Vec::new())
+            }
+        }
+    }
+
+    /// Queues the command of the transaction, a command that can't be parsed
+    /// is rejected and makes EXEC discard the transaction
+    fn queue(
+        frame: Frame,
+        name: &str,
+        server_info: &mut ServerInfo,
+        connection: Connection,
+    ) -> Frame {
+        match Command::from_frame(frame.clone()) {
+            Ok(_) => {
+                connection.session().queue(frame);
+
+                Frame::Simple("QUEUED".into())
+            }
+            Err(err) => {
+                connection.session().flag_queued_error();
+
+                if !name.is_empty() && !err.to_string().starts_with(UNKNOWN_COMMAND_ERR) {
+                    server_info.record_command_rejected(name);
                 }
-                None => (
-                    command.execute(db, server_info, connection).await,
-                    command.to_frame().encode().bytes().len(),
-                ),
-            },
-            Err(err) => (Frame::Error(err.to_string()), 0),
+
+                error::with_error_prefix(Frame::Error(err.to_string()))
+            }
         }
     }
 
+    /// Executes the commands queued since MULTI, replying with an array of their replies.
+    /// A command failing at execution only has an error reply, the others are still executed.
+    /// The other commands are held off until the transaction is done,
+    /// and its writes are propagated wrapped in MULTI and EXEC.
+    async fn exec(
+        db: &Db,
+        server_info: &mut ServerInfo,
+        connection: Connection,
+    ) -> (Frame, Vec<Frame>) {
+        let transaction = match connection.session().take_transaction() {
+            Some(transaction) => transaction,
+            None => {
+                let err = redis_error(RedisErrorKind::Err, "EXEC without MULTI");
+                return (err, Vec::new());
+            }
+        };
+
+        if transaction.queued_errors {
+            let err = "Transaction discarded because of previous errors.";
+            return (redis_error(RedisErrorKind::ExecAbort, err), Vec::new());
+        }
+
+        let _transaction = db.lock_transaction().await;
+
+        let mut responses = Vec::new();
+        let mut propagated = Vec::new();
+
+        for frame in transaction.commands {
+            let (response, frames) = Box::pin(Command::execute_in(
+                frame,
+                db,
+                server_info,
+                connection.clone(),
+                true,
+            ))
+            .await;
+
+            responses.push(response);
+            propagated.extend(frames);
+        }
+
+        // A single write is atomic on its own
+        if propagated.len() > 1 {
+            propagated.insert(0, Multi::default().to_frame());
+            propagated.push(Exec::default().to_frame());
+        }
+
+        (Frame::Array(responses), propagated)
+    }
+
     /// Parse the frame into a command for replica
     /// Then execute the command as a replica
     ///
     /// # Returns
     ///
     /// Returns response to the command as a Frame
-    /// And the byte length of the parsed frame
     ///
     /// # Errors
     ///
@@ -158,22 +590,76 @@ impl Command {
         db: &Db,
         server_info: &mut ServerInfo,
         connection: Connection,
-    ) -> (Frame, usize) {
+    ) -> Frame {
         match Command::from_frame_writes(frame) {
-            Ok(command) => (
-                command.execute_replica(db, server_info, connection),
-                command.to_frame().encode().bytes().len(),
-            ),
-            Err(err) => (Frame::Error(err.to_string()), 0),
+            Ok(command) => command.execute_replica(db, server_info, connection),
+            Err(err) => Frame::Error(err.to_string()),
         }
     }
 
+    /// Returns the keys accessed by the command in the frame,
+    /// found at the positions of its [`CommandSpec`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command is unknown, has the wrong number of arguments
+    /// or has no key arguments
+    pub fn get_keys(frame: Frame) -> crate::Result<Vec<String>> {
+        let mut frames = Parse::new(frame)?;
+
+        let mut args = Vec::new();
+        while let Ok(arg) = frames.next_string() {
+            args.push(arg);
+        }
+
+        let spec = args
+            .first()
+            .and_then(|name| CommandSpec::lookup(name))
+            .ok_or("ERR Invalid command specified")?;
+
+        if !spec.accepts(args.len()) {
+            return Err("ERR Invalid number of arguments specified for command".into());
+        }
+
+        match spec.keys(&args) {
+            keys if keys.is_empty() => Err("ERR The command has no key arguments".into()),
+            keys => Ok(keys.into_iter().cloned().collect()),
+        }
+    }
+
+    /// Removes the keys of the command that are past their expiry,
+    /// so they expire on access even when the expiry task is disabled
+    fn expire_keys(frame: &Frame, db: &Db) {
+        for key in Command::get_keys(frame.clone()).unwrap_or_default() {
+            db.expire_if_needed(&key);
+        }
+    }
+
+    /// Name of the command in the frame, empty if there is none
+    pub fn name(frame: &Frame) -> String {
+        Parse::new(frame.clone())
+            .and_then(|mut frames| frames.next_string())
+            .unwrap_or_default()
+    }
+
+    /// Whether the command modifies the dataset, so it is propagated to the replicas,
+    /// see [`CommandSpec::is_write`]
     pub fn is_propagatable(frame: Frame) -> crate::Result<bool> {
         let mut frames = Parse::new(frame)?;
+        let name = frames.next_string()?.to_uppercase();
+
+        if !CommandSpec::lookup(&name).is_some_and(|spec| spec.is_write()) {
+            return Ok(false);
+        }
 
-        match frames.next_string()?.to_uppercase().as_str() {
-            "SET" => Ok(true),
-            _ => Ok(false),
+        match name.as_str() {
+            // Only SET and INCRBY modify the value, GET is read-only
+            "BITFIELD" => Ok(BitField::parse_frames(&mut frames).is_ok_and(|cmd| cmd.is_write())),
+            // Only RESTORE modifies the function libraries
+            "FUNCTION" => Ok(Function::parse_frames(&mut frames).is_ok_and(|cmd| cmd.is_write())),
+            // Only SORT with STORE modifies the dataset
+            "SORT" => Ok(Sort::parse_frames(&mut frames).is_ok_and(|cmd| cmd.is_write())),
+            _ => Ok(true),
         }
     }
 
@@ -207,5 +693,127 @@ pub trait CommandTrait {
     /// Returns the command as a Frame
     fn to_frame(&self) -> Frame;
 
+    /// Frame propagated to the replicas for the received frame of a write command and its reply.
+    /// Commands whose effect can differ when replayed are propagated in a resolved form.
+    fn propagated_frame(&self, frame: Frame, _response: &Frame) -> Frame {
+        frame
+    }
+
     fn as_any(&self) -> &dyn std::any::Any;
 }
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::{spec::COMMAND_SPECS, *};
+    use crate::server::tests::{connect, send, start_server};
+
+    fn frame(name: &str) -> Frame {
+        Frame::Array(vec![Frame::Bulk(Bytes::from(name.to_string()))])
+    }
+
+    /// Whether the replication link rejects the command for not being a write,
+    /// the other errors come from parsing the missing arguments
+    fn rejected_as_read(name: &str) -> bool {
+        Command::from_frame_writes(frame(name))
+            .err()
+            .is_some_and(|err| err.to_string().contains("not a 'write' command"))
+    }
+
+    #[test]
+    fn write_commands_are_accepted_from_the_master() {
+        for spec in COMMAND_SPECS.iter().filter(|spec| spec.is_write()) {
+            assert!(!rejected_as_read(spec.name), "{} rejected", spec.name);
+        }
+    }
+
+    #[test]
+    fn other_commands_are_rejected_from_the_master() {
+        for spec in COMMAND_SPECS.iter().filter(|spec| !spec.is_write()) {
+            let replication = REPLICATION_COMMANDS.contains(&spec.name);
+            assert_eq!(rejected_as_read(spec.name), !replication, "{}", spec.name);
+        }
+    }
+
+    /// Whether from_frame knows the command, the other errors come from parsing the missing arguments
+    fn is_known(name: &str) -> bool {
+        Command::from_frame(frame(name))
+            .err()
+            .is_none_or(|err| !err.to_string().starts_with(UNKNOWN_COMMAND_ERR))
+    }
+
+    #[test]
+    fn every_spec_is_a_known_command() {
+        for spec in COMMAND_SPECS {
+            assert!(
+                is_known(spec.name),
+                "{} has a spec but is unknown",
+                spec.name
+            );
+        }
+        assert!(!is_known("NOSUCHCOMMAND"));
+    }
+
+    #[test]
+    fn every_known_command_has_a_spec() {
+        // The names matched by from_frame, one arm per line
+        let source = include_str!("mod.rs");
+        let body = &source[source.find("fn from_frame(").unwrap()..];
+        let body = &body[..body.find("frames.finish()").unwrap()];
+        let names: Vec<_> = body
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix('"')?.split_once("\" =>"))
+            .map(|(name, _)| name)
+            .collect();
+
+        assert!(names.contains(&"MGET"));
+        for name in &names {
+            assert!(CommandSpec::lookup(name).is_some(), "{name} has no spec");
+        }
+        assert_eq!(names.len(), COMMAND_SPECS.len());
+    }
+
+    #[test]
+    fn command_count_is_the_number_of_specs() {
+        let count = Command::from_frame(Frame::Array(vec![
+            Frame::Bulk("COMMAND".into()),
+            Frame::Bulk("COUNT".into()),
+        ]))
+        .unwrap();
+
+        let count = count
+            .as_any()
+            .downcast_ref::<CommandInfo>()
+            .unwrap()
+            .execute();
+        assert_eq!(count, Frame::Integer(COMMAND_SPECS.len() as i64));
+    }
+
+    #[tokio::test]
+    async fn transaction_is_propagated_in_multi_exec() {
+        let master = start_server(&[]).await;
+
+        // Stands for a replica, only reading the propagated commands
+        let replica = connect(master).await;
+        send(&replica, &["REPLCONF", "listening-port", "6390"]).await;
+        send(&replica, &["PSYNC", "?", "-1"]).await;
+        replica.read_rdb().await.unwrap();
+
+        let client = connect(master).await;
+        send(&client, &["MULTI"]).await;
+        send(&client, &["SET", "a", "1"]).await;
+        send(&client, &["GET", "a"]).await;
+        send(&client, &["SET", "b", "2"]).await;
+        send(&client, &["EXEC"]).await;
+
+        let mut propagated = Vec::new();
+        while propagated.len() < 4 {
+            let frame = replica.read_frame().await.unwrap().unwrap();
+            if Command::name(&frame) != "PING" {
+                propagated.push(Command::name(&frame));
+            }
+        }
+        assert_eq!(propagated, ["MULTI", "SET", "SET", "EXEC"]);
+    }
+}
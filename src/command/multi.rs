@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{
+    connection::Connection,
+    error::{redis_error, RedisErrorKind},
+    Db, Frame, Info, Parse,
+};
+
+use super::CommandTrait;
+
+/// MULTI, starts a transaction, the next commands are queued until EXEC
+#[derive(Debug, Default)]
+pub struct Multi {}
+
+impl Multi {
+    pub fn execute(&self, connection: Connection) -> Frame {
+        match connection.session().multi() {
+            true => Frame::Simple("OK".into()),
+            false => redis_error(RedisErrorKind::Err, "MULTI calls can not be nested"),
+        }
+    }
+
+    pub fn parse_frames(_frames: &mut Parse) -> crate::Result<Multi> {
+        Ok(Multi {})
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![Frame::Bulk(Bytes::from("MULTI"))])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Multi {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Multi::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, _db: &Db, _server_info: &mut Info, connection: Connection) -> Frame {
+        self.execute(connection)
+    }
+
+    fn execute_replica(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
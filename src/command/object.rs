@@ -0,0 +1,273 @@
+use async_trait::async_trait;
+
+use crate::{
+    connection::Connection,
+    db,
+    error::{redis_error, RedisErrorKind},
+    Db, Frame, Info, Parse,
+};
+
+use super::CommandTrait;
+
+/// Lines of the OBJECT HELP reply, in the format of the Redis help messages
+const HELP: &[&str] = &[
+    "OBJECT <subcommand> [<arg> [value] [opt] ...]. Subcommands are:",
+    "ENCODING <key>",
+    "    Return the kind of internal representation used in order to store the value",
+    "    associated with a <key>.",
+    "FREQ <key>",
+    "    Return the access frequency index of the <key>. The returned integer is",
+    "    proportional to the logarithm of the recent access frequency of the key.",
+    "IDLETIME <key>",
+    "    Return the idle time of the <key>, that is the approximated number of",
+    "    seconds elapsed since the last access to the key.",
+    "REFCOUNT <key>",
+    "    Return the number of references of the value associated with the specified",
+    "    <key>.",
+    "HELP",
+    "    Print this help.",
+];
+
+#[derive(Debug)]
+pub enum Object {
+    /// OBJECT ENCODING \<key\>
+    Encoding(String),
+    /// OBJECT FREQ \<key\>
+    Freq(String),
+    /// OBJECT IDLETIME \<key\>
+    IdleTime(String),
+    /// OBJECT REFCOUNT \<key\>
+    RefCount(String),
+    /// OBJECT HELP
+    Help,
+}
+
+impl Object {
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Object> {
+        let subcommand = frames.next_string()?;
+
+        match subcommand.to_uppercase().as_str() {
+            "ENCODING" => Ok(Object::Encoding(frames.next_string()?)),
+            "FREQ" => Ok(Object::Freq(frames.next_string()?)),
+            "IDLETIME" => Ok(Object::IdleTime(frames.next_string()?)),
+            "REFCOUNT" => Ok(Object::RefCount(frames.next_string()?)),
+            "HELP" => Ok(Object::Help),
+            _ => Err(format!("ERR unknown subcommand '{}'. Try OBJECT HELP.", subcommand).into()),
+        }
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let (subcommand, key) = match self {
+            Object::Encoding(key) => ("ENCODING", Some(key)),
+            Object::Freq(key) => ("FREQ", Some(key)),
+            Object::IdleTime(key) => ("IDLETIME", Some(key)),
+            Object::RefCount(key) => ("REFCOUNT", Some(key)),
+            Object::Help => ("HELP", None),
+        };
+
+        let mut frames = vec![Frame::Bulk("OBJECT".into()), Frame::Bulk(subcommand.into())];
+        if let Some(key) = key {
+            frames.push(Frame::Bulk(key.clone().into()));
+        }
+
+        Frame::Array(frames)
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        let key = match self {
+            Object::Encoding(key)
+            | Object::Freq(key)
+            | Object::IdleTime(key)
+            | Object::RefCount(key) => key,
+            Object::Help => {
+                return Frame::Array(
+                    HELP.iter()
+                        .map(|line| Frame::Bulk(line.to_string().into()))
+                        .collect(),
+                )
+            }
+        };
+
+        // Inspecting the object does not count as an access.
+        // Unlike TYPE, which replies none, a missing key is an error
+        let entry = match db.get_no_touch(key) {
+            Some(entry) => entry,
+            None => return redis_error(RedisErrorKind::Err, "no such key"),
+        };
+
+        match self {
+            Object::Encoding(_) => Frame::Bulk(entry.encoding().into()),
+            Object::IdleTime(_) => Frame::Integer(db::lru_idle_secs(entry.lru()) as i64),
+            Object::RefCount(_) => Frame::Integer(1),
+            // Only the LRU clock is tracked, there is no LFU policy
+            Object::Freq(_) => redis_error(
+                RedisErrorKind::Err,
+                "An LFU maxmemory policy is not selected, access frequency not tracked.",
+            ),
+            Object::Help => unreachable!(),
+        }
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Object {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Object::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::command::{ListDirection, XAddId, ZAddFlags};
+
+    fn encoding(value: &str) -> Frame {
+        let db = Db::new();
+        db.set("key".to_string(), Bytes::from(value.to_string()), None);
+
+        Object::Encoding("key".to_string()).execute(&db)
+    }
+
+    #[tokio::test]
+    async fn embstr_up_to_44_bytes() {
+        assert_eq!(encoding(&"a".repeat(44)), Frame::Bulk("embstr".into()));
+        assert_eq!(encoding(&"a".repeat(45)), Frame::Bulk("raw".into()));
+    }
+
+    #[tokio::test]
+    async fn embstr_limit_counts_bytes_not_chars() {
+        // 2 bytes per char in UTF-8
+        assert_eq!(encoding(&"é".repeat(22)), Frame::Bulk("embstr".into()));
+        assert_eq!(encoding(&"é".repeat(23)), Frame::Bulk("raw".into()));
+    }
+
+    fn key_encoding(db: &Db, key: &str) -> Frame {
+        Object::Encoding(key.to_string()).execute(db)
+    }
+
+    #[tokio::test]
+    async fn missing_key_is_an_error_unlike_type() {
+        let db = Db::new();
+        let key = || "missing".to_string();
+
+        for object in [
+            Object::Encoding(key()),
+            Object::Freq(key()),
+            Object::IdleTime(key()),
+            Object::RefCount(key()),
+        ] {
+            assert_eq!(object.execute(&db), Frame::Error("ERR no such key".into()));
+        }
+        assert_eq!(
+            crate::command::Type::new(key()).execute(&db),
+            Frame::Simple("none".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn integer_is_int_until_appended() {
+        let db = Db::new();
+        db.set("key".to_string(), Bytes::from("123"), None);
+        assert_eq!(key_encoding(&db, "key"), Frame::Bulk("int".into()));
+        assert_eq!(
+            db.mget(&["key".to_string()]),
+            vec![Some(Bytes::from("123"))]
+        );
+
+        // Even when the result is still an integer
+        assert_eq!(db.append("key".to_string(), Bytes::from("4")).unwrap(), 4);
+        assert_eq!(key_encoding(&db, "key"), Frame::Bulk("raw".into()));
+        assert_eq!(
+            db.mget(&["key".to_string()]),
+            vec![Some(Bytes::from("1234"))]
+        );
+    }
+
+    fn members(range: std::ops::Range<usize>, prefix: &str) -> Vec<Bytes> {
+        range.map(|i| Bytes::from(format!("{prefix}{i}"))).collect()
+    }
+
+    #[tokio::test]
+    async fn small_sets_are_compact() {
+        let db = Db::new();
+        db.sadd("ints".to_string(), members(0..10, "")).unwrap();
+        db.sadd("strings".to_string(), members(0..10, "member"))
+            .unwrap();
+
+        assert_eq!(key_encoding(&db, "ints"), Frame::Bulk("intset".into()));
+        assert_eq!(key_encoding(&db, "strings"), Frame::Bulk("listpack".into()));
+    }
+
+    #[tokio::test]
+    async fn large_sets_are_hashtables() {
+        let db = Db::new();
+        db.sadd("ints".to_string(), members(0..513, "")).unwrap();
+        db.sadd("strings".to_string(), members(0..129, "member"))
+            .unwrap();
+
+        assert_eq!(key_encoding(&db, "ints"), Frame::Bulk("hashtable".into()));
+        assert_eq!(
+            key_encoding(&db, "strings"),
+            Frame::Bulk("hashtable".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn zset_becomes_skiplist_past_the_threshold() {
+        let db = Db::new();
+        let scored = |range| {
+            members(range, "member")
+                .into_iter()
+                .map(|member| (1.0, member))
+                .collect()
+        };
+        db.zadd("small".to_string(), ZAddFlags::default(), scored(0..128))
+            .unwrap();
+        db.zadd("large".to_string(), ZAddFlags::default(), scored(0..129))
+            .unwrap();
+
+        assert_eq!(key_encoding(&db, "small"), Frame::Bulk("listpack".into()));
+        assert_eq!(key_encoding(&db, "large"), Frame::Bulk("skiplist".into()));
+    }
+
+    #[tokio::test]
+    async fn other_types_encodings() {
+        let db = Db::new();
+        db.push("list".to_string(), ListDirection::Right, members(0..3, ""))
+            .unwrap();
+        db.hset(
+            "hash".to_string(),
+            vec![("field".to_string(), Bytes::from("value"))],
+        )
+        .unwrap();
+        db.xadd(
+            "stream".to_string(),
+            XAddId::Auto,
+            vec![("field".to_string(), Bytes::from("value"))],
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(key_encoding(&db, "list"), Frame::Bulk("listpack".into()));
+        assert_eq!(key_encoding(&db, "hash"), Frame::Bulk("listpack".into()));
+        assert_eq!(key_encoding(&db, "stream"), Frame::Bulk("stream".into()));
+    }
+}
@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug, Default)]
+pub struct Persist {
+    key: String,
+}
+
+impl Persist {
+    pub fn new(key: impl ToString) -> Persist {
+        Persist {
+            key: key.to_string(),
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        Frame::Integer(db.persist(&self.key) as i64)
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Persist> {
+        let key = frames.next_string()?;
+
+        Ok(Persist::new(key))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk("PERSIST".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Persist {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Persist::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -1,48 +1,65 @@
 use async_trait::async_trait;
+use bytes::Bytes;
 
-use crate::{connection::Connection, Db, Frame, Info, Parse};
+use crate::{connection::Connection, parse, Db, Frame, Info, Parse};
 
 use super::CommandTrait;
 
-#[derive(Debug)]
+/// PING [message]
+#[derive(Debug, Default)]
 pub struct Ping {
-    message: String,
+    message: Option<Bytes>,
 }
 
-impl Default for Ping {
-    fn default() -> Self {
-        Ping::new()
+impl Ping {
+    pub fn new(message: Option<Bytes>) -> Ping {
+        Ping { message }
     }
-}
 
-impl Ping {
-    pub fn new() -> Ping {
-        Ping {
-            message: "PONG".to_string(),
+    /// Replies PONG, or the message if there is one.
+    /// A RESP2 client in subscribe mode gets a `pong` array instead,
+    /// like the messages it receives.
+    pub fn execute(&self, connection: &Connection) -> Frame {
+        let session = connection.session();
+
+        if session.protocol() == 2 && session.is_subscribe_mode() {
+            let message = self.message.clone().unwrap_or_default();
+            return Frame::Array(vec![Frame::Bulk("pong".into()), Frame::Bulk(message)]);
         }
-    }
 
-    pub fn execute(&self) -> Frame {
-        Frame::Simple(self.message.clone())
+        match &self.message {
+            Some(message) => Frame::Bulk(message.clone()),
+            None => Frame::Simple("PONG".to_string()),
+        }
     }
 
-    pub fn parse_frames(_frames: &mut Parse) -> crate::Result<Ping> {
-        Ok(Ping::new())
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Ping> {
+        match frames.next_bytes() {
+            Ok(message) => Ok(Ping::new(Some(message))),
+            Err(parse::Error::EndOfStream) => Ok(Ping::new(None)),
+            Err(err) => Err(err.into()),
+        }
     }
 
     pub fn to_frame(&self) -> Frame {
-        Frame::Array(vec![Frame::Bulk("PING".into())])
+        let mut frames = vec![Frame::Bulk("PING".into())];
+
+        if let Some(message) = &self.message {
+            frames.push(Frame::Bulk(message.clone()));
+        }
+
+        Frame::Array(frames)
     }
 }
 
 #[async_trait]
 impl CommandTrait for Ping {
-    fn parse_frames(&self, _frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
-        Ok(Box::new(Ping::parse_frames(_frames)?))
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Ping::parse_frames(frames)?))
     }
 
-    async fn execute(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
-        self.execute()
+    async fn execute(&self, _db: &Db, _server_info: &mut Info, connection: Connection) -> Frame {
+        self.execute(&connection)
     }
 
     fn execute_replica(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
@@ -57,3 +74,31 @@ impl CommandTrait for Ping {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::{connect, send, start_server};
+
+    #[tokio::test]
+    async fn subscribe_mode_replies_pong_array() {
+        let client = connect(start_server(&[]).await).await;
+
+        assert_eq!(send(&client, &["PING"]).await, Frame::Simple("PONG".into()));
+        assert_eq!(
+            send(&client, &["PING", "hi"]).await,
+            Frame::Bulk("hi".into())
+        );
+
+        send(&client, &["SUBSCRIBE", "channel"]).await;
+
+        assert_eq!(
+            send(&client, &["PING"]).await,
+            Frame::Array(vec![Frame::Bulk("pong".into()), Frame::Bulk("".into())])
+        );
+        assert_eq!(
+            send(&client, &["PING", "hi"]).await,
+            Frame::Array(vec![Frame::Bulk("pong".into()), Frame::Bulk("hi".into())])
+        );
+    }
+}
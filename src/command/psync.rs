@@ -1,3 +1,5 @@
+use std::io::Cursor;
+
 use async_trait::async_trait;
 
 use crate::{connection::Connection, replicaiton::rdb, Db, Frame, Info, Parse};
@@ -19,13 +21,10 @@ impl Psync {
     }
 
     pub fn parse_frames(frames: &mut Parse) -> crate::Result<Psync> {
-        let _replid = frames.next_string()?;
+        let replid = frames.next_string()?;
         let offset = frames.next_int()?;
 
-        Ok(Psync::new(
-            offset,
-            "8371b4fb1155b71f4a04d3e1bc3e18c4a990aeeb",
-        ))
+        Ok(Psync::new(offset, replid))
     }
 
     pub fn to_frame(&self) -> Frame {
@@ -37,11 +36,18 @@ impl Psync {
     }
 
     /// Sent by master to a replica to create a replication stream.
-    pub fn execute(&self, server_info: &mut Info) -> Frame {
+    /// A replica of the same history, whose offset is still in the backlog,
+    /// continues with the bytes it missed, the others get a full resync
+    pub async fn execute(&self, server_info: &mut Info, connection: Connection) -> Frame {
+        if let Some(frame) = self.partial_resync(server_info, connection).await {
+            return frame;
+        }
+
         // Simple string part of the frame
         let full_resync = format!(
-            "FULLRESYNC {} 0",
-            server_info.master_replid().unwrap_or_default()
+            "FULLRESYNC {} {}",
+            server_info.master_replid().unwrap_or_default(),
+            server_info.offset()
         );
         // RDB part of the frame
         let rdb = rdb::empty_rdb();
@@ -50,6 +56,35 @@ impl Psync {
 
         Frame::Rdb(full_resync, rdb)
     }
+
+    /// Replies `+CONTINUE <replid>` and sends the missed commands from the backlog.
+    /// Returns `None` if a full resync is needed
+    async fn partial_resync(&self, server_info: &Info, connection: Connection) -> Option<Frame> {
+        // The replica asks for the offset of the next byte it needs
+        let offset = u64::try_from(self.offset).ok()?;
+        let backlog = server_info.partial_resync(&self.replid, offset)?;
+        let replid = server_info.master_replid()?;
+
+        let reply = Frame::Simple(format!("CONTINUE {}", replid));
+        if let Err(err) = connection.write_frame(reply).await {
+            return Some(Frame::Error(err.to_string()));
+        }
+
+        // The backlog only holds whole commands, from the offset of a replica
+        let mut cursor = Cursor::new(backlog.as_slice());
+        while (cursor.position() as usize) < backlog.len() {
+            let frame = match Frame::parse(&mut cursor) {
+                Ok(frame) => frame,
+                Err(err) => return Some(Frame::Error(err.to_string())),
+            };
+
+            if let Err(err) = connection.write_frame(frame).await {
+                return Some(Frame::Error(err.to_string()));
+            }
+        }
+
+        Some(Frame::NoSend)
+    }
 }
 
 #[async_trait]
@@ -58,12 +93,12 @@ impl CommandTrait for Psync {
         Ok(Box::new(Psync::parse_frames(frames)?))
     }
 
-    async fn execute(&self, _db: &Db, server_info: &mut Info, _connection: Connection) -> Frame {
-        self.execute(server_info)
+    async fn execute(&self, _db: &Db, server_info: &mut Info, connection: Connection) -> Frame {
+        self.execute(server_info, connection).await
     }
 
-    fn execute_replica(&self, _db: &Db, server_info: &mut Info, _connection: Connection) -> Frame {
-        self.execute(server_info)
+    fn execute_replica(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        Frame::Null
     }
 
     fn to_frame(&self) -> Frame {
@@ -74,3 +109,104 @@ impl CommandTrait for Psync {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::{connect, send, start_server};
+
+    /// Connects as a replica with a full resync, returns the connection
+    /// with the replid and the offset of the master
+    async fn full_resync(master: std::net::SocketAddr) -> (Connection, String, u64) {
+        let replica = connect(master).await;
+        send(&replica, &["REPLCONF", "listening-port", "6390"]).await;
+
+        let reply = send(&replica, &["PSYNC", "?", "-1"]).await;
+        replica.read_rdb().await.unwrap();
+
+        match reply {
+            Frame::Simple(reply) => match reply.split(' ').collect::<Vec<_>>()[..] {
+                ["FULLRESYNC", replid, offset] => {
+                    (replica, replid.to_string(), offset.parse().unwrap())
+                }
+                _ => panic!("expected FULLRESYNC, got {reply}"),
+            },
+            reply => panic!("expected FULLRESYNC, got {reply:?}"),
+        }
+    }
+
+    /// Next frame of the replication stream, skipping the pings of the master
+    async fn next_command(connection: &Connection) -> Frame {
+        loop {
+            let frame = connection.read_frame().await.unwrap().unwrap();
+            if crate::Command::name(&frame) != "PING" {
+                return frame;
+            }
+        }
+    }
+
+    fn set(key: &str, value: &str) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk("SET".into()),
+            Frame::Bulk(key.to_string().into()),
+            Frame::Bulk(value.to_string().into()),
+        ])
+    }
+
+    #[tokio::test]
+    async fn replica_continues_with_the_missed_commands() {
+        let master = start_server(&[]).await;
+        let (replica, replid, offset) = full_resync(master).await;
+
+        let client = connect(master).await;
+        send(&client, &["SET", "a", "1"]).await;
+        send(&client, &["SET", "b", "2"]).await;
+        assert_eq!(next_command(&replica).await, set("a", "1"));
+        assert_eq!(next_command(&replica).await, set("b", "2"));
+
+        // Reconnects having only received the first SET
+        let offset = offset + set("a", "1").encode().len() as u64 + 1;
+        let reconnected = connect(master).await;
+        assert_eq!(
+            send(&reconnected, &["PSYNC", &replid, &offset.to_string()]).await,
+            Frame::Simple(format!("CONTINUE {replid}"))
+        );
+        assert_eq!(next_command(&reconnected).await, set("b", "2"));
+    }
+
+    #[tokio::test]
+    async fn previous_replid_continues_after_change_repl_id() {
+        let master = start_server(&[]).await;
+        let (replica, replid, offset) = full_resync(master).await;
+
+        let client = connect(master).await;
+        send(&client, &["SET", "a", "1"]).await;
+        assert_eq!(next_command(&replica).await, set("a", "1"));
+        send(&client, &["DEBUG", "CHANGE-REPL-ID"]).await;
+        send(&client, &["SET", "b", "2"]).await;
+
+        let new_replid = match send(&client, &["INFO", "replication"]).await {
+            Frame::Bulk(info) => String::from_utf8_lossy(&info)
+                .lines()
+                .find_map(|line| line.strip_prefix("master_replid:").map(str::to_string))
+                .unwrap(),
+            info => panic!("expected the INFO, got {info:?}"),
+        };
+
+        let offset = offset + set("a", "1").encode().len() as u64 + 1;
+        let reconnected = connect(master).await;
+        assert_eq!(
+            send(&reconnected, &["PSYNC", &replid, &offset.to_string()]).await,
+            Frame::Simple(format!("CONTINUE {new_replid}"))
+        );
+        assert_eq!(next_command(&reconnected).await, set("b", "2"));
+
+        // Past the end of the previous history the replica needs a full resync
+        let past_end = connect(master).await;
+        match send(&past_end, &["PSYNC", &replid, &(offset + 1).to_string()]).await {
+            Frame::Rdb(reply, _) => assert!(reply.starts_with("FULLRESYNC")),
+            Frame::Simple(reply) => assert!(reply.starts_with("FULLRESYNC")),
+            reply => panic!("expected FULLRESYNC, got {reply:?}"),
+        }
+    }
+}
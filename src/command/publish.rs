@@ -0,0 +1,65 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug, Default)]
+pub struct Publish {
+    channel: String,
+    message: Bytes,
+}
+
+impl Publish {
+    pub fn new(channel: impl ToString, message: Bytes) -> Publish {
+        Publish {
+            channel: channel.to_string(),
+            message,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        let count = db.publish(&self.channel, self.message.clone());
+
+        Frame::Integer(count as i64)
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Publish> {
+        let channel = frames.next_string()?;
+        let message = frames.next_bytes()?;
+
+        Ok(Publish::new(channel, message))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk("PUBLISH".into()),
+            Frame::Bulk(self.channel.clone().into()),
+            Frame::Bulk(self.message.clone()),
+        ])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Publish {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Publish::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
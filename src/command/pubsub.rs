@@ -0,0 +1,148 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, parse, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug)]
+pub enum PubSub {
+    /// PUBSUB CHANNELS [pattern]
+    Channels(Option<String>),
+    /// PUBSUB NUMSUB [channel [channel ...]]
+    NumSub(Vec<String>),
+    /// PUBSUB NUMPAT
+    /// Pattern subscriptions are not supported, so there are none
+    NumPat,
+}
+
+impl PubSub {
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<PubSub> {
+        let subcommand = frames.next_string()?;
+
+        match subcommand.to_uppercase().as_str() {
+            "CHANNELS" => match frames.next_string() {
+                Ok(pattern) => Ok(PubSub::Channels(Some(pattern))),
+                Err(parse::Error::EndOfStream) => Ok(PubSub::Channels(None)),
+                Err(err) => Err(err.into()),
+            },
+            "NUMSUB" => {
+                let mut channels = Vec::new();
+                while let Ok(channel) = frames.next_string() {
+                    channels.push(channel);
+                }
+
+                Ok(PubSub::NumSub(channels))
+            }
+            "NUMPAT" => Ok(PubSub::NumPat),
+            _ => Err(format!("ERR unknown subcommand '{}'. Try PUBSUB HELP.", subcommand).into()),
+        }
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![Frame::Bulk("PUBSUB".into())];
+
+        match self {
+            PubSub::Channels(pattern) => {
+                frames.push(Frame::Bulk("CHANNELS".into()));
+                if let Some(pattern) = pattern {
+                    frames.push(Frame::Bulk(pattern.clone().into()));
+                }
+            }
+            PubSub::NumSub(channels) => {
+                frames.push(Frame::Bulk("NUMSUB".into()));
+                frames.extend(
+                    channels
+                        .iter()
+                        .map(|channel| Frame::Bulk(channel.clone().into())),
+                );
+            }
+            PubSub::NumPat => frames.push(Frame::Bulk("NUMPAT".into())),
+        }
+
+        Frame::Array(frames)
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match self {
+            PubSub::Channels(pattern) => Frame::Array(
+                db.pubsub_channels(pattern.as_deref())
+                    .into_iter()
+                    .map(|channel| Frame::Bulk(Bytes::from(channel)))
+                    .collect(),
+            ),
+            // Flat list of the channels with their number of subscribers
+            PubSub::NumSub(channels) => Frame::Array(
+                channels
+                    .iter()
+                    .flat_map(|channel| {
+                        [
+                            Frame::Bulk(Bytes::from(channel.clone())),
+                            Frame::Integer(db.pubsub_numsub(channel) as i64),
+                        ]
+                    })
+                    .collect(),
+            ),
+            PubSub::NumPat => Frame::Integer(0),
+        }
+    }
+}
+
+#[async_trait]
+impl CommandTrait for PubSub {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(PubSub::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::{connect, send, start_server};
+
+    #[tokio::test]
+    async fn numsub_counts_the_subscribed_clients() {
+        let addr = start_server(&[]).await;
+
+        let first = connect(addr).await;
+        let second = connect(addr).await;
+        for subscriber in [&first, &second] {
+            send(subscriber, &["SUBSCRIBE", "channel"]).await;
+        }
+
+        let client = connect(addr).await;
+        assert_eq!(
+            send(&client, &["PUBSUB", "NUMSUB", "channel", "other"]).await,
+            Frame::Array(vec![
+                Frame::Bulk("channel".into()),
+                Frame::Integer(2),
+                Frame::Bulk("other".into()),
+                Frame::Integer(0),
+            ])
+        );
+        assert_eq!(
+            send(&client, &["PUBSUB", "CHANNELS"]).await,
+            Frame::Array(vec![Frame::Bulk("channel".into())])
+        );
+        assert_eq!(
+            send(&client, &["PUBSUB", "NUMPAT"]).await,
+            Frame::Integer(0)
+        );
+    }
+}
@@ -14,12 +14,16 @@ pub struct ReplConfListeningPort(pub u16);
 pub enum ReplConf {
     /// REPLCONF listening-port \<port\>
     ListeningPort(ReplConfListeningPort),
-    /// REPLCONF capa psync2
-    Capa,
+    /// REPLCONF capa \<capability\> [capa \<capability\> ...]
+    Capa(Vec<String>),
     /// REPLCONF getack *
     GetAck,
     /// REPLCONF ack \<offset\>
     Ack(u64),
+    /// REPLCONF \<option\> [\<value\> ...]
+    /// Options exchanged by real Redis instances that are not needed here,
+    /// like ip-address, they are accepted and ignored
+    Ignored(String, Vec<String>),
 }
 
 impl ReplConf {
@@ -27,14 +31,12 @@ impl ReplConf {
         match frames.next_string() {
             Ok(section) => match section.as_str().to_lowercase().as_str() {
                 "listening-port" => ReplConf::parse_port(frames),
-                "capa" => ReplConf::parse_psync2(frames),
+                "capa" => ReplConf::parse_capa(frames),
                 "getack" => ReplConf::parse_get_ack(frames),
                 "ack" => ReplConf::parse_ack(frames),
-                _ => {
-                    Err(format!("Protocol error: unsupported REPLCONF section: {}", section).into())
-                }
+                _ => ReplConf::parse_ignored(section, frames),
             },
-            Err(parse::Error::EndOfStream) => Ok(ReplConf::Capa),
+            Err(parse::Error::EndOfStream) => Ok(ReplConf::Capa(Vec::new())),
             Err(err) => Err(err.into()),
         }
     }
@@ -48,14 +50,32 @@ impl ReplConf {
         Ok(ReplConf::ListeningPort(ReplConfListeningPort(port)))
     }
 
-    fn parse_psync2(frames: &mut Parse) -> crate::Result<ReplConf> {
-        let psync2 = frames.next_string()?.to_lowercase();
+    /// Parse the capabilities, a replica can send several of them
+    /// like `REPLCONF capa eof capa psync2`
+    fn parse_capa(frames: &mut Parse) -> crate::Result<ReplConf> {
+        let mut capabilities = vec![frames.next_string()?.to_lowercase()];
 
-        if psync2 == "psync2" {
-            Ok(ReplConf::Capa)
-        } else {
-            Err("Protocol error: expected command: REPLCONF capa psync2".into())
+        while let Some(option) = frames.peek_string() {
+            if option.to_lowercase() != "capa" {
+                break;
+            }
+
+            // Consume the "capa" string
+            frames.next_string()?;
+            capabilities.push(frames.next_string()?.to_lowercase());
         }
+
+        Ok(ReplConf::Capa(capabilities))
+    }
+
+    fn parse_ignored(option: String, frames: &mut Parse) -> crate::Result<ReplConf> {
+        let mut args = Vec::new();
+
+        while let Ok(arg) = frames.next_string() {
+            args.push(arg);
+        }
+
+        Ok(ReplConf::Ignored(option, args))
     }
 
     fn parse_get_ack(frames: &mut Parse) -> crate::Result<ReplConf> {
@@ -90,19 +110,15 @@ impl ReplConf {
 
                 Frame::Array(vec![frame_first, frame_second])
             }
-            ReplConf::Capa => {
-                let frame_first = Frame::Array(vec![
-                    Frame::Bulk(Bytes::from("REPLCONF".to_string())),
-                    Frame::Bulk(Bytes::from("listening-port".to_string())),
-                    Frame::Bulk(Bytes::from("".to_string())),
-                ]);
-                let frame_second = Frame::Array(vec![
-                    Frame::Bulk(Bytes::from("REPLCONF".to_string())),
-                    Frame::Bulk(Bytes::from("capa".to_string())),
-                    Frame::Bulk(Bytes::from("psync2".to_string())),
-                ]);
+            ReplConf::Capa(capabilities) => {
+                let mut frames = vec![Frame::Bulk(Bytes::from("REPLCONF".to_string()))];
 
-                Frame::Array(vec![frame_first, frame_second])
+                for capability in capabilities {
+                    frames.push(Frame::Bulk(Bytes::from("capa".to_string())));
+                    frames.push(Frame::Bulk(Bytes::from(capability.clone())));
+                }
+
+                Frame::Array(frames)
             }
             ReplConf::GetAck => Frame::Array(vec![
                 Frame::Bulk(Bytes::from("REPLCONF".to_string())),
@@ -114,6 +130,18 @@ impl ReplConf {
                 Frame::Bulk(Bytes::from("ACK".to_string())),
                 Frame::Bulk(Bytes::from(ack_offset.to_string())),
             ]),
+            ReplConf::Ignored(option, args) => {
+                let mut frames = vec![
+                    Frame::Bulk(Bytes::from("REPLCONF".to_string())),
+                    Frame::Bulk(Bytes::from(option.clone())),
+                ];
+
+                for arg in args {
+                    frames.push(Frame::Bulk(Bytes::from(arg.clone())));
+                }
+
+                Frame::Array(frames)
+            }
         }
     }
 
@@ -123,7 +151,7 @@ impl ReplConf {
                 server_info.add_slave(("127.0.0.1".to_string(), listening_port.0), connection);
                 Frame::Simple("OK".into())
             }
-            ReplConf::Capa => Frame::Simple("OK".into()),
+            ReplConf::Capa(_) | ReplConf::Ignored(..) => Frame::Simple("OK".into()),
             ReplConf::GetAck => Frame::Array(vec![
                 Frame::Bulk(Bytes::from("REPLCONF".to_string())),
                 Frame::Bulk(Bytes::from("ACK".to_string())),
@@ -169,3 +197,50 @@ impl CommandTrait for ReplConf {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::{connect, send, start_server};
+
+    fn parse(args: &[&str]) -> ReplConf {
+        let frame = Frame::Array(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+
+        ReplConf::parse_frames(&mut Parse::new(frame).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn multiple_capabilities_are_parsed() {
+        match parse(&["capa", "eof", "capa", "psync2"]) {
+            ReplConf::Capa(capabilities) => assert_eq!(capabilities, ["eof", "psync2"]),
+            replconf => panic!("expected capabilities, got {replconf:?}"),
+        }
+    }
+
+    #[test]
+    fn unknown_options_are_kept() {
+        match parse(&["ip-address", "10.0.0.1"]) {
+            ReplConf::Ignored(option, args) => {
+                assert_eq!(option, "ip-address");
+                assert_eq!(args, ["10.0.0.1"]);
+            }
+            replconf => panic!("expected an ignored option, got {replconf:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn capabilities_and_unknown_options_are_acknowledged() {
+        let client = connect(start_server(&[]).await).await;
+
+        for command in [
+            &["REPLCONF", "capa", "eof", "capa", "psync2"][..],
+            &["REPLCONF", "ip-address", "10.0.0.1"],
+        ] {
+            assert_eq!(send(&client, command).await, Frame::Simple("OK".into()));
+        }
+    }
+}
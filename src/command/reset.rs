@@ -0,0 +1,50 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// RESET, restores the default state of the connection
+#[derive(Debug, Default)]
+pub struct Reset {}
+
+impl Reset {
+    /// Drops the subscriptions and switches back to RESP2
+    pub fn execute(&self, connection: Connection) -> Frame {
+        connection.session().reset();
+
+        Frame::Simple("RESET".into())
+    }
+
+    pub fn parse_frames(_frames: &mut Parse) -> crate::Result<Reset> {
+        Ok(Reset {})
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![Frame::Bulk(Bytes::from("RESET"))])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Reset {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Reset::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, _db: &Db, _server_info: &mut Info, connection: Connection) -> Frame {
+        self.execute(connection)
+    }
+
+    fn execute_replica(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,160 @@
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, parse, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// RESTORE key ttl serialized-value [REPLACE] [ABSTTL] [IDLETIME seconds] [FREQ frequency]
+/// There is no LFU policy, so FREQ is validated and otherwise ignored
+#[derive(Debug, Default)]
+pub struct Restore {
+    key: String,
+    /// Time to live in milliseconds, 0 for no expiry
+    ttl: u64,
+    payload: Bytes,
+    replace: bool,
+    /// ABSTTL, the ttl is a unix timestamp in milliseconds
+    absolute: bool,
+    idle: Option<u32>,
+    freq: Option<u8>,
+}
+
+impl Restore {
+    pub fn new(
+        key: impl ToString,
+        ttl: u64,
+        payload: Bytes,
+        replace: bool,
+        absolute: bool,
+        idle: Option<u32>,
+        freq: Option<u8>,
+    ) -> Restore {
+        Restore {
+            key: key.to_string(),
+            ttl,
+            payload,
+            replace,
+            absolute,
+            idle,
+            freq,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        let expiry = match (self.ttl, self.absolute) {
+            (0, _) => None,
+            (ttl, true) => SystemTime::UNIX_EPOCH.checked_add(Duration::from_millis(ttl)),
+            (ttl, false) => SystemTime::now().checked_add(Duration::from_millis(ttl)),
+        };
+
+        match db.restore(
+            self.key.clone(),
+            &self.payload,
+            expiry,
+            self.idle,
+            self.replace,
+        ) {
+            Ok(()) => Frame::Simple("OK".to_string()),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Restore> {
+        const SYNTAX_ERR: &str = "ERR syntax error";
+
+        let key = frames.next_string()?;
+        let ttl =
+            u64::try_from(frames.next_int()?).map_err(|_| "ERR Invalid TTL value, must be >= 0")?;
+        let payload = frames.next_bytes()?;
+
+        let mut replace = false;
+        let mut absolute = false;
+        let mut idle = None;
+        let mut freq = None;
+
+        loop {
+            let option = match frames.next_string() {
+                Ok(option) => option.to_uppercase(),
+                Err(parse::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            match option.as_str() {
+                "REPLACE" => replace = true,
+                "ABSTTL" => absolute = true,
+                "IDLETIME" if idle.is_none() => {
+                    let secs = u32::try_from(frames.next_int()?)
+                        .map_err(|_| "ERR Invalid IDLETIME value, must be >= 0")?;
+                    idle = Some(secs);
+                }
+                "FREQ" if freq.is_none() => {
+                    let value = u8::try_from(frames.next_int()?)
+                        .map_err(|_| "ERR Invalid FREQ value, must be >= 0 and <= 255")?;
+                    freq = Some(value);
+                }
+                _ => return Err(SYNTAX_ERR.into()),
+            }
+        }
+
+        if idle.is_some() && freq.is_some() {
+            return Err("ERR IDLETIME and FREQ cannot be set at the same time.".into());
+        }
+
+        Ok(Restore::new(
+            key, ttl, payload, replace, absolute, idle, freq,
+        ))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("RESTORE".into()),
+            Frame::Bulk(self.key.clone().into()),
+            Frame::Bulk(self.ttl.to_string().into()),
+            Frame::Bulk(self.payload.clone()),
+        ];
+
+        if self.replace {
+            frames.push(Frame::Bulk("REPLACE".into()));
+        }
+        if self.absolute {
+            frames.push(Frame::Bulk("ABSTTL".into()));
+        }
+        if let Some(idle) = self.idle {
+            frames.push(Frame::Bulk("IDLETIME".into()));
+            frames.push(Frame::Bulk(idle.to_string().into()));
+        }
+        if let Some(freq) = self.freq {
+            frames.push(Frame::Bulk("FREQ".into()));
+            frames.push(Frame::Bulk(freq.to_string().into()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Restore {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Restore::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,76 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::{CommandTrait, ListDirection};
+
+#[derive(Debug, Default)]
+pub struct RPush {
+    key: String,
+    values: Vec<Bytes>,
+}
+
+impl RPush {
+    pub fn new(key: impl ToString, values: Vec<Bytes>) -> RPush {
+        RPush {
+            key: key.to_string(),
+            values,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.push(self.key.clone(), ListDirection::Right, self.values.clone()) {
+            Ok(len) => Frame::Integer(len as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<RPush> {
+        let key = frames.next_string()?;
+        let mut values = vec![frames.next_bytes()?];
+
+        while let Ok(value) = frames.next_bytes() {
+            values.push(value);
+        }
+
+        Ok(RPush::new(key, values))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("RPUSH".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ];
+
+        for value in &self.values {
+            frames.push(Frame::Bulk(value.clone()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for RPush {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(RPush::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,226 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, glob, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// Number of keys visited by a SCAN call when COUNT is not given
+const DEFAULT_COUNT: usize = 10;
+
+/// Cursor and options shared by SCAN, HSCAN, SSCAN and ZSCAN
+#[derive(Debug)]
+pub struct ScanArgs {
+    pub cursor: u64,
+    pattern: Option<String>,
+    pub count: usize,
+}
+
+impl ScanArgs {
+    /// Parse the cursor and the MATCH and COUNT options
+    /// Other options are passed to `parse_option`, that returns false if it does not know them
+    pub fn parse_frames(
+        frames: &mut Parse,
+        mut parse_option: impl FnMut(&str, &mut Parse) -> crate::Result<bool>,
+    ) -> crate::Result<ScanArgs> {
+        let cursor = frames
+            .next_string()?
+            .parse::<u64>()
+            .map_err(|_| "ERR invalid cursor")?;
+
+        let mut pattern = None;
+        let mut count = DEFAULT_COUNT;
+
+        while let Ok(option) = frames.next_string() {
+            match option.to_uppercase().as_str() {
+                "MATCH" => pattern = Some(frames.next_string()?),
+                "COUNT" => {
+                    count = match frames.next_int()? {
+                        count if count >= 1 => count as usize,
+                        _ => return Err("ERR syntax error".into()),
+                    }
+                }
+                option => {
+                    if !parse_option(option, frames)? {
+                        return Err("ERR syntax error".into());
+                    }
+                }
+            }
+        }
+
+        Ok(ScanArgs {
+            cursor,
+            pattern,
+            count,
+        })
+    }
+
+    /// Returns true if the item matches the MATCH pattern, if any
+    pub fn matches(&self, item: &str) -> bool {
+        match &self.pattern {
+            Some(pattern) => glob::matches(pattern, item),
+            None => true,
+        }
+    }
+
+    /// Frames of the cursor and the options
+    pub fn to_frames(&self) -> Vec<Frame> {
+        let mut frames = vec![Frame::Bulk(self.cursor.to_string().into())];
+
+        if let Some(pattern) = &self.pattern {
+            frames.push(Frame::Bulk("MATCH".into()));
+            frames.push(Frame::Bulk(pattern.clone().into()));
+        }
+
+        frames.push(Frame::Bulk("COUNT".into()));
+        frames.push(Frame::Bulk(self.count.to_string().into()));
+
+        frames
+    }
+}
+
+#[derive(Debug)]
+pub struct Scan {
+    args: ScanArgs,
+    /// Only return keys of this type, as reported by TYPE
+    key_type: Option<String>,
+}
+
+impl Scan {
+    pub fn new(args: ScanArgs, key_type: Option<String>) -> Scan {
+        Scan { args, key_type }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        let (cursor, keys) = db.scan(self.args.cursor, self.args.count);
+
+        // Filters are applied after the keys are visited, like in Redis,
+        // so a call may return less keys than COUNT, or none at all
+        let keys = keys
+            .into_iter()
+            .filter(|key| self.args.matches(key))
+            .filter(|key| match &self.key_type {
+                Some(key_type) => db.get_type(key) == *key_type,
+                None => true,
+            })
+            .map(|key| Frame::Bulk(key.into()))
+            .collect();
+
+        Frame::Array(vec![
+            Frame::Bulk(cursor.to_string().into()),
+            Frame::Array(keys),
+        ])
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Scan> {
+        let mut key_type = None;
+
+        let args = ScanArgs::parse_frames(frames, |option, frames| match option {
+            "TYPE" => {
+                key_type = Some(frames.next_string()?.to_lowercase());
+                Ok(true)
+            }
+            _ => Ok(false),
+        })?;
+
+        Ok(Scan::new(args, key_type))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![Frame::Bulk("SCAN".into())];
+        frames.extend(self.args.to_frames());
+
+        if let Some(key_type) = &self.key_type {
+            frames.push(Frame::Bulk("TYPE".into()));
+            frames.push(Frame::Bulk(key_type.clone().into()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Scan {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Scan::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::command::{ListDirection, XAddId};
+
+    fn parse(args: &[&str]) -> Scan {
+        let frame = Frame::Array(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+
+        Scan::parse_frames(&mut Parse::new(frame).unwrap()).unwrap()
+    }
+
+    fn keys(frame: Frame) -> Vec<Bytes> {
+        let mut keys: Vec<_> = match frame.into_array().unwrap().pop() {
+            Some(Frame::Array(keys)) => keys
+                .into_iter()
+                .map(|key| match key {
+                    Frame::Bulk(key) => key,
+                    key => panic!("expected a bulk key, got {key:?}"),
+                })
+                .collect(),
+            frame => panic!("expected an array of keys, got {frame:?}"),
+        };
+        keys.sort();
+
+        keys
+    }
+
+    #[tokio::test]
+    async fn type_filter_only_returns_keys_of_the_type() {
+        let db = Db::new();
+        db.set("string".to_string(), Bytes::from("value"), None);
+        db.push(
+            "list".to_string(),
+            ListDirection::Right,
+            vec![Bytes::from("a")],
+        )
+        .unwrap();
+        for key in ["stream1", "stream2"] {
+            db.xadd(
+                key.to_string(),
+                XAddId::Auto,
+                vec![("field".to_string(), Bytes::from("value"))],
+                false,
+            )
+            .unwrap();
+        }
+
+        let reply = parse(&["0", "COUNT", "100", "TYPE", "stream"]).execute(&db);
+        assert_eq!(keys(reply), ["stream1", "stream2"]);
+
+        let reply = parse(&["0", "COUNT", "100", "TYPE", "LIST"]).execute(&db);
+        assert_eq!(keys(reply), ["list"]);
+
+        let reply = parse(&["0", "COUNT", "100", "TYPE", "hash"]).execute(&db);
+        assert!(keys(reply).is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -7,61 +7,182 @@ use crate::{connection::Connection, db::Db, parse, Frame, Info, Parse};
 
 use super::CommandTrait;
 
-#[derive(Debug, Default)]
+/// Condition on the existence of the key for SET
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SetCondition {
+    #[default]
+    Always,
+    /// NX, only set the key if it does not already exist
+    IfNotExists,
+    /// XX, only set the key if it already exists
+    IfExists,
+}
+
+#[derive(Debug)]
 pub struct Set {
     key: String,
     value: Bytes,
+    /// Time to live, counted from when the command was received
     expire: Option<Duration>,
+    condition: SetCondition,
+    /// GET, reply with the previous value instead of OK
+    get_old: bool,
+    /// Time the command was received, the expiry propagated to the replicas counts from it
+    received_at: SystemTime,
 }
 
 impl Set {
-    pub fn new(key: impl ToString, value: Bytes, expire: Option<Duration>) -> Self {
+    pub fn new(
+        key: impl ToString,
+        value: Bytes,
+        expire: Option<Duration>,
+        condition: SetCondition,
+        get_old: bool,
+    ) -> Self {
         Self {
             key: key.to_string(),
             value,
             expire,
+            condition,
+            get_old,
+            received_at: SystemTime::now(),
         }
     }
 
+    /// Time at which the key expires, in milliseconds since the epoch
+    fn expires_at_millis(&self) -> Option<u128> {
+        let when = self.received_at.checked_add(self.expire?)?;
+
+        when.duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .ok()
+    }
+
     pub fn execute(&self, db: &Db) -> Frame {
-        db.set(self.key.clone(), self.value.clone(), self.expire);
-        Frame::Simple("OK".to_string())
+        // Plain SET always succeeds
+        if self.condition == SetCondition::Always && !self.get_old {
+            db.set(self.key.clone(), self.value.clone(), self.expire);
+            return Frame::Simple("OK".to_string());
+        }
+
+        let result = db.set_if(
+            self.key.clone(),
+            self.value.clone(),
+            self.expire,
+            self.condition,
+            self.get_old,
+        );
+
+        match result {
+            // With GET the previous value is returned even if NX or XX prevented the set
+            Ok((_, Some(prev))) if self.get_old => Frame::Bulk(prev),
+            Ok((_, None)) if self.get_old => Frame::Null,
+            Ok((true, _)) => Frame::Simple("OK".to_string()),
+            Ok((false, _)) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        }
     }
 
+    /// Parse SET key value [NX | XX] [GET] [EX seconds | PX milliseconds],
+    /// the options can be given in any order
     pub fn parse_frames(frames: &mut Parse) -> crate::Result<Set> {
+        const SYNTAX_ERR: &str = "ERR syntax error";
+        const EXPIRE_ERR: &str = "ERR invalid expire time in 'set' command";
+
         let key = frames.next_string()?;
         let value = frames.next_bytes()?;
 
-        let expire = match frames.next_string() {
-            // Parse the EX option for seconds
-            Ok(s) if s.to_uppercase() == "EX" => {
-                let expire = frames.next_uint()?;
-                Some(Duration::from_secs(expire))
-            }
-            // Parse the PX option for milliseconds
-            Ok(s) if s.to_uppercase() == "PX" => {
-                let expire = frames.next_uint()?;
-                Some(Duration::from_millis(expire))
-            }
-            Ok(_) => return Err("Protocol error: expected EX or PX for expiration".into()),
-            // No expiration if end of stream is reached
-            Err(parse::Error::EndOfStream) => None,
-            Err(err) => return Err(err.into()),
+        let received_at = SystemTime::now();
+        // Unix time in milliseconds of EXAT and PXAT, as a time to live
+        let ttl_until = |millis: u64| {
+            (SystemTime::UNIX_EPOCH + Duration::from_millis(millis))
+                .duration_since(received_at)
+                // A time in the past, the key expires right away
+                .unwrap_or(Duration::ZERO)
         };
 
-        Ok(Set::new(key, value, expire))
+        let now_millis = received_at
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        // The value of an expiry option in milliseconds, which must be positive
+        // and must not overflow once added to the time it counts from
+        let expire_millis = |frames: &mut Parse, unit: u64, from: u64| -> crate::Result<u64> {
+            u64::try_from(frames.next_int()?)
+                .ok()
+                .filter(|&time| time > 0)
+                .and_then(|time| time.checked_mul(unit))
+                .filter(|millis| from.checked_add(*millis) <= Some(i64::MAX as u64))
+                .ok_or_else(|| EXPIRE_ERR.into())
+        };
+
+        let mut expire = None;
+        let mut condition = SetCondition::Always;
+        let mut get_old = false;
+
+        loop {
+            let option = match frames.next_string() {
+                Ok(option) => option.to_uppercase(),
+                Err(parse::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            match option.as_str() {
+                // Parse the EX option for seconds
+                "EX" if expire.is_none() => {
+                    let millis = expire_millis(frames, 1000, now_millis)?;
+                    expire = Some(Duration::from_millis(millis));
+                }
+                // Parse the PX option for milliseconds
+                "PX" if expire.is_none() => {
+                    let millis = expire_millis(frames, 1, now_millis)?;
+                    expire = Some(Duration::from_millis(millis));
+                }
+                "EXAT" if expire.is_none() => {
+                    expire = Some(ttl_until(expire_millis(frames, 1000, 0)?));
+                }
+                "PXAT" if expire.is_none() => {
+                    expire = Some(ttl_until(expire_millis(frames, 1, 0)?));
+                }
+                "NX" if condition == SetCondition::Always => condition = SetCondition::IfNotExists,
+                "XX" if condition == SetCondition::Always => condition = SetCondition::IfExists,
+                "GET" => get_old = true,
+                _ => return Err(SYNTAX_ERR.into()),
+            }
+        }
+
+        let mut set = Set::new(key, value, expire, condition, get_old);
+        set.received_at = received_at;
+
+        Ok(set)
     }
 
     pub fn to_frame(&self) -> Frame {
+        let expire = self.expire.map(|expire| ("PX", expire.as_millis()));
+
+        self.frame_with_expire(expire)
+    }
+
+    /// The SET frame with the expiry option and its value in milliseconds
+    fn frame_with_expire(&self, expire: Option<(&str, u128)>) -> Frame {
         let mut frame = vec![
             Frame::Bulk("SET".into()),
             Frame::Bulk(self.key.clone().into()),
             Frame::Bulk(self.value.clone()),
         ];
 
-        if let Some(expire) = self.expire {
-            frame.push(Frame::Bulk("EX".into()));
-            frame.push(Frame::Bulk(expire.as_secs().to_string().into()));
+        if let Some((option, millis)) = expire {
+            frame.push(Frame::Bulk(option.to_string().into()));
+            frame.push(Frame::Bulk(millis.to_string().into()));
+        }
+
+        match self.condition {
+            SetCondition::Always => {}
+            SetCondition::IfNotExists => frame.push(Frame::Bulk("NX".into())),
+            SetCondition::IfExists => frame.push(Frame::Bulk("XX".into())),
+        }
+
+        if self.get_old {
+            frame.push(Frame::Bulk("GET".into()));
         }
 
         Frame::Array(frame)
@@ -87,7 +208,51 @@ impl CommandTrait for Set {
         self.to_frame()
     }
 
+    /// The expiry is propagated as PXAT, so the replicas don't count
+    /// the time to live from when they receive the command
+    fn propagated_frame(&self, _frame: Frame, _response: &Frame) -> Frame {
+        let expire = self.expires_at_millis().map(|millis| ("PXAT", millis));
+
+        self.frame_with_expire(expire)
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> crate::Result<Set> {
+        let frame = Frame::Array(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+
+        Set::parse_frames(&mut Parse::new(frame).unwrap())
+    }
+
+    #[test]
+    fn invalid_expire_time_is_rejected() {
+        let err = "ERR invalid expire time in 'set' command";
+
+        for (option, time) in [
+            ("EX", "0"),
+            ("PX", "0"),
+            ("EX", "-1"),
+            ("PXAT", "0"),
+            ("EX", "9223372036854775807"),
+            ("PX", "9223372036854775807"),
+            ("EXAT", "9223372036854776"),
+        ] {
+            let result = parse(&["key", "value", option, time]);
+            assert_eq!(result.unwrap_err().to_string(), err, "{option} {time}");
+        }
+
+        assert!(parse(&["key", "value", "EX", "100"]).is_ok());
+        assert!(parse(&["key", "value", "PXAT", "1"]).is_ok());
+    }
+}
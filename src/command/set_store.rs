@@ -0,0 +1,190 @@
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// Set operation of SINTERSTORE, SUNIONSTORE and SDIFFSTORE
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum SetOperation {
+    #[default]
+    Inter,
+    Union,
+    Diff,
+}
+
+impl SetOperation {
+    /// Applies the operation to the members of the sets, a missing key is an empty set.
+    /// The members are returned once each, in the order of the first set they appear in.
+    pub fn apply(&self, sets: &[Vec<Bytes>]) -> Vec<Bytes> {
+        let (first, others) = match sets.split_first() {
+            Some(split) => split,
+            None => return Vec::new(),
+        };
+        let others = others
+            .iter()
+            .map(|set| set.iter().collect::<HashSet<_>>())
+            .collect::<Vec<_>>();
+
+        let mut seen = HashSet::new();
+        let members: Box<dyn Iterator<Item = &Bytes>> = match self {
+            SetOperation::Inter => Box::new(
+                first
+                    .iter()
+                    .filter(|member| others.iter().all(|set| set.contains(member))),
+            ),
+            SetOperation::Union => Box::new(sets.iter().flatten()),
+            SetOperation::Diff => Box::new(
+                first
+                    .iter()
+                    .filter(|member| !others.iter().any(|set| set.contains(member))),
+            ),
+        };
+
+        members
+            .filter(|member| seen.insert(*member))
+            .cloned()
+            .collect()
+    }
+
+    fn command_name(&self) -> &'static str {
+        match self {
+            SetOperation::Inter => "SINTERSTORE",
+            SetOperation::Union => "SUNIONSTORE",
+            SetOperation::Diff => "SDIFFSTORE",
+        }
+    }
+}
+
+/// SINTERSTORE | SUNIONSTORE | SDIFFSTORE destination key [key ...]
+#[derive(Debug, Default)]
+pub struct SetStore {
+    op: SetOperation,
+    destination: String,
+    keys: Vec<String>,
+}
+
+impl SetStore {
+    pub fn new(op: SetOperation, destination: impl ToString, keys: Vec<String>) -> SetStore {
+        SetStore {
+            op,
+            destination: destination.to_string(),
+            keys,
+        }
+    }
+
+    /// Replies with the number of members stored, the destination is deleted if there are none
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.set_store(self.op, self.destination.clone(), &self.keys) {
+            Ok(len) => Frame::Integer(len as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse, op: SetOperation) -> crate::Result<SetStore> {
+        let destination = frames.next_string()?;
+
+        let mut keys = vec![frames.next_string()?];
+        while let Ok(key) = frames.next_string() {
+            keys.push(key);
+        }
+
+        Ok(SetStore::new(op, destination, keys))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk(self.op.command_name().into()),
+            Frame::Bulk(self.destination.clone().into()),
+        ];
+
+        for key in &self.keys {
+            frames.push(Frame::Bulk(key.clone().into()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for SetStore {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(SetStore::parse_frames(frames, self.op)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::{connect, send, start_server};
+
+    fn members(members: &[&str]) -> Vec<Bytes> {
+        members
+            .iter()
+            .map(|member| Bytes::from(member.to_string()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn empty_result_deletes_the_destination() {
+        let db = Db::new();
+        db.sadd("a".to_string(), members(&["1", "2"])).unwrap();
+        db.sadd("b".to_string(), members(&["3"])).unwrap();
+        db.sadd("destination".to_string(), members(&["x"])).unwrap();
+
+        let keys = vec!["a".to_string(), "b".to_string()];
+        let store = SetStore::new(SetOperation::Inter, "destination", keys);
+        assert_eq!(store.execute(&db), Frame::Integer(0));
+        assert_eq!(db.get_type("destination"), "none");
+    }
+
+    #[tokio::test]
+    async fn deleted_destination_is_propagated() {
+        let master = start_server(&[]).await;
+        let master_port = master.port().to_string();
+        let replica = start_server(&["--replicaof", "127.0.0.1", &master_port]).await;
+
+        let client = connect(master).await;
+        send(&client, &["SADD", "a", "1", "2"]).await;
+        send(&client, &["SADD", "b", "3"]).await;
+        send(&client, &["SADD", "destination", "x"]).await;
+        assert_eq!(
+            send(&client, &["SINTERSTORE", "destination", "a", "b"]).await,
+            Frame::Integer(0)
+        );
+        assert_eq!(
+            send(&client, &["WAIT", "1", "1000"]).await,
+            Frame::Integer(1)
+        );
+
+        let client = connect(replica).await;
+        assert_eq!(
+            send(&client, &["TYPE", "destination"]).await,
+            Frame::Simple("none".into())
+        );
+        assert_eq!(
+            send(&client, &["TYPE", "a"]).await,
+            Frame::Simple("set".into())
+        );
+    }
+}
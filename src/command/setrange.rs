@@ -0,0 +1,152 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// Max length of a string value, 512MB like in Redis
+const MAX_STRING_LEN: u64 = 512 * 1024 * 1024;
+
+#[derive(Debug, Default)]
+pub struct SetRange {
+    key: String,
+    offset: usize,
+    value: Bytes,
+}
+
+impl SetRange {
+    pub fn new(key: impl ToString, offset: usize, value: Bytes) -> SetRange {
+        SetRange {
+            key: key.to_string(),
+            offset,
+            value,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.setrange(self.key.clone(), self.offset, self.value.clone()) {
+            Ok(len) => Frame::Integer(len as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<SetRange> {
+        let key = frames.next_string()?;
+        let offset = frames
+            .next_int()
+            .ok()
+            .and_then(|offset| u64::try_from(offset).ok())
+            .ok_or("ERR offset is out of range")?;
+        let value = frames.next_bytes()?;
+
+        if offset + value.len() as u64 > MAX_STRING_LEN {
+            return Err("ERR string exceeds maximum allowed size (proto-max-bulk-len)".into());
+        }
+
+        Ok(SetRange::new(key, offset as usize, value))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk("SETRANGE".into()),
+            Frame::Bulk(self.key.clone().into()),
+            Frame::Bulk(self.offset.to_string().into()),
+            Frame::Bulk(self.value.clone()),
+        ])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for SetRange {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(SetRange::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn missing_key_is_zero_padded() {
+        let db = Db::new();
+
+        let reply = SetRange::new("key", 5, Bytes::from("x")).execute(&db);
+
+        assert_eq!(reply, Frame::Integer(6));
+        assert_eq!(
+            db.getrange("key", 0, -1).unwrap(),
+            Bytes::from_static(b"\0\0\0\0\0x")
+        );
+    }
+
+    #[tokio::test]
+    async fn short_string_is_zero_padded() {
+        let db = Db::new();
+        db.set("key".to_string(), Bytes::from("ab"), None);
+
+        let reply = SetRange::new("key", 4, Bytes::from("cd")).execute(&db);
+
+        assert_eq!(reply, Frame::Integer(6));
+        assert_eq!(
+            db.getrange("key", 0, -1).unwrap(),
+            Bytes::from_static(b"ab\0\0cd")
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_value_does_not_create_key() {
+        let db = Db::new();
+
+        let reply = SetRange::new("key", 5, Bytes::new()).execute(&db);
+
+        assert_eq!(reply, Frame::Integer(0));
+        assert!(db.get("key").is_none());
+    }
+
+    #[tokio::test]
+    async fn large_offset_is_read_back() {
+        let db = Db::new();
+        let offset = 1024 * 1024;
+
+        let reply = SetRange::new("key", offset, Bytes::from("end")).execute(&db);
+
+        assert_eq!(reply, Frame::Integer(offset as i64 + 3));
+        let end = db.getrange("key", offset as i64, -1).unwrap();
+        assert_eq!(end, Bytes::from("end"));
+        let padding = db.getrange("key", 0, offset as i64 - 1).unwrap();
+        assert!(padding.len() == offset && padding.iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn offset_past_max_string_len_is_rejected() {
+        let frame = Frame::Array(vec![
+            Frame::Bulk("SETRANGE".into()),
+            Frame::Bulk("key".into()),
+            Frame::Bulk(MAX_STRING_LEN.to_string().into()),
+            Frame::Bulk("x".into()),
+        ]);
+        let mut frames = Parse::new(frame).unwrap();
+        frames.next_string().unwrap();
+
+        assert!(SetRange::parse_frames(&mut frames).is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug, Default)]
+pub struct SMembers {
+    key: String,
+}
+
+impl SMembers {
+    pub fn new(key: impl ToString) -> SMembers {
+        SMembers {
+            key: key.to_string(),
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.smembers(&self.key) {
+            Ok(members) => Frame::Array(members.into_iter().map(Frame::Bulk).collect()),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<SMembers> {
+        let key = frames.next_string()?;
+        Ok(SMembers::new(key))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk("SMEMBERS".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for SMembers {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(SMembers::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
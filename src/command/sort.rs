@@ -0,0 +1,154 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, parse, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// Options of SORT
+#[derive(Debug, Clone, Default)]
+pub struct SortOpts {
+    /// BY pattern, the elements are sorted by the values of the keys it names,
+    /// a pattern without `*` skips the sorting
+    pub by: Option<String>,
+    /// LIMIT offset count
+    pub limit: Option<(i64, i64)>,
+    /// GET patterns, `#` being the element itself
+    pub get: Vec<String>,
+    pub desc: bool,
+    /// Sort lexicographically instead of numerically
+    pub alpha: bool,
+}
+
+/// SORT key [BY pattern] [LIMIT offset count] [GET pattern [GET pattern ...]] [ASC | DESC] [ALPHA]
+/// [STORE destination]
+#[derive(Debug, Default)]
+pub struct Sort {
+    key: String,
+    opts: SortOpts,
+    /// STORE destination, the result is stored as a list
+    store: Option<String>,
+}
+
+impl Sort {
+    pub fn new(key: impl ToString, opts: SortOpts, store: Option<String>) -> Sort {
+        Sort {
+            key: key.to_string(),
+            opts,
+            store,
+        }
+    }
+
+    /// Returns true if the result is stored, so it has to be propagated
+    pub fn is_write(&self) -> bool {
+        self.store.is_some()
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        if let Some(destination) = &self.store {
+            return match db.sort_store(&self.key, &self.opts, destination.clone()) {
+                Ok(len) => Frame::Integer(len as i64),
+                Err(err) => Frame::Error(err.to_string()),
+            };
+        }
+
+        match db.sort(&self.key, &self.opts) {
+            Ok(values) => Frame::Array(
+                values
+                    .into_iter()
+                    .map(|value| value.map(Frame::Bulk).unwrap_or(Frame::Null))
+                    .collect(),
+            ),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Sort> {
+        const SYNTAX_ERR: &str = "ERR syntax error";
+
+        let key = frames.next_string()?;
+
+        let mut opts = SortOpts::default();
+        let mut store = None;
+
+        loop {
+            let option = match frames.next_string() {
+                Ok(option) => option.to_uppercase(),
+                Err(parse::Error::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            match option.as_str() {
+                "BY" => opts.by = Some(frames.next_string()?),
+                "LIMIT" => opts.limit = Some((frames.next_int()?, frames.next_int()?)),
+                "GET" => opts.get.push(frames.next_string()?),
+                "ASC" => opts.desc = false,
+                "DESC" => opts.desc = true,
+                "ALPHA" => opts.alpha = true,
+                "STORE" => store = Some(frames.next_string()?),
+                _ => return Err(SYNTAX_ERR.into()),
+            }
+        }
+
+        Ok(Sort::new(key, opts, store))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk(Bytes::from("SORT")),
+            Frame::Bulk(Bytes::from(self.key.clone())),
+        ];
+
+        if let Some(by) = &self.opts.by {
+            frames.push(Frame::Bulk(Bytes::from("BY")));
+            frames.push(Frame::Bulk(Bytes::from(by.clone())));
+        }
+        if let Some((offset, count)) = self.opts.limit {
+            frames.push(Frame::Bulk(Bytes::from("LIMIT")));
+            frames.push(Frame::Bulk(Bytes::from(offset.to_string())));
+            frames.push(Frame::Bulk(Bytes::from(count.to_string())));
+        }
+        for pattern in &self.opts.get {
+            frames.push(Frame::Bulk(Bytes::from("GET")));
+            frames.push(Frame::Bulk(Bytes::from(pattern.clone())));
+        }
+        if self.opts.desc {
+            frames.push(Frame::Bulk(Bytes::from("DESC")));
+        }
+        if self.opts.alpha {
+            frames.push(Frame::Bulk(Bytes::from("ALPHA")));
+        }
+        if let Some(destination) = &self.store {
+            frames.push(Frame::Bulk(Bytes::from("STORE")));
+            frames.push(Frame::Bulk(Bytes::from(destination.clone())));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Sort {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Sort::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        if self.is_write() {
+            self.execute(db);
+        }
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,169 @@
+/// Arity and key positions of a command, like the legacy key specs of Redis
+#[derive(Debug)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    /// Number of arguments including the command name,
+    /// a negative arity `-n` means at least `n` arguments
+    pub arity: i64,
+    /// Flags of the command, like `write` for the commands modifying the dataset
+    pub flags: &'static [&'static str],
+    /// Position of the first key, 0 if the command has no keys
+    pub first_key: usize,
+    /// Position of the last key, negative positions count from the end
+    pub last_key: i64,
+    /// Step between the keys
+    pub step: usize,
+}
+
+const WRITE: &[&str] = &["write"];
+const READONLY: &[&str] = &["readonly"];
+const ADMIN: &[&str] = &["admin", "noscript"];
+const PUBSUB: &[&str] = &["pubsub"];
+
+const fn spec(
+    name: &'static str,
+    arity: i64,
+    flags: &'static [&'static str],
+    first_key: usize,
+    last_key: i64,
+    step: usize,
+) -> CommandSpec {
+    CommandSpec {
+        name,
+        arity,
+        flags,
+        first_key,
+        last_key,
+        step,
+    }
+}
+
+/// Specs of all the supported commands
+/// Commands with keys at variable positions, like XREAD, are listed without keys.
+/// Commands only modifying the dataset with some arguments, like SORT with STORE, are flagged `write`
+pub const COMMAND_SPECS: &[CommandSpec] = &[
+    spec("APPEND", 3, WRITE, 1, 1, 1),
+    spec("BITFIELD", -2, WRITE, 1, 1, 1),
+    spec("BITOP", -4, WRITE, 2, -1, 1),
+    spec("BITPOS", -3, READONLY, 1, 1, 1),
+    spec("CLUSTER", -2, &[], 0, 0, 0),
+    spec("COMMAND", -1, &[], 0, 0, 0),
+    spec("CONFIG", -2, ADMIN, 0, 0, 0),
+    spec("DBSIZE", 1, READONLY, 0, 0, 0),
+    spec("DEBUG", -2, ADMIN, 0, 0, 0),
+    spec("DISCARD", 1, &[], 0, 0, 0),
+    spec("DUMP", 2, READONLY, 1, 1, 1),
+    spec("ECHO", 2, &[], 0, 0, 0),
+    spec("EXEC", 1, &[], 0, 0, 0),
+    spec("EXPIRE", -3, WRITE, 1, 1, 1),
+    spec("EXPIREAT", -3, WRITE, 1, 1, 1),
+    spec("FUNCTION", -2, WRITE, 0, 0, 0),
+    spec("GET", 2, READONLY, 1, 1, 1),
+    spec("GETRANGE", 4, READONLY, 1, 1, 1),
+    spec("GETSET", 3, WRITE, 1, 1, 1),
+    spec("HELLO", -1, &[], 0, 0, 0),
+    spec("HGET", 3, READONLY, 1, 1, 1),
+    spec("HRANDFIELD", -2, READONLY, 1, 1, 1),
+    spec("HSCAN", -3, READONLY, 1, 1, 1),
+    spec("HSET", -4, WRITE, 1, 1, 1),
+    spec("HSETNX", 4, WRITE, 1, 1, 1),
+    spec("INFO", -1, &[], 0, 0, 0),
+    spec("KEYS", 2, READONLY, 0, 0, 0),
+    spec("LCS", -3, READONLY, 1, 2, 1),
+    spec("LMOVE", 5, WRITE, 1, 2, 1),
+    spec("LMPOP", -4, WRITE, 0, 0, 0),
+    spec("LOLWUT", -1, READONLY, 0, 0, 0),
+    spec("LPUSH", -3, WRITE, 1, 1, 1),
+    spec("LRANGE", 4, READONLY, 1, 1, 1),
+    spec("LTRIM", 4, WRITE, 1, 1, 1),
+    spec("MGET", -2, READONLY, 1, -1, 1),
+    spec("MULTI", 1, &[], 0, 0, 0),
+    spec("OBJECT", -2, READONLY, 2, 2, 1),
+    spec("PERSIST", 2, WRITE, 1, 1, 1),
+    spec("PEXPIRE", -3, WRITE, 1, 1, 1),
+    spec("PEXPIREAT", -3, WRITE, 1, 1, 1),
+    spec("PING", -1, &[], 0, 0, 0),
+    spec("PSYNC", -3, ADMIN, 0, 0, 0),
+    spec("PTTL", 2, READONLY, 1, 1, 1),
+    spec("PUBLISH", 3, PUBSUB, 0, 0, 0),
+    spec("PUBSUB", -2, PUBSUB, 0, 0, 0),
+    spec("REPLCONF", -1, ADMIN, 0, 0, 0),
+    spec("RESET", 1, &[], 0, 0, 0),
+    spec("RESTORE", -4, WRITE, 1, 1, 1),
+    spec("RPOPLPUSH", 3, WRITE, 1, 2, 1),
+    spec("RPUSH", -3, WRITE, 1, 1, 1),
+    spec("SADD", -3, WRITE, 1, 1, 1),
+    spec("SCAN", -2, READONLY, 0, 0, 0),
+    spec("SDIFFSTORE", -3, WRITE, 1, -1, 1),
+    spec("SET", -3, WRITE, 1, 1, 1),
+    spec("SETRANGE", 4, WRITE, 1, 1, 1),
+    spec("SINTERSTORE", -3, WRITE, 1, -1, 1),
+    spec("SMEMBERS", 2, READONLY, 1, 1, 1),
+    spec("SORT", -2, WRITE, 1, 1, 1),
+    spec("SPOP", -2, WRITE, 1, 1, 1),
+    spec("SRANDMEMBER", -2, READONLY, 1, 1, 1),
+    spec("SREM", -3, WRITE, 1, 1, 1),
+    spec("SSCAN", -3, READONLY, 1, 1, 1),
+    spec("STRLEN", 2, READONLY, 1, 1, 1),
+    spec("SUNIONSTORE", -3, WRITE, 1, -1, 1),
+    spec("SUBSCRIBE", -2, PUBSUB, 0, 0, 0),
+    spec("TTL", 2, READONLY, 1, 1, 1),
+    spec("TYPE", 2, READONLY, 1, 1, 1),
+    spec("UNSUBSCRIBE", -1, PUBSUB, 0, 0, 0),
+    spec("WAIT", 3, &[], 0, 0, 0),
+    spec("WAITAOF", 4, &[], 0, 0, 0),
+    spec("XADD", -5, WRITE, 1, 1, 1),
+    spec("XGROUP", -2, WRITE, 2, 2, 1),
+    spec("XINFO", -2, READONLY, 2, 2, 1),
+    spec("XRANGE", -4, READONLY, 1, 1, 1),
+    spec("XREAD", -4, READONLY, 0, 0, 0),
+    spec("ZADD", -4, WRITE, 1, 1, 1),
+    spec("ZRANDMEMBER", -2, READONLY, 1, 1, 1),
+    spec("ZRANGEBYLEX", -4, READONLY, 1, 1, 1),
+    spec("ZSCAN", -3, READONLY, 1, 1, 1),
+];
+
+impl CommandSpec {
+    /// Returns the spec of the command, the name is case insensitive
+    pub fn lookup(name: &str) -> Option<&'static CommandSpec> {
+        COMMAND_SPECS
+            .iter()
+            .find(|spec| spec.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Whether the command may modify the dataset, so it is propagated to the replicas
+    pub fn is_write(&self) -> bool {
+        self.flags.contains(&"write")
+    }
+
+    /// Whether the number of arguments, including the command name, matches the arity
+    pub fn accepts(&self, args_count: usize) -> bool {
+        match self.arity {
+            arity if arity >= 0 => args_count as i64 == arity,
+            arity => args_count as i64 >= -arity,
+        }
+    }
+
+    /// Returns the keys among the arguments of the command, including the command name
+    pub fn keys<'a, T>(&self, args: &'a [T]) -> Vec<&'a T> {
+        if self.first_key == 0 {
+            return Vec::new();
+        }
+
+        let last_key = match self.last_key {
+            last_key if last_key >= 0 => last_key,
+            last_key => args.len() as i64 + last_key,
+        };
+        if last_key < self.first_key as i64 {
+            return Vec::new();
+        }
+
+        args.iter()
+            .enumerate()
+            .skip(self.first_key)
+            .take_while(|(pos, _)| *pos as i64 <= last_key)
+            .step_by(self.step.max(1))
+            .map(|(_, arg)| arg)
+            .collect()
+    }
+}
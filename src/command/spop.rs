@@ -0,0 +1,131 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, parse, Db, Frame, Info, Parse};
+
+use super::{CommandTrait, SRem};
+
+#[derive(Debug, Default)]
+pub struct SPop {
+    key: String,
+    /// The reply is a single bulk without count and an array with it,
+    /// even for `SPOP key 1`
+    count: Option<u64>,
+}
+
+impl SPop {
+    pub fn new(key: impl ToString, count: Option<u64>) -> SPop {
+        SPop {
+            key: key.to_string(),
+            count,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        let popped = db.spop(&self.key, self.count.unwrap_or(1) as usize);
+
+        match (popped, self.count) {
+            (Ok(mut members), None) => members.pop().map(Frame::Bulk).unwrap_or(Frame::Null),
+            (Ok(members), Some(_)) => Frame::Array(members.into_iter().map(Frame::Bulk).collect()),
+            (Err(err), _) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<SPop> {
+        let key = frames.next_string()?;
+
+        let count = match frames.next_int() {
+            Ok(count) if count < 0 => {
+                return Err("ERR value is out of range, must be positive".into())
+            }
+            Ok(count) => Some(count as u64),
+            Err(parse::Error::EndOfStream) => None,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(SPop::new(key, count))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("SPOP".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ];
+
+        if let Some(count) = self.count {
+            frames.push(Frame::Bulk(count.to_string().into()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for SPop {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(SPop::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    /// The members are picked at random, so the popped members are propagated as SREM
+    fn propagated_frame(&self, frame: Frame, response: &Frame) -> Frame {
+        let members = match response {
+            Frame::Bulk(member) => vec![member.clone()],
+            Frame::Array(members) => members
+                .iter()
+                .filter_map(|member| match member {
+                    Frame::Bulk(member) => Some(member.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => return frame,
+        };
+
+        SRem::new(&self.key, members).to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn without_count_replies_bulk() {
+        let db = Db::new();
+        db.sadd("myset".to_string(), vec![Bytes::from("a")])
+            .unwrap();
+
+        assert_eq!(
+            SPop::new("myset", None).execute(&db),
+            Frame::Bulk("a".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn with_count_replies_array() {
+        let db = Db::new();
+        db.sadd("myset".to_string(), vec![Bytes::from("a")])
+            .unwrap();
+
+        assert_eq!(
+            SPop::new("myset", Some(1)).execute(&db),
+            Frame::Array(vec![Frame::Bulk("a".into())])
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, parse, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug, Default)]
+pub struct SRandMember {
+    key: String,
+    /// The reply is a single bulk without count and an array with it,
+    /// even for `SRANDMEMBER key 1`
+    count: Option<i64>,
+}
+
+impl SRandMember {
+    pub fn new(key: impl ToString, count: Option<i64>) -> SRandMember {
+        SRandMember {
+            key: key.to_string(),
+            count,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        let members = match db.srandmember(&self.key, self.count.unwrap_or(1)) {
+            Ok(members) => members,
+            Err(err) => return Frame::Error(err.to_string()),
+        };
+
+        match self.count {
+            Some(_) => Frame::Array(members.into_iter().map(Frame::Bulk).collect()),
+            None => members
+                .into_iter()
+                .next()
+                .map(Frame::Bulk)
+                .unwrap_or(Frame::Null),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<SRandMember> {
+        let key = frames.next_string()?;
+
+        let count = match frames.next_int() {
+            Ok(count) => Some(count),
+            Err(parse::Error::EndOfStream) => None,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(SRandMember::new(key, count))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("SRANDMEMBER".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ];
+
+        if let Some(count) = self.count {
+            frames.push(Frame::Bulk(count.to_string().into()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for SRandMember {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(SRandMember::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn negative_count_repeats_members() {
+        let db = Db::new();
+        let members = vec![Bytes::from("a"), Bytes::from("b"), Bytes::from("c")];
+        db.sadd("myset".to_string(), members.clone()).unwrap();
+
+        let reply = SRandMember::new("myset", Some(-10)).execute(&db);
+
+        let frames = match reply {
+            Frame::Array(frames) => frames,
+            reply => panic!("expected an array, got {:?}", reply),
+        };
+        assert_eq!(frames.len(), 10);
+        for frame in frames {
+            assert!(matches!(frame, Frame::Bulk(member) if members.contains(&member)));
+        }
+    }
+
+    #[tokio::test]
+    async fn too_large_negative_count_is_out_of_range() {
+        let db = Db::new();
+        db.sadd("myset".to_string(), vec![Bytes::from("a")])
+            .unwrap();
+
+        let reply = SRandMember::new("myset", Some(i64::MIN)).execute(&db);
+
+        assert_eq!(reply, Frame::Error("ERR value is out of range".into()));
+    }
+}
@@ -0,0 +1,76 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug, Default)]
+pub struct SRem {
+    key: String,
+    members: Vec<Bytes>,
+}
+
+impl SRem {
+    pub fn new(key: impl ToString, members: Vec<Bytes>) -> SRem {
+        SRem {
+            key: key.to_string(),
+            members,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.srem(&self.key, self.members.clone()) {
+            Ok(removed) => Frame::Integer(removed as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<SRem> {
+        let key = frames.next_string()?;
+        let mut members = vec![frames.next_bytes()?];
+
+        while let Ok(member) = frames.next_bytes() {
+            members.push(member);
+        }
+
+        Ok(SRem::new(key, members))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("SREM".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ];
+
+        for member in &self.members {
+            frames.push(Frame::Bulk(member.clone()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for SRem {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(SRem::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
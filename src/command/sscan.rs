@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::{scan::ScanArgs, CommandTrait};
+
+#[derive(Debug)]
+pub struct SScan {
+    key: String,
+    args: ScanArgs,
+}
+
+impl SScan {
+    pub fn new(key: impl ToString, args: ScanArgs) -> SScan {
+        SScan {
+            key: key.to_string(),
+            args,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        let (cursor, members) = match db.sscan(&self.key, self.args.cursor, self.args.count) {
+            Ok(page) => page,
+            Err(err) => return Frame::Error(err.to_string()),
+        };
+
+        let frames = members
+            .into_iter()
+            .filter(|member| self.args.matches(&String::from_utf8_lossy(member)))
+            .map(Frame::Bulk)
+            .collect();
+
+        Frame::Array(vec![
+            Frame::Bulk(cursor.to_string().into()),
+            Frame::Array(frames),
+        ])
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<SScan> {
+        let key = frames.next_string()?;
+        let args = ScanArgs::parse_frames(frames, |_, _| Ok(false))?;
+
+        Ok(SScan::new(key, args))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("SSCAN".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ];
+        frames.extend(self.args.to_frames());
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for SScan {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(SScan::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -0,0 +1,93 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug, Default)]
+pub struct Strlen {
+    key: String,
+}
+
+impl Strlen {
+    pub fn new(key: impl ToString) -> Strlen {
+        Strlen {
+            key: key.to_string(),
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.strlen(&self.key) {
+            Ok(len) => Frame::Integer(len as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Strlen> {
+        let key = frames.next_string()?;
+        Ok(Strlen::new(key))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk("STRLEN".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Strlen {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Strlen::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn missing_key_has_zero_length() {
+        let db = Db::new();
+
+        assert_eq!(Strlen::new("missing").execute(&db), Frame::Integer(0));
+    }
+
+    #[tokio::test]
+    async fn string_length_is_in_bytes() {
+        let db = Db::new();
+        db.set("key".to_string(), Bytes::from("héllo"), None);
+
+        assert_eq!(Strlen::new("key").execute(&db), Frame::Integer(6));
+    }
+
+    #[tokio::test]
+    async fn wrong_type_is_rejected() {
+        let db = Db::new();
+        db.sadd("set".to_string(), vec![Bytes::from("a")]).unwrap();
+
+        assert_eq!(
+            Strlen::new("set").execute(&db),
+            Frame::Error(crate::db::WRONGTYPE_ERR.to_string())
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug, Default)]
+pub struct Subscribe {
+    channels: Vec<String>,
+}
+
+impl Subscribe {
+    pub fn new(channels: Vec<String>) -> Subscribe {
+        Subscribe { channels }
+    }
+
+    /// Subscribes to every channel, replying with a confirmation per channel,
+    /// the messages are then forwarded to the client by a task per channel
+    pub async fn execute(&self, db: &Db, connection: Connection) -> Frame {
+        for channel in &self.channels {
+            let reply = {
+                let mut session = connection.session();
+
+                if !session.is_subscribed(channel) {
+                    let receiver = db.subscribe(channel);
+                    let task = tokio::spawn(forward_messages(
+                        channel.clone(),
+                        receiver,
+                        connection.clone(),
+                    ));
+                    session.subscribe(channel.clone(), task);
+                }
+
+                session.push(vec![
+                    Frame::Bulk(Bytes::from("subscribe")),
+                    Frame::Bulk(Bytes::from(channel.clone())),
+                    Frame::Integer(session.subscription_count() as i64),
+                ])
+            };
+
+            if let Err(err) = connection.write_frame(reply).await {
+                return Frame::Error(err.to_string());
+            }
+        }
+
+        Frame::NoSend
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Subscribe> {
+        let mut channels = vec![frames.next_string()?];
+        while let Ok(channel) = frames.next_string() {
+            channels.push(channel);
+        }
+
+        Ok(Subscribe::new(channels))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![Frame::Bulk(Bytes::from("SUBSCRIBE"))];
+
+        for channel in &self.channels {
+            frames.push(Frame::Bulk(Bytes::from(channel.clone())));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+/// Writes the messages published to the channel to the client,
+/// until the client unsubscribes or disconnects
+async fn forward_messages(
+    channel: String,
+    mut receiver: broadcast::Receiver<Bytes>,
+    connection: Connection,
+) {
+    loop {
+        let message = match receiver.recv().await {
+            Ok(message) => message,
+            // The client is too slow, the oldest messages are dropped
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+
+        let frame = connection.session().push(vec![
+            Frame::Bulk(Bytes::from("message")),
+            Frame::Bulk(Bytes::from(channel.clone())),
+            Frame::Bulk(message),
+        ]);
+
+        if connection.write_frame(frame).await.is_err() {
+            break;
+        }
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Subscribe {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Subscribe::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, connection: Connection) -> Frame {
+        self.execute(db, connection).await
+    }
+
+    fn execute_replica(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+    };
+
+    use crate::server::tests::{connect, send, start_server};
+
+    /// Reads from the socket until the bytes end with `end`
+    async fn read_until(stream: &mut TcpStream, end: &[u8]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        while !bytes.ends_with(end) {
+            let mut buf = [0; 1024];
+            let read = tokio::time::timeout(Duration::from_secs(1), stream.read(&mut buf))
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(read > 0, "connection closed after {bytes:?}");
+            bytes.extend_from_slice(&buf[..read]);
+        }
+
+        bytes
+    }
+
+    /// Subscribes a client speaking the protocol to `channel`, publishes a message on it,
+    /// and returns the raw subscription confirmation and message
+    async fn subscribe_and_publish(protocol: &str) -> (Vec<u8>, Vec<u8>) {
+        let addr = start_server(&[]).await;
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let hello = format!("*2\r\n$5\r\nHELLO\r\n$1\r\n{protocol}\r\n");
+        stream.write_all(hello.as_bytes()).await.unwrap();
+        read_until(&mut stream, b"modules\r\n*0\r\n").await;
+
+        stream
+            .write_all(b"*2\r\n$9\r\nSUBSCRIBE\r\n$7\r\nchannel\r\n")
+            .await
+            .unwrap();
+        let subscribed = read_until(&mut stream, b":1\r\n").await;
+
+        let publisher = connect(addr).await;
+        send(&publisher, &["PUBLISH", "channel", "hello"]).await;
+        let message = read_until(&mut stream, b"hello\r\n").await;
+
+        (subscribed, message)
+    }
+
+    #[tokio::test]
+    async fn resp3_delivers_push_frames() {
+        let (subscribed, message) = subscribe_and_publish("3").await;
+
+        assert_eq!(
+            subscribed,
+            b">3\r\n$9\r\nsubscribe\r\n$7\r\nchannel\r\n:1\r\n"
+        );
+        assert_eq!(
+            message,
+            b">3\r\n$7\r\nmessage\r\n$7\r\nchannel\r\n$5\r\nhello\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn resp2_delivers_arrays() {
+        let (subscribed, message) = subscribe_and_publish("2").await;
+
+        assert_eq!(
+            subscribed,
+            b"*3\r\n$9\r\nsubscribe\r\n$7\r\nchannel\r\n:1\r\n"
+        );
+        assert_eq!(
+            message,
+            b"*3\r\n$7\r\nmessage\r\n$7\r\nchannel\r\n$5\r\nhello\r\n"
+        );
+    }
+}
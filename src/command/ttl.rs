@@ -0,0 +1,133 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// TTL and PTTL, the remaining time to live of a key
+#[derive(Debug)]
+pub struct Ttl {
+    key: String,
+    /// The time to live is in milliseconds instead of seconds
+    millis: bool,
+}
+
+impl Ttl {
+    pub fn new(key: impl ToString, millis: bool) -> Ttl {
+        Ttl {
+            key: key.to_string(),
+            millis,
+        }
+    }
+
+    /// Replies -2 if the key does not exist and -1 if it has no expiry
+    pub fn execute(&self, db: &Db) -> Frame {
+        let ttl = match db.ttl(&self.key) {
+            None => return Frame::Integer(-2),
+            Some(None) => return Frame::Integer(-1),
+            Some(Some(ttl)) => ttl.as_millis() as i64,
+        };
+
+        match self.millis {
+            true => Frame::Integer(ttl),
+            // Rounded to the nearest second
+            false => Frame::Integer((ttl + 500) / 1000),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self.millis {
+            true => "PTTL",
+            false => "TTL",
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse, millis: bool) -> crate::Result<Ttl> {
+        let key = frames.next_string()?;
+        Ok(Ttl::new(key, millis))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk(self.name().into()),
+            Frame::Bulk(self.key.clone().into()),
+        ])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Ttl {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Ttl::parse_frames(frames, self.millis)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use crate::{
+        connection::Connection,
+        server::tests::{connect, send, start_server},
+        Frame,
+    };
+
+    /// Asserts the PTTL of the key is the TTL set minus the time elapsed since,
+    /// give or take the round trips, a TTL rounded to seconds would be off by 234ms
+    async fn assert_pttl(connection: &Connection, set_at: Instant) {
+        let expected = 1234 - set_at.elapsed().as_millis() as i64;
+
+        match send(connection, &["PTTL", "key"]).await {
+            Frame::Integer(pttl) => {
+                assert!((expected - 50..=expected + 50).contains(&pttl), "{pttl}")
+            }
+            frame => panic!("expected the TTL, got {frame:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn millisecond_ttl_is_kept_through_replication_and_rdb() {
+        let dir = std::env::temp_dir().join(format!("ttl-fidelity-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let master = start_server(&["--dir", dir.to_str().unwrap()]).await;
+        let master_port = master.port().to_string();
+        let replica = start_server(&["--replicaof", "127.0.0.1", &master_port]).await;
+
+        let master = connect(master).await;
+        let set_at = Instant::now();
+        send(&master, &["SET", "key", "value", "PX", "1234"]).await;
+        assert_pttl(&master, set_at).await;
+
+        assert_eq!(
+            send(&master, &["WAIT", "1", "1000"]).await,
+            Frame::Integer(1)
+        );
+        assert_pttl(&connect(replica).await, set_at).await;
+
+        // Saves the RDB file and loads it back
+        assert_eq!(
+            send(&master, &["DEBUG", "RELOAD"]).await,
+            Frame::Simple("OK".into())
+        );
+        assert_pttl(&master, set_at).await;
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
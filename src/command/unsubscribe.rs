@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug, Default)]
+pub struct Unsubscribe {
+    /// All the subscribed channels if empty
+    channels: Vec<String>,
+}
+
+impl Unsubscribe {
+    pub fn new(channels: Vec<String>) -> Unsubscribe {
+        Unsubscribe { channels }
+    }
+
+    /// Unsubscribes from every channel, replying with a confirmation per channel
+    pub async fn execute(&self, connection: Connection) -> Frame {
+        let channels = match self.channels.is_empty() {
+            true => connection.session().subscriptions(),
+            false => self.channels.clone(),
+        };
+
+        // Without any subscription there is still a single reply, with a null channel
+        if channels.is_empty() {
+            return connection.session().push(vec![
+                Frame::Bulk(Bytes::from("unsubscribe")),
+                Frame::Null,
+                Frame::Integer(0),
+            ]);
+        }
+
+        for channel in channels {
+            let reply = {
+                let mut session = connection.session();
+                let count = session.unsubscribe(&channel);
+
+                session.push(vec![
+                    Frame::Bulk(Bytes::from("unsubscribe")),
+                    Frame::Bulk(Bytes::from(channel)),
+                    Frame::Integer(count as i64),
+                ])
+            };
+
+            if let Err(err) = connection.write_frame(reply).await {
+                return Frame::Error(err.to_string());
+            }
+        }
+
+        Frame::NoSend
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<Unsubscribe> {
+        let mut channels = Vec::new();
+        while let Ok(channel) = frames.next_string() {
+            channels.push(channel);
+        }
+
+        Ok(Unsubscribe::new(channels))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![Frame::Bulk(Bytes::from("UNSUBSCRIBE"))];
+
+        for channel in &self.channels {
+            frames.push(Frame::Bulk(Bytes::from(channel.clone())));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for Unsubscribe {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(Unsubscribe::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, _db: &Db, _server_info: &mut Info, connection: Connection) -> Frame {
+        self.execute(connection).await
+    }
+
+    fn execute_replica(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
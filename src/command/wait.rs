@@ -58,3 +58,69 @@ impl CommandTrait for Wait {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::Instant,
+    };
+
+    use crate::{
+        server::tests::{connect, send, start_server},
+        Frame,
+    };
+
+    #[tokio::test]
+    async fn no_replicas_returns_immediately() {
+        let client = connect(start_server(&[]).await).await;
+        // The master offset is past 0
+        send(&client, &["SET", "key", "value"]).await;
+
+        let started = Instant::now();
+        assert_eq!(
+            send(&client, &["WAIT", "1", "5000"]).await,
+            Frame::Integer(0)
+        );
+        assert!(started.elapsed().as_millis() < 1000);
+    }
+
+    #[tokio::test]
+    async fn writes_during_wait_do_not_move_the_target() {
+        let master = start_server(&[]).await;
+        let master_port = master.port().to_string();
+        start_server(&["--replicaof", "127.0.0.1", &master_port]).await;
+
+        // Another connection keeps writing while WAIT runs
+        let stop = Arc::new(AtomicBool::new(false));
+        let writer = connect(master).await;
+        let writing = tokio::spawn({
+            let stop = stop.clone();
+            async move {
+                let mut i = 0;
+                while !stop.load(Ordering::Relaxed) {
+                    send(&writer, &["SET", "background", &i.to_string()]).await;
+                    i += 1;
+                }
+            }
+        });
+
+        let client = connect(master).await;
+        for i in 0..5 {
+            send(&client, &["SET", "key", &i.to_string()]).await;
+
+            let started = Instant::now();
+            assert_eq!(
+                send(&client, &["WAIT", "1", "2000"]).await,
+                Frame::Integer(1)
+            );
+            assert!(started.elapsed().as_millis() < 1500);
+        }
+
+        stop.store(true, Ordering::Relaxed);
+        writing.await.unwrap();
+    }
+}
@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{
+    connection::Connection,
+    error::{redis_error, RedisErrorKind},
+    Db, Frame, Info, Parse,
+};
+
+use super::CommandTrait;
+
+/// WAITAOF numlocal numreplicas timeout
+/// Waits for the writes to be fsynced to the AOF of the master and the replicas.
+/// The AOF is not supported, so the local count is always 0 and the replicas
+/// are counted once they acknowledged the offset, like with WAIT.
+#[derive(Debug, Default)]
+pub struct WaitAof {
+    local_count: u64,
+    replica_count: u64,
+    timeout: Duration,
+}
+
+impl WaitAof {
+    pub fn new(local_count: u64, replica_count: u64, timeout: Duration) -> WaitAof {
+        WaitAof {
+            local_count,
+            replica_count,
+            timeout,
+        }
+    }
+
+    /// Replies with the number of fsynced local AOFs and the number of synced replicas
+    pub async fn execute(&self, server_info: &Info) -> Frame {
+        if !server_info.role().is_master() {
+            return redis_error(
+                RedisErrorKind::Err,
+                "WAITAOF cannot be used with replica instances. Please also note that writes to replicas are just local and are not propagated.",
+            );
+        }
+
+        if self.local_count > 0 {
+            return redis_error(
+                RedisErrorKind::Err,
+                "WAITAOF cannot be used when numlocal is set but appendonly is disabled.",
+            );
+        }
+
+        let offset = server_info.offset();
+        let replicas = server_info
+            .count_sync_repl(offset, self.replica_count, self.timeout)
+            .await;
+
+        Frame::Array(vec![Frame::Integer(0), Frame::Integer(replicas as i64)])
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<WaitAof> {
+        let local_count = frames.next_uint()?;
+        let replica_count = frames.next_uint()?;
+        let timeout = frames.next_uint()?;
+
+        Ok(WaitAof::new(
+            local_count,
+            replica_count,
+            Duration::from_millis(timeout),
+        ))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk(Bytes::from("WAITAOF")),
+            Frame::Bulk(Bytes::from(self.local_count.to_string())),
+            Frame::Bulk(Bytes::from(self.replica_count.to_string())),
+            Frame::Bulk(Bytes::from(self.timeout.as_millis().to_string())),
+        ])
+    }
+}
+
+#[async_trait]
+impl CommandTrait for WaitAof {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(WaitAof::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, _db: &Db, server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(server_info).await
+    }
+
+    fn execute_replica(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::{connect, send, start_server};
+
+    fn counts(local: i64, replicas: i64) -> Frame {
+        Frame::Array(vec![Frame::Integer(local), Frame::Integer(replicas)])
+    }
+
+    #[tokio::test]
+    async fn numlocal_is_rejected_without_appendonly() {
+        let client = connect(start_server(&[]).await).await;
+
+        assert_eq!(
+            send(&client, &["WAITAOF", "1", "0", "0"]).await,
+            Frame::Error(
+                "ERR WAITAOF cannot be used when numlocal is set but appendonly is disabled."
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            send(&client, &["WAITAOF", "0", "0", "0"]).await,
+            counts(0, 0)
+        );
+    }
+
+    #[tokio::test]
+    async fn synced_replicas_are_counted() {
+        let master = start_server(&[]).await;
+        let master_port = master.port().to_string();
+        let replica = start_server(&["--replicaof", "127.0.0.1", &master_port]).await;
+
+        let client = connect(master).await;
+        send(&client, &["SET", "key", "value"]).await;
+        assert_eq!(
+            send(&client, &["WAITAOF", "0", "1", "1000"]).await,
+            counts(0, 1)
+        );
+
+        let client = connect(replica).await;
+        assert!(matches!(
+            send(&client, &["WAITAOF", "0", "0", "0"]).await,
+            Frame::Error(err) if err.starts_with("ERR WAITAOF cannot be used with replica instances")
+        ));
+    }
+}
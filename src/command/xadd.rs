@@ -10,6 +10,8 @@ pub struct XAdd {
     stream_key: String,
     id: XAddId,
     key_value: Vec<(String, Bytes)>,
+    /// Whether a missing stream is left missing instead of being created
+    nomkstream: bool,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -20,34 +22,44 @@ pub enum XAddId {
 }
 
 impl XAdd {
-    pub fn new(stream_key: String, id: XAddId, key_value: Vec<(String, Bytes)>) -> XAdd {
+    pub fn new(
+        stream_key: String,
+        id: XAddId,
+        key_value: Vec<(String, Bytes)>,
+        nomkstream: bool,
+    ) -> XAdd {
         XAdd {
             stream_key,
             id,
             key_value,
+            nomkstream,
         }
     }
 
-    pub async fn execute(&self, db: &Db) -> Frame {
-        let id = db
-            .xadd(self.stream_key.clone(), self.id, self.key_value.clone())
-            .await;
+    /// Replies with the id of the added entry, or a null if NOMKSTREAM prevented creating the stream
+    pub fn execute(&self, db: &Db) -> Frame {
+        let id = db.xadd(
+            self.stream_key.clone(),
+            self.id,
+            self.key_value.clone(),
+            self.nomkstream,
+        );
 
         match id {
-            Ok(id) => Frame::Bulk(id.into()),
-            Err(err) => {
-                eprintln!("XAdd error: {:?}", err);
-                Frame::Error(
-                "ERR The ID specified in XADD is equal or smaller than the target stream top item"
-                    .into(),
-            )
-            }
+            Ok(Some(id)) => Frame::Bulk(id.into()),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
         }
     }
 
     pub fn parse_frames(frames: &mut Parse) -> crate::Result<XAdd> {
         let stream_key = frames.next_string()?;
-        let id = frames.next_string()?;
+
+        let mut id = frames.next_string()?;
+        let nomkstream = id.eq_ignore_ascii_case("NOMKSTREAM");
+        if nomkstream {
+            id = frames.next_string()?;
+        }
         let id = XAdd::parse_id(id.as_str())?;
         let mut key_value = Vec::new();
 
@@ -57,7 +69,7 @@ impl XAdd {
             key_value.push((key, value));
         }
 
-        Ok(XAdd::new(stream_key, id, key_value))
+        Ok(XAdd::new(stream_key, id, key_value, nomkstream))
     }
 
     pub fn parse_id(id: &str) -> crate::Result<XAddId> {
@@ -65,19 +77,18 @@ impl XAdd {
             return Ok(XAddId::Auto);
         }
 
-        let parts: Vec<&str> = id.split('-').collect();
-        let timestamp = parts[0].parse()?;
-        let idx = parts[1];
-
-        if idx == "*" {
-            return Ok(XAddId::AutoSeq(parts[0].parse()?));
+        if let Some(timestamp) = id.strip_suffix("-*") {
+            let timestamp = timestamp
+                .parse()
+                .map_err(|_| "ERR Invalid stream ID specified as stream command argument")?;
+            return Ok(XAddId::AutoSeq(timestamp));
         }
 
-        let idx = idx.parse()?;
-        if (timestamp, idx) == (0, 0) {
+        let id = StreamEntryId::parse(id, 0)?;
+        if id == StreamEntryId::default() {
             Err("ERR The ID specified in XADD must be greater than 0-0".into())
         } else {
-            Ok(XAddId::Explicit(StreamEntryId::new(timestamp, idx)))
+            Ok(XAddId::Explicit(id))
         }
     }
 
@@ -87,6 +98,10 @@ impl XAdd {
             Frame::Bulk(self.stream_key.clone().into()),
         ];
 
+        if self.nomkstream {
+            frames.push(Frame::Bulk("NOMKSTREAM".into()));
+        }
+
         match self.id {
             XAddId::Auto => frames.push(Frame::Bulk("*".into())),
             XAddId::AutoSeq(timestamp) => {
@@ -111,10 +126,11 @@ impl CommandTrait for XAdd {
     }
 
     async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
-        self.execute(db).await
+        self.execute(db)
     }
 
-    fn execute_replica(&self, _db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
         Frame::Null
     }
 
@@ -122,7 +138,106 @@ impl CommandTrait for XAdd {
         self.to_frame()
     }
 
+    /// Propagated with the id of the added entry,
+    /// so the replicas don't generate their own id
+    fn propagated_frame(&self, frame: Frame, response: &Frame) -> Frame {
+        let id = match response {
+            Frame::Bulk(id) => std::str::from_utf8(id)
+                .ok()
+                .and_then(|id| StreamEntryId::parse(id, 0).ok()),
+            _ => None,
+        };
+
+        match id {
+            Some(id) => XAdd::new(
+                self.stream_key.clone(),
+                XAddId::Explicit(id),
+                self.key_value.clone(),
+                self.nomkstream,
+            )
+            .to_frame(),
+            None => frame,
+        }
+    }
+
     fn as_any(&self) -> &dyn std::any::Any {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn star_generates_the_id() {
+        assert!(matches!(XAdd::parse_id("*"), Ok(XAddId::Auto)));
+    }
+
+    #[test]
+    fn timestamp_with_star_generates_the_sequence() {
+        assert!(matches!(XAdd::parse_id("5-*"), Ok(XAddId::AutoSeq(5))));
+        assert!(XAdd::parse_id("a-*").is_err());
+    }
+
+    #[test]
+    fn explicit_id_is_parsed() {
+        assert!(matches!(
+            XAdd::parse_id("12345-678"),
+            Ok(XAddId::Explicit(id)) if id == StreamEntryId::new(12345, 678)
+        ));
+        assert!(XAdd::parse_id("1-2-3").is_err());
+    }
+
+    #[test]
+    fn zero_id_is_rejected() {
+        let err = XAdd::parse_id("0-0").unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "ERR The ID specified in XADD must be greater than 0-0"
+        );
+    }
+
+    #[tokio::test]
+    async fn id_not_greater_than_top_item_is_rejected() {
+        let db = Db::new();
+        let xadd = |id| {
+            XAdd::new(
+                "stream".to_string(),
+                XAddId::Explicit(id),
+                vec![("field".to_string(), Bytes::from("value"))],
+                false,
+            )
+            .execute(&db)
+        };
+
+        assert_eq!(xadd(StreamEntryId::new(1, 1)), Frame::Bulk("1-1".into()));
+        for id in [StreamEntryId::new(1, 1), StreamEntryId::new(1, 0)] {
+            assert_eq!(
+                xadd(id),
+                Frame::Error(
+                    "ERR The ID specified in XADD is equal or smaller than the target stream top item"
+                        .to_string()
+                )
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn non_stream_key_is_wrongtype() {
+        let db = Db::new();
+        db.set("key".to_string(), Bytes::from("value"), None);
+
+        let reply = XAdd::new(
+            "key".to_string(),
+            XAddId::Explicit(StreamEntryId::new(1, 1)),
+            vec![("field".to_string(), Bytes::from("value"))],
+            false,
+        )
+        .execute(&db);
+
+        assert_eq!(reply, Frame::Error(crate::db::WRONGTYPE_ERR.to_string()));
+        assert_eq!(db.get_type("key"), "string");
+    }
+}
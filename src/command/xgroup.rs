@@ -0,0 +1,246 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, db::StreamEntryId, parse, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+#[derive(Debug)]
+pub enum XGroup {
+    /// XGROUP CREATE key group \<id | $\> [MKSTREAM]
+    /// The group starts after the last entry with `$`, then the id is `None`
+    Create {
+        key: String,
+        group: String,
+        id: Option<StreamEntryId>,
+        mkstream: bool,
+    },
+    /// XGROUP DESTROY key group
+    Destroy { key: String, group: String },
+    /// XGROUP CREATECONSUMER key group consumer
+    CreateConsumer {
+        key: String,
+        group: String,
+        consumer: String,
+    },
+    /// XGROUP DELCONSUMER key group consumer
+    DelConsumer {
+        key: String,
+        group: String,
+        consumer: String,
+    },
+}
+
+impl XGroup {
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<XGroup> {
+        let subcommand = frames.next_string()?;
+
+        match subcommand.to_uppercase().as_str() {
+            "CREATE" => {
+                let key = frames.next_string()?;
+                let group = frames.next_string()?;
+                let id = match frames.next_string()?.as_str() {
+                    "$" => None,
+                    id => Some(StreamEntryId::parse(id, 0)?),
+                };
+                let mkstream = match frames.next_string() {
+                    Ok(option) if option.eq_ignore_ascii_case("MKSTREAM") => true,
+                    Ok(_) => return Err("ERR syntax error".into()),
+                    Err(parse::Error::EndOfStream) => false,
+                    Err(err) => return Err(err.into()),
+                };
+
+                Ok(XGroup::Create {
+                    key,
+                    group,
+                    id,
+                    mkstream,
+                })
+            }
+            "DESTROY" => Ok(XGroup::Destroy {
+                key: frames.next_string()?,
+                group: frames.next_string()?,
+            }),
+            "CREATECONSUMER" => Ok(XGroup::CreateConsumer {
+                key: frames.next_string()?,
+                group: frames.next_string()?,
+                consumer: frames.next_string()?,
+            }),
+            "DELCONSUMER" => Ok(XGroup::DelConsumer {
+                key: frames.next_string()?,
+                group: frames.next_string()?,
+                consumer: frames.next_string()?,
+            }),
+            _ => Err(format!("ERR unknown subcommand '{}'. Try XGROUP HELP.", subcommand).into()),
+        }
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![Frame::Bulk("XGROUP".into())];
+
+        match self {
+            XGroup::Create {
+                key,
+                group,
+                id,
+                mkstream,
+            } => {
+                frames.push(Frame::Bulk("CREATE".into()));
+                frames.push(Frame::Bulk(key.clone().into()));
+                frames.push(Frame::Bulk(group.clone().into()));
+                frames.push(Frame::Bulk(match id {
+                    Some(id) => id.to_string().into(),
+                    None => "$".into(),
+                }));
+                if *mkstream {
+                    frames.push(Frame::Bulk("MKSTREAM".into()));
+                }
+            }
+            XGroup::Destroy { key, group } => {
+                frames.push(Frame::Bulk("DESTROY".into()));
+                frames.push(Frame::Bulk(key.clone().into()));
+                frames.push(Frame::Bulk(group.clone().into()));
+            }
+            XGroup::CreateConsumer {
+                key,
+                group,
+                consumer,
+            } => {
+                frames.push(Frame::Bulk("CREATECONSUMER".into()));
+                frames.push(Frame::Bulk(key.clone().into()));
+                frames.push(Frame::Bulk(group.clone().into()));
+                frames.push(Frame::Bulk(consumer.clone().into()));
+            }
+            XGroup::DelConsumer {
+                key,
+                group,
+                consumer,
+            } => {
+                frames.push(Frame::Bulk("DELCONSUMER".into()));
+                frames.push(Frame::Bulk(key.clone().into()));
+                frames.push(Frame::Bulk(group.clone().into()));
+                frames.push(Frame::Bulk(consumer.clone().into()));
+            }
+        }
+
+        Frame::Array(frames)
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        let result = match self {
+            XGroup::Create {
+                key,
+                group,
+                id,
+                mkstream,
+            } => db
+                .xgroup_create(key, group, *id, *mkstream)
+                .map(|()| Frame::Simple("OK".into())),
+            XGroup::Destroy { key, group } => db
+                .xgroup_destroy(key, group)
+                .map(|destroyed| Frame::Integer(destroyed as i64)),
+            XGroup::CreateConsumer {
+                key,
+                group,
+                consumer,
+            } => db
+                .xgroup_createconsumer(key, group, consumer)
+                .map(|created| Frame::Integer(created as i64)),
+            XGroup::DelConsumer {
+                key,
+                group,
+                consumer,
+            } => db
+                .xgroup_delconsumer(key, group, consumer)
+                .map(|pending| Frame::Integer(pending as i64)),
+        };
+
+        result.unwrap_or_else(|err| Frame::Error(err.to_string()))
+    }
+}
+
+#[async_trait]
+impl CommandTrait for XGroup {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(XGroup::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::{command::XAddId, db::tests::deliver};
+
+    fn stream_with_group(db: &Db) {
+        for ms in 1..=3 {
+            db.xadd(
+                "stream".to_string(),
+                XAddId::AutoSeq(ms),
+                vec![("field".to_string(), Bytes::from("value"))],
+                false,
+            )
+            .unwrap();
+        }
+        db.xgroup_create("stream", "group", Some(StreamEntryId::new(0, 0)), false)
+            .unwrap();
+    }
+
+    fn del_consumer(db: &Db, consumer: &str) -> Frame {
+        XGroup::DelConsumer {
+            key: "stream".to_string(),
+            group: "group".to_string(),
+            consumer: consumer.to_string(),
+        }
+        .execute(db)
+    }
+
+    #[tokio::test]
+    async fn delconsumer_returns_its_pending_entries_count() {
+        let db = Db::new();
+        stream_with_group(&db);
+        deliver(&db, "stream", "group", "alice", StreamEntryId::new(1, 0));
+        deliver(&db, "stream", "group", "alice", StreamEntryId::new(2, 0));
+        deliver(&db, "stream", "group", "bob", StreamEntryId::new(3, 0));
+
+        assert_eq!(del_consumer(&db, "alice"), Frame::Integer(2));
+
+        // The entries of the other consumers stay pending
+        let stream = db.xinfo_stream("stream").unwrap();
+        let (_, group) = stream.groups()[0];
+        assert_eq!(
+            group.pending().keys().copied().collect::<Vec<_>>(),
+            [StreamEntryId::new(3, 0)]
+        );
+        assert_eq!(group.consumers().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn delconsumer_of_missing_consumer_is_zero() {
+        let db = Db::new();
+        stream_with_group(&db);
+
+        assert_eq!(del_consumer(&db, "alice"), Frame::Integer(0));
+        assert!(db
+            .xgroup_createconsumer("stream", "group", "alice")
+            .unwrap());
+        assert_eq!(del_consumer(&db, "alice"), Frame::Integer(0));
+    }
+}
@@ -0,0 +1,376 @@
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{
+    connection::Connection,
+    db::{ConsumerGroup, Stream, StreamEntry},
+    parse, Db, Frame, Info, Parse,
+};
+
+use super::CommandTrait;
+
+/// Entries, and pending entries per group and per consumer, shown by FULL without COUNT
+const DEFAULT_FULL_COUNT: usize = 10;
+
+/// XINFO STREAM key [FULL [COUNT count]]
+#[derive(Debug)]
+pub struct XInfoStream {
+    key: String,
+    /// FULL, details every consumer group with its pending entries and consumers
+    full: bool,
+    /// Limit on the entries and pending entries listed by FULL, 0 for no limit
+    count: usize,
+}
+
+impl XInfoStream {
+    pub fn new(key: impl ToString, full: bool, count: usize) -> XInfoStream {
+        XInfoStream {
+            key: key.to_string(),
+            full,
+            count,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        let stream = match db.xinfo_stream(&self.key) {
+            Ok(stream) => stream,
+            Err(err) => return Frame::Error(err.to_string()),
+        };
+
+        match self.full {
+            true => self.full_frame(&stream),
+            false => summary_frame(&stream),
+        }
+    }
+
+    /// The stream with its entries and the details of every consumer group
+    fn full_frame(&self, stream: &Stream) -> Frame {
+        let entries = stream.entries();
+        let groups = stream
+            .groups()
+            .into_iter()
+            .map(|(name, group)| self.group_frame(name, group, entries))
+            .collect();
+
+        Frame::Array(vec![
+            Frame::Bulk("length".into()),
+            Frame::Integer(entries.len() as i64),
+            Frame::Bulk("last-generated-id".into()),
+            Frame::Bulk(stream.get_last_id().to_string().into()),
+            Frame::Bulk("entries".into()),
+            Frame::Array(self.limit(entries.iter()).map(entry_frame).collect()),
+            Frame::Bulk("groups".into()),
+            Frame::Array(groups),
+        ])
+    }
+
+    /// The consumer group with its pending entries list and its consumers
+    fn group_frame(&self, name: &str, group: &ConsumerGroup, entries: &[StreamEntry]) -> Frame {
+        // Without deleted entries, the entries read are the ones up to the last delivered
+        let entries_read = entries
+            .iter()
+            .take_while(|entry| entry.id() <= group.last_delivered_id())
+            .count();
+
+        let pending = self
+            .limit(group.pending().iter())
+            .map(|(id, pending)| {
+                Frame::Array(vec![
+                    Frame::Bulk(id.to_string().into()),
+                    Frame::Bulk(Bytes::from(pending.consumer().to_string())),
+                    Frame::Integer(unix_millis(pending.delivery_time())),
+                    Frame::Integer(pending.delivery_count() as i64),
+                ])
+            })
+            .collect();
+
+        let consumers = group
+            .consumers()
+            .into_iter()
+            .map(|(name, consumer)| {
+                let pending = self
+                    .limit(consumer.pending().iter())
+                    .filter_map(|id| group.pending().get(id).map(|pending| (id, pending)))
+                    .map(|(id, pending)| {
+                        Frame::Array(vec![
+                            Frame::Bulk(id.to_string().into()),
+                            Frame::Integer(unix_millis(pending.delivery_time())),
+                            Frame::Integer(pending.delivery_count() as i64),
+                        ])
+                    })
+                    .collect();
+
+                Frame::Array(vec![
+                    Frame::Bulk("name".into()),
+                    Frame::Bulk(Bytes::from(name.clone())),
+                    Frame::Bulk("seen-time".into()),
+                    Frame::Integer(unix_millis(consumer.seen_time())),
+                    Frame::Bulk("pel-count".into()),
+                    Frame::Integer(consumer.pending().len() as i64),
+                    Frame::Bulk("pending".into()),
+                    Frame::Array(pending),
+                ])
+            })
+            .collect();
+
+        Frame::Array(vec![
+            Frame::Bulk("name".into()),
+            Frame::Bulk(Bytes::from(name.to_string())),
+            Frame::Bulk("last-delivered-id".into()),
+            Frame::Bulk(group.last_delivered_id().to_string().into()),
+            Frame::Bulk("entries-read".into()),
+            Frame::Integer(entries_read as i64),
+            Frame::Bulk("lag".into()),
+            Frame::Integer((entries.len() - entries_read) as i64),
+            Frame::Bulk("pel-count".into()),
+            Frame::Integer(group.pending().len() as i64),
+            Frame::Bulk("pending".into()),
+            Frame::Array(pending),
+            Frame::Bulk("consumers".into()),
+            Frame::Array(consumers),
+        ])
+    }
+
+    /// Takes up to COUNT items, all of them with a COUNT of 0
+    fn limit<I: Iterator>(&self, items: I) -> std::iter::Take<I> {
+        match self.count {
+            0 => items.take(usize::MAX),
+            count => items.take(count),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<XInfoStream> {
+        let subcommand = frames.next_string()?;
+        if !subcommand.eq_ignore_ascii_case("STREAM") {
+            return Err(format!("ERR unknown subcommand '{}'. Try XINFO HELP.", subcommand).into());
+        }
+
+        let key = frames.next_string()?;
+
+        let full = match frames.next_string() {
+            Ok(option) if option.eq_ignore_ascii_case("FULL") => true,
+            Ok(_) => return Err("ERR syntax error".into()),
+            Err(parse::Error::EndOfStream) => false,
+            Err(err) => return Err(err.into()),
+        };
+
+        let count = match frames.next_string() {
+            Ok(option) if full && option.eq_ignore_ascii_case("COUNT") => {
+                frames.next_uint()? as usize
+            }
+            Ok(_) => return Err("ERR syntax error".into()),
+            Err(parse::Error::EndOfStream) => DEFAULT_FULL_COUNT,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(XInfoStream::new(key, full, count))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("XINFO".into()),
+            Frame::Bulk("STREAM".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ];
+
+        if self.full {
+            frames.push(Frame::Bulk("FULL".into()));
+            frames.push(Frame::Bulk("COUNT".into()));
+            frames.push(Frame::Bulk(self.count.to_string().into()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+/// The stream length and its first and last entries, with the number of consumer groups
+fn summary_frame(stream: &Stream) -> Frame {
+    let entries = stream.entries();
+
+    Frame::Array(vec![
+        Frame::Bulk("length".into()),
+        Frame::Integer(entries.len() as i64),
+        Frame::Bulk("last-generated-id".into()),
+        Frame::Bulk(stream.get_last_id().to_string().into()),
+        Frame::Bulk("groups".into()),
+        Frame::Integer(stream.groups().len() as i64),
+        Frame::Bulk("first-entry".into()),
+        entries.first().map_or(Frame::Null, entry_frame),
+        Frame::Bulk("last-entry".into()),
+        entries.last().map_or(Frame::Null, entry_frame),
+    ])
+}
+
+/// The entry id with its fields and values, like in the XRANGE reply
+fn entry_frame(entry: &StreamEntry) -> Frame {
+    let mut key_value = Vec::new();
+    for (key, value) in entry.key_value() {
+        key_value.push(Frame::Bulk(key.clone().into()));
+        key_value.push(Frame::Bulk(value.clone()));
+    }
+
+    Frame::Array(vec![
+        Frame::Bulk(entry.id().to_string().into()),
+        Frame::Array(key_value),
+    ])
+}
+
+fn unix_millis(time: SystemTime) -> i64 {
+    time.duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as i64)
+}
+
+#[async_trait]
+impl CommandTrait for XInfoStream {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(XInfoStream::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        command::XAddId,
+        db::{tests::deliver, StreamEntryId},
+    };
+
+    fn stream_with_group(db: &Db) {
+        for ms in 1..=3 {
+            db.xadd(
+                "stream".to_string(),
+                XAddId::AutoSeq(ms),
+                vec![("field".to_string(), Bytes::from("value"))],
+                false,
+            )
+            .unwrap();
+        }
+        db.xgroup_create("stream", "group", Some(StreamEntryId::new(0, 0)), false)
+            .unwrap();
+        deliver(db, "stream", "group", "alice", StreamEntryId::new(1, 0));
+        deliver(db, "stream", "group", "alice", StreamEntryId::new(2, 0));
+    }
+
+    /// Returns the value following the name in a reply of names and values
+    fn field<'a>(frame: &'a Frame, name: &str) -> &'a Frame {
+        let frames = match frame {
+            Frame::Array(frames) => frames,
+            frame => panic!("expected an array, got {frame:?}"),
+        };
+
+        frames
+            .chunks(2)
+            .find(|pair| pair[0] == Frame::Bulk(Bytes::from(name.to_string())))
+            .map(|pair| &pair[1])
+            .unwrap_or_else(|| panic!("no {name} in {frame:?}"))
+    }
+
+    fn entry(id: &str) -> Frame {
+        Frame::Array(vec![
+            Frame::Bulk(Bytes::from(id.to_string())),
+            Frame::Array(vec![
+                Frame::Bulk("field".into()),
+                Frame::Bulk("value".into()),
+            ]),
+        ])
+    }
+
+    fn len(frame: &Frame) -> usize {
+        match frame {
+            Frame::Array(frames) => frames.len(),
+            frame => panic!("expected an array, got {frame:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn summary_counts_the_groups() {
+        let db = Db::new();
+        stream_with_group(&db);
+
+        assert_eq!(
+            XInfoStream::new("stream", false, 0).execute(&db),
+            Frame::Array(vec![
+                Frame::Bulk("length".into()),
+                Frame::Integer(3),
+                Frame::Bulk("last-generated-id".into()),
+                Frame::Bulk("3-0".into()),
+                Frame::Bulk("groups".into()),
+                Frame::Integer(1),
+                Frame::Bulk("first-entry".into()),
+                entry("1-0"),
+                Frame::Bulk("last-entry".into()),
+                entry("3-0"),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn full_details_the_groups() {
+        let db = Db::new();
+        stream_with_group(&db);
+
+        let info = XInfoStream::new("stream", true, 0).execute(&db);
+        assert_eq!(field(&info, "length"), &Frame::Integer(3));
+        assert_eq!(
+            field(&info, "entries"),
+            &Frame::Array(vec![entry("1-0"), entry("2-0"), entry("3-0")])
+        );
+
+        let group = match field(&info, "groups") {
+            Frame::Array(groups) if groups.len() == 1 => &groups[0],
+            groups => panic!("expected a group, got {groups:?}"),
+        };
+        assert_eq!(field(group, "name"), &Frame::Bulk("group".into()));
+        assert_eq!(
+            field(group, "last-delivered-id"),
+            &Frame::Bulk("2-0".into())
+        );
+        assert_eq!(field(group, "entries-read"), &Frame::Integer(2));
+        assert_eq!(field(group, "lag"), &Frame::Integer(1));
+        assert_eq!(field(group, "pel-count"), &Frame::Integer(2));
+        assert_eq!(len(field(group, "pending")), 2);
+
+        let consumer = match field(group, "consumers") {
+            Frame::Array(consumers) if consumers.len() == 1 => &consumers[0],
+            consumers => panic!("expected a consumer, got {consumers:?}"),
+        };
+        assert_eq!(field(consumer, "name"), &Frame::Bulk("alice".into()));
+        assert_eq!(field(consumer, "pel-count"), &Frame::Integer(2));
+        assert_eq!(len(field(consumer, "pending")), 2);
+    }
+
+    #[tokio::test]
+    async fn full_count_limits_the_lists() {
+        let db = Db::new();
+        stream_with_group(&db);
+
+        let info = XInfoStream::new("stream", true, 1).execute(&db);
+        assert_eq!(field(&info, "entries"), &Frame::Array(vec![entry("1-0")]));
+
+        let group = match field(&info, "groups") {
+            Frame::Array(groups) => &groups[0],
+            groups => panic!("expected the groups, got {groups:?}"),
+        };
+        // The counts are not limited
+        assert_eq!(field(group, "pel-count"), &Frame::Integer(2));
+        assert_eq!(len(field(group, "pending")), 1);
+    }
+}
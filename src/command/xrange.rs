@@ -52,28 +52,21 @@ impl XRange {
         let stream_key = frames.next_string()?;
         let start = frames.next_string()?;
         let end = frames.next_string()?;
+        // A range bound without sequence includes all the entries of its timestamp
         let start = if start == "-" {
             None
         } else {
-            Some(XRange::parse_id(start.as_str())?)
+            Some(StreamEntryId::parse(&start, 0)?)
         };
         let end = if end == "+" {
             None
         } else {
-            Some(XRange::parse_id(end.as_str())?)
+            Some(StreamEntryId::parse(&end, usize::MAX)?)
         };
 
         Ok(XRange::new(stream_key, start, end))
     }
 
-    pub fn parse_id(id: &str) -> crate::Result<StreamEntryId> {
-        let split_id = id.split('-').collect::<Vec<&str>>();
-        let timestamp = split_id[0].parse::<u128>()?;
-        let sequence = split_id[1].parse::<usize>()?;
-
-        Ok(StreamEntryId::new(timestamp, sequence))
-    }
-
     pub fn to_frame(&self) -> Frame {
         let mut frames = vec![Frame::Bulk("XRANGE".into())];
         frames.push(Frame::Bulk(self.stream_key.clone().into()));
@@ -116,3 +109,43 @@ impl CommandTrait for XRange {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(start: &str, end: &str) -> XRange {
+        let mut frames = Parse::new(Frame::Array(vec![
+            Frame::Bulk("stream".into()),
+            Frame::Bulk(start.to_string().into()),
+            Frame::Bulk(end.to_string().into()),
+        ]))
+        .unwrap();
+
+        XRange::parse_frames(&mut frames).unwrap()
+    }
+
+    #[test]
+    fn minus_and_plus_are_open_bounds() {
+        let range = parse("-", "+");
+
+        assert_eq!(range.start, None);
+        assert_eq!(range.end, None);
+    }
+
+    #[test]
+    fn timestamp_bounds_include_all_its_sequences() {
+        let range = parse("5", "7");
+
+        assert_eq!(range.start, Some(StreamEntryId::new(5, 0)));
+        assert_eq!(range.end, Some(StreamEntryId::new(7, usize::MAX)));
+    }
+
+    #[test]
+    fn explicit_bounds_are_kept() {
+        let range = parse("5-1", "7-2");
+
+        assert_eq!(range.start, Some(StreamEntryId::new(5, 1)));
+        assert_eq!(range.end, Some(StreamEntryId::new(7, 2)));
+    }
+}
@@ -1,4 +1,4 @@
-use std::str;
+use std::fmt;
 
 use async_trait::async_trait;
 
@@ -16,18 +16,16 @@ pub enum StartIds {
     Min,
 }
 
-impl ToString for StartIds {
-    fn to_string(&self) -> String {
+impl fmt::Display for StartIds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             StartIds::Explicit(ids) => {
-                let mut ids_str = String::new();
                 for id in ids {
-                    ids_str.push_str(&id.to_string());
-                    ids_str.push(' ');
+                    write!(f, "{} ", id)?;
                 }
-                ids_str
+                Ok(())
             }
-            StartIds::Min => "$".to_string(),
+            StartIds::Min => write!(f, "$"),
         }
     }
 }
@@ -49,11 +47,25 @@ impl XRead {
     }
 
     pub async fn execute(&self, db: &Db) -> Frame {
+        self.read(db, self.block).await
+    }
+
+    /// Whether the command waits for new entries, with the BLOCK option
+    pub fn is_blocking(&self) -> bool {
+        self.block.is_some()
+    }
+
+    /// Reads without waiting for new entries, like a blocking XREAD in a transaction
+    pub async fn execute_non_blocking(&self, db: &Db) -> Frame {
+        self.read(db, None).await
+    }
+
+    async fn read(&self, db: &Db, block: Option<u64>) -> Frame {
         let stream_ids = match &self.start_ids {
             StartIds::Explicit(ids) => ids.clone(),
             StartIds::Min => db.get_streams_last_ids(&self.stream_keys),
         };
-        let streams = db.xread(&self.stream_keys, &stream_ids, self.block).await;
+        let streams = db.xread(&self.stream_keys, &stream_ids, block).await;
 
         if streams.is_empty() {
             return Frame::Null;
@@ -139,7 +151,7 @@ impl XRead {
         while let Some(key) = frames.peek_string() {
             // If the key is a valid stream id or if the key is "$",
             // then we have reached the end of the keys
-            if Self::parse_id(&key).is_ok() || key == "$" {
+            if StreamEntryId::parse(&key, 0).is_ok() || key == "$" {
                 break;
             }
             // Otherwise, add the key to the list of keys and proceed frames iterator
@@ -165,20 +177,12 @@ impl XRead {
         let mut ids = Vec::new();
 
         while let Ok(id) = frames.next_string() {
-            ids.push(Self::parse_id(&id)?);
+            ids.push(StreamEntryId::parse(&id, 0)?);
         }
 
         Ok(ids)
     }
 
-    fn parse_id(id: &str) -> crate::Result<StreamEntryId> {
-        let split_id = id.split('-').collect::<Vec<&str>>();
-        let timestamp = split_id[0].parse::<u128>()?;
-        let sequence = split_id[1].parse::<usize>()?;
-
-        Ok(StreamEntryId::new(timestamp, sequence))
-    }
-
     pub fn to_frame(&self) -> Frame {
         let mut frames = vec![Frame::Bulk("XREAD".into())];
 
@@ -0,0 +1,167 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// Options of ZADD, given before the score-member pairs
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ZAddFlags {
+    /// Only add new members, don't update existing ones
+    pub nx: bool,
+    /// Only update existing members, don't add new ones
+    pub xx: bool,
+    /// Only update existing members if the new score is greater
+    pub gt: bool,
+    /// Only update existing members if the new score is less
+    pub lt: bool,
+    /// Count the changed members along with the added ones
+    pub ch: bool,
+    /// Increment the score of the member, like ZINCRBY
+    pub incr: bool,
+}
+
+impl ZAddFlags {
+    fn validate(&self) -> crate::Result<()> {
+        if self.nx && self.xx {
+            return Err("ERR XX and NX options at the same time are not compatible".into());
+        }
+
+        if (self.gt && self.lt) || (self.nx && (self.gt || self.lt)) {
+            return Err("ERR GT, LT, and/or NX options at the same time are not compatible".into());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ZAdd {
+    key: String,
+    flags: ZAddFlags,
+    members: Vec<(f64, Bytes)>,
+}
+
+impl ZAdd {
+    pub fn new(key: impl ToString, flags: ZAddFlags, members: Vec<(f64, Bytes)>) -> ZAdd {
+        ZAdd {
+            key: key.to_string(),
+            flags,
+            members,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.zadd(self.key.clone(), self.flags, self.members.clone()) {
+            // With INCR the reply is the new score, or null if the member was not updated
+            Ok((_, score)) if self.flags.incr => score
+                .map(|score| Frame::Bulk(format_score(score).into()))
+                .unwrap_or(Frame::Null),
+            Ok((count, _)) => Frame::Integer(count as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<ZAdd> {
+        let key = frames.next_string()?;
+        let mut flags = ZAddFlags::default();
+
+        // Flags come first, the first argument that is not a flag is the first score
+        let mut arg = frames.next_string()?;
+        loop {
+            match arg.to_uppercase().as_str() {
+                "NX" => flags.nx = true,
+                "XX" => flags.xx = true,
+                "GT" => flags.gt = true,
+                "LT" => flags.lt = true,
+                "CH" => flags.ch = true,
+                "INCR" => flags.incr = true,
+                _ => break,
+            }
+
+            arg = frames.next_string()?;
+        }
+
+        flags.validate()?;
+
+        let mut members = vec![(parse_score(&arg)?, frames.next_bytes()?)];
+
+        while let Ok(score) = frames.next_string() {
+            members.push((parse_score(&score)?, frames.next_bytes()?));
+        }
+
+        if flags.incr && members.len() > 1 {
+            return Err("ERR INCR option supports a single increment-element pair".into());
+        }
+
+        Ok(ZAdd::new(key, flags, members))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("ZADD".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ];
+
+        let flags = [
+            (self.flags.nx, "NX"),
+            (self.flags.xx, "XX"),
+            (self.flags.gt, "GT"),
+            (self.flags.lt, "LT"),
+            (self.flags.ch, "CH"),
+            (self.flags.incr, "INCR"),
+        ];
+        for (_, flag) in flags.iter().filter(|(set, _)| *set) {
+            frames.push(Frame::Bulk(flag.to_string().into()));
+        }
+
+        for (score, member) in &self.members {
+            frames.push(Frame::Bulk(format_score(*score).into()));
+            frames.push(Frame::Bulk(member.clone()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+/// Parse a sorted set score, `inf`, `+inf` and `-inf` are valid scores
+pub fn parse_score(score: &str) -> crate::Result<f64> {
+    match score.parse::<f64>() {
+        Ok(score) if !score.is_nan() => Ok(score),
+        _ => Err("ERR value is not a valid float".into()),
+    }
+}
+
+/// Format a sorted set score like Redis does in replies
+pub fn format_score(score: f64) -> String {
+    if score.is_infinite() {
+        if score > 0.0 { "inf" } else { "-inf" }.to_string()
+    } else {
+        score.to_string()
+    }
+}
+
+#[async_trait]
+impl CommandTrait for ZAdd {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(ZAdd::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db);
+        Frame::Null
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
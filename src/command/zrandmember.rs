@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, parse, Db, Frame, Info, Parse};
+
+use super::{zadd::format_score, CommandTrait};
+
+#[derive(Debug, Default)]
+pub struct ZRandMember {
+    key: String,
+    /// The reply is a single bulk without count and an array with it,
+    /// even for `ZRANDMEMBER key 1`
+    count: Option<i64>,
+    with_scores: bool,
+}
+
+impl ZRandMember {
+    pub fn new(key: impl ToString, count: Option<i64>, with_scores: bool) -> ZRandMember {
+        ZRandMember {
+            key: key.to_string(),
+            count,
+            with_scores,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        let members = match db.zrandmember(&self.key, self.count.unwrap_or(1)) {
+            Ok(members) => members,
+            Err(err) => return Frame::Error(err.to_string()),
+        };
+
+        if self.count.is_none() {
+            return members
+                .into_iter()
+                .next()
+                .map(|(member, _)| Frame::Bulk(member))
+                .unwrap_or(Frame::Null);
+        }
+
+        let mut frames = Vec::new();
+        for (member, score) in members {
+            frames.push(Frame::Bulk(member));
+
+            if self.with_scores {
+                frames.push(Frame::Bulk(format_score(score).into()));
+            }
+        }
+
+        Frame::Array(frames)
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<ZRandMember> {
+        let key = frames.next_string()?;
+
+        let count = match frames.next_int() {
+            Ok(count) => Some(count),
+            Err(parse::Error::EndOfStream) => return Ok(ZRandMember::new(key, None, false)),
+            Err(err) => return Err(err.into()),
+        };
+
+        let with_scores = match frames.next_string() {
+            Ok(s) if s.to_uppercase() == "WITHSCORES" => true,
+            Ok(_) => return Err("ERR syntax error".into()),
+            Err(parse::Error::EndOfStream) => false,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(ZRandMember::new(key, count, with_scores))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("ZRANDMEMBER".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ];
+
+        if let Some(count) = self.count {
+            frames.push(Frame::Bulk(count.to_string().into()));
+        }
+
+        if self.with_scores {
+            frames.push(Frame::Bulk("WITHSCORES".into()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for ZRandMember {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(ZRandMember::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
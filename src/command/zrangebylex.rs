@@ -0,0 +1,220 @@
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::{connection::Connection, parse, Db, Frame, Info, Parse};
+
+use super::CommandTrait;
+
+/// Bound of a lexicographical range of sorted set members
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexBound {
+    /// `[member`, the member is in the range
+    Inclusive(Bytes),
+    /// `(member`, the member is not in the range
+    Exclusive(Bytes),
+    /// `-`, lower than any member
+    Min,
+    /// `+`, greater than any member
+    Max,
+}
+
+impl LexBound {
+    pub fn parse(bound: Bytes) -> crate::Result<LexBound> {
+        match bound.first() {
+            Some(b'[') => Ok(LexBound::Inclusive(bound.slice(1..))),
+            Some(b'(') => Ok(LexBound::Exclusive(bound.slice(1..))),
+            Some(b'-') if bound.len() == 1 => Ok(LexBound::Min),
+            Some(b'+') if bound.len() == 1 => Ok(LexBound::Max),
+            _ => Err("ERR min or max not valid string range item".into()),
+        }
+    }
+
+    /// Whether the member is in a range starting at the bound
+    pub fn admits_from(&self, member: &[u8]) -> bool {
+        match self {
+            LexBound::Inclusive(bound) => member >= bound.as_ref(),
+            LexBound::Exclusive(bound) => member > bound.as_ref(),
+            LexBound::Min => true,
+            LexBound::Max => false,
+        }
+    }
+
+    /// Whether the member is in a range ending at the bound
+    pub fn admits_to(&self, member: &[u8]) -> bool {
+        match self {
+            LexBound::Inclusive(bound) => member <= bound.as_ref(),
+            LexBound::Exclusive(bound) => member < bound.as_ref(),
+            LexBound::Min => false,
+            LexBound::Max => true,
+        }
+    }
+
+    fn to_bytes(&self) -> Bytes {
+        match self {
+            LexBound::Inclusive(bound) => [b"[", bound.as_ref()].concat().into(),
+            LexBound::Exclusive(bound) => [b"(", bound.as_ref()].concat().into(),
+            LexBound::Min => Bytes::from("-"),
+            LexBound::Max => Bytes::from("+"),
+        }
+    }
+}
+
+/// ZRANGEBYLEX key min max [LIMIT offset count],
+/// the members are expected to have the same score
+#[derive(Debug)]
+pub struct ZRangeByLex {
+    key: String,
+    min: LexBound,
+    max: LexBound,
+    /// Offset and count of the members to return, all of them with a negative count
+    limit: Option<(i64, i64)>,
+}
+
+impl ZRangeByLex {
+    pub fn new(
+        key: impl ToString,
+        min: LexBound,
+        max: LexBound,
+        limit: Option<(i64, i64)>,
+    ) -> ZRangeByLex {
+        ZRangeByLex {
+            key: key.to_string(),
+            min,
+            max,
+            limit,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        match db.zrangebylex(&self.key, &self.min, &self.max, self.limit) {
+            Ok(members) => Frame::Array(members.into_iter().map(Frame::Bulk).collect()),
+            Err(err) => Frame::Error(err.to_string()),
+        }
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<ZRangeByLex> {
+        let key = frames.next_string()?;
+        let min = LexBound::parse(frames.next_bytes()?)?;
+        let max = LexBound::parse(frames.next_bytes()?)?;
+
+        let limit = match frames.next_string() {
+            Ok(option) if option.to_uppercase() == "LIMIT" => {
+                Some((frames.next_int()?, frames.next_int()?))
+            }
+            Ok(_) => return Err("ERR syntax error".into()),
+            Err(parse::Error::EndOfStream) => None,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(ZRangeByLex::new(key, min, max, limit))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("ZRANGEBYLEX".into()),
+            Frame::Bulk(self.key.clone().into()),
+            Frame::Bulk(self.min.to_bytes()),
+            Frame::Bulk(self.max.to_bytes()),
+        ];
+
+        if let Some((offset, count)) = self.limit {
+            frames.push(Frame::Bulk("LIMIT".into()));
+            frames.push(Frame::Bulk(offset.to_string().into()));
+            frames.push(Frame::Bulk(count.to_string().into()));
+        }
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for ZRangeByLex {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(ZRangeByLex::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bound(bound: &str) -> crate::Result<LexBound> {
+        LexBound::parse(Bytes::from(bound.to_string()))
+    }
+
+    #[test]
+    fn prefixes_are_parsed() {
+        assert_eq!(
+            bound("[alpha").unwrap(),
+            LexBound::Inclusive(Bytes::from("alpha"))
+        );
+        assert_eq!(
+            bound("(alpha").unwrap(),
+            LexBound::Exclusive(Bytes::from("alpha"))
+        );
+        assert_eq!(bound("-").unwrap(), LexBound::Min);
+        assert_eq!(bound("+").unwrap(), LexBound::Max);
+        // Only the first char is a prefix
+        assert_eq!(bound("[").unwrap(), LexBound::Inclusive(Bytes::new()));
+        assert_eq!(bound("((").unwrap(), LexBound::Exclusive(Bytes::from("(")));
+    }
+
+    #[test]
+    fn invalid_bounds_are_rejected() {
+        for invalid in ["", "alpha", "-alpha", "+alpha", "--"] {
+            assert!(bound(invalid).is_err(), "{:?} accepted", invalid);
+        }
+    }
+
+    #[test]
+    fn inclusive_bound_admits_the_member() {
+        let bound = bound("[b").unwrap();
+
+        assert!(bound.admits_from(b"b"));
+        assert!(bound.admits_to(b"b"));
+        assert!(!bound.admits_from(b"a"));
+        assert!(!bound.admits_to(b"ba"));
+    }
+
+    #[test]
+    fn exclusive_bound_does_not_admit_the_member() {
+        let bound = bound("(b").unwrap();
+
+        assert!(!bound.admits_from(b"b"));
+        assert!(!bound.admits_to(b"b"));
+        // A longer member with the bound as prefix is greater
+        assert!(bound.admits_from(b"ba"));
+        assert!(bound.admits_to(b"a"));
+    }
+
+    #[test]
+    fn min_and_max_are_outside_all_members() {
+        assert!(LexBound::Min.admits_from(b""));
+        assert!(!LexBound::Min.admits_to(b""));
+        assert!(LexBound::Max.admits_to(b"\xff"));
+        assert!(!LexBound::Max.admits_from(b"\xff"));
+    }
+
+    #[test]
+    fn bounds_are_encoded_back() {
+        for encoded in ["[alpha", "(alpha", "-", "+"] {
+            assert_eq!(bound(encoded).unwrap().to_bytes(), Bytes::from(encoded));
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use async_trait::async_trait;
+
+use crate::{connection::Connection, Db, Frame, Info, Parse};
+
+use super::{scan::ScanArgs, zadd::format_score, CommandTrait};
+
+#[derive(Debug)]
+pub struct ZScan {
+    key: String,
+    args: ScanArgs,
+}
+
+impl ZScan {
+    pub fn new(key: impl ToString, args: ScanArgs) -> ZScan {
+        ZScan {
+            key: key.to_string(),
+            args,
+        }
+    }
+
+    pub fn execute(&self, db: &Db) -> Frame {
+        let (cursor, members) = match db.zscan(&self.key, self.args.cursor, self.args.count) {
+            Ok(page) => page,
+            Err(err) => return Frame::Error(err.to_string()),
+        };
+
+        let mut frames = Vec::new();
+        for (member, score) in members
+            .into_iter()
+            .filter(|(member, _)| self.args.matches(&String::from_utf8_lossy(member)))
+        {
+            frames.push(Frame::Bulk(member));
+            frames.push(Frame::Bulk(format_score(score).into()));
+        }
+
+        Frame::Array(vec![
+            Frame::Bulk(cursor.to_string().into()),
+            Frame::Array(frames),
+        ])
+    }
+
+    pub fn parse_frames(frames: &mut Parse) -> crate::Result<ZScan> {
+        let key = frames.next_string()?;
+        let args = ScanArgs::parse_frames(frames, |_, _| Ok(false))?;
+
+        Ok(ZScan::new(key, args))
+    }
+
+    pub fn to_frame(&self) -> Frame {
+        let mut frames = vec![
+            Frame::Bulk("ZSCAN".into()),
+            Frame::Bulk(self.key.clone().into()),
+        ];
+        frames.extend(self.args.to_frames());
+
+        Frame::Array(frames)
+    }
+}
+
+#[async_trait]
+impl CommandTrait for ZScan {
+    fn parse_frames(&self, frames: &mut Parse) -> crate::Result<Box<dyn CommandTrait>> {
+        Ok(Box::new(ZScan::parse_frames(frames)?))
+    }
+
+    async fn execute(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn execute_replica(&self, db: &Db, _server_info: &mut Info, _connection: Connection) -> Frame {
+        self.execute(db)
+    }
+
+    fn to_frame(&self) -> Frame {
+        self.to_frame()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
@@ -5,6 +5,30 @@ pub struct Config {
     pub replica_of: Option<(String, u16)>,
     pub dir: String,
     pub db_filename: String,
+    pub replica_read_only: bool,
+    pub max_clients: usize,
+    /// Commands per second allowed on a connection, 0 for unlimited
+    pub max_commands_per_sec: u64,
+    /// Whether the test only DEBUG subcommands, like DUMP-KEYSPACE, are allowed
+    pub enable_debug_commands: bool,
+    /// Size in bytes of the replication backlog
+    pub repl_backlog_size: usize,
+    /// Seconds without replicas after which the backlog is freed, 0 to never free it
+    pub repl_backlog_ttl: u64,
+    /// Whether the large deleted or overwritten values are dropped on a background thread
+    pub lazyfree_lazy_server_del: bool,
+    /// Whether the CRC64 checksum is written at the end of the RDB files
+    pub rdbchecksum: bool,
+    /// Size in bytes of the send and receive buffers of the client sockets, 0 for the OS default
+    pub tcp_socket_buffer_size: usize,
+    pub hash_max_listpack_entries: usize,
+    pub hash_max_listpack_value: usize,
+    pub list_max_listpack_size: i64,
+    pub set_max_intset_entries: usize,
+    pub set_max_listpack_entries: usize,
+    pub set_max_listpack_value: usize,
+    pub zset_max_listpack_entries: usize,
+    pub zset_max_listpack_value: usize,
 }
 
 impl Config {
@@ -13,6 +37,23 @@ impl Config {
         let mut replica_of = None;
         let mut dir = String::new();
         let mut db_filename = String::new();
+        let mut replica_read_only = true;
+        let mut max_clients = 10000;
+        let mut max_commands_per_sec = 0;
+        let mut enable_debug_commands = false;
+        let mut repl_backlog_size = 1024 * 1024;
+        let mut repl_backlog_ttl = 3600;
+        let mut lazyfree_lazy_server_del = false;
+        let mut rdbchecksum = true;
+        let mut tcp_socket_buffer_size = 0;
+        let mut hash_max_listpack_entries = 128;
+        let mut hash_max_listpack_value = 64;
+        let mut list_max_listpack_size = -2;
+        let mut set_max_intset_entries = 512;
+        let mut set_max_listpack_entries = 128;
+        let mut set_max_listpack_value = 64;
+        let mut zset_max_listpack_entries = 128;
+        let mut zset_max_listpack_value = 64;
 
         while let Some(arg) = args.next() {
             match arg.as_str() {
@@ -28,6 +69,57 @@ impl Config {
                 "--dbfilename" => {
                     db_filename = Self::match_dbfilename(args.next())?;
                 }
+                "--replica-read-only" => {
+                    replica_read_only = Self::match_yes_no(args.next())?;
+                }
+                "--maxclients" => {
+                    max_clients = Self::match_max_clients(args.next())?;
+                }
+                "--hash-max-listpack-entries" => {
+                    hash_max_listpack_entries = Self::match_size(args.next())?;
+                }
+                "--hash-max-listpack-value" => {
+                    hash_max_listpack_value = Self::match_size(args.next())?;
+                }
+                "--list-max-listpack-size" => {
+                    list_max_listpack_size = Self::match_list_size(args.next())?;
+                }
+                "--enable-debug-commands" => {
+                    enable_debug_commands = true;
+                }
+                "--max-commands-per-sec" => {
+                    max_commands_per_sec = Self::match_rate(args.next())?;
+                }
+                "--repl-backlog-size" => {
+                    repl_backlog_size = Self::match_memory(args.next())?;
+                }
+                "--repl-backlog-ttl" => {
+                    repl_backlog_ttl = Self::match_rate(args.next())?;
+                }
+                "--lazyfree-lazy-server-del" => {
+                    lazyfree_lazy_server_del = Self::match_yes_no(args.next())?;
+                }
+                "--rdbchecksum" => {
+                    rdbchecksum = Self::match_yes_no(args.next())?;
+                }
+                "--tcp-socket-buffer-size" => {
+                    tcp_socket_buffer_size = Self::match_memory(args.next())?;
+                }
+                "--set-max-intset-entries" => {
+                    set_max_intset_entries = Self::match_size(args.next())?;
+                }
+                "--set-max-listpack-entries" => {
+                    set_max_listpack_entries = Self::match_size(args.next())?;
+                }
+                "--set-max-listpack-value" => {
+                    set_max_listpack_value = Self::match_size(args.next())?;
+                }
+                "--zset-max-listpack-entries" => {
+                    zset_max_listpack_entries = Self::match_size(args.next())?;
+                }
+                "--zset-max-listpack-value" => {
+                    zset_max_listpack_value = Self::match_size(args.next())?;
+                }
 
                 _ => {}
             }
@@ -38,9 +130,74 @@ impl Config {
             replica_of,
             dir,
             db_filename,
+            replica_read_only,
+            max_clients,
+            max_commands_per_sec,
+            enable_debug_commands,
+            repl_backlog_size,
+            repl_backlog_ttl,
+            lazyfree_lazy_server_del,
+            rdbchecksum,
+            tcp_socket_buffer_size,
+            hash_max_listpack_entries,
+            hash_max_listpack_value,
+            list_max_listpack_size,
+            set_max_intset_entries,
+            set_max_listpack_entries,
+            set_max_listpack_value,
+            zset_max_listpack_entries,
+            zset_max_listpack_value,
         })
     }
 
+    /// Name and value of every parameter that can't change at runtime, as reported by CONFIG GET.
+    /// The parameters of unsupported features report their default value.
+    /// The encoding limits are reported by [`EncodingConfig::params`](crate::EncodingConfig::params).
+    pub fn params(&self) -> Vec<(String, String)> {
+        let yes_no = |value: bool| if value { "yes" } else { "no" }.to_string();
+        let replica_of = match &self.replica_of {
+            Some((host, port)) => format!("{} {}", host, port),
+            None => String::new(),
+        };
+
+        vec![
+            ("port", self.port.to_string()),
+            ("bind", "* -::*".to_string()),
+            ("dir", self.dir.clone()),
+            ("dbfilename", self.db_filename.clone()),
+            ("replicaof", replica_of),
+            ("replica-read-only", yes_no(self.replica_read_only)),
+            // Authentication is not supported, there is never a password to mask
+            ("requirepass", String::new()),
+            ("maxclients", self.max_clients.to_string()),
+            ("maxmemory", "0".to_string()),
+            ("maxmemory-policy", "noeviction".to_string()),
+            ("appendonly", yes_no(false)),
+            ("appendfilename", "appendonly.aof".to_string()),
+            ("hz", "10".to_string()),
+            ("timeout", "0".to_string()),
+            (
+                "max-commands-per-sec",
+                self.max_commands_per_sec.to_string(),
+            ),
+            ("enable-debug-commands", yes_no(self.enable_debug_commands)),
+            ("repl-backlog-size", self.repl_backlog_size.to_string()),
+            ("repl-backlog-ttl", self.repl_backlog_ttl.to_string()),
+            (
+                "lazyfree-lazy-server-del",
+                yes_no(self.lazyfree_lazy_server_del),
+            ),
+            ("rdbchecksum", yes_no(self.rdbchecksum)),
+            (
+                "tcp-socket-buffer-size",
+                self.tcp_socket_buffer_size.to_string(),
+            ),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect()
+    }
+
     fn match_port(port_arg: Option<String>) -> crate::Result<u16> {
         let port = port_arg.ok_or("Port value not found")?;
 
@@ -75,4 +232,67 @@ impl Config {
     fn match_dbfilename(dbfilename: Option<String>) -> crate::Result<String> {
         dbfilename.ok_or("Dbfilename value not found".into())
     }
+
+    fn match_max_clients(max_clients: Option<String>) -> crate::Result<usize> {
+        let max_clients = max_clients.ok_or("Max clients value not found")?;
+
+        match max_clients.parse::<usize>() {
+            Ok(max_clients) if max_clients > 0 => Ok(max_clients),
+            _ => Err("Invalid max clients".into()),
+        }
+    }
+
+    fn match_size(size: Option<String>) -> crate::Result<usize> {
+        let size = size.ok_or("Size value not found")?;
+
+        size.parse::<usize>().map_err(|_| "Invalid size".into())
+    }
+
+    fn match_rate(rate: Option<String>) -> crate::Result<u64> {
+        let rate = rate.ok_or("Rate value not found")?;
+
+        rate.parse::<u64>().map_err(|_| "Invalid rate".into())
+    }
+
+    /// Parses a memory size in bytes, with an optional unit like `512kb` or `1mb`,
+    /// `k`, `m` and `g` being powers of 1000 and `kb`, `mb` and `gb` powers of 1024
+    fn match_memory(size: Option<String>) -> crate::Result<usize> {
+        let size = size.ok_or("Size value not found")?.to_lowercase();
+
+        let digits = size.trim_end_matches(char::is_alphabetic);
+        let unit = match &size[digits.len()..] {
+            "" | "b" => 1,
+            "k" => 1000,
+            "kb" => 1024,
+            "m" => 1000 * 1000,
+            "mb" => 1024 * 1024,
+            "g" => 1000 * 1000 * 1000,
+            "gb" => 1024 * 1024 * 1024,
+            _ => return Err("Invalid size".into()),
+        };
+
+        digits
+            .parse::<usize>()
+            .ok()
+            .and_then(|size| size.checked_mul(unit))
+            .ok_or_else(|| "Invalid size".into())
+    }
+
+    /// Parses `list-max-listpack-size`, a number of entries or a negative byte limit,
+    /// any integer is accepted like Redis does
+    fn match_list_size(size: Option<String>) -> crate::Result<i64> {
+        let size = size.ok_or("Size value not found")?;
+
+        size.parse::<i64>().map_err(|_| "Invalid list size".into())
+    }
+
+    fn match_yes_no(value: Option<String>) -> crate::Result<bool> {
+        let value = value.ok_or("Yes/no value not found")?;
+
+        match value.to_lowercase().as_str() {
+            "yes" => Ok(true),
+            "no" => Ok(false),
+            _ => Err("Argument must be 'yes' or 'no'".into()),
+        }
+    }
 }
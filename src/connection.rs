@@ -1,6 +1,7 @@
 use std::{
     io::{self, Cursor},
     net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard},
 };
 
 use tokio::{
@@ -17,11 +18,13 @@ use bytes::{Buf, Bytes, BytesMut};
 
 use crate::frame::Error as FrameError;
 use crate::frame::Frame;
+use crate::session::ClientSession;
 
 #[derive(Debug)]
 pub enum ConnectionMessage {
-    ReadFrame(oneshot::Sender<crate::Result<Option<Frame>>>),
-    ReadRdb(oneshot::Sender<crate::Result<Option<Frame>>>),
+    /// Reads a frame, replying with the number of bytes it took on the wire
+    ReadFrame(oneshot::Sender<crate::Result<Option<(Frame, usize)>>>),
+    ReadRdb(oneshot::Sender<crate::Result<Option<(Frame, usize)>>>),
     WriteFrame(Frame, oneshot::Sender<crate::Result<()>>),
 }
 
@@ -53,25 +56,29 @@ impl ConnectionReaderActor {
         }
     }
 
+    /// Reads the frames requested by the [`Connection`],
+    /// until the peer disconnects or all the handles are dropped
     pub async fn run(mut self) -> crate::Result<()> {
         while let Some(message) = self.receiver.recv().await {
-            match message {
-                ConnectionMessage::ReadFrame(sender) => {
-                    let frame = self.read_frame().await;
-                    let _ = sender.send(frame);
-                }
-                ConnectionMessage::ReadRdb(sender) => {
-                    let frame = self.read_rdb().await;
-                    let _ = sender.send(frame);
-                }
-                _ => (),
+            let (frame, sender) = match message {
+                ConnectionMessage::ReadFrame(sender) => (self.read_frame().await, sender),
+                ConnectionMessage::ReadRdb(sender) => (self.read_rdb().await, sender),
+                _ => continue,
+            };
+
+            // Nothing more can be read once the socket is closed or broken
+            let is_closed = !matches!(frame, Ok(Some(_)));
+            let _ = sender.send(frame);
+
+            if is_closed {
+                break;
             }
         }
 
         Ok(())
     }
 
-    async fn read_frame(&mut self) -> crate::Result<Option<Frame>> {
+    async fn read_frame(&mut self) -> crate::Result<Option<(Frame, usize)>> {
         loop {
             if let Some(frame) = self.parse_frame()? {
                 return Ok(Some(frame));
@@ -86,7 +93,8 @@ impl ConnectionReaderActor {
         }
     }
 
-    fn parse_frame(&mut self) -> crate::Result<Option<Frame>> {
+    /// Parses a frame from the buffer, with the number of bytes it took
+    fn parse_frame(&mut self) -> crate::Result<Option<(Frame, usize)>> {
         let mut buf = Cursor::new(&self.buffer[..]);
 
         match Frame::check(&mut buf) {
@@ -99,7 +107,7 @@ impl ConnectionReaderActor {
 
                 self.buffer.advance(len);
 
-                Ok(Some(frame))
+                Ok(Some((frame, len)))
             }
             // Not enough bytes is present in frame buffer
             // So wait for more data to be received
@@ -112,7 +120,7 @@ impl ConnectionReaderActor {
     /// Read RDB frame from the stream
     /// RDB frame is sent like $<length>\r\n<contents>
     /// Doesn't read any other frame type
-    async fn read_rdb(&mut self) -> crate::Result<Option<Frame>> {
+    async fn read_rdb(&mut self) -> crate::Result<Option<(Frame, usize)>> {
         loop {
             if let Some(frame) = self.parse_rdb()? {
                 return Ok(Some(frame));
@@ -134,7 +142,7 @@ impl ConnectionReaderActor {
     /// # Errors
     ///
     /// This function will return an error if the buffer is not enough to parse the rdb.
-    fn parse_rdb(&mut self) -> crate::Result<Option<Frame>> {
+    fn parse_rdb(&mut self) -> crate::Result<Option<(Frame, usize)>> {
         let mut buf = Cursor::new(&self.buffer[..]);
 
         match Frame::check_rdb(&mut buf) {
@@ -147,7 +155,7 @@ impl ConnectionReaderActor {
 
                 self.buffer.advance(len);
 
-                Ok(Some(frame))
+                Ok(Some((frame, len)))
             }
             // Not enough bytes is present in frame buffer
             // So wait for more data to be received
@@ -184,6 +192,8 @@ impl ConnectionWriterActor {
         }
     }
 
+    /// Writes the frames sent by the [`Connection`],
+    /// until a write fails because the peer disconnected or all the handles are dropped
     pub async fn run(mut self) -> crate::Result<()> {
         while let Some(message) = self.receiver.recv().await {
             if let ConnectionMessage::WriteFrame(frame, sender) = message {
@@ -192,7 +202,12 @@ impl ConnectionWriterActor {
                     .await
                     .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync + 'static>);
 
+                let is_broken = result.is_err();
                 let _ = sender.send(result);
+
+                if is_broken {
+                    break;
+                }
             }
         }
 
@@ -204,7 +219,7 @@ impl ConnectionWriterActor {
             Frame::Array(val) => {
                 self.stream.write_u8(b'*').await?;
 
-                self.write_decimal(val.len() as u64).await?;
+                self.write_decimal(val.len() as i64).await?;
 
                 for entry in val {
                     self.write_value(entry).await?;
@@ -229,11 +244,16 @@ impl ConnectionWriterActor {
                 self.stream.write_u8(b':').await?;
                 self.write_decimal(*val).await?;
             }
+            Frame::Boolean(val) => {
+                self.stream.write_u8(b'#').await?;
+                self.stream.write_u8(if *val { b't' } else { b'f' }).await?;
+                self.stream.write_all(b"\r\n").await?;
+            }
             Frame::Bulk(val) => {
                 let len = val.len();
 
                 self.stream.write_u8(b'$').await?;
-                self.write_decimal(len as u64).await?;
+                self.write_decimal(len as i64).await?;
                 self.stream.write_all(val).await?;
                 self.stream.write_all(b"\r\n").await?;
             }
@@ -256,7 +276,15 @@ impl ConnectionWriterActor {
             Frame::NoSend => {}
             Frame::Array(val) => {
                 self.stream.write_u8(b'*').await?;
-                self.write_decimal(val.len() as u64).await?;
+                self.write_decimal(val.len() as i64).await?;
+
+                for entry in val {
+                    self.write_value(entry).await?;
+                }
+            }
+            Frame::Push(val) => {
+                self.stream.write_u8(b'>').await?;
+                self.write_decimal(val.len() as i64).await?;
 
                 for entry in val {
                     self.write_value(entry).await?;
@@ -274,10 +302,11 @@ impl ConnectionWriterActor {
         Ok(())
     }
 
-    async fn write_decimal(&mut self, val: u64) -> io::Result<()> {
+    async fn write_decimal(&mut self, val: i64) -> io::Result<()> {
         use std::io::Write;
 
-        let mut buf = [0u8; 12];
+        // Enough for the sign and the 19 digits of an i64
+        let mut buf = [0u8; 20];
         let mut buf = Cursor::new(&mut buf[..]);
         write!(&mut buf, "{}", val)?;
 
@@ -295,7 +324,7 @@ impl ConnectionWriterActor {
     ///
     /// This function will return an error if .
     async fn write_rdb(&mut self, content: &Bytes) -> io::Result<()> {
-        let len = content.len() as u64;
+        let len = content.len() as i64;
 
         self.stream.write_u8(b'$').await?;
         self.write_decimal(len).await?;
@@ -311,6 +340,7 @@ pub struct Connection {
     write_sender: mpsc::Sender<ConnectionMessage>,
     read_sender: mpsc::Sender<ConnectionMessage>,
     addr: SocketAddr,
+    session: Arc<Mutex<ClientSession>>,
 }
 
 impl Drop for Connection {
@@ -322,6 +352,10 @@ impl Drop for Connection {
 impl Connection {
     pub fn new(stream: TcpStream, addr: SocketAddr) -> Self {
         let id = stream.peer_addr().unwrap();
+        // The replies are small, they are sent right away instead of waiting to be coalesced
+        if let Err(err) = stream.set_nodelay(true) {
+            eprintln!("Failed to set TCP_NODELAY: {}", err);
+        }
         let (stream_reader, stream_writer) = stream.into_split();
 
         let (read_tx, read_rx) = mpsc::channel(10);
@@ -347,10 +381,17 @@ impl Connection {
             addr,
             write_sender: write_tx,
             read_sender: read_tx,
+            session: Arc::new(Mutex::new(ClientSession::default())),
         }
     }
 
     pub async fn read_frame(&self) -> crate::Result<Option<Frame>> {
+        Ok(self.read_frame_with_len().await?.map(|(frame, _)| frame))
+    }
+
+    /// Reads a frame with the number of bytes it took on the wire,
+    /// which is not the length of the frame encoded again, e.g. for inline commands
+    pub async fn read_frame_with_len(&self) -> crate::Result<Option<(Frame, usize)>> {
         let (tx, rx) = oneshot::channel();
 
         self.read_sender
@@ -367,7 +408,7 @@ impl Connection {
             .send(ConnectionMessage::ReadRdb(tx))
             .await?;
 
-        rx.await?
+        Ok(rx.await??.map(|(frame, _)| frame))
     }
 
     pub async fn write_frame(&self, frame: Frame) -> crate::Result<()> {
@@ -383,4 +424,62 @@ impl Connection {
     pub fn addr(&self) -> SocketAddr {
         self.addr
     }
+
+    /// State of the client, the lock must not be held across an await
+    pub fn session(&self) -> MutexGuard<'_, ClientSession> {
+        self.session.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::{net::TcpListener, time::timeout};
+
+    use super::*;
+
+    /// Returns the client and the server ends of a local socket
+    async fn socket_pair() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+
+        (client, server)
+    }
+
+    #[tokio::test]
+    async fn reader_ends_after_client_disconnects() {
+        let (client, server) = socket_pair().await;
+        let id = server.peer_addr().unwrap();
+        let (reader, _writer) = server.into_split();
+        let (sender, receiver) = mpsc::channel(1);
+        let actor = tokio::spawn(ConnectionReaderActor::new(id, reader, receiver).run());
+
+        drop(client);
+        let (tx, rx) = oneshot::channel();
+        sender.send(ConnectionMessage::ReadFrame(tx)).await.unwrap();
+
+        assert!(matches!(rx.await.unwrap(), Ok(None)));
+        // The handle is still alive, the actor stops on its own
+        let result = timeout(Duration::from_secs(1), actor).await.unwrap();
+        assert!(result.unwrap().is_ok());
+        assert!(sender.is_closed());
+    }
+
+    #[tokio::test]
+    async fn writer_ends_when_handles_are_dropped() {
+        let (_client, server) = socket_pair().await;
+        let id = server.peer_addr().unwrap();
+        let (_reader, writer) = server.into_split();
+        let (sender, receiver) = mpsc::channel(1);
+        let actor = tokio::spawn(ConnectionWriterActor::new(id, writer, receiver).run());
+
+        drop(sender);
+
+        let result = timeout(Duration::from_secs(1), actor).await.unwrap();
+        assert!(result.unwrap().is_ok());
+    }
 }
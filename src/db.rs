@@ -1,17 +1,33 @@
 use std::{
-    collections::{BTreeMap, HashMap},
-    sync::{Arc, Mutex},
+    collections::{
+        hash_map::DefaultHasher, BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque,
+    },
+    fmt,
+    hash::{Hash, Hasher},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex,
+    },
+    thread,
     time::{Duration, SystemTime},
 };
 
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use tokio::{
-    sync::{broadcast, Notify},
+    sync::{broadcast, Notify, RwLock, RwLockReadGuard, RwLockWriteGuard},
     task::JoinSet,
     time::Instant,
 };
 
-use crate::command::XAddId;
+use crate::{
+    command::{
+        BitFieldOverflow, BitFieldType, BitOperation, BitUnit, ExpireCondition,
+        FunctionRestorePolicy, LexBound, ListDirection, SetCondition, SetOperation, SortOpts,
+        XAddId, ZAddFlags,
+    },
+    glob, random,
+    replicaiton::rdb::{RdbEntry, RdbValue, RedisDB},
+};
 
 #[derive(Debug, Clone)]
 pub struct Db {
@@ -22,7 +38,121 @@ pub struct Db {
 pub struct Shared {
     store: Mutex<Store>,
     task_expiry_notify: Notify,
+    /// Whether the expiry task removes the expired entries,
+    /// when disabled they are only removed on access
+    active_expire: AtomicBool,
+    /// Pub/sub channels, a channel exists while it has subscribers
+    channels: Mutex<HashMap<String, broadcast::Sender<Bytes>>>,
+    /// Held shared by the commands and exclusively by the transactions,
+    /// so no other command runs in the middle of a transaction
+    transaction: RwLock<()>,
+}
+
+/// Size limits of the compact encodings, like `zset-max-listpack-entries`
+#[derive(Debug, Clone, Copy)]
+pub struct EncodingConfig {
+    pub hash_max_listpack_entries: usize,
+    pub hash_max_listpack_value: usize,
+    /// Entries per listpack when positive, byte limit from -1 (4kb) to -5 (64kb) when negative
+    pub list_max_listpack_size: i64,
+    pub set_max_intset_entries: usize,
+    pub set_max_listpack_entries: usize,
+    pub set_max_listpack_value: usize,
+    pub zset_max_listpack_entries: usize,
+    pub zset_max_listpack_value: usize,
+}
+
+impl Default for EncodingConfig {
+    fn default() -> Self {
+        Self {
+            hash_max_listpack_entries: 128,
+            hash_max_listpack_value: 64,
+            list_max_listpack_size: -2,
+            set_max_intset_entries: 512,
+            set_max_listpack_entries: 128,
+            set_max_listpack_value: 64,
+            zset_max_listpack_entries: 128,
+            zset_max_listpack_value: 64,
+        }
+    }
+}
+
+impl EncodingConfig {
+    /// Name and value of every limit, as reported by CONFIG GET
+    pub fn params(&self) -> Vec<(String, String)> {
+        vec![
+            (
+                "hash-max-listpack-entries",
+                self.hash_max_listpack_entries.to_string(),
+            ),
+            (
+                "hash-max-listpack-value",
+                self.hash_max_listpack_value.to_string(),
+            ),
+            (
+                "list-max-listpack-size",
+                self.list_max_listpack_size.to_string(),
+            ),
+            (
+                "set-max-intset-entries",
+                self.set_max_intset_entries.to_string(),
+            ),
+            (
+                "set-max-listpack-entries",
+                self.set_max_listpack_entries.to_string(),
+            ),
+            (
+                "set-max-listpack-value",
+                self.set_max_listpack_value.to_string(),
+            ),
+            (
+                "zset-max-listpack-entries",
+                self.zset_max_listpack_entries.to_string(),
+            ),
+            (
+                "zset-max-listpack-value",
+                self.zset_max_listpack_value.to_string(),
+            ),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect()
+    }
+
+    /// Changes the limit with the given name, the existing values
+    /// keep their encoding until they are modified.
+    /// Returns false if there is no limit with this name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is not valid for the limit.
+    pub fn set(&mut self, name: &str, value: &str) -> crate::Result<bool> {
+        let invalid = || {
+            format!(
+                "ERR CONFIG SET failed (possibly related to argument '{}') - argument couldn't be parsed into an integer",
+                name
+            )
+        };
+        let size = || value.parse::<usize>().map_err(|_| invalid());
+
+        match name {
+            "hash-max-listpack-entries" => self.hash_max_listpack_entries = size()?,
+            "hash-max-listpack-value" => self.hash_max_listpack_value = size()?,
+            "list-max-listpack-size" => {
+                self.list_max_listpack_size = value.parse::<i64>().map_err(|_| invalid())?
+            }
+            "set-max-intset-entries" => self.set_max_intset_entries = size()?,
+            "set-max-listpack-entries" => self.set_max_listpack_entries = size()?,
+            "set-max-listpack-value" => self.set_max_listpack_value = size()?,
+            "zset-max-listpack-entries" => self.zset_max_listpack_entries = size()?,
+            "zset-max-listpack-value" => self.zset_max_listpack_value = size()?,
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
 }
+
 #[derive(Debug)]
 pub struct Store {
     // Key to entry mapping for all entries
@@ -34,6 +164,12 @@ pub struct Store {
     next_id: u64,
     // Flag to indicate that the store is being dropped
     is_dropped: bool,
+    // Limits of the compact encodings
+    encoding: EncodingConfig,
+    // Function libraries, name to code
+    functions: BTreeMap<String, Bytes>,
+    // Sender to the lazy free thread, while lazyfree-lazy-server-del is enabled
+    lazyfree: Option<mpsc::Sender<Entry>>,
 }
 
 #[derive(Debug, Clone)]
@@ -42,429 +178,3262 @@ pub enum Entry {
     String(StringEntry),
     /// Entry for a stream value
     Stream(Stream),
+    /// Entry for a list value
+    List(ListEntry),
+    /// Entry for a set value
+    Set(SetEntry),
+    /// Entry for a hash value
+    Hash(HashEntry),
+    /// Entry for a sorted set value
+    ZSet(ZSetEntry),
 }
 
-#[derive(Debug, Clone)]
-pub struct StringEntry {
-    // Unique identifier for the entry
-    id: u64,
-    value: Bytes,
-    expires_at: Option<Instant>,
-}
+impl Entry {
+    /// Number of allocations to free when the entry is dropped,
+    /// the compact encodings are a single allocation like in Redis
+    fn free_effort(&self) -> usize {
+        match self {
+            Entry::String(_) => 1,
+            Entry::Stream(stream) => stream.entries.len(),
+            Entry::List(list) if list.quicklist => list.values.len(),
+            Entry::Set(set) => match &set.encoding {
+                SetEncoding::Hashtable(members) => members.len(),
+                _ => 1,
+            },
+            Entry::Hash(hash) => match &hash.encoding {
+                HashEncoding::Hashtable(fields) => fields.len(),
+                _ => 1,
+            },
+            Entry::ZSet(zset) => match &zset.encoding {
+                ZSetEncoding::Skiplist(scores, _) => scores.len(),
+                _ => 1,
+            },
+            Entry::List(_) => 1,
+        }
+    }
 
-impl StringEntry {
-    pub fn value(&self) -> &Bytes {
-        &self.value
+    /// Returns the name of the internal encoding of the entry
+    pub fn encoding(&self) -> &'static str {
+        match self {
+            Entry::String(entry) => entry.encoding(),
+            Entry::Stream(_) => "stream",
+            Entry::List(list) => list.encoding(),
+            Entry::Set(set) => set.encoding(),
+            Entry::Hash(hash) => hash.encoding(),
+            Entry::ZSet(zset) => zset.encoding(),
+        }
     }
 
-    pub fn value_mut(&mut self) -> &mut Bytes {
-        &mut self.value
+    /// Returns the type name of the entry, as reported by the TYPE command
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Entry::String(_) => "string",
+            Entry::Stream(_) => "stream",
+            Entry::List(_) => "list",
+            Entry::Set(_) => "set",
+            Entry::Hash(_) => "hash",
+            Entry::ZSet(_) => "zset",
+        }
     }
-}
 
-#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
-pub struct StreamEntryId(u128, usize);
+    /// Returns the LRU clock of the last access to the entry
+    pub fn lru(&self) -> u32 {
+        match self {
+            Entry::String(entry) => entry.lru,
+            Entry::Stream(stream) => stream.lru,
+            Entry::List(list) => list.lru,
+            Entry::Set(set) => set.lru,
+            Entry::Hash(hash) => hash.lru,
+            Entry::ZSet(zset) => zset.lru,
+        }
+    }
 
-impl StreamEntryId {
-    pub fn new(timestamp: u128, sequence: usize) -> Self {
-        Self(timestamp, sequence)
+    /// Returns the expiry and the unique id of the entry, if it has an expiry
+    fn expiry(&self) -> Option<(Instant, u64)> {
+        let (expires_at, id) = match self {
+            Entry::String(entry) => (entry.expires_at, entry.id),
+            Entry::Stream(stream) => (stream.expires_at, stream.id),
+            Entry::List(list) => (list.expires_at, list.id),
+            Entry::Set(set) => (set.expires_at, set.id),
+            Entry::Hash(hash) => (hash.expires_at, hash.id),
+            Entry::ZSet(zset) => (zset.expires_at, zset.id),
+        };
+
+        expires_at.map(|expiry| (expiry, id))
+    }
+
+    /// Returns a mutable reference to the expiry and the unique id of the entry
+    fn expiry_mut(&mut self) -> (&mut Option<Instant>, u64) {
+        match self {
+            Entry::String(entry) => (&mut entry.expires_at, entry.id),
+            Entry::Stream(stream) => (&mut stream.expires_at, stream.id),
+            Entry::List(list) => (&mut list.expires_at, list.id),
+            Entry::Set(set) => (&mut set.expires_at, set.id),
+            Entry::Hash(hash) => (&mut hash.expires_at, hash.id),
+            Entry::ZSet(zset) => (&mut zset.expires_at, zset.id),
+        }
+    }
+
+    /// Builds an entry from a value loaded from an RDB file
+    fn from_rdb(id: u64, value: RdbValue, config: &EncodingConfig) -> Entry {
+        match value {
+            RdbValue::String(value) => Entry::String(StringEntry {
+                id,
+                value: value.into(),
+                expires_at: None,
+                lru: lru_clock(),
+            }),
+            RdbValue::List(values) => {
+                let mut list = ListEntry::new(id);
+                list.values
+                    .extend(values.into_iter().map(ListpackElement::from));
+                list.update_encoding(config);
+                Entry::List(list)
+            }
+            RdbValue::Set(members) => {
+                let mut set = SetEntry::new(id);
+                for member in members {
+                    set.insert(member, config);
+                }
+                Entry::Set(set)
+            }
+            RdbValue::Hash(fields) => {
+                let mut hash = HashEntry::new(id);
+                for (field, value) in fields {
+                    hash.insert(String::from_utf8_lossy(&field).into_owned(), value, config);
+                }
+                Entry::Hash(hash)
+            }
+            RdbValue::ZSet(members) => {
+                let mut zset = ZSetEntry::new(id);
+                for (member, score) in members {
+                    zset.insert(member, score, config);
+                }
+                Entry::ZSet(zset)
+            }
+            RdbValue::Stream(entries) => {
+                let mut stream = Stream::new(id);
+                stream.entries = entries
+                    .into_iter()
+                    .map(|((ms, seq), key_value)| {
+                        let key_value = key_value
+                            .into_iter()
+                            .map(|(key, value)| (String::from_utf8_lossy(&key).into_owned(), value))
+                            .collect();
+
+                        StreamEntry::new(StreamEntryId(ms as u128, seq as usize), key_value)
+                    })
+                    .collect();
+                Entry::Stream(stream)
+            }
+        }
     }
-}
 
-impl ToString for StreamEntryId {
-    fn to_string(&self) -> String {
-        format!("{}-{}", self.0, self.1)
+    /// Returns the value of the entry, to be saved in an RDB file
+    fn to_rdb(&self) -> RdbValue {
+        match self {
+            Entry::String(entry) => RdbValue::String(entry.value()),
+            Entry::List(list) => RdbValue::List(list.iter().collect()),
+            Entry::Set(set) => RdbValue::Set(set.iter().collect()),
+            Entry::Hash(hash) => RdbValue::Hash(
+                hash.iter()
+                    .map(|(field, value)| (Bytes::from(field), value.clone()))
+                    .collect(),
+            ),
+            Entry::ZSet(zset) => RdbValue::ZSet(zset.sorted()),
+            Entry::Stream(stream) => RdbValue::Stream(
+                stream
+                    .entries
+                    .iter()
+                    .map(|entry| {
+                        let StreamEntryId(ms, seq) = entry.id;
+                        let key_value = entry
+                            .key_value
+                            .iter()
+                            .map(|(key, value)| (Bytes::from(key.clone()), value.clone()))
+                            .collect();
+
+                        ((ms as u64, seq as u64), key_value)
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Updates the access time of the entry
+    fn touch(&mut self) {
+        self.set_lru(lru_clock());
+    }
+
+    /// Sets the LRU clock of the last access to the entry
+    fn set_lru(&mut self, lru: u32) {
+        match self {
+            Entry::String(entry) => entry.lru = lru,
+            Entry::Stream(stream) => stream.lru = lru,
+            Entry::List(list) => list.lru = lru,
+            Entry::Set(set) => set.lru = lru,
+            Entry::Hash(hash) => hash.lru = lru,
+            Entry::ZSet(zset) => zset.lru = lru,
+        }
     }
 }
 
-#[derive(Debug, Clone, Default)]
-pub struct StreamEntry {
-    /// Unique identifier for the entry
-    id: StreamEntryId,
-    /// Key-value pairs for the entry
-    key_value: Vec<(String, Bytes)>,
+/// Value of a string, strings holding a 64-bit integer are stored as the integer.
+/// Strings up to 44 bytes are embedded in the object allocation in Redis,
+/// the limit is on the bytes of the value, not on its characters.
+#[derive(Debug, Clone)]
+enum StringValue {
+    Int(i64),
+    Embstr(Bytes),
+    Raw(Bytes),
+}
+
+impl From<Bytes> for StringValue {
+    fn from(value: Bytes) -> Self {
+        match parse_int_encoded(&value) {
+            Some(value) => StringValue::Int(value),
+            None if value.len() <= EMBSTR_SIZE_LIMIT => StringValue::Embstr(value),
+            None => StringValue::Raw(value),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
-pub struct Stream {
-    update_sender: Option<broadcast::Sender<StreamEntryId>>,
-    entries: Vec<StreamEntry>,
+pub struct StringEntry {
+    // Unique identifier for the entry
+    id: u64,
+    value: StringValue,
+    expires_at: Option<Instant>,
+    // LRU clock of the last access
+    lru: u32,
 }
 
-impl Stream {
-    fn subscribe(&mut self) -> broadcast::Receiver<StreamEntryId> {
-        match &self.update_sender {
-            Some(sender) => sender.subscribe(),
-            None => {
-                let (sender, receiver) = broadcast::channel(1);
-                self.update_sender = Some(sender);
-                receiver
-            }
+impl StringEntry {
+    /// The value as bytes, integers are formatted in decimal
+    pub fn value(&self) -> Bytes {
+        match &self.value {
+            StringValue::Int(value) => Bytes::from(value.to_string()),
+            StringValue::Embstr(value) | StringValue::Raw(value) => value.clone(),
         }
     }
 
-    fn send_update(&self, id: StreamEntryId) {
-        if let Some(sender) = &self.update_sender {
-            let _ = sender.send(id).unwrap();
+    /// Replaces the value of a string modified in place, like by APPEND,
+    /// the value is kept raw even if it holds an integer, as in Redis
+    pub fn set_value(&mut self, value: Bytes) {
+        self.value = StringValue::Raw(value);
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.value {
+            StringValue::Int(value) => value.to_string().len(),
+            StringValue::Embstr(value) | StringValue::Raw(value) => value.len(),
         }
     }
 
-    fn get_last_id(&self) -> StreamEntryId {
-        self.entries
-            .last()
-            .map(|entry| entry.id)
-            .unwrap_or(StreamEntryId(0, 0))
+    pub fn encoding(&self) -> &'static str {
+        match &self.value {
+            StringValue::Int(_) => "int",
+            StringValue::Embstr(_) => "embstr",
+            StringValue::Raw(_) => "raw",
+        }
     }
 }
 
-impl StreamEntry {
-    pub fn new(id: StreamEntryId, key_value: Vec<(String, Bytes)>) -> Self {
-        Self { id, key_value }
+#[derive(Debug, Clone)]
+pub struct ListEntry {
+    // Unique identifier for the entry
+    id: u64,
+    values: VecDeque<ListpackElement>,
+    /// Whether the list is too large for the listpack encoding,
+    /// the values are stored the same way with both encodings
+    /// as the nodes of a quicklist are listpacks
+    quicklist: bool,
+    expires_at: Option<Instant>,
+    // LRU clock of the last access
+    lru: u32,
+}
+
+impl ListEntry {
+    fn new(id: u64) -> Self {
+        Self {
+            id,
+            values: VecDeque::new(),
+            quicklist: false,
+            expires_at: None,
+            lru: lru_clock(),
+        }
     }
 
-    pub fn id(&self) -> StreamEntryId {
-        self.id
+    /// Returns the elements from head to tail, the integer elements are formatted
+    pub fn iter(&self) -> impl Iterator<Item = Bytes> + '_ {
+        self.values.iter().map(Bytes::from)
     }
 
-    pub fn key_value(&self) -> &[(String, Bytes)] {
-        &self.key_value
+    pub fn encoding(&self) -> &'static str {
+        match self.quicklist {
+            true => "quicklist",
+            false => "listpack",
+        }
     }
-}
 
-impl Db {
-    pub fn new() -> Self {
-        let db = Self {
-            shared: Arc::new(Shared::new()),
-        };
+    /// Converts the list to the quicklist encoding when it grows past `list-max-listpack-size`,
+    /// and back to listpack when it shrinks to half of it, so the encoding does not flap
+    fn update_encoding(&mut self, config: &EncodingConfig) {
+        let size = config.list_max_listpack_size;
 
-        // Spawn the task that will remove expired entries
-        tokio::spawn(task_expiry(db.shared.clone()));
+        let (len, limit) = match size {
+            // 0 keeps a single entry per node, like 1
+            size if size >= 0 => (self.values.len(), size.max(1) as usize),
+            // Negative sizes are byte limits, from -1 for 4kb to -5 and below for 64kb
+            size => (
+                self.values.iter().map(|value| value.len()).sum(),
+                4096 << (-size - 1).clamp(0, 4),
+            ),
+        };
 
-        db
+        if !self.quicklist && len > limit {
+            self.quicklist = true;
+        } else if self.quicklist && len <= limit / 2 {
+            self.quicklist = false;
+        }
     }
 
-    pub fn from_rdb(rdb: HashMap<String, (String, Option<SystemTime>)>) -> Self {
-        let db = Self::new();
-        let current_time = SystemTime::now();
+    fn pop(&mut self, direction: ListDirection) -> Option<Bytes> {
+        let value = match direction {
+            ListDirection::Left => self.values.pop_front(),
+            ListDirection::Right => self.values.pop_back(),
+        };
 
-        // Insert all the entries from the RDB into the database
-        for (key, (value, expiry)) in rdb {
-            let expire = match expiry {
-                Some(expiry) => match expiry.duration_since(current_time) {
-                    // If the expiry is in the future, then we set the expiry
-                    Ok(duration) => Some(duration),
-                    // If the expiry is in the past, then the key has expired
-                    // so we skip inserting it
-                    Err(_) => continue,
-                },
-                None => None,
-            };
+        value.map(Bytes::from)
+    }
 
-            db.set(key, Bytes::from(value), expire);
-        }
+    fn push(&mut self, direction: ListDirection, value: Bytes) {
+        let value = ListpackElement::from(value);
 
-        db
+        match direction {
+            ListDirection::Left => self.values.push_front(value),
+            ListDirection::Right => self.values.push_back(value),
+        }
     }
+}
 
-    /// Sets the value of a key in the database.
-    /// If the key already exists, the previous value will be overwritten.
-    /// Optionally, the key can be set to expire after a specified duration.
-    ///
-    /// # Panics
-    ///
-    /// Panics if the lock is poisoned.
-    pub fn set(&self, key: String, value: Bytes, expire: Option<Duration>) {
-        let mut store = self.shared.store.lock().unwrap();
+/// Element of a list, the elements holding an integer
+/// are stored as integers like in the listpack entries
+#[derive(Debug, Clone, PartialEq)]
+enum ListpackElement {
+    Int(i64),
+    Str(Bytes),
+}
 
-        let id = store.next_id();
+impl From<Bytes> for ListpackElement {
+    fn from(value: Bytes) -> Self {
+        match parse_int_encoded(&value) {
+            Some(value) => ListpackElement::Int(value),
+            None => ListpackElement::Str(value),
+        }
+    }
+}
 
-        let mut should_notify = false;
+impl From<&ListpackElement> for Bytes {
+    fn from(value: &ListpackElement) -> Self {
+        match value {
+            ListpackElement::Int(value) => Bytes::from(value.to_string()),
+            ListpackElement::Str(value) => value.clone(),
+        }
+    }
+}
 
-        let expires_at = expire.map(|duration| {
-            let when = Instant::now() + duration;
+impl From<ListpackElement> for Bytes {
+    fn from(value: ListpackElement) -> Self {
+        match value {
+            ListpackElement::Int(value) => Bytes::from(value.to_string()),
+            ListpackElement::Str(value) => value,
+        }
+    }
+}
 
-            // Worker needs to be notified if the new expiry is the earliest one
-            should_notify = store.next_expiry().map(|next| when < next).unwrap_or(true);
+impl ListpackElement {
+    /// Length of the element as a string
+    fn len(&self) -> usize {
+        match self {
+            ListpackElement::Int(value) => value.to_string().len(),
+            ListpackElement::Str(value) => value.len(),
+        }
+    }
+}
 
-            // Insert the new expiry into the BTreeMap
-            store.expires.insert((when, id), key.clone());
-            when
-        });
+#[derive(Debug, Clone)]
+pub struct SetEntry {
+    // Unique identifier for the entry
+    id: u64,
+    encoding: SetEncoding,
+    expires_at: Option<Instant>,
+    // LRU clock of the last access
+    lru: u32,
+}
 
-        let entry = Entry::String(StringEntry {
-            id,
-            value,
-            expires_at,
-        });
+/// Representation of a set, small ones are kept in compact arrays
+/// and converted to a hash table when they grow past the [`EncodingConfig`] limits
+#[derive(Debug, Clone)]
+pub enum SetEncoding {
+    /// Sorted integers, while all the members are integers
+    Intset(Vec<i64>),
+    /// Members in insertion order
+    Listpack(Vec<Bytes>),
+    Hashtable(HashSet<Bytes>),
+}
 
-        // If there was an existing entry with an expiry, remove the previous expiry
-        let prev = store.data.insert(key, entry);
-        if let Some(prev) = prev {
-            match prev {
-                Entry::String(prev) => {
-                    if let Some(expiry) = prev.expires_at {
-                        store.expires.remove(&(expiry, prev.id));
-                    }
-                }
-                // If the previous entry was a stream, then we do not need to remove the expiry
-                // as streams do not have an expiry
-                Entry::Stream(_) => {}
-            }
+impl SetEntry {
+    fn new(id: u64) -> Self {
+        Self {
+            id,
+            encoding: SetEncoding::Intset(Vec::new()),
+            expires_at: None,
+            lru: lru_clock(),
         }
+    }
 
-        // Release the lock so the task will be able to acquire it if needed
-        drop(store);
+    /// Name of the encoding, as reported by OBJECT ENCODING
+    pub fn encoding(&self) -> &'static str {
+        match self.encoding {
+            SetEncoding::Intset(_) => "intset",
+            SetEncoding::Listpack(_) => "listpack",
+            SetEncoding::Hashtable(_) => "hashtable",
+        }
+    }
 
-        //  Notify the task expiry task to wake up, so it can recompute the next expiry
-        if should_notify {
-            self.shared.task_expiry_notify.notify_one();
+    pub fn len(&self) -> usize {
+        match &self.encoding {
+            SetEncoding::Intset(members) => members.len(),
+            SetEncoding::Listpack(members) => members.len(),
+            SetEncoding::Hashtable(members) => members.len(),
         }
     }
 
-    /// Returns the entry with the specified key from the database.
-    /// Returns `None` if the entry does not exist (possibly due to expiry).
-    ///
-    /// # Panics
-    ///
-    /// Panics if the lock is poisoned.
-    pub fn get(&self, key: &str) -> Option<Entry> {
-        let store = self.shared.store.lock().unwrap();
-        store.data.get(key).cloned()
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
-    pub fn keys(&self) -> Vec<String> {
-        let store = self.shared.store.lock().unwrap();
-        store.data.keys().cloned().collect()
+    pub fn contains(&self, member: &[u8]) -> bool {
+        match &self.encoding {
+            SetEncoding::Intset(members) => parse_int_encoded(member)
+                .is_some_and(|member| members.binary_search(&member).is_ok()),
+            SetEncoding::Listpack(members) => members.iter().any(|current| current == member),
+            SetEncoding::Hashtable(members) => members.contains(member),
+        }
     }
 
-    /// Removes the entry with the specified key from the database.
-    /// Returns the value of the entry if it existed. Otherwise, returns `None`.
-    /// Sometimes due to the entry being expired, it may not be present in the database.
-    ///
-    /// # Panics
-    ///
-    /// Panics if the lock is poisoned.
-    pub fn remove(&self, key: &str) -> Option<Entry> {
-        let mut store = self.shared.store.lock().unwrap();
+    /// Returns the members, the integers of an intset are formatted as strings
+    pub fn iter(&self) -> Box<dyn Iterator<Item = Bytes> + '_> {
+        match &self.encoding {
+            SetEncoding::Intset(members) => {
+                Box::new(members.iter().map(|member| Bytes::from(member.to_string())))
+            }
+            SetEncoding::Listpack(members) => Box::new(members.iter().cloned()),
+            SetEncoding::Hashtable(members) => Box::new(members.iter().cloned()),
+        }
+    }
+
+    /// Adds the member, converting the set to the listpack encoding when a non-integer is added
+    /// and to the hashtable encoding if it grows past the limits of its encoding.
+    /// Returns false if the member was already in the set.
+    fn insert(&mut self, member: Bytes, config: &EncodingConfig) -> bool {
+        match &mut self.encoding {
+            SetEncoding::Intset(members) => {
+                let value = match parse_int_encoded(&member) {
+                    Some(value) => value,
+                    None => {
+                        let fits_listpack = members.len() < config.set_max_listpack_entries
+                            && member.len() <= config.set_max_listpack_value;
 
-        match store.data.remove(key) {
-            Some(prev) => {
-                match prev {
-                    Entry::String(prev) => {
-                        // If there was an existing entry with an expiry, remove the previous expiry
-                        if let Some(expiry) = prev.expires_at {
-                            store.expires.remove(&(expiry, prev.id));
+                        match fits_listpack {
+                            true => self.convert_to_listpack(),
+                            false => self.convert_to_hashtable(),
                         }
-                        Some(Entry::String(prev))
+                        return self.insert(member, config);
+                    }
+                };
+
+                match members.binary_search(&value) {
+                    Ok(_) => false,
+                    Err(pos) => {
+                        members.insert(pos, value);
+
+                        if members.len() > config.set_max_intset_entries {
+                            self.convert_to_hashtable();
+                        }
+
+                        true
                     }
-                    Entry::Stream(prev) => Some(Entry::Stream(prev)),
                 }
             }
-            None => None,
+            SetEncoding::Listpack(members) => {
+                if members.contains(&member) {
+                    return false;
+                }
+
+                let is_large = member.len() > config.set_max_listpack_value;
+                members.push(member);
+
+                if is_large || members.len() > config.set_max_listpack_entries {
+                    self.convert_to_hashtable();
+                }
+
+                true
+            }
+            SetEncoding::Hashtable(members) => members.insert(member),
         }
     }
 
-    pub async fn xadd(
-        &self,
-        stream_key: String,
-        id: XAddId,
+    /// Removes the member, returns false if it was not in the set
+    fn remove(&mut self, member: &[u8]) -> bool {
+        match &mut self.encoding {
+            SetEncoding::Intset(members) => {
+                match parse_int_encoded(member).map(|member| members.binary_search(&member)) {
+                    Some(Ok(pos)) => {
+                        members.remove(pos);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            SetEncoding::Listpack(members) => {
+                match members.iter().position(|current| current == member) {
+                    Some(pos) => {
+                        members.remove(pos);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            SetEncoding::Hashtable(members) => members.remove(member),
+        }
+    }
+
+    fn convert_to_listpack(&mut self) {
+        if let SetEncoding::Intset(_) = self.encoding {
+            self.encoding = SetEncoding::Listpack(self.iter().collect());
+        }
+    }
+
+    fn convert_to_hashtable(&mut self) {
+        if !matches!(self.encoding, SetEncoding::Hashtable(_)) {
+            self.encoding = SetEncoding::Hashtable(self.iter().collect());
+        }
+    }
+}
+
+/// Parses a value that can be stored as an integer, like the members of an intset,
+/// the integer must format back to the same string, e.g. not "+1" or "01"
+fn parse_int_encoded(member: &[u8]) -> Option<i64> {
+    let value = std::str::from_utf8(member).ok()?.parse::<i64>().ok()?;
+
+    (value.to_string().as_bytes() == member).then_some(value)
+}
+
+#[derive(Debug, Clone)]
+pub struct HashEntry {
+    // Unique identifier for the entry
+    id: u64,
+    encoding: HashEncoding,
+    expires_at: Option<Instant>,
+    // LRU clock of the last access
+    lru: u32,
+}
+
+/// Internal representation of a hash, small hashes are kept
+/// as a flat list of field-value pairs in insertion order
+#[derive(Debug, Clone)]
+enum HashEncoding {
+    Listpack(Vec<(ListpackField, Bytes)>),
+    Hashtable(HashMap<String, Bytes>),
+}
+
+/// Field of a listpack encoded hash, the fields holding an integer
+/// are stored as integers like in the listpack entries
+#[derive(Debug, Clone, PartialEq)]
+enum ListpackField {
+    Int(i64),
+    Str(String),
+}
+
+impl From<String> for ListpackField {
+    fn from(field: String) -> Self {
+        match parse_int_encoded(field.as_bytes()) {
+            Some(field) => ListpackField::Int(field),
+            None => ListpackField::Str(field),
+        }
+    }
+}
+
+impl ListpackField {
+    /// Whether the field is the one named, an integer field is compared to its decimal form
+    fn is(&self, field: &str) -> bool {
+        match self {
+            ListpackField::Int(current) => parse_int_encoded(field.as_bytes()) == Some(*current),
+            ListpackField::Str(current) => current == field,
+        }
+    }
+
+    fn into_string(self) -> String {
+        match self {
+            ListpackField::Int(field) => field.to_string(),
+            ListpackField::Str(field) => field,
+        }
+    }
+}
+
+impl HashEntry {
+    fn new(id: u64) -> Self {
+        Self {
+            id,
+            encoding: HashEncoding::Listpack(Vec::new()),
+            expires_at: None,
+            lru: lru_clock(),
+        }
+    }
+
+    pub fn encoding(&self) -> &'static str {
+        match self.encoding {
+            HashEncoding::Listpack(_) => "listpack",
+            HashEncoding::Hashtable(_) => "hashtable",
+        }
+    }
+
+    /// Returns the value of the field, a listpack is scanned linearly
+    pub fn get(&self, field: &str) -> Option<&Bytes> {
+        match &self.encoding {
+            HashEncoding::Listpack(fields) => fields
+                .iter()
+                .find(|(current, _)| current.is(field))
+                .map(|(_, value)| value),
+            HashEncoding::Hashtable(fields) => fields.get(field),
+        }
+    }
+
+    /// Returns the fields with their values, the integer fields of a listpack are formatted
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (String, &Bytes)> + '_> {
+        match &self.encoding {
+            HashEncoding::Listpack(fields) => Box::new(
+                fields
+                    .iter()
+                    .map(|(field, value)| (field.clone().into_string(), value)),
+            ),
+            HashEncoding::Hashtable(fields) => {
+                Box::new(fields.iter().map(|(field, value)| (field.clone(), value)))
+            }
+        }
+    }
+
+    /// Sets the value of the field, converting the hash to the hashtable encoding
+    /// if it grows past the limits of the listpack encoding.
+    /// Returns false if the field was already in the hash.
+    fn insert(&mut self, field: String, value: Bytes, config: &EncodingConfig) -> bool {
+        match &mut self.encoding {
+            HashEncoding::Listpack(fields) => {
+                let is_large = field.len() > config.hash_max_listpack_value
+                    || value.len() > config.hash_max_listpack_value;
+
+                let is_new = match fields.iter_mut().find(|(current, _)| current.is(&field)) {
+                    Some((_, current)) => {
+                        *current = value;
+                        false
+                    }
+                    None => {
+                        fields.push((ListpackField::from(field), value));
+                        true
+                    }
+                };
+
+                if is_large || fields.len() > config.hash_max_listpack_entries {
+                    self.convert_to_hashtable();
+                }
+
+                is_new
+            }
+            HashEncoding::Hashtable(fields) => fields.insert(field, value).is_none(),
+        }
+    }
+
+    fn convert_to_hashtable(&mut self) {
+        if let HashEncoding::Listpack(fields) = &mut self.encoding {
+            self.encoding = HashEncoding::Hashtable(
+                fields
+                    .drain(..)
+                    .map(|(field, value)| (field.into_string(), value))
+                    .collect(),
+            );
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ZSetEntry {
+    // Unique identifier for the entry
+    id: u64,
+    encoding: ZSetEncoding,
+    expires_at: Option<Instant>,
+    // LRU clock of the last access
+    lru: u32,
+}
+
+/// Representation of a sorted set, small ones are kept in a compact sorted list
+/// and converted to the regular encoding when they grow past the [`EncodingConfig`] limits
+#[derive(Debug, Clone)]
+pub enum ZSetEncoding {
+    /// Members with their scores, ordered by score then by member
+    Listpack(Vec<(Bytes, f64)>),
+    /// Scores by member, and the members ordered by score then by member
+    Skiplist(HashMap<Bytes, f64>, BTreeSet<ScoredMember>),
+}
+
+/// Member of a sorted set ordered by score then by member
+#[derive(Debug, Clone)]
+pub struct ScoredMember(f64, Bytes);
+
+impl PartialEq for ScoredMember {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for ScoredMember {}
+
+impl PartialOrd for ScoredMember {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScoredMember {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0
+            .total_cmp(&other.0)
+            .then_with(|| self.1.cmp(&other.1))
+    }
+}
+
+impl ZSetEntry {
+    fn new(id: u64) -> Self {
+        Self {
+            id,
+            encoding: ZSetEncoding::Listpack(Vec::new()),
+            expires_at: None,
+            lru: lru_clock(),
+        }
+    }
+
+    /// Name of the encoding, as reported by OBJECT ENCODING
+    pub fn encoding(&self) -> &'static str {
+        match self.encoding {
+            ZSetEncoding::Listpack(_) => "listpack",
+            ZSetEncoding::Skiplist(..) => "skiplist",
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.encoding {
+            ZSetEncoding::Listpack(members) => members.len(),
+            ZSetEncoding::Skiplist(scores, _) => scores.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn score(&self, member: &[u8]) -> Option<f64> {
+        match &self.encoding {
+            ZSetEncoding::Listpack(members) => members
+                .iter()
+                .find(|(current, _)| current == member)
+                .map(|(_, score)| *score),
+            ZSetEncoding::Skiplist(scores, _) => scores.get(member).copied(),
+        }
+    }
+
+    /// Returns the members with their scores, ordered by score then by member
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (&Bytes, &f64)> + '_> {
+        match &self.encoding {
+            ZSetEncoding::Listpack(members) => {
+                Box::new(members.iter().map(|(member, score)| (member, score)))
+            }
+            ZSetEncoding::Skiplist(_, sorted) => Box::new(
+                sorted
+                    .iter()
+                    .map(|ScoredMember(score, member)| (member, score)),
+            ),
+        }
+    }
+
+    /// Returns the members with their scores, ordered by score then by member
+    pub fn sorted(&self) -> Vec<(Bytes, f64)> {
+        self.iter()
+            .map(|(member, score)| (member.clone(), *score))
+            .collect()
+    }
+
+    /// Adds the member or updates its score, converting the sorted set
+    /// to the skiplist encoding if it grows past the listpack limits
+    fn insert(&mut self, member: Bytes, score: f64, config: &EncodingConfig) {
+        match &mut self.encoding {
+            ZSetEncoding::Listpack(members) => {
+                if let Some(pos) = members.iter().position(|(current, _)| *current == member) {
+                    members.remove(pos);
+                }
+
+                let is_large = member.len() > config.zset_max_listpack_value;
+                let pos = members.partition_point(|(current, current_score)| {
+                    current_score
+                        .total_cmp(&score)
+                        .then_with(|| current.cmp(&member))
+                        .is_lt()
+                });
+                members.insert(pos, (member, score));
+
+                if is_large || members.len() > config.zset_max_listpack_entries {
+                    self.convert_to_skiplist();
+                }
+            }
+            ZSetEncoding::Skiplist(scores, sorted) => {
+                if let Some(old) = scores.insert(member.clone(), score) {
+                    sorted.remove(&ScoredMember(old, member.clone()));
+                }
+                sorted.insert(ScoredMember(score, member));
+            }
+        }
+    }
+
+    fn convert_to_skiplist(&mut self) {
+        if let ZSetEncoding::Listpack(members) = &mut self.encoding {
+            let members = std::mem::take(members);
+
+            let scores = members.iter().cloned().collect();
+            let sorted = members
+                .into_iter()
+                .map(|(member, score)| ScoredMember(score, member))
+                .collect();
+
+            self.encoding = ZSetEncoding::Skiplist(scores, sorted);
+        }
+    }
+}
+
+/// Error returned by XADD when the id is not greater than the top item of the stream
+pub const XADD_ID_TOO_SMALL_ERR: &str =
+    "ERR The ID specified in XADD is equal or smaller than the target stream top item";
+
+/// Error returned by XADD when the top item of the stream has the greatest possible id
+const XADD_ID_EXHAUSTED_ERR: &str =
+    "ERR The stream has exhausted the last possible ID, unable to add more items";
+
+/// Max value of the LRU clock, the clock is stored on 24 bits like in Redis
+pub const LRU_CLOCK_MAX: u32 = (1 << 24) - 1;
+
+/// Returns the current LRU clock: seconds since the epoch wrapped to [`LRU_CLOCK_MAX`]
+pub fn lru_clock() -> u32 {
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    (secs & LRU_CLOCK_MAX as u64) as u32
+}
+
+/// Returns the number of seconds elapsed since the given LRU clock value
+pub fn lru_idle_secs(lru: u32) -> u32 {
+    let now = lru_clock();
+
+    if now >= lru {
+        now - lru
+    } else {
+        // The clock wrapped around since the last access
+        (LRU_CLOCK_MAX - lru) + now
+    }
+}
+
+/// Messages buffered for a slow subscriber before it starts missing them
+const PUBSUB_CHANNEL_CAPACITY: usize = 1024;
+
+/// Free effort above which the removed entries are dropped by the lazy free thread
+const LAZYFREE_THRESHOLD: usize = 64;
+
+/// Max length of a string with the embstr encoding
+const EMBSTR_SIZE_LIMIT: usize = 44;
+
+/// Max number of random items returned with a negative count,
+/// the reply is built in memory, so a larger count could exhaust it
+const RANDOM_COUNT_MAX: u64 = 1 << 24;
+
+/// Error returned when an operation is applied to a key of another type
+pub const WRONGTYPE_ERR: &str = "WRONGTYPE Operation against a key holding the wrong kind of value";
+
+const XGROUP_NO_KEY_ERR: &str = "ERR The XGROUP subcommand requires the key to exist. Note that for CREATE you may want to use the MKSTREAM option to create an empty stream automatically.";
+
+/// Id of a stream entry, ordered by timestamp then sequence
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StreamEntryId(u128, usize);
+
+impl StreamEntryId {
+    pub fn new(timestamp: u128, sequence: usize) -> Self {
+        Self(timestamp, sequence)
+    }
+
+    /// Parses an id formatted as `<timestamp>-<sequence>`,
+    /// the sequence is `missing_sequence` when the id is only a timestamp.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a part is not a number, like with more than one hyphen.
+    pub fn parse(id: &str, missing_sequence: usize) -> crate::Result<Self> {
+        const INVALID_ID_ERR: &str = "ERR Invalid stream ID specified as stream command argument";
+
+        let (timestamp, sequence) = match id.split_once('-') {
+            Some((timestamp, sequence)) => {
+                (timestamp, sequence.parse().map_err(|_| INVALID_ID_ERR)?)
+            }
+            None => (id, missing_sequence),
+        };
+        let timestamp = timestamp.parse().map_err(|_| INVALID_ID_ERR)?;
+
+        Ok(Self(timestamp, sequence))
+    }
+}
+
+impl fmt::Display for StreamEntryId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.0, self.1)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct StreamEntry {
+    /// Unique identifier for the entry
+    id: StreamEntryId,
+    /// Key-value pairs for the entry
+    key_value: Vec<(String, Bytes)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Stream {
+    // Unique identifier for the entry
+    id: u64,
+    update_sender: Option<broadcast::Sender<StreamEntryId>>,
+    entries: Vec<StreamEntry>,
+    /// Consumer groups by name
+    groups: HashMap<String, ConsumerGroup>,
+    expires_at: Option<Instant>,
+    // LRU clock of the last access
+    lru: u32,
+}
+
+/// Consumer group of a stream, the entries are delivered to its consumers
+/// and stay in its pending entries list until they are acknowledged
+#[derive(Debug, Clone)]
+pub struct ConsumerGroup {
+    /// Id of the last entry delivered to the group
+    last_delivered_id: StreamEntryId,
+    consumers: HashMap<String, Consumer>,
+    /// Pending entries list, the delivered entries not acknowledged yet
+    pending: BTreeMap<StreamEntryId, PendingEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Consumer {
+    /// Last time the consumer was seen, created or reading
+    seen_time: SystemTime,
+    /// Ids of the entries of the group pending list delivered to the consumer
+    pending: BTreeSet<StreamEntryId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PendingEntry {
+    consumer: String,
+    delivery_time: SystemTime,
+    delivery_count: u64,
+}
+
+impl ConsumerGroup {
+    fn new(last_delivered_id: StreamEntryId) -> Self {
+        Self {
+            last_delivered_id,
+            consumers: HashMap::new(),
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// Removes the consumer with its pending entries, they are not delivered to anyone else.
+    /// Returns the number of pending entries of the consumer, `None` if it does not exist.
+    fn remove_consumer(&mut self, consumer: &str) -> Option<u64> {
+        let consumer = self.consumers.remove(consumer)?;
+
+        for id in &consumer.pending {
+            self.pending.remove(id);
+        }
+
+        Some(consumer.pending.len() as u64)
+    }
+
+    pub fn last_delivered_id(&self) -> StreamEntryId {
+        self.last_delivered_id
+    }
+
+    /// Returns the consumers sorted by name
+    pub fn consumers(&self) -> Vec<(&String, &Consumer)> {
+        let mut consumers: Vec<_> = self.consumers.iter().collect();
+        consumers.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+        consumers
+    }
+
+    pub fn pending(&self) -> &BTreeMap<StreamEntryId, PendingEntry> {
+        &self.pending
+    }
+}
+
+impl Consumer {
+    pub fn seen_time(&self) -> SystemTime {
+        self.seen_time
+    }
+
+    pub fn pending(&self) -> &BTreeSet<StreamEntryId> {
+        &self.pending
+    }
+}
+
+impl PendingEntry {
+    pub fn consumer(&self) -> &str {
+        &self.consumer
+    }
+
+    pub fn delivery_time(&self) -> SystemTime {
+        self.delivery_time
+    }
+
+    pub fn delivery_count(&self) -> u64 {
+        self.delivery_count
+    }
+}
+
+impl Stream {
+    fn new(id: u64) -> Self {
+        Self {
+            id,
+            update_sender: None,
+            entries: Vec::new(),
+            groups: HashMap::new(),
+            expires_at: None,
+            lru: lru_clock(),
+        }
+    }
+
+    pub fn entries(&self) -> &[StreamEntry] {
+        &self.entries
+    }
+
+    /// Returns the consumer groups sorted by name
+    pub fn groups(&self) -> Vec<(&String, &ConsumerGroup)> {
+        let mut groups: Vec<_> = self.groups.iter().collect();
+        groups.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+        groups
+    }
+
+    fn subscribe(&mut self) -> broadcast::Receiver<StreamEntryId> {
+        match &self.update_sender {
+            Some(sender) => sender.subscribe(),
+            None => {
+                let (sender, receiver) = broadcast::channel(1);
+                self.update_sender = Some(sender);
+                receiver
+            }
+        }
+    }
+
+    fn send_update(&self, id: StreamEntryId) {
+        if let Some(sender) = &self.update_sender {
+            let _ = sender.send(id).unwrap();
+        }
+    }
+
+    /// Returns the consumer group, or the NOGROUP error for the stream stored at key
+    fn group_mut(&mut self, key: &str, group: &str) -> crate::Result<&mut ConsumerGroup> {
+        self.groups.get_mut(group).ok_or_else(|| {
+            format!(
+                "NOGROUP No such consumer group '{}' for key name '{}'",
+                group, key
+            )
+            .into()
+        })
+    }
+
+    pub fn get_last_id(&self) -> StreamEntryId {
+        self.entries
+            .last()
+            .map(|entry| entry.id)
+            .unwrap_or(StreamEntryId(0, 0))
+    }
+}
+
+impl StreamEntry {
+    pub fn new(id: StreamEntryId, key_value: Vec<(String, Bytes)>) -> Self {
+        Self { id, key_value }
+    }
+
+    pub fn id(&self) -> StreamEntryId {
+        self.id
+    }
+
+    pub fn key_value(&self) -> &[(String, Bytes)] {
+        &self.key_value
+    }
+}
+
+impl Db {
+    pub fn new() -> Self {
+        let db = Self {
+            shared: Arc::new(Shared::new()),
+        };
+
+        // Spawn the task that will remove expired entries
+        tokio::spawn(task_expiry(db.shared.clone()));
+
+        db
+    }
+
+    /// Waits for the transaction being executed, if any,
+    /// then holds off the transactions until the guard is dropped
+    pub async fn lock_command(&self) -> RwLockReadGuard<'_, ()> {
+        self.shared.transaction.read().await
+    }
+
+    /// Waits for the commands being executed, then holds off
+    /// the other commands until the guard is dropped
+    pub async fn lock_transaction(&self) -> RwLockWriteGuard<'_, ()> {
+        self.shared.transaction.write().await
+    }
+
+    /// Enables or disables the removal of the expired entries by the expiry task,
+    /// like DEBUG SET-ACTIVE-EXPIRE
+    pub fn set_active_expire(&self, enabled: bool) {
+        self.shared.active_expire.store(enabled, Ordering::Relaxed);
+
+        // Wake up the task, so it removes the entries that expired while it was disabled
+        self.shared.task_expiry_notify.notify_one();
+    }
+
+    /// Holds the store lock for the duration, so every command accessing the keys waits,
+    /// like a single threaded Redis blocked by DEBUG SLEEP. Blocks the calling thread.
+    pub fn stall(&self, duration: Duration) {
+        let _store = self.shared.store.lock().unwrap();
+
+        std::thread::sleep(duration);
+    }
+
+    /// Sets the size limits of the compact encodings, for the values created afterwards
+    pub fn encoding_config(&self) -> EncodingConfig {
+        self.shared.store.lock().unwrap().encoding
+    }
+
+    pub fn set_encoding_config(&self, config: EncodingConfig) {
+        let mut store = self.shared.store.lock().unwrap();
+        store.encoding = config;
+    }
+
+    /// Enables or disables dropping the large deleted or overwritten values
+    /// on a background thread, like lazyfree-lazy-server-del
+    pub fn set_lazyfree_server_del(&self, enabled: bool) {
+        let mut store = self.shared.store.lock().unwrap();
+
+        // Dropping the sender stops the thread, once it has freed the pending entries
+        store.lazyfree = enabled.then(|| {
+            let (sender, receiver) = mpsc::channel();
+            thread::spawn(move || thread_lazyfree(receiver));
+            sender
+        });
+    }
+
+    pub fn from_rdb(rdb: HashMap<String, (RdbValue, Option<SystemTime>)>) -> Self {
+        let db = Self::new();
+        db.load_rdb(rdb);
+        db
+    }
+
+    /// Replaces all the entries of the database with the entries loaded from an RDB file.
+    /// Entries that expired since the file was saved are skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn load_rdb(&self, rdb: HashMap<String, (RdbValue, Option<SystemTime>)>) {
+        let mut store = self.shared.store.lock().unwrap();
+
+        for entry in std::mem::take(&mut store.data).into_values() {
+            store.free(entry);
+        }
+        store.expires.clear();
+
+        let current_time = SystemTime::now();
+        let now = Instant::now();
+
+        // Insert all the entries from the RDB into the database
+        for (key, (value, expiry)) in rdb {
+            let expires_at = match expiry {
+                Some(expiry) => match expiry.duration_since(current_time) {
+                    // If the expiry is in the future, then we set the expiry
+                    Ok(duration) => Some(now + duration),
+                    // If the expiry is in the past, then the key has expired
+                    // so we skip inserting it
+                    Err(_) => continue,
+                },
+                None => None,
+            };
+
+            let id = store.next_id();
+            let mut entry = Entry::from_rdb(id, value, &store.encoding);
+
+            if let Some(when) = expires_at {
+                let (entry_expires_at, id) = entry.expiry_mut();
+                *entry_expires_at = Some(when);
+                store.expires.insert((when, id), key.clone());
+            }
+
+            store.data.insert(key, entry);
+        }
+
+        // Release the lock so the task will be able to acquire it
+        drop(store);
+
+        // Notify the task expiry task to recompute the next expiry
+        self.shared.task_expiry_notify.notify_one();
+    }
+
+    /// Serializes the value of the key with [`RedisDB::dump_value`].
+    /// Returns `None` if the key does not exist.
+    pub fn dump(&self, key: &str) -> Option<Bytes> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let entry = store.data.get_mut(key)?;
+        entry.touch();
+
+        Some(Bytes::from(RedisDB::dump_value(&entry.to_rdb())))
+    }
+
+    /// Creates the key from a value serialized by [`Db::dump`],
+    /// with the given expiry and idle time in seconds.
+    /// The key is not created if the expiry is in the past.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload is invalid,
+    /// or if the key exists and `replace` is false.
+    pub fn restore(
+        &self,
+        key: String,
+        payload: &[u8],
+        expiry: Option<SystemTime>,
+        idle: Option<u32>,
+        replace: bool,
+    ) -> crate::Result<()> {
+        let value = RedisDB::restore_value(payload)?;
+
+        let mut store = self.shared.store.lock().unwrap();
+
+        if !replace && store.data.contains_key(&key) {
+            return Err("BUSYKEY Target key name already exists.".into());
+        }
+
+        let expires_at = match expiry.map(|expiry| expiry.duration_since(SystemTime::now())) {
+            Some(Ok(duration)) => Some(Instant::now() + duration),
+            // Already expired, the key is only deleted
+            Some(Err(_)) => {
+                let prev = store.data.remove(&key);
+                store.discard(prev);
+                return Ok(());
+            }
+            None => None,
+        };
+
+        let id = store.next_id();
+        let mut entry = Entry::from_rdb(id, value, &store.encoding);
+
+        if let Some(idle) = idle {
+            entry.set_lru(lru_clock().wrapping_sub(idle) & LRU_CLOCK_MAX);
+        }
+
+        let mut should_notify = false;
+
+        if let Some(when) = expires_at {
+            let (entry_expires_at, id) = entry.expiry_mut();
+            *entry_expires_at = Some(when);
+            should_notify = store.next_expiry().map(|next| when < next).unwrap_or(true);
+            store.expires.insert((when, id), key.clone());
+        }
+
+        let prev = store.data.insert(key, entry);
+        store.discard(prev);
+
+        drop(store);
+
+        if should_notify {
+            self.shared.task_expiry_notify.notify_one();
+        }
+
+        Ok(())
+    }
+
+    /// Returns all the entries of the database, to be saved in an RDB file
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn to_rdb(&self) -> Vec<RdbEntry> {
+        let store = self.shared.store.lock().unwrap();
+
+        let current_time = SystemTime::now();
+        let now = Instant::now();
+
+        store
+            .data
+            .iter()
+            .map(|(key, entry)| {
+                let expiry = entry
+                    .expiry()
+                    .map(|(when, _)| current_time + when.saturating_duration_since(now));
+
+                (key.clone(), entry.to_rdb(), expiry)
+            })
+            .collect()
+    }
+
+    /// Sets the value of a key in the database.
+    /// If the key already exists, the previous value will be overwritten.
+    /// Optionally, the key can be set to expire after a specified duration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn set(&self, key: String, value: Bytes, expire: Option<Duration>) {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let should_notify = store.insert_string(key, value, expire);
+
+        // Release the lock so the task will be able to acquire it if needed
+        drop(store);
+
+        //  Notify the task expiry task to wake up, so it can recompute the next expiry
+        if should_notify {
+            self.shared.task_expiry_notify.notify_one();
+        }
+    }
+
+    /// Sets the key to the string value like [`Db::set`],
+    /// if the key exists for XX or doesn't exist for NX.
+    /// Returns whether the value was set, and the previous value if `get` is true.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `get` is true and the key holds a non-string value,
+    /// the value is not set then.
+    pub fn set_if(
+        &self,
+        key: String,
+        value: Bytes,
+        expire: Option<Duration>,
+        condition: SetCondition,
+        get: bool,
+    ) -> crate::Result<(bool, Option<Bytes>)> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let prev = match store.data.get(&key) {
+            Some(Entry::String(entry)) if get => Some(entry.value()),
+            Some(_) if get => return Err(WRONGTYPE_ERR.into()),
+            _ => None,
+        };
+
+        let exists = store.data.contains_key(&key);
+        let should_set = match condition {
+            SetCondition::Always => true,
+            SetCondition::IfNotExists => !exists,
+            SetCondition::IfExists => exists,
+        };
+
+        if !should_set {
+            return Ok((false, prev));
+        }
+
+        let should_notify = store.insert_string(key, value, expire);
+
+        drop(store);
+
+        if should_notify {
+            self.shared.task_expiry_notify.notify_one();
+        }
+
+        Ok((true, prev))
+    }
+
+    /// Sets the key to the string value without expiry,
+    /// returning the previous value if it was a string.
+    pub fn getset(&self, key: String, value: Bytes) -> Option<Bytes> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let id = store.next_id();
+        let entry = Entry::String(StringEntry {
+            id,
+            value: value.into(),
+            expires_at: None,
+            lru: lru_clock(),
+        });
+
+        let prev = store.data.insert(key, entry)?;
+        let value = match &prev {
+            Entry::String(prev) => Some(prev.value()),
+            _ => None,
+        };
+
+        store.discard(Some(prev));
+
+        value
+    }
+
+    /// Returns the entry with the specified key from the database.
+    /// Returns `None` if the entry does not exist (possibly due to expiry).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn get(&self, key: &str) -> Option<Entry> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        store.data.get_mut(key).map(|entry| {
+            entry.touch();
+            entry.clone()
+        })
+    }
+
+    /// Returns the values of the strings stored at keys,
+    /// `None` for the missing keys and the keys holding another type.
+    pub fn mget(&self, keys: &[String]) -> Vec<Option<Bytes>> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        keys.iter()
+            .map(|key| {
+                let entry = store.data.get_mut(key)?;
+                entry.touch();
+
+                match entry {
+                    Entry::String(entry) => Some(entry.value()),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the entry with the specified key from the database
+    /// without updating its access time.
+    /// Used by the introspection commands, like DEBUG OBJECT.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn get_no_touch(&self, key: &str) -> Option<Entry> {
+        let store = self.shared.store.lock().unwrap();
+        store.data.get(key).cloned()
+    }
+
+    /// Returns the length of the string stored at key.
+    /// Returns 0 if the key does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-string value.
+    pub fn strlen(&self, key: &str) -> crate::Result<usize> {
+        let store = self.shared.store.lock().unwrap();
+
+        match store.data.get(key) {
+            Some(Entry::String(entry)) => Ok(entry.len()),
+            Some(_) => Err(WRONGTYPE_ERR.into()),
+            None => Ok(0),
+        }
+    }
+
+    /// Returns the longest common subsequence of the strings stored at the keys,
+    /// missing keys being empty strings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a key holds a non-string value,
+    /// or if the strings are too long for the table of the subsequence lengths.
+    pub fn lcs(&self, key1: &str, key2: &str) -> crate::Result<CommonSubsequence> {
+        let store = self.shared.store.lock().unwrap();
+
+        let value = |key| match store.data.get(key) {
+            Some(Entry::String(entry)) => Ok(entry.value()),
+            Some(_) => Err("ERR The specified keys must contain string values"),
+            None => Ok(Bytes::new()),
+        };
+        let (a, b) = (value(key1)?, value(key2)?);
+
+        drop(store);
+
+        common_subsequence(&a, &b)
+    }
+
+    /// Appends the value at the end of the string stored at key.
+    /// If the key does not exist, it is created holding the value (without expiry).
+    /// Returns the length of the string after the append.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-string value.
+    pub fn append(&self, key: String, value: Bytes) -> crate::Result<usize> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        match store.data.get_mut(&key) {
+            Some(Entry::String(entry)) => {
+                let mut appended = BytesMut::with_capacity(entry.len() + value.len());
+                appended.extend_from_slice(&entry.value());
+                appended.extend_from_slice(&value);
+
+                entry.set_value(appended.freeze());
+                entry.lru = lru_clock();
+
+                Ok(entry.len())
+            }
+            Some(_) => Err(WRONGTYPE_ERR.into()),
+            None => {
+                let len = value.len();
+                let id = store.next_id();
+
+                store.data.insert(
+                    key,
+                    Entry::String(StringEntry {
+                        id,
+                        value: value.into(),
+                        expires_at: None,
+                        lru: lru_clock(),
+                    }),
+                );
+
+                Ok(len)
+            }
+        }
+    }
+
+    /// Overwrites the string stored at key starting at the byte offset,
+    /// the string is zero-padded up to the offset if it is shorter.
+    /// A missing key is created, unless the value is empty.
+    /// Returns the length of the string after the change.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-string value.
+    pub fn setrange(&self, key: String, offset: usize, value: Bytes) -> crate::Result<usize> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        if !store.data.contains_key(&key) {
+            // Nothing to write, the key is not created
+            if value.is_empty() {
+                return Ok(0);
+            }
+
+            let id = store.next_id();
+            store.data.insert(
+                key.clone(),
+                Entry::String(StringEntry {
+                    id,
+                    value: Bytes::new().into(),
+                    expires_at: None,
+                    lru: lru_clock(),
+                }),
+            );
+        }
+
+        let entry = match store.data.get_mut(&key) {
+            Some(Entry::String(entry)) => entry,
+            _ => return Err(WRONGTYPE_ERR.into()),
+        };
+
+        entry.lru = lru_clock();
+
+        if value.is_empty() {
+            return Ok(entry.len());
+        }
+
+        let len = entry.len().max(offset + value.len());
+        let mut updated = BytesMut::with_capacity(len);
+        updated.extend_from_slice(&entry.value());
+        // Zero padding between the end of the string and the offset
+        updated.resize(len, 0);
+        updated[offset..offset + value.len()].copy_from_slice(&value);
+
+        entry.set_value(updated.freeze());
+
+        Ok(len)
+    }
+
+    /// Returns the substring of the string stored at key between the start and end offsets,
+    /// both inclusive, negative offsets counting from the end.
+    /// The range is clamped to the string, empty if it is out of the string or the key is missing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-string value.
+    pub fn getrange(&self, key: &str, start: i64, end: i64) -> crate::Result<Bytes> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let value = match store.data.get_mut(key) {
+            Some(Entry::String(entry)) => {
+                entry.lru = lru_clock();
+                entry.value()
+            }
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+            None => return Ok(Bytes::new()),
+        };
+        drop(store);
+
+        let len = value.len() as i64;
+
+        // Both offsets past the start, from the end, in reverse order
+        if start < 0 && end < 0 && start > end {
+            return Ok(Bytes::new());
+        }
+
+        let start = if start < 0 {
+            (len + start).max(0)
+        } else {
+            start
+        };
+        let end = if end < 0 {
+            (len + end).max(0)
+        } else {
+            end.min(len - 1)
+        };
+
+        if len == 0 || start > end {
+            return Ok(Bytes::new());
+        }
+
+        Ok(value.slice(start as usize..=end as usize))
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        let store = self.shared.store.lock().unwrap();
+        store.data.keys().cloned().collect()
+    }
+
+    /// Returns every key with its type name and remaining time to live, sorted by key
+    pub fn keyspace(&self) -> Vec<(String, &'static str, Option<Duration>)> {
+        let store = self.shared.store.lock().unwrap();
+        let now = Instant::now();
+
+        let mut keyspace: Vec<_> = store
+            .data
+            .iter()
+            .map(|(key, entry)| {
+                let ttl = entry
+                    .expiry()
+                    .map(|(expiry, _)| expiry.saturating_duration_since(now));
+
+                (key.clone(), entry.type_name(), ttl)
+            })
+            .collect();
+        keyspace.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+        keyspace
+    }
+
+    /// Incrementally iterates over the keys, returning up to `count` keys
+    /// starting at the cursor and the cursor to continue from, 0 when the iteration is complete.
+    ///
+    /// The keys are visited in the order of their hash and the cursor is the hash
+    /// of the next key, so a key present during the whole iteration is always returned,
+    /// even if other keys are added or removed in between.
+    pub fn scan(&self, cursor: u64, count: usize) -> (u64, Vec<String>) {
+        let store = self.shared.store.lock().unwrap();
+
+        let (cursor, keys) = scan_page(store.data.keys().map(|key| (key, ())), cursor, count);
+
+        (
+            cursor,
+            keys.into_iter().map(|(key, _)| key.clone()).collect(),
+        )
+    }
+
+    /// Incrementally iterates over the fields of the hash stored at key, like [`Db::scan`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-hash value.
+    pub fn hscan(
+        &self,
+        key: &str,
+        cursor: u64,
+        count: usize,
+    ) -> crate::Result<(u64, Vec<(String, Bytes)>)> {
+        let store = self.shared.store.lock().unwrap();
+
+        match store.data.get(key) {
+            Some(Entry::Hash(hash)) => {
+                let (cursor, fields) = scan_page(hash.iter(), cursor, count);
+
+                Ok((
+                    cursor,
+                    fields
+                        .into_iter()
+                        .map(|(field, value)| (field, value.clone()))
+                        .collect(),
+                ))
+            }
+            Some(_) => Err(WRONGTYPE_ERR.into()),
+            None => Ok((0, Vec::new())),
+        }
+    }
+
+    /// Incrementally iterates over the members of the set stored at key, like [`Db::scan`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-set value.
+    pub fn sscan(&self, key: &str, cursor: u64, count: usize) -> crate::Result<(u64, Vec<Bytes>)> {
+        let store = self.shared.store.lock().unwrap();
+
+        match store.data.get(key) {
+            Some(Entry::Set(set)) => {
+                let (cursor, members) =
+                    scan_page(set.iter().map(|member| (member, ())), cursor, count);
+
+                Ok((
+                    cursor,
+                    members.into_iter().map(|(member, _)| member).collect(),
+                ))
+            }
+            Some(_) => Err(WRONGTYPE_ERR.into()),
+            None => Ok((0, Vec::new())),
+        }
+    }
+
+    /// Incrementally iterates over the members and scores of the sorted set stored at key,
+    /// like [`Db::scan`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-zset value.
+    pub fn zscan(
+        &self,
+        key: &str,
+        cursor: u64,
+        count: usize,
+    ) -> crate::Result<(u64, Vec<(Bytes, f64)>)> {
+        let store = self.shared.store.lock().unwrap();
+
+        match store.data.get(key) {
+            Some(Entry::ZSet(zset)) => {
+                let (cursor, members) = scan_page(zset.iter(), cursor, count);
+
+                Ok((
+                    cursor,
+                    members
+                        .into_iter()
+                        .map(|(member, score)| (member.clone(), *score))
+                        .collect(),
+                ))
+            }
+            Some(_) => Err(WRONGTYPE_ERR.into()),
+            None => Ok((0, Vec::new())),
+        }
+    }
+
+    /// Removes the entry with the specified key from the database.
+    /// Returns true if the entry existed.
+    /// Sometimes due to the entry being expired, it may not be present in the database.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn remove(&self, key: &str) -> bool {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let prev = store.data.remove(key);
+        let removed = prev.is_some();
+        store.discard(prev);
+
+        removed
+    }
+
+    /// Removes the entry with the specified key if its expiry is in the past.
+    /// Returns true if the entry was removed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn expire_if_needed(&self, key: &str) -> bool {
+        let mut store = self.shared.store.lock().unwrap();
+
+        match store.data.get(key).and_then(|entry| entry.expiry()) {
+            Some((when, _)) if when <= Instant::now() => {}
+            _ => return false,
+        }
+
+        let prev = store.data.remove(key);
+        store.discard(prev);
+
+        true
+    }
+
+    /// Returns the number of keys in the database
+    pub fn dbsize(&self) -> usize {
+        let store = self.shared.store.lock().unwrap();
+        store.data.len()
+    }
+
+    /// Returns the remaining time to live of the key, `None` if the key does not exist
+    /// and `Some(None)` if it has no expiry
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn ttl(&self, key: &str) -> Option<Option<Duration>> {
+        let store = self.shared.store.lock().unwrap();
+        let entry = store.data.get(key)?;
+
+        Some(
+            entry
+                .expiry()
+                .map(|(expiry, _)| expiry.saturating_duration_since(Instant::now())),
+        )
+    }
+
+    /// Sets the time at which the entry with the specified key expires,
+    /// if its current expiry meets the condition.
+    /// The entry is removed right away if the time is in the past.
+    /// Returns false if the key does not exist or the condition is not met.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock is poisoned.
+    pub fn expire_at(&self, key: &str, when: SystemTime, condition: ExpireCondition) -> bool {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let entry = match store.data.get_mut(key) {
+            Some(entry) => entry,
+            None => return false,
+        };
+
+        let (expires_at, id) = entry.expiry_mut();
+
+        let now = Instant::now();
+        let (when, expired) = match when.duration_since(SystemTime::now()) {
+            Ok(duration) => (now + duration, false),
+            Err(err) => (now.checked_sub(err.duration()).unwrap_or(now), true),
+        };
+
+        let applies = match (condition, *expires_at) {
+            (ExpireCondition::Always, _) => true,
+            (ExpireCondition::IfNoExpiry, current) => current.is_none(),
+            (ExpireCondition::IfExpiry, current) => current.is_some(),
+            // Without expiry the TTL is infinite, no expiry is greater
+            (ExpireCondition::IfGreater, current) => current.is_some_and(|current| when > current),
+            (ExpireCondition::IfLess, current) => current.is_none_or(|current| when < current),
+        };
+        if !applies {
+            return false;
+        }
+
+        if expired {
+            let prev = store.data.remove(key);
+            store.discard(prev);
+            return true;
+        }
+
+        let prev = expires_at.replace(when);
+
+        if let Some(prev) = prev {
+            store.expires.remove(&(prev, id));
+        }
+
+        // Worker needs to be notified if the new expiry is the earliest one
+        let should_notify = store.next_expiry().map(|next| when < next).unwrap_or(true);
+        store.expires.insert((when, id), key.to_string());
+
+        drop(store);
+
+        if should_notify {
+            self.shared.task_expiry_notify.notify_one();
+        }
+
+        true
+    }
+
+    /// Removes the expiry of the entry with the specified key.
+    /// Returns false if the key does not exist or has no expiry.
+    pub fn persist(&self, key: &str) -> bool {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let expiry = store.data.get_mut(key).and_then(|entry| {
+            let (expires_at, id) = entry.expiry_mut();
+            expires_at.take().map(|expiry| (expiry, id))
+        });
+
+        match expiry {
+            Some(expiry) => {
+                store.expires.remove(&expiry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Adds an entry to the stream stored at key, creating the stream unless `nomkstream` is set.
+    /// Returns the id of the entry, `None` if the stream does not exist with `nomkstream`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-stream value
+    /// or if the id is not greater than the last id of the stream.
+    pub fn xadd(
+        &self,
+        stream_key: String,
+        id: XAddId,
         key_value: Vec<(String, Bytes)>,
-    ) -> crate::Result<String> {
+        nomkstream: bool,
+    ) -> crate::Result<Option<String>> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        if nomkstream && !store.data.contains_key(&stream_key) {
+            return Ok(None);
+        }
+
+        let entry_id = store.next_id();
+        let stream = store
+            .data
+            .entry(stream_key)
+            .or_insert_with(|| Entry::Stream(Stream::new(entry_id)));
+
+        let stream = match stream {
+            Entry::Stream(stream) => stream,
+            _ => return Err(WRONGTYPE_ERR.into()),
+        };
+
+        let last_id = stream.get_last_id();
+        let StreamEntryId(last_timestamp, last_seq) = last_id;
+
+        let id = match id {
+            XAddId::Auto => {
+                let timestamp = SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)?
+                    .as_millis();
+
+                // If the clock went backwards, keep generating ids after the top item
+                if timestamp <= last_timestamp && !stream.entries.is_empty() {
+                    match (last_seq.checked_add(1), last_timestamp.checked_add(1)) {
+                        (Some(seq), _) => StreamEntryId(last_timestamp, seq),
+                        // The sequence is exhausted, the id moves to the next millisecond
+                        (None, Some(timestamp)) => StreamEntryId(timestamp, 0),
+                        (None, None) => return Err(XADD_ID_EXHAUSTED_ERR.into()),
+                    }
+                } else {
+                    StreamEntryId(timestamp, 0)
+                }
+            }
+            XAddId::AutoSeq(timestamp) => {
+                if timestamp < last_timestamp {
+                    return Err(XADD_ID_TOO_SMALL_ERR.into());
+                }
+
+                let seq = if timestamp == last_timestamp && !stream.entries.is_empty() {
+                    // No sequence is left for the timestamp
+                    last_seq.checked_add(1).ok_or(XADD_ID_TOO_SMALL_ERR)?
+                } else if timestamp == 0 {
+                    // 0-0 is not a valid id, so the sequence starts at 1
+                    1
+                } else {
+                    0
+                };
+
+                StreamEntryId(timestamp, seq)
+            }
+            XAddId::Explicit(id) => {
+                if id <= last_id {
+                    return Err(XADD_ID_TOO_SMALL_ERR.into());
+                }
+
+                id
+            }
+        };
+
+        let entry = StreamEntry::new(id, key_value);
+
+        stream.entries.push(entry);
+        stream.lru = lru_clock();
+        stream.send_update(id);
+
+        Ok(Some(format!("{}-{}", id.0, id.1)))
+    }
+
+    pub fn xrange(
+        &self,
+        stream_key: &str,
+        start: Option<StreamEntryId>,
+        end: Option<StreamEntryId>,
+    ) -> Vec<StreamEntry> {
+        let store = self.shared.store.lock().unwrap();
+        let stream = store.data.get(stream_key);
+
+        let stream = match stream {
+            Some(Entry::Stream(stream)) => stream,
+            _ => return Vec::new(),
+        };
+
+        let start = start.unwrap_or(StreamEntryId(0, 0));
+        let end = end.unwrap_or(StreamEntryId(u128::MAX, usize::MAX));
+
+        stream
+            .entries
+            .iter()
+            .filter(|entry| entry.id >= start && entry.id <= end)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns a copy of the stream stored at key, with its consumer groups
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-stream value or does not exist.
+    pub fn xinfo_stream(&self, key: &str) -> crate::Result<Stream> {
+        let store = self.shared.store.lock().unwrap();
+
+        match store.data.get(key) {
+            Some(Entry::Stream(stream)) => Ok(stream.clone()),
+            Some(_) => Err(WRONGTYPE_ERR.into()),
+            None => Err("ERR no such key".into()),
+        }
+    }
+
+    /// Creates the consumer group of the stream stored at key,
+    /// starting after the given id or after the last entry if `None`.
+    /// The stream is created when missing if `mkstream` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-stream value, if the stream does not exist
+    /// and `mkstream` is not set, or if the group already exists.
+    pub fn xgroup_create(
+        &self,
+        key: &str,
+        group: &str,
+        id: Option<StreamEntryId>,
+        mkstream: bool,
+    ) -> crate::Result<()> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        if mkstream && !store.data.contains_key(key) {
+            let id = store.next_id();
+            store
+                .data
+                .insert(key.to_string(), Entry::Stream(Stream::new(id)));
+        }
+
+        let stream = match store.data.get_mut(key) {
+            Some(Entry::Stream(stream)) => stream,
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+            None => return Err(XGROUP_NO_KEY_ERR.into()),
+        };
+
+        if stream.groups.contains_key(group) {
+            return Err("BUSYGROUP Consumer Group name already exists".into());
+        }
+
+        let last_delivered_id = id.unwrap_or_else(|| stream.get_last_id());
+        stream
+            .groups
+            .insert(group.to_string(), ConsumerGroup::new(last_delivered_id));
+
+        Ok(())
+    }
+
+    /// Removes the consumer group of the stream stored at key, with its pending entries.
+    /// Returns false if the group does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-stream value or does not exist.
+    pub fn xgroup_destroy(&self, key: &str, group: &str) -> crate::Result<bool> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        match store.data.get_mut(key) {
+            Some(Entry::Stream(stream)) => Ok(stream.groups.remove(group).is_some()),
+            Some(_) => Err(WRONGTYPE_ERR.into()),
+            None => Err(XGROUP_NO_KEY_ERR.into()),
+        }
+    }
+
+    /// Creates the consumer in the consumer group of the stream stored at key.
+    /// Returns false if the consumer already exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-stream value or does not exist,
+    /// or if the group does not exist.
+    pub fn xgroup_createconsumer(
+        &self,
+        key: &str,
+        group: &str,
+        consumer: &str,
+    ) -> crate::Result<bool> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let stream = match store.data.get_mut(key) {
+            Some(Entry::Stream(stream)) => stream,
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+            None => return Err(XGROUP_NO_KEY_ERR.into()),
+        };
+
+        let group = stream.group_mut(key, group)?;
+
+        if group.consumers.contains_key(consumer) {
+            return Ok(false);
+        }
+
+        group.consumers.insert(
+            consumer.to_string(),
+            Consumer {
+                seen_time: SystemTime::now(),
+                pending: BTreeSet::new(),
+            },
+        );
+
+        Ok(true)
+    }
+
+    /// Removes the consumer from the consumer group of the stream stored at key,
+    /// its pending entries are dropped from the group pending list.
+    /// Returns the number of pending entries the consumer had, 0 if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-stream value or does not exist,
+    /// or if the group does not exist.
+    pub fn xgroup_delconsumer(&self, key: &str, group: &str, consumer: &str) -> crate::Result<u64> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let stream = match store.data.get_mut(key) {
+            Some(Entry::Stream(stream)) => stream,
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+            None => return Err(XGROUP_NO_KEY_ERR.into()),
+        };
+
+        let group = stream.group_mut(key, group)?;
+
+        Ok(group.remove_consumer(consumer).unwrap_or(0))
+    }
+
+    pub async fn xread(
+        &self,
+        stream_keys: &[String],
+        stream_ids: &[StreamEntryId],
+        block: Option<u64>,
+    ) -> Vec<(String, Vec<StreamEntry>)> {
+        if let Some(block_timeout) = block {
+            let mut join_set = JoinSet::new();
+
+            // For each stream key, spawn a task that will wait until the stream updates
+            for (idx, stream_key) in stream_keys.iter().enumerate() {
+                let mut store = self.shared.store.lock().unwrap();
+
+                let entry_id = store.next_id();
+                let stream = store
+                    .data
+                    .entry(stream_key.to_string())
+                    .or_insert_with(|| Entry::Stream(Stream::new(entry_id)));
+
+                let stream = match stream {
+                    Entry::Stream(stream) => stream,
+                    _ => continue,
+                };
+
+                let mut receiver = stream.subscribe();
+                let stream_target_id = stream_ids.get(idx).cloned().unwrap_or(StreamEntryId(0, 0));
+                join_set.spawn(async move {
+                    // Wait until the stream updates with an entry
+                    // with id greater than the target stream id
+                    while receiver.recv().await.unwrap() <= stream_target_id {}
+                });
+            }
+
+            // If a block timeout is set, spawn a task that will sleep for the duration
+            if block_timeout > 0 {
+                let sleep = tokio::time::sleep(Duration::from_millis(block_timeout));
+                join_set.spawn(sleep);
+            }
+
+            // Wait until any task completes
+            let _ = join_set.join_next().await.expect("JoinSet is empty");
+        }
+
+        let store = self.shared.store.lock().unwrap();
+
+        // Collect all the entries for each stream key
+        // that have an id greater than the target stream id
+        stream_keys
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, key)| {
+                store.data.get(key).and_then(|entry| match entry {
+                    Entry::Stream(stream) => {
+                        let entries = stream
+                            .entries
+                            .iter()
+                            .filter(|entry| {
+                                let id = stream_ids.get(idx).unwrap_or(&StreamEntryId(0, 0));
+                                entry.id > *id
+                            })
+                            .cloned()
+                            .collect();
+                        Some((key.clone(), entries))
+                    }
+                    _ => None,
+                })
+            })
+            .collect()
+    }
+
+    pub fn get_stream_last_id(&self, key: &str) -> StreamEntryId {
+        let store = self.shared.store.lock().unwrap();
+        let stream = store.data.get(key);
+
+        match stream {
+            Some(Entry::Stream(stream)) => stream.get_last_id(),
+            _ => StreamEntryId(0, 0),
+        }
+    }
+
+    pub fn get_streams_last_ids(&self, keys: &[String]) -> Vec<StreamEntryId> {
+        let store = self.shared.store.lock().unwrap();
+
+        keys.iter()
+            .filter_map(|key| {
+                store.data.get(key).map(|entry| match entry {
+                    Entry::Stream(stream) => stream.get_last_id(),
+                    _ => StreamEntryId(0, 0),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the value of the bit field at the bit offset of the string stored at key.
+    /// Bits past the end of the string (or of a missing key) are read as zeros.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-string value.
+    pub fn bitfield_get(&self, key: &str, ty: BitFieldType, offset: u64) -> crate::Result<i64> {
+        let store = self.shared.store.lock().unwrap();
+
+        match store.data.get(key) {
+            Some(Entry::String(entry)) => Ok(read_bit_field(&entry.value(), ty, offset)),
+            Some(_) => Err(WRONGTYPE_ERR.into()),
+            None => Ok(0),
+        }
+    }
+
+    /// Sets the bit field at the bit offset of the string stored at key,
+    /// the string is created or zero-padded as needed.
+    /// Returns the previous value of the field,
+    /// or `None` if the value does not fit and the overflow behavior is FAIL.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-string value.
+    pub fn bitfield_set(
+        &self,
+        key: &str,
+        ty: BitFieldType,
+        offset: u64,
+        value: i64,
+        overflow: BitFieldOverflow,
+    ) -> crate::Result<Option<i64>> {
+        self.update_bit_field(key, ty, offset, |_| overflow.apply(ty, value as i128))
+    }
+
+    /// Increments the bit field at the bit offset of the string stored at key,
+    /// the string is created or zero-padded as needed.
+    /// Returns the new value of the field,
+    /// or `None` if the value overflows and the overflow behavior is FAIL.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-string value.
+    pub fn bitfield_incrby(
+        &self,
+        key: &str,
+        ty: BitFieldType,
+        offset: u64,
+        increment: i64,
+        overflow: BitFieldOverflow,
+    ) -> crate::Result<Option<i64>> {
+        let mut new_value = None;
+
+        self.update_bit_field(key, ty, offset, |old| {
+            new_value = overflow.apply(ty, old as i128 + increment as i128);
+            new_value
+        })?;
+
+        Ok(new_value)
+    }
+
+    /// Returns the position of the first bit set to `bit` in the string stored at key,
+    /// within the optional range, negative indexes counting from the end.
+    /// Returns -1 if there is no such bit, except when looking for a clear bit
+    /// without an explicit end: the string is then considered zero padded on the right.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-string value.
+    pub fn bitpos(
+        &self,
+        key: &str,
+        bit: bool,
+        start: Option<i64>,
+        end: Option<i64>,
+        unit: BitUnit,
+    ) -> crate::Result<i64> {
+        let store = self.shared.store.lock().unwrap();
+
+        let value = match store.data.get(key) {
+            Some(Entry::String(entry)) => entry.value(),
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+            None => return Ok(if bit { -1 } else { 0 }),
+        };
+
+        let (first, last) = match bit_range(value.len(), start, end, unit) {
+            Some(range) => range,
+            None => return Ok(-1),
+        };
+
+        let found = (first..=last).find(|&pos| (value[pos / 8] >> (7 - pos % 8)) & 1 == bit as u8);
+
+        match found {
+            Some(pos) => Ok(pos as i64),
+            None if !bit && end.is_none() => Ok(last as i64 + 1),
+            None => Ok(-1),
+        }
+    }
+
+    /// Stores the result of the bitwise operation between the strings stored at keys
+    /// in the destination key, missing keys are read as empty strings.
+    /// The destination is removed if the result is empty.
+    /// Returns the length of the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a source key holds a non-string value.
+    pub fn bitop(&self, op: BitOperation, dest: String, keys: &[String]) -> crate::Result<usize> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let mut values = Vec::with_capacity(keys.len());
+        for key in keys {
+            match store.data.get(key) {
+                Some(Entry::String(entry)) => values.push(entry.value()),
+                Some(_) => return Err(WRONGTYPE_ERR.into()),
+                None => values.push(Bytes::new()),
+            }
+        }
+
+        let result = op.apply(&values);
+        let len = result.len();
+
+        let prev = if result.is_empty() {
+            store.data.remove(&dest)
+        } else {
+            let id = store.next_id();
+
+            store.data.insert(
+                dest,
+                Entry::String(StringEntry {
+                    id,
+                    value: Bytes::from(result).into(),
+                    expires_at: None,
+                    lru: lru_clock(),
+                }),
+            )
+        };
+
+        // The destination is overwritten without expiry
+        store.discard(prev);
+
+        Ok(len)
+    }
+
+    /// Replaces the bit field with the value computed from the previous one.
+    /// Returns the previous value, or `None` if no value was computed.
+    fn update_bit_field(
+        &self,
+        key: &str,
+        ty: BitFieldType,
+        offset: u64,
+        update: impl FnOnce(i64) -> Option<i64>,
+    ) -> crate::Result<Option<i64>> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let value = match store.data.get(key) {
+            Some(Entry::String(entry)) => entry.value(),
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+            None => Bytes::new(),
+        };
+
+        let old = read_bit_field(&value, ty, offset);
+        let new = match update(old) {
+            Some(new) => new,
+            None => return Ok(None),
+        };
+
+        let mut bytes = value.to_vec();
+        write_bit_field(&mut bytes, ty, offset, new);
+
+        match store.data.get_mut(key) {
+            Some(Entry::String(entry)) => {
+                entry.set_value(Bytes::from(bytes));
+                entry.lru = lru_clock();
+            }
+            _ => {
+                let id = store.next_id();
+
+                store.data.insert(
+                    key.to_string(),
+                    Entry::String(StringEntry {
+                        id,
+                        value: Bytes::from(bytes).into(),
+                        expires_at: None,
+                        lru: lru_clock(),
+                    }),
+                );
+            }
+        }
+
+        Ok(Some(old))
+    }
+
+    /// Adds the members to the set stored at key, creating the set if needed.
+    /// Returns the number of members that were added (not already present).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-set value.
+    pub fn sadd(&self, key: String, members: Vec<Bytes>) -> crate::Result<usize> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let config = store.encoding;
+        let id = store.next_id();
+        let entry = store
+            .data
+            .entry(key)
+            .or_insert_with(|| Entry::Set(SetEntry::new(id)));
+
+        let set = match entry {
+            Entry::Set(set) => set,
+            _ => return Err(WRONGTYPE_ERR.into()),
+        };
+
+        set.lru = lru_clock();
+
+        Ok(members
+            .into_iter()
+            .filter(|member| set.insert(member.clone(), &config))
+            .count())
+    }
+
+    /// Removes the members from the set stored at key,
+    /// the key is deleted with the last member.
+    /// Returns the number of removed members.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-set value.
+    pub fn srem(&self, key: &str, members: Vec<Bytes>) -> crate::Result<usize> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let set = match store.data.get_mut(key) {
+            Some(Entry::Set(set)) => set,
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+            None => return Ok(0),
+        };
+
+        let removed = members
+            .into_iter()
+            .filter(|member| set.remove(member))
+            .count();
+
+        if set.is_empty() {
+            let prev = store.data.remove(key);
+            store.discard(prev);
+        }
+
+        Ok(removed)
+    }
+
+    /// Pushes the values one after the other to the head or the tail of the list stored at key,
+    /// creating the list if needed, so LPUSH inserts them in reverse order.
+    /// Returns the length of the list.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-list value.
+    pub fn push(
+        &self,
+        key: String,
+        direction: ListDirection,
+        values: Vec<Bytes>,
+    ) -> crate::Result<usize> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        // Make borrow checker happy
+        let store = &mut *store;
+
+        let id = store.next_id();
+
+        let entry = store
+            .data
+            .entry(key)
+            .or_insert_with(|| Entry::List(ListEntry::new(id)));
+
+        let list = match entry {
+            Entry::List(list) => list,
+            _ => return Err(WRONGTYPE_ERR.into()),
+        };
+
+        list.lru = lru_clock();
+        for value in values {
+            list.push(direction, value);
+        }
+        list.update_encoding(&store.encoding);
+
+        Ok(list.values.len())
+    }
+
+    /// Returns the elements of the list stored at key in the inclusive range,
+    /// negative indexes counting from the tail.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-list value.
+    pub fn lrange(&self, key: &str, start: i64, stop: i64) -> crate::Result<Vec<Bytes>> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let list = match store.data.get_mut(key) {
+            Some(Entry::List(list)) => list,
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+            None => return Ok(Vec::new()),
+        };
+
+        list.lru = lru_clock();
+
+        match list_range(list.values.len(), start, stop) {
+            Some((start, stop)) => Ok(list.values.range(start..=stop).map(Bytes::from).collect()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Trims the list stored at key to the inclusive range,
+    /// negative indexes counting from the tail.
+    /// The key is removed when the range is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-list value.
+    pub fn ltrim(&self, key: &str, start: i64, stop: i64) -> crate::Result<()> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        // Make borrow checker happy
+        let store = &mut *store;
+
+        let list = match store.data.get_mut(key) {
+            Some(Entry::List(list)) => list,
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+            None => return Ok(()),
+        };
+
+        list.lru = lru_clock();
+
+        match list_range(list.values.len(), start, stop) {
+            Some((start, stop)) => {
+                list.values.truncate(stop + 1);
+                list.values.drain(..start);
+                list.update_encoding(&store.encoding);
+            }
+            None => {
+                let prev = store.data.remove(key);
+                store.discard(prev);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Pops an element from one end of the source list and pushes it to one end of the destination,
+    /// creating the destination if needed. The source is rotated when it is also the destination.
+    /// Returns the moved element, or `None` if the source does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source or the destination holds a non-list value.
+    pub fn lmove(
+        &self,
+        source: &str,
+        destination: String,
+        from: ListDirection,
+        to: ListDirection,
+    ) -> crate::Result<Option<Bytes>> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        match store.data.get(source) {
+            Some(Entry::List(_)) => {}
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+            None => return Ok(None),
+        }
+        match store.data.get(&destination) {
+            Some(Entry::List(_)) | None => {}
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+        }
+
+        // Make borrow checker happy
+        let store = &mut *store;
+
+        let value = match store.data.get_mut(source) {
+            Some(Entry::List(list)) => {
+                let value = list.pop(from);
+                list.update_encoding(&store.encoding);
+                value
+            }
+            _ => None,
+        };
+        let value = match value {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        // Lists are never empty, the key is removed with its last element
+        if let Some(Entry::List(list)) = store.data.get(source) {
+            if list.values.is_empty() {
+                let prev = store.data.remove(source);
+                store.discard(prev);
+            }
+        }
+
+        let id = store.next_id();
+
+        let entry = store
+            .data
+            .entry(destination)
+            .or_insert_with(|| Entry::List(ListEntry::new(id)));
+
+        if let Entry::List(list) = entry {
+            list.lru = lru_clock();
+            list.push(to, value.clone());
+            list.update_encoding(&store.encoding);
+        }
+
+        Ok(Some(value))
+    }
+
+    /// Pops up to `count` elements from one end of the first non-empty list of keys,
+    /// missing keys being skipped. The key is removed with its last element.
+    /// Returns the key popped from with its elements, or `None` if all the lists are empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a key before the first non-empty list holds a non-list value.
+    pub fn lmpop(
+        &self,
+        keys: &[String],
+        direction: ListDirection,
+        count: usize,
+    ) -> crate::Result<Option<(String, Vec<Bytes>)>> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        // Make borrow checker happy
+        let store = &mut *store;
+
+        for key in keys {
+            let list = match store.data.get_mut(key) {
+                Some(Entry::List(list)) if !list.values.is_empty() => list,
+                Some(Entry::List(_)) | None => continue,
+                Some(_) => return Err(WRONGTYPE_ERR.into()),
+            };
+
+            list.lru = lru_clock();
+            let values = (0..count)
+                .map_while(|_| list.pop(direction))
+                .collect::<Vec<_>>();
+            list.update_encoding(&store.encoding);
+
+            if list.values.is_empty() {
+                let prev = store.data.remove(key);
+                store.discard(prev);
+            }
+
+            return Ok(Some((key.clone(), values)));
+        }
+
+        Ok(None)
+    }
+
+    /// Sorts the elements of the list, set or sorted set stored at key, see [`Store::sort`].
+    /// Returns the sorted elements, or the values of the GET patterns for each of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a value of another type,
+    /// or if an element can't be sorted numerically.
+    pub fn sort(&self, key: &str, opts: &SortOpts) -> crate::Result<Vec<Option<Bytes>>> {
+        let store = self.shared.store.lock().unwrap();
+
+        store.sort(key, opts)
+    }
+
+    /// Sorts like [`Db::sort`] and stores the result in a list at destination,
+    /// missing values being stored as empty strings.
+    /// The destination is removed when the result is empty.
+    /// Returns the length of the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sort fails, the destination is then left untouched.
+    pub fn sort_store(
+        &self,
+        key: &str,
+        opts: &SortOpts,
+        destination: String,
+    ) -> crate::Result<usize> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let values = store.sort(key, opts)?;
+        let len = values.len();
+
+        let prev = match values.is_empty() {
+            true => store.data.remove(&destination),
+            false => {
+                let id = store.next_id();
+                let mut list = ListEntry::new(id);
+                list.values.extend(
+                    values
+                        .into_iter()
+                        .map(|value| ListpackElement::from(value.unwrap_or_default())),
+                );
+                list.update_encoding(&store.encoding);
+
+                store.data.insert(destination, Entry::List(list))
+            }
+        };
+
+        store.discard(prev);
+
+        Ok(len)
+    }
+
+    /// Returns all the members of the set stored at key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-set value.
+    pub fn smembers(&self, key: &str) -> crate::Result<Vec<Bytes>> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        match store.data.get_mut(key) {
+            Some(Entry::Set(set)) => {
+                set.lru = lru_clock();
+                Ok(set.iter().collect())
+            }
+            Some(_) => Err(WRONGTYPE_ERR.into()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Stores the result of the set operation on the sets stored at keys in destination,
+    /// replacing any value. The destination is deleted if the result is empty.
+    /// Returns the number of members of the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if one of the keys holds a non-set value.
+    pub fn set_store(
+        &self,
+        op: SetOperation,
+        destination: String,
+        keys: &[String],
+    ) -> crate::Result<usize> {
         let mut store = self.shared.store.lock().unwrap();
-        let stream = store.data.entry(stream_key).or_insert_with(|| {
-            Entry::Stream({
-                Stream {
-                    update_sender: None,
-                    entries: Vec::new(),
+
+        let mut sets = Vec::with_capacity(keys.len());
+        for key in keys {
+            match store.data.get(key) {
+                Some(Entry::Set(set)) => sets.push(set.iter().collect()),
+                Some(_) => return Err(WRONGTYPE_ERR.into()),
+                None => sets.push(Vec::new()),
+            }
+        }
+
+        let members = op.apply(&sets);
+        let len = members.len();
+
+        let prev = match members.is_empty() {
+            true => store.data.remove(&destination),
+            false => {
+                let id = store.next_id();
+                let mut set = SetEntry::new(id);
+                for member in members {
+                    set.insert(member, &store.encoding);
                 }
-            })
-        });
 
-        let stream = match stream {
-            Entry::Stream(stream) => stream,
-            _ => return Err("ERR Operation against a key holding the wrong kind of value".into()),
+                store.data.insert(destination, Entry::Set(set))
+            }
         };
 
-        let id = match id {
-            XAddId::Auto => {
-                let timestamp = SystemTime::now()
-                    .duration_since(SystemTime::UNIX_EPOCH)?
-                    .as_millis();
-                let id = stream
-                    .entries
-                    .iter()
-                    .filter(|entry| entry.id.0 == timestamp)
-                    .count();
+        store.discard(prev);
 
-                StreamEntryId(timestamp, id)
-            }
-            XAddId::AutoSeq(timestamp) => {
-                let seq = stream
-                    .entries
-                    .iter()
-                    .filter(|entry| entry.id.0 == timestamp)
-                    .count();
-                let seq = if timestamp == 0 { seq + 1 } else { seq };
+        Ok(len)
+    }
 
-                StreamEntryId(timestamp, seq)
+    /// Removes and returns up to `count` random members from the set stored at key.
+    /// The key is removed when the set becomes empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-set value.
+    pub fn spop(&self, key: &str, count: usize) -> crate::Result<Vec<Bytes>> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let set = match store.data.get_mut(key) {
+            Some(Entry::Set(set)) => set,
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+            None => return Ok(Vec::new()),
+        };
+
+        let members = set.iter().collect::<Vec<_>>();
+        let popped = random::sample(&members, count);
+
+        for member in &popped {
+            set.remove(member);
+        }
+
+        if set.is_empty() {
+            let prev = store.data.remove(key);
+            store.discard(prev);
+        }
+
+        Ok(popped)
+    }
+
+    /// Returns random members of the set stored at key.
+    /// With a positive count the members are distinct and at most `count` are returned,
+    /// with a negative count exactly `-count` members are returned, possibly repeated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-set value or if `-count` is too large.
+    pub fn srandmember(&self, key: &str, count: i64) -> crate::Result<Vec<Bytes>> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let set = match store.data.get_mut(key) {
+            Some(Entry::Set(set)) => set,
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+            None => return Ok(Vec::new()),
+        };
+
+        set.lru = lru_clock();
+
+        random_items(&set.iter().collect::<Vec<_>>(), count)
+    }
+
+    /// Sets the fields of the hash stored at key, creating the hash if needed.
+    /// Returns the number of fields that were added (not updated).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-hash value.
+    pub fn hset(&self, key: String, fields: Vec<(String, Bytes)>) -> crate::Result<usize> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        // Make borrow checker happy
+        let store = &mut *store;
+
+        let id = store.next_id();
+
+        let entry = store
+            .data
+            .entry(key)
+            .or_insert_with(|| Entry::Hash(HashEntry::new(id)));
+
+        let hash = match entry {
+            Entry::Hash(hash) => hash,
+            _ => return Err(WRONGTYPE_ERR.into()),
+        };
+
+        hash.lru = lru_clock();
+
+        Ok(fields
+            .into_iter()
+            .filter(|(field, value)| hash.insert(field.clone(), value.clone(), &store.encoding))
+            .count())
+    }
+
+    /// Sets the field of the hash stored at key only if it does not exist yet,
+    /// creating the hash if needed.
+    /// Returns true if the field was set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-hash value.
+    pub fn hsetnx(&self, key: String, field: String, value: Bytes) -> crate::Result<bool> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        // Make borrow checker happy
+        let store = &mut *store;
+
+        let id = store.next_id();
+
+        let entry = store
+            .data
+            .entry(key)
+            .or_insert_with(|| Entry::Hash(HashEntry::new(id)));
+
+        let hash = match entry {
+            Entry::Hash(hash) => hash,
+            _ => return Err(WRONGTYPE_ERR.into()),
+        };
+
+        hash.lru = lru_clock();
+
+        if hash.get(&field).is_some() {
+            return Ok(false);
+        }
+
+        Ok(hash.insert(field, value, &store.encoding))
+    }
+
+    /// Adds the members with their scores to the sorted set stored at key,
+    /// creating the sorted set if needed, or updates the scores of existing members.
+    /// With INCR, the score is added to the current score of the member.
+    ///
+    /// Returns the number of added members (added or updated with CH)
+    /// and the score of the last member, `None` if it was not added or updated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-zset value,
+    /// or if an increment makes the score NaN.
+    pub fn zadd(
+        &self,
+        key: String,
+        flags: ZAddFlags,
+        members: Vec<(f64, Bytes)>,
+    ) -> crate::Result<(usize, Option<f64>)> {
+        let mut store = self.shared.store.lock().unwrap();
+        let config = store.encoding;
+
+        let id = store.next_id();
+
+        let entry = store
+            .data
+            .entry(key.clone())
+            .or_insert_with(|| Entry::ZSet(ZSetEntry::new(id)));
+
+        let zset = match entry {
+            Entry::ZSet(zset) => zset,
+            _ => return Err(WRONGTYPE_ERR.into()),
+        };
+
+        zset.lru = lru_clock();
+
+        let mut count = 0;
+        let mut last_score = None;
+
+        for (score, member) in members {
+            last_score = None;
+
+            let current = zset.score(&member);
+
+            let score = match (current, flags.incr) {
+                (Some(current), true) => current + score,
+                _ => score,
+            };
+
+            if score.is_nan() {
+                return Err("ERR resulting score is not a number (NaN)".into());
             }
-            XAddId::Explicit(id) => {
-                let StreamEntryId(timestamp, seq) = id;
-                let last_id = stream
-                    .entries
-                    .last()
-                    .map(|entry| entry.id)
-                    .unwrap_or(StreamEntryId(0, 0));
-                let StreamEntryId(last_timestamp, last_seq) = last_id;
 
-                if timestamp < last_timestamp {
-                    return Err("Timestamp is less than the last timestamp".into());
+            match current {
+                Some(_) if flags.nx => continue,
+                None if flags.xx => continue,
+                // GT and LT only restrict the update of existing members
+                Some(current) if flags.gt && score <= current => continue,
+                Some(current) if flags.lt && score >= current => continue,
+                Some(current) => {
+                    if score != current {
+                        zset.insert(member, score, &config);
+                        if flags.ch {
+                            count += 1;
+                        }
+                    }
                 }
-                if seq <= last_seq {
-                    return Err("Sequence is less than the last sequence or equal to it".into());
+                None => {
+                    zset.insert(member, score, &config);
+                    count += 1;
                 }
+            }
 
-                id
+            last_score = Some(score);
+        }
+
+        // Nothing was added to a new sorted set, e.g. with XX
+        if zset.is_empty() {
+            let prev = store.data.remove(&key);
+            store.discard(prev);
+        }
+
+        Ok((count, last_score))
+    }
+
+    /// Returns the value of the field of the hash stored at key.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-hash value.
+    pub fn hget(&self, key: &str, field: &str) -> crate::Result<Option<Bytes>> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        match store.data.get_mut(key) {
+            Some(Entry::Hash(hash)) => {
+                hash.lru = lru_clock();
+                Ok(hash.get(field).cloned())
             }
+            Some(_) => Err(WRONGTYPE_ERR.into()),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns random fields of the hash stored at key.
+    /// With a positive count the fields are distinct and at most `count` are returned,
+    /// with a negative count exactly `-count` fields are returned, possibly repeated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-hash value or if `-count` is too large.
+    pub fn hrandfield(&self, key: &str, count: i64) -> crate::Result<Vec<(String, Bytes)>> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let hash = match store.data.get_mut(key) {
+            Some(Entry::Hash(hash)) => hash,
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+            None => return Ok(Vec::new()),
         };
 
-        let entry = StreamEntry::new(id, key_value);
+        hash.lru = lru_clock();
 
-        stream.entries.push(entry);
-        stream.send_update(id);
+        let fields = hash.iter().collect::<Vec<_>>();
+        let fields = random_items(&fields, count)?;
 
-        Ok(format!("{}-{}", id.0, id.1))
+        Ok(fields
+            .into_iter()
+            .map(|(field, value)| (field, value.clone()))
+            .collect())
     }
 
-    pub fn xrange(
+    /// Returns random members of the sorted set stored at key, with their scores.
+    /// With a positive count the members are distinct and at most `count` are returned,
+    /// with a negative count exactly `-count` members are returned, possibly repeated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-sorted set value or if `-count` is too large.
+    pub fn zrandmember(&self, key: &str, count: i64) -> crate::Result<Vec<(Bytes, f64)>> {
+        let mut store = self.shared.store.lock().unwrap();
+
+        let zset = match store.data.get_mut(key) {
+            Some(Entry::ZSet(zset)) => zset,
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+            None => return Ok(Vec::new()),
+        };
+
+        zset.lru = lru_clock();
+
+        let members = random_items(&zset.iter().collect::<Vec<_>>(), count)?;
+
+        Ok(members
+            .into_iter()
+            .map(|(member, score)| (member.clone(), *score))
+            .collect())
+    }
+
+    /// Returns the members of the sorted set stored at key between the lexicographical bounds,
+    /// skipping `offset` members and returning up to `count` of them with a LIMIT.
+    /// The members are expected to have the same score, they are visited in the sorted set order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key holds a non-sorted set value.
+    pub fn zrangebylex(
         &self,
-        stream_key: &str,
-        start: Option<StreamEntryId>,
-        end: Option<StreamEntryId>,
-    ) -> Vec<StreamEntry> {
+        key: &str,
+        min: &LexBound,
+        max: &LexBound,
+        limit: Option<(i64, i64)>,
+    ) -> crate::Result<Vec<Bytes>> {
         let store = self.shared.store.lock().unwrap();
-        let stream = store.data.get(stream_key);
 
-        let stream = match stream {
-            Some(Entry::Stream(stream)) => stream,
-            _ => return Vec::new(),
+        let zset = match store.data.get(key) {
+            Some(Entry::ZSet(zset)) => zset,
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+            None => return Ok(Vec::new()),
         };
 
-        let start = start.unwrap_or(StreamEntryId(0, 0));
-        let end = end.unwrap_or(StreamEntryId(u128::MAX, usize::MAX));
+        let (offset, count) = match limit {
+            // A negative offset returns an empty range
+            Some((offset, _)) if offset < 0 => return Ok(Vec::new()),
+            Some((offset, count)) if count >= 0 => (offset as usize, count as usize),
+            Some((offset, _)) => (offset as usize, usize::MAX),
+            None => (0, usize::MAX),
+        };
 
-        stream
-            .entries
+        Ok(zset
             .iter()
-            .filter(|entry| entry.id >= start && entry.id <= end)
+            .map(|(member, _)| member)
+            .filter(|member| min.admits_from(member) && max.admits_to(member))
+            .skip(offset)
+            .take(count)
             .cloned()
-            .collect()
+            .collect())
     }
 
-    pub async fn xread(
-        &self,
-        stream_keys: &[String],
-        stream_ids: &[StreamEntryId],
-        block: Option<u64>,
-    ) -> Vec<(String, Vec<StreamEntry>)> {
-        if let Some(block_timeout) = block {
-            let mut join_set = JoinSet::new();
+    /// Subscribes to the pub/sub channel, creating it if needed
+    pub fn subscribe(&self, channel: &str) -> broadcast::Receiver<Bytes> {
+        let mut channels = self.shared.channels.lock().unwrap();
 
-            // For each stream key, spawn a task that will wait until the stream updates
-            for (idx, stream_key) in stream_keys.iter().enumerate() {
-                let mut store = self.shared.store.lock().unwrap();
+        match channels.get(channel) {
+            Some(sender) => sender.subscribe(),
+            None => {
+                let (sender, receiver) = broadcast::channel(PUBSUB_CHANNEL_CAPACITY);
+                channels.insert(channel.to_string(), sender);
+                receiver
+            }
+        }
+    }
 
-                let stream = store.data.entry(stream_key.to_string()).or_insert_with(|| {
-                    Entry::Stream({
-                        Stream {
-                            update_sender: None,
-                            entries: Vec::new(),
-                        }
-                    })
-                });
+    /// Publishes the message to the subscribers of the channel.
+    /// Returns the number of subscribers that received the message.
+    pub fn publish(&self, channel: &str, message: Bytes) -> usize {
+        let mut channels = self.shared.channels.lock().unwrap();
 
-                let stream = match stream {
-                    Entry::Stream(stream) => stream,
-                    _ => continue,
-                };
+        let sender = match channels.get(channel) {
+            Some(sender) => sender,
+            None => return 0,
+        };
 
-                let mut receiver = stream.subscribe();
-                let stream_target_id = stream_ids.get(idx).cloned().unwrap_or(StreamEntryId(0, 0));
-                join_set.spawn(async move {
-                    // Wait until the stream updates with an entry
-                    // with id greater than the target stream id
-                    while receiver.recv().await.unwrap() <= stream_target_id {}
-                });
+        match sender.send(message) {
+            Ok(count) => count,
+            // All the subscribers are gone
+            Err(_) => {
+                channels.remove(channel);
+                0
             }
+        }
+    }
 
-            // If a block timeout is set, spawn a task that will sleep for the duration
-            if block_timeout > 0 {
-                let sleep = tokio::time::sleep(Duration::from_millis(block_timeout));
-                join_set.spawn(sleep);
-            }
+    /// Returns the pub/sub channels with subscribers, matching the glob pattern if any
+    pub fn pubsub_channels(&self, pattern: Option<&str>) -> Vec<String> {
+        let channels = self.shared.channels.lock().unwrap();
 
-            // Wait until any task completes
-            let _ = join_set.join_next().await.expect("JoinSet is empty");
-        }
+        channels
+            .iter()
+            .filter(|(_, sender)| sender.receiver_count() > 0)
+            .filter(|(channel, _)| pattern.is_none_or(|pattern| glob::matches(pattern, channel)))
+            .map(|(channel, _)| channel.clone())
+            .collect()
+    }
+
+    /// Returns the number of subscribers of the pub/sub channel
+    pub fn pubsub_numsub(&self, channel: &str) -> usize {
+        let channels = self.shared.channels.lock().unwrap();
+
+        channels
+            .get(channel)
+            .map_or(0, |sender| sender.receiver_count())
+    }
 
+    /// Returns the name and code of every function library, sorted by name
+    pub fn function_libraries(&self) -> Vec<(String, Bytes)> {
         let store = self.shared.store.lock().unwrap();
 
-        // Collect all the entries for each stream key
-        // that have an id greater than the target stream id
-        stream_keys
+        store
+            .functions
             .iter()
-            .enumerate()
-            .filter_map(|(idx, key)| {
-                store.data.get(key).and_then(|entry| match entry {
-                    Entry::Stream(stream) => {
-                        let entries = stream
-                            .entries
-                            .iter()
-                            .filter(|entry| {
-                                let id = stream_ids.get(idx).unwrap_or(&StreamEntryId(0, 0));
-                                entry.id > *id
-                            })
-                            .cloned()
-                            .collect();
-                        Some((key.clone(), entries))
-                    }
-                    _ => None,
-                })
-            })
+            .map(|(name, code)| (name.clone(), code.clone()))
             .collect()
     }
 
-    pub fn get_stream_last_id(&self, key: &str) -> StreamEntryId {
-        let store = self.shared.store.lock().unwrap();
-        let stream = store.data.get(key);
+    /// Replaces the function libraries with the ones loaded from an RDB file,
+    /// the libraries without a valid name are skipped
+    pub fn load_functions(&self, functions: &[Bytes]) {
+        let mut store = self.shared.store.lock().unwrap();
 
-        match stream {
-            Some(Entry::Stream(stream)) => stream.get_last_id(),
-            _ => StreamEntryId(0, 0),
-        }
+        store.functions = functions
+            .iter()
+            .filter_map(|code| Some((function_library_name(code).ok()?, code.clone())))
+            .collect();
     }
 
-    pub fn get_streams_last_ids(&self, keys: &[String]) -> Vec<StreamEntryId> {
+    /// Serializes the function libraries in the FUNCTION DUMP format
+    pub fn function_dump(&self) -> Bytes {
         let store = self.shared.store.lock().unwrap();
+        let functions = store.functions.values().cloned().collect::<Vec<_>>();
 
-        keys.iter()
-            .filter_map(|key| {
-                store.data.get(key).map(|entry| match entry {
-                    Entry::Stream(stream) => stream.get_last_id(),
-                    _ => StreamEntryId(0, 0),
-                })
-            })
-            .collect()
+        Bytes::from(RedisDB::dump_functions(&functions))
+    }
+
+    /// Restores the function libraries serialized by [`Db::function_dump`].
+    /// Nothing is restored if any library can't be.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload is invalid, if a library has no valid name
+    /// or, with the APPEND policy, if a library already exists.
+    pub fn function_restore(
+        &self,
+        payload: &[u8],
+        policy: FunctionRestorePolicy,
+    ) -> crate::Result<()> {
+        let libraries = RedisDB::restore_functions(payload)?
+            .into_iter()
+            .map(|code| Ok((function_library_name(&code)?, code)))
+            .collect::<crate::Result<Vec<_>>>()?;
+
+        let mut store = self.shared.store.lock().unwrap();
+
+        match policy {
+            FunctionRestorePolicy::Flush => store.functions.clear(),
+            FunctionRestorePolicy::Append => {
+                if let Some((name, _)) = libraries
+                    .iter()
+                    .find(|(name, _)| store.functions.contains_key(name))
+                {
+                    return Err(format!("ERR Library {} already exists", name).into());
+                }
+            }
+            FunctionRestorePolicy::Replace => {}
+        }
+
+        store.functions.extend(libraries);
+
+        Ok(())
     }
 
     pub fn get_type(&self, key: &str) -> String {
         let store = self.shared.store.lock().unwrap();
 
         match store.data.get(key) {
-            Some(entry) => match entry {
-                Entry::String(_) => "string".to_string(),
-                Entry::Stream(_) => "stream".to_string(),
-            },
+            Some(entry) => entry.type_name().to_string(),
             None => "none".to_string(),
         }
     }
@@ -500,8 +3469,14 @@ impl Shared {
                 expires: BTreeMap::new(),
                 next_id: 0,
                 is_dropped: false,
+                encoding: EncodingConfig::default(),
+                functions: BTreeMap::new(),
+                lazyfree: None,
             }),
             task_expiry_notify: Notify::new(),
+            active_expire: AtomicBool::new(true),
+            channels: Mutex::new(HashMap::new()),
+            transaction: RwLock::new(()),
         }
     }
 
@@ -532,14 +3507,10 @@ impl Shared {
 
             // Else remove the entry from both the data and expires stores
             if let Some(entry) = store.data.get(key) {
-                match entry {
-                    Entry::String(entry) => {
-                        if entry.id == id {
-                            store.data.remove(key);
-                        }
+                if entry.expiry() == Some((expiry, id)) {
+                    if let Some(entry) = store.data.remove(key) {
+                        store.free(entry);
                     }
-                    // If the entry is a stream, it does not have an expiry
-                    Entry::Stream(_) => {}
                 }
             }
 
@@ -560,6 +3531,29 @@ impl Shared {
 }
 
 impl Store {
+    /// Removes the expiry of an entry removed or overwritten in the data, then drops it
+    fn discard(&mut self, prev: Option<Entry>) {
+        if let Some(prev) = prev {
+            if let Some(expiry) = prev.expiry() {
+                self.expires.remove(&expiry);
+            }
+
+            self.free(prev);
+        }
+    }
+
+    /// Drops an entry removed from the data, the large ones are sent
+    /// to the lazy free thread when it is enabled, so the lock is not held while freeing them
+    fn free(&self, entry: Entry) {
+        match &self.lazyfree {
+            Some(lazyfree) if entry.free_effort() > LAZYFREE_THRESHOLD => {
+                // The thread only stops once the sender is dropped, so the send can't fail
+                let _ = lazyfree.send(entry);
+            }
+            _ => drop(entry),
+        }
+    }
+
     /// Returns the next id of this [`Store`] [`Entry`].
     fn next_id(&mut self) -> u64 {
         let id = self.next_id;
@@ -571,12 +3565,489 @@ impl Store {
     pub fn next_expiry(&self) -> Option<Instant> {
         self.expires.keys().next().map(|(expiry, _)| *expiry)
     }
+
+    /// Inserts the string entry, replacing any previous entry and its expiry.
+    /// Returns true if the expiry task needs to be notified of the new expiry.
+    fn insert_string(&mut self, key: String, value: Bytes, expire: Option<Duration>) -> bool {
+        let id = self.next_id();
+
+        let mut should_notify = false;
+
+        // A time to live too far in the future to represent never expires
+        let expires_at = expire.and_then(|duration| {
+            let when = Instant::now().checked_add(duration)?;
+
+            // Worker needs to be notified if the new expiry is the earliest one
+            should_notify = self.next_expiry().map(|next| when < next).unwrap_or(true);
+
+            // Insert the new expiry into the BTreeMap
+            self.expires.insert((when, id), key.clone());
+            Some(when)
+        });
+
+        let entry = Entry::String(StringEntry {
+            id,
+            value: value.into(),
+            expires_at,
+            lru: lru_clock(),
+        });
+
+        // If there was an existing entry with an expiry, remove the previous expiry
+        let prev = self.data.insert(key, entry);
+        self.discard(prev);
+
+        should_notify
+    }
+
+    /// Sorts the elements of the list, set or sorted set stored at key,
+    /// by their values or the values of the keys named by the BY pattern,
+    /// then applies the LIMIT and replaces each element by the values of the GET patterns.
+    fn sort(&self, key: &str, opts: &SortOpts) -> crate::Result<Vec<Option<Bytes>>> {
+        let elements: Vec<Bytes> = match self.data.get(key) {
+            Some(Entry::List(list)) => list.iter().collect(),
+            Some(Entry::Set(set)) => set.iter().collect(),
+            Some(Entry::ZSet(zset)) => zset.iter().map(|(member, _)| member.clone()).collect(),
+            Some(_) => return Err(WRONGTYPE_ERR.into()),
+            None => Vec::new(),
+        };
+
+        // A BY pattern without `*` names the same key for every element, there is nothing to sort by
+        let should_sort = opts.by.as_ref().is_none_or(|by| by.contains('*'));
+
+        let mut elements = match should_sort {
+            true => self.sort_elements(elements, opts)?,
+            false => elements,
+        };
+
+        if let Some((offset, count)) = opts.limit {
+            let offset = (offset.max(0) as usize).min(elements.len());
+            let count = match count < 0 {
+                true => elements.len() - offset,
+                false => (count as usize).min(elements.len() - offset),
+            };
+
+            elements = elements.drain(offset..offset + count).collect();
+        }
+
+        if opts.get.is_empty() {
+            return Ok(elements.into_iter().map(Some).collect());
+        }
+
+        Ok(elements
+            .iter()
+            .flat_map(|element| {
+                opts.get
+                    .iter()
+                    .map(move |pattern| self.sort_lookup(pattern, element))
+            })
+            .collect())
+    }
+
+    fn sort_elements(&self, elements: Vec<Bytes>, opts: &SortOpts) -> crate::Result<Vec<Bytes>> {
+        let weights = elements
+            .iter()
+            .map(|element| match &opts.by {
+                Some(by) => self.sort_lookup(by, element),
+                None => Some(element.clone()),
+            })
+            .collect::<Vec<_>>();
+
+        let mut elements = match opts.alpha {
+            true => {
+                let mut sorted = weights.into_iter().zip(elements).collect::<Vec<_>>();
+                // Missing weights come first
+                sorted.sort_by(|(a, _), (b, _)| a.cmp(b));
+                sorted.into_iter().map(|(_, element)| element).collect()
+            }
+            false => {
+                let mut sorted = Vec::with_capacity(elements.len());
+                for (weight, element) in weights.into_iter().zip(elements) {
+                    // Missing weights count as 0
+                    let weight = match weight {
+                        Some(weight) => parse_sort_weight(&weight)?,
+                        None => 0.0,
+                    };
+                    sorted.push((weight, element));
+                }
+
+                sorted.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+                sorted
+                    .into_iter()
+                    .map(|(_, element)| element)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        if opts.desc {
+            elements.reverse();
+        }
+
+        Ok(elements)
+    }
+
+    /// Returns the value named by the SORT pattern for the element:
+    /// the element itself for `#`, otherwise the string at the key built by replacing
+    /// the first `*` of the pattern by the element, or the hash field for `key->field`
+    fn sort_lookup(&self, pattern: &str, element: &Bytes) -> Option<Bytes> {
+        if pattern == "#" {
+            return Some(element.clone());
+        }
+
+        let star = pattern.find('*')?;
+        let element = String::from_utf8_lossy(element);
+
+        let (key_pattern, field) = match pattern[star + 1..].find("->") {
+            Some(arrow) if star + 1 + arrow + 2 < pattern.len() => (
+                &pattern[..star + 1 + arrow],
+                Some(&pattern[star + 1 + arrow + 2..]),
+            ),
+            _ => (pattern, None),
+        };
+        let key = key_pattern.replacen('*', &element, 1);
+
+        match (self.data.get(&key)?, field) {
+            (Entry::String(entry), None) => Some(entry.value()),
+            (Entry::Hash(hash), Some(field)) => hash.get(field).cloned(),
+            _ => None,
+        }
+    }
+}
+
+/// Converts the inclusive range of a list command, with negative indexes counting from the tail,
+/// to indexes in the list. Returns `None` if the range is empty.
+fn list_range(len: usize, start: i64, stop: i64) -> Option<(usize, usize)> {
+    let len = len as i64;
+
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let stop = if stop < 0 {
+        len + stop
+    } else {
+        stop.min(len - 1)
+    };
+
+    if start > stop || start >= len {
+        return None;
+    }
+
+    Some((start as usize, stop as usize))
+}
+
+/// Parses the weight of an element for a numeric SORT
+fn parse_sort_weight(weight: &[u8]) -> crate::Result<f64> {
+    std::str::from_utf8(weight)
+        .ok()
+        .and_then(|weight| weight.trim().parse::<f64>().ok())
+        .filter(|weight| !weight.is_nan())
+        .ok_or_else(|| "ERR One or more scores can't be converted into double".into())
+}
+
+/// Returns up to `count` distinct random items with a positive count,
+/// and exactly `-count` items, possibly repeated, with a negative count
+///
+/// # Errors
+///
+/// Returns an error if `-count` is over [`RANDOM_COUNT_MAX`].
+fn random_items<T: Clone>(items: &[T], count: i64) -> crate::Result<Vec<T>> {
+    if count >= 0 {
+        return Ok(random::sample(items, count as usize));
+    }
+
+    if count.unsigned_abs() > RANDOM_COUNT_MAX {
+        return Err("ERR value is out of range".into());
+    }
+
+    Ok(random::sample_with_replacement(
+        items,
+        count.unsigned_abs() as usize,
+    ))
+}
+
+/// Hash of the key used as the SCAN cursor
+/// `DefaultHasher::new` always uses the same keys, so the order of the keys is stable across calls
+fn scan_hash(key: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns an ordered snapshot of up to `count` items starting at the cursor,
+/// and the cursor of the next item, 0 when there are no more items.
+/// Items are ordered by the hash of their key, see [`Db::scan`].
+/// Only the first `count + 1` items are kept while going through the collection,
+/// so it is not collected and sorted as a whole on each call.
+fn scan_page<K, V>(
+    items: impl Iterator<Item = (K, V)>,
+    cursor: u64,
+    count: usize,
+) -> (u64, Vec<(K, V)>)
+where
+    K: Hash + Ord,
+{
+    // Max-heap of the first items, the greatest is dropped when a smaller one comes
+    let mut first = BinaryHeap::new();
+    for (key, value) in items {
+        let hash = scan_hash(&key);
+        if hash < cursor {
+            continue;
+        }
+
+        let item = ScanItem(hash, key, value);
+        if first.len() <= count {
+            first.push(item);
+        } else if first.peek().is_some_and(|greatest| item < *greatest) {
+            first.pop();
+            first.push(item);
+        }
+    }
+
+    let mut items = first.into_sorted_vec();
+    let next_cursor = match items.len() > count {
+        true => items.pop().map_or(0, |ScanItem(hash, _, _)| hash),
+        false => 0,
+    };
+
+    let items = items
+        .into_iter()
+        .map(|ScanItem(_, key, value)| (key, value))
+        .collect();
+
+    (next_cursor, items)
+}
+
+/// Item of a SCAN page, ordered by the hash of its key then by its key
+struct ScanItem<K, V>(u64, K, V);
+
+impl<K: Ord, V> PartialEq for ScanItem<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl<K: Ord, V> Eq for ScanItem<K, V> {}
+
+impl<K: Ord, V> PartialOrd for ScanItem<K, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<K: Ord, V> Ord for ScanItem<K, V> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.cmp(&other.0).then_with(|| self.1.cmp(&other.1))
+    }
+}
+
+/// Reads the bit field, bit 0 being the most significant bit of the first byte
+fn read_bit_field(bytes: &[u8], ty: BitFieldType, offset: u64) -> i64 {
+    let mut value: u64 = 0;
+
+    for i in 0..ty.bits as u64 {
+        let pos = offset + i;
+        let bit = bytes
+            .get((pos / 8) as usize)
+            .map(|byte| (byte >> (7 - pos % 8)) & 1)
+            .unwrap_or(0);
+
+        value = (value << 1) | bit as u64;
+    }
+
+    if ty.signed {
+        // Sign extend the field to 64 bits
+        let shift = 64 - ty.bits as u32;
+        ((value << shift) as i64) >> shift
+    } else {
+        value as i64
+    }
+}
+
+/// Converts the start and end of a range of a string of `len` bytes to bit positions,
+/// negative indexes count from the end and out of range indexes are clamped.
+/// Returns `None` if the range is empty.
+fn bit_range(
+    len: usize,
+    start: Option<i64>,
+    end: Option<i64>,
+    unit: BitUnit,
+) -> Option<(usize, usize)> {
+    let len = match unit {
+        BitUnit::Byte => len as i64,
+        BitUnit::Bit => len as i64 * 8,
+    };
+
+    let normalize = |index: i64| {
+        if index < 0 {
+            (len + index).max(0)
+        } else {
+            index
+        }
+    };
+    let start = normalize(start.unwrap_or(0));
+    let end = normalize(end.unwrap_or(-1)).min(len - 1);
+
+    if start > end {
+        return None;
+    }
+
+    match unit {
+        BitUnit::Byte => Some((start as usize * 8, end as usize * 8 + 7)),
+        BitUnit::Bit => Some((start as usize, end as usize)),
+    }
+}
+
+/// Writes the bit field, growing the bytes with zeros if needed
+fn write_bit_field(bytes: &mut Vec<u8>, ty: BitFieldType, offset: u64, value: i64) {
+    let last_byte = ((offset + ty.bits as u64 - 1) / 8) as usize;
+    if bytes.len() <= last_byte {
+        bytes.resize(last_byte + 1, 0);
+    }
+
+    let value = value as u64;
+    for i in 0..ty.bits as u64 {
+        let pos = offset + i;
+        let bit = (value >> (ty.bits as u64 - 1 - i)) & 1;
+        let mask = 1 << (7 - pos % 8);
+
+        if bit == 1 {
+            bytes[(pos / 8) as usize] |= mask;
+        } else {
+            bytes[(pos / 8) as usize] &= !mask;
+        }
+    }
+}
+
+/// Reads the library name from the shebang of its code, like `#!lua name=mylib`.
+/// The code is not evaluated, only the Lua engine is known.
+fn function_library_name(code: &[u8]) -> crate::Result<String> {
+    let shebang = code
+        .split(|byte| *byte == b'\n')
+        .next()
+        .and_then(|line| line.strip_prefix(b"#!"))
+        .ok_or("ERR Missing library metadata")?;
+    let shebang = std::str::from_utf8(shebang).map_err(|_| "ERR Invalid metadata value given")?;
+
+    let mut parts = shebang.split_whitespace();
+    let engine = parts.next().unwrap_or_default();
+    if !engine.eq_ignore_ascii_case("lua") {
+        return Err(format!("ERR Engine '{}' not found", engine).into());
+    }
+
+    let mut name = None;
+    for part in parts {
+        match part.split_once('=') {
+            Some(("name", value)) => name = Some(value),
+            _ => return Err(format!("ERR Invalid metadata value given: {}", part).into()),
+        }
+    }
+
+    match name {
+        Some(name)
+            if !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+        {
+            Ok(name.to_string())
+        }
+        Some(_) => Err("ERR Library names can only contain letters, numbers, or underscores(_) and must be at least one character long".into()),
+        None => Err("ERR Library name was not given".into()),
+    }
+}
+
+/// Longest common subsequence of two strings, see [`Db::lcs`]
+#[derive(Debug)]
+pub struct CommonSubsequence {
+    pub sequence: Bytes,
+    /// Inclusive ranges of the matching parts in both strings,
+    /// from the last match to the first one
+    pub matches: Vec<((usize, usize), (usize, usize))>,
+}
+
+/// Computes the longest common subsequence with the dynamic programming table
+/// of the subsequence lengths, then walks it back from the end of both strings
+/// to collect the subsequence and its matching ranges
+fn common_subsequence(a: &[u8], b: &[u8]) -> crate::Result<CommonSubsequence> {
+    // The table is limited to proto-max-bulk-len like in Redis
+    const MAX_TABLE_SIZE: usize = 512 * 1024 * 1024;
+
+    let width = b.len() + 1;
+    let cells = (a.len() + 1)
+        .checked_mul(width)
+        .filter(|&cells| cells < u32::MAX as usize && cells * 4 <= MAX_TABLE_SIZE)
+        .ok_or("ERR Insufficient memory, transient memory for LCS exceeds proto-max-bulk-len")?;
+
+    // lens[i * width + j] is the length of the subsequence of a[..i] and b[..j]
+    let mut lens = vec![0u32; cells];
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            lens[i * width + j] = match a[i - 1] == b[j - 1] {
+                true => lens[(i - 1) * width + j - 1] + 1,
+                false => lens[(i - 1) * width + j].max(lens[i * width + j - 1]),
+            };
+        }
+    }
+
+    let mut sequence = Vec::with_capacity(lens[cells - 1] as usize);
+    let mut matches = Vec::new();
+    // Range being matched, as the ends of the range in both strings and its length
+    let mut current: Option<(usize, usize, usize)> = None;
+
+    let (mut i, mut j) = (a.len(), b.len());
+    while i > 0 && j > 0 {
+        if a[i - 1] == b[j - 1] {
+            sequence.push(a[i - 1]);
+            current = match current {
+                Some((a_end, b_end, len)) => Some((a_end, b_end, len + 1)),
+                None => Some((i - 1, j - 1, 1)),
+            };
+            i -= 1;
+            j -= 1;
+            continue;
+        }
+
+        if let Some((a_end, b_end, len)) = current.take() {
+            matches.push(((a_end + 1 - len, a_end), (b_end + 1 - len, b_end)));
+        }
+
+        match lens[(i - 1) * width + j] > lens[i * width + j - 1] {
+            true => i -= 1,
+            false => j -= 1,
+        }
+    }
+
+    if let Some((a_end, b_end, len)) = current {
+        matches.push(((a_end + 1 - len, a_end), (b_end + 1 - len, b_end)));
+    }
+
+    sequence.reverse();
+
+    Ok(CommonSubsequence {
+        sequence: Bytes::from(sequence),
+        matches,
+    })
+}
+
+/// Drops the entries sent by [`Store::free`], until the sender is dropped
+fn thread_lazyfree(receiver: mpsc::Receiver<Entry>) {
+    for entry in receiver {
+        drop(entry);
+    }
 }
 
 /// Task that removes all expired entries from the [`Store`].
 /// Task will sleep until the next expiry, or until it is notified.
+/// Does nothing while active expiry is disabled.
 async fn task_expiry(shared: Arc<Shared>) {
     while !shared.is_drop() {
+        // While active expiry is disabled, wait until it is enabled again
+        if !shared.active_expire.load(Ordering::Relaxed) {
+            shared.task_expiry_notify.notified().await;
+            continue;
+        }
+
         // Remove all expired entries
         // If there is an expiry returned, then we need to wait until the next expiry
         if let Some(next_expiry) = shared.remove_expired() {
@@ -591,3 +4062,330 @@ async fn task_expiry(shared: Arc<Shared>) {
         }
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Delivers the entry to the consumer of the group, like XREADGROUP does,
+    /// creating the consumer if needed
+    pub(crate) fn deliver(db: &Db, key: &str, group: &str, consumer: &str, id: StreamEntryId) {
+        let mut store = db.shared.store.lock().unwrap();
+
+        let group = match store.data.get_mut(key) {
+            Some(Entry::Stream(stream)) => stream.groups.get_mut(group).unwrap(),
+            entry => panic!("expected a stream, got {entry:?}"),
+        };
+
+        group
+            .consumers
+            .entry(consumer.to_string())
+            .or_insert_with(|| Consumer {
+                seen_time: SystemTime::now(),
+                pending: BTreeSet::new(),
+            })
+            .pending
+            .insert(id);
+        group.pending.insert(
+            id,
+            PendingEntry {
+                consumer: consumer.to_string(),
+                delivery_time: SystemTime::now(),
+                delivery_count: 1,
+            },
+        );
+        group.last_delivered_id = group.last_delivered_id.max(id);
+    }
+
+    fn xadd(db: &Db, id: XAddId) -> crate::Result<Option<String>> {
+        db.xadd(
+            "stream".to_string(),
+            id,
+            vec![("field".to_string(), Bytes::from("value"))],
+            false,
+        )
+    }
+
+    #[tokio::test]
+    async fn removed_and_reloaded_huge_lists_are_freed_off_the_lock() {
+        let db = Db::new();
+        let huge_list = |key: &str| {
+            let values = (0..100_000).map(|i| Bytes::from(i.to_string())).collect();
+            db.push(key.to_string(), ListDirection::Right, values)
+                .unwrap();
+        };
+
+        // Stands for the lazy free thread, to see what is sent to it
+        let (sender, receiver) = mpsc::channel();
+        db.shared.store.lock().unwrap().lazyfree = Some(sender);
+
+        huge_list("list");
+        assert!(db.remove("list"));
+        assert!(matches!(receiver.try_recv(), Ok(Entry::List(_))));
+
+        huge_list("list");
+        db.load_rdb(HashMap::new());
+        assert!(matches!(receiver.try_recv(), Ok(Entry::List(_))));
+        assert_eq!(db.dbsize(), 0);
+    }
+
+    #[tokio::test]
+    async fn collection_reads_touch_the_lru() {
+        let db = Db::new();
+        let value = || vec![Bytes::from("a")];
+        db.push("list".to_string(), ListDirection::Right, value())
+            .unwrap();
+        db.sadd("set".to_string(), value()).unwrap();
+        db.hset(
+            "hash".to_string(),
+            vec![("a".to_string(), Bytes::from("1"))],
+        )
+        .unwrap();
+        db.zadd(
+            "zset".to_string(),
+            ZAddFlags::default(),
+            vec![(1.0, Bytes::from("a"))],
+        )
+        .unwrap();
+
+        let idle = |key: &str| {
+            let store = db.shared.store.lock().unwrap();
+            lru_idle_secs(store.data[key].lru())
+        };
+        let make_idle = || {
+            let mut store = db.shared.store.lock().unwrap();
+            for entry in store.data.values_mut() {
+                entry.set_lru(lru_clock().wrapping_sub(100) & LRU_CLOCK_MAX);
+            }
+        };
+
+        make_idle();
+        db.lrange("list", 0, -1).unwrap();
+        db.smembers("set").unwrap();
+        db.hrandfield("hash", 1).unwrap();
+        db.zrandmember("zset", 1).unwrap();
+        for key in ["list", "set", "hash", "zset"] {
+            assert!(idle(key) < 100, "{key}");
+        }
+
+        make_idle();
+        db.srandmember("set", -2).unwrap();
+        assert!(idle("set") < 100);
+        assert!(idle("list") >= 100);
+    }
+
+    #[tokio::test]
+    async fn overwritten_huge_list_is_freed_off_the_lock() {
+        let db = Db::new();
+        let values = (0..100_000).map(|i| Bytes::from(i.to_string())).collect();
+        db.push("list".to_string(), ListDirection::Right, values)
+            .unwrap();
+        db.push(
+            "small".to_string(),
+            ListDirection::Right,
+            vec![Bytes::from("a")],
+        )
+        .unwrap();
+
+        // Stands for the lazy free thread, to see what is sent to it
+        let (sender, receiver) = mpsc::channel();
+        db.shared.store.lock().unwrap().lazyfree = Some(sender);
+
+        db.set("small".to_string(), Bytes::from("value"), None);
+        assert!(receiver.try_recv().is_err());
+
+        db.set("list".to_string(), Bytes::from("value"), None);
+
+        match receiver.try_recv() {
+            Ok(Entry::List(list)) => assert_eq!(list.values.len(), 100_000),
+            entry => panic!("expected the list, got {entry:?}"),
+        }
+        assert_eq!(db.get_type("list"), "string");
+    }
+
+    #[test]
+    fn stream_ids_order_by_timestamp_then_sequence() {
+        assert!(StreamEntryId::new(1, 5) < StreamEntryId::new(2, 0));
+        assert!(StreamEntryId::new(2, 0) < StreamEntryId::new(2, 1));
+        assert!(StreamEntryId::new(10, 0) > StreamEntryId::new(9, usize::MAX));
+    }
+
+    #[test]
+    fn stream_id_is_split_on_first_hyphen() {
+        let id = StreamEntryId::parse("12345-678", 0).unwrap();
+
+        assert_eq!(id, StreamEntryId::new(12345, 678));
+        assert_eq!(id.to_string(), "12345-678");
+    }
+
+    #[test]
+    fn stream_id_without_sequence_uses_default() {
+        assert_eq!(
+            StreamEntryId::parse("5", 0).unwrap(),
+            StreamEntryId::new(5, 0)
+        );
+        assert_eq!(
+            StreamEntryId::parse("5", usize::MAX).unwrap(),
+            StreamEntryId::new(5, usize::MAX)
+        );
+    }
+
+    #[test]
+    fn invalid_stream_ids_are_rejected() {
+        for id in ["1-2-3", "1--2", "-1", "1-", "a-1", "1-a", ""] {
+            assert!(StreamEntryId::parse(id, 0).is_err(), "{}", id);
+        }
+    }
+
+    #[tokio::test]
+    async fn exhausted_sequence_rejects_timestamp_with_auto_sequence() {
+        let db = Db::new();
+        xadd(&db, XAddId::Explicit(StreamEntryId::new(5, usize::MAX))).unwrap();
+
+        let err = xadd(&db, XAddId::AutoSeq(5)).unwrap_err();
+
+        assert_eq!(err.to_string(), XADD_ID_TOO_SMALL_ERR);
+    }
+
+    #[tokio::test]
+    async fn exhausted_sequence_moves_auto_id_to_next_millisecond() {
+        let db = Db::new();
+        let last = StreamEntryId::new(u128::MAX - 1, usize::MAX);
+        xadd(&db, XAddId::Explicit(last)).unwrap();
+
+        let id = xadd(&db, XAddId::Auto).unwrap().unwrap();
+
+        assert_eq!(id, StreamEntryId::new(u128::MAX, 0).to_string());
+    }
+
+    #[tokio::test]
+    async fn greatest_id_exhausts_stream() {
+        let db = Db::new();
+        let last = StreamEntryId::new(u128::MAX, usize::MAX);
+        xadd(&db, XAddId::Explicit(last)).unwrap();
+
+        let err = xadd(&db, XAddId::Auto).unwrap_err();
+
+        assert_eq!(err.to_string(), XADD_ID_EXHAUSTED_ERR);
+    }
+
+    #[tokio::test]
+    async fn scan_pages_go_through_every_item_in_hash_order() {
+        let db = Db::new();
+        let names = (0..100).map(|i| format!("item:{i}")).collect::<Vec<_>>();
+        for name in &names {
+            db.set(name.clone(), Bytes::from("value"), None);
+        }
+        let members = names.iter().map(|name| Bytes::from(name.clone())).collect();
+        db.sadd("set".to_string(), members).unwrap();
+
+        let mut keys = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let (next_cursor, page) = db.scan(cursor, 7);
+            assert!(page.len() <= 7);
+            keys.extend(page);
+            if next_cursor == 0 {
+                break;
+            }
+            cursor = next_cursor;
+        }
+        let hashes = keys.iter().map(scan_hash).collect::<Vec<_>>();
+        assert!(hashes.is_sorted());
+        keys.sort();
+        let mut expected = names.clone();
+        expected.push("set".to_string());
+        expected.sort();
+        assert_eq!(keys, expected);
+
+        let mut members = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let (next_cursor, page) = db.sscan("set", cursor, 10).unwrap();
+            members.extend(page);
+            if next_cursor == 0 {
+                break;
+            }
+            cursor = next_cursor;
+        }
+        members.sort();
+        let mut expected = names
+            .iter()
+            .map(|name| Bytes::from(name.clone()))
+            .collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(members, expected);
+    }
+
+    #[test]
+    fn list_elements_holding_integers_are_stored_as_integers() {
+        let elements = ["12", "-7", "007", "1.5", "abc", "9223372036854775808"]
+            .map(Bytes::from)
+            .map(ListpackElement::from);
+
+        assert_eq!(elements[0], ListpackElement::Int(12));
+        assert_eq!(elements[1], ListpackElement::Int(-7));
+        for element in &elements[2..] {
+            assert!(matches!(element, ListpackElement::Str(_)));
+        }
+    }
+
+    #[tokio::test]
+    async fn integer_list_elements_are_read_back_as_pushed() {
+        let db = Db::new();
+        let values = ["1", "abc", "-42", "007"].map(Bytes::from).to_vec();
+
+        db.push("list".to_string(), ListDirection::Right, values.clone())
+            .unwrap();
+
+        assert_eq!(db.lrange("list", 0, -1).unwrap(), values);
+        match db.get("list") {
+            Some(Entry::List(list)) => {
+                assert_eq!(list.encoding(), "listpack");
+                assert_eq!(list.values[0], ListpackElement::Int(1));
+                assert_eq!(list.iter().collect::<Vec<_>>(), values);
+            }
+            entry => panic!("expected a list, got {entry:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn integer_hash_fields_are_read_back_as_set() {
+        let db = Db::new();
+        let fields = [
+            ("1", "val1"),
+            ("2", "val2"),
+            ("-3", "val3"),
+            ("007", "val4"),
+        ]
+        .map(|(field, value)| (field.to_string(), Bytes::from(value)))
+        .to_vec();
+
+        db.hset("hash".to_string(), fields.clone()).unwrap();
+
+        for (field, value) in &fields {
+            assert_eq!(db.hget("hash", field).unwrap(), Some(value.clone()));
+        }
+        // Only the field named as stored matches
+        assert_eq!(db.hget("hash", "7").unwrap(), None);
+        assert_eq!(db.hget("hash", "01").unwrap(), None);
+        match db.get("hash") {
+            Some(Entry::Hash(hash)) => {
+                assert_eq!(hash.encoding(), "listpack");
+                match &hash.encoding {
+                    HashEncoding::Listpack(stored) => {
+                        assert_eq!(stored[0].0, ListpackField::Int(1));
+                        assert_eq!(stored[3].0, ListpackField::Str("007".to_string()));
+                    }
+                    encoding => panic!("expected a listpack, got {encoding:?}"),
+                }
+                let read = hash
+                    .iter()
+                    .map(|(field, value)| (field, value.clone()))
+                    .collect::<Vec<_>>();
+                assert_eq!(read, fields);
+            }
+            entry => panic!("expected a hash, got {entry:?}"),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::Frame;
+
+/// Kind of an error reply, told apart by clients from the first word of the message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedisErrorKind {
+    /// Generic error
+    Err,
+    /// Operation against a key holding the wrong type of value
+    WrongType,
+    /// Transaction discarded because of errors while queuing its commands
+    ExecAbort,
+    /// Script or function not found
+    NoScript,
+    /// Consumer group created twice
+    BusyGroup,
+    /// Consumer group not found
+    NoGroup,
+    /// Dataset still loading at startup
+    Loading,
+    /// Write command sent to a read only replica
+    ReadOnly,
+    /// Unsupported protocol version requested with HELLO
+    NoProto,
+}
+
+const KINDS: [RedisErrorKind; 9] = [
+    RedisErrorKind::Err,
+    RedisErrorKind::WrongType,
+    RedisErrorKind::ExecAbort,
+    RedisErrorKind::NoScript,
+    RedisErrorKind::BusyGroup,
+    RedisErrorKind::NoGroup,
+    RedisErrorKind::Loading,
+    RedisErrorKind::ReadOnly,
+    RedisErrorKind::NoProto,
+];
+
+impl RedisErrorKind {
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            RedisErrorKind::Err => "ERR",
+            RedisErrorKind::WrongType => "WRONGTYPE",
+            RedisErrorKind::ExecAbort => "EXECABORT",
+            RedisErrorKind::NoScript => "NOSCRIPT",
+            RedisErrorKind::BusyGroup => "BUSYGROUP",
+            RedisErrorKind::NoGroup => "NOGROUP",
+            RedisErrorKind::Loading => "LOADING",
+            RedisErrorKind::ReadOnly => "READONLY",
+            RedisErrorKind::NoProto => "NOPROTO",
+        }
+    }
+
+    /// Returns the kind of the error message from its prefix, if it has one
+    pub fn of(message: &str) -> Option<RedisErrorKind> {
+        let prefix = message.split(' ').next()?;
+
+        KINDS.into_iter().find(|kind| kind.prefix() == prefix)
+    }
+}
+
+/// Error reply with the message prefixed by the kind
+pub fn redis_error(kind: RedisErrorKind, message: &str) -> Frame {
+    Frame::Error(format!("{} {}", kind.prefix(), message))
+}
+
+/// Prefixes error replies without a known kind with `ERR`,
+/// like the protocol errors returned while parsing the commands
+pub fn with_error_prefix(frame: Frame) -> Frame {
+    match frame {
+        Frame::Error(message) if RedisErrorKind::of(&message).is_none() => {
+            redis_error(RedisErrorKind::Err, &message)
+        }
+        frame => frame,
+    }
+}
@@ -6,9 +6,13 @@ use bytes::{Buf, Bytes};
 pub enum Frame {
     Simple(String),
     Error(String),
-    Integer(u64),
+    Integer(i64),
+    /// RESP3 boolean, clients on RESP2 get an integer instead
+    Boolean(bool),
     Bulk(Bytes),
     Array(Vec<Frame>),
+    /// RESP3 out of band data, like pub/sub messages, sent as an array on RESP2
+    Push(Vec<Frame>),
     Null,
     /// RDB is a special frame that contains a simple string and a rdb payload
     Rdb(String, Bytes),
@@ -17,8 +21,15 @@ pub enum Frame {
     NoSend,
 }
 
+/// Deepest nesting of arrays and pushes accepted, the frames are parsed recursively
+const MAX_NESTING: usize = 128;
+
 impl Frame {
     pub fn parse(src: &mut Cursor<&[u8]>) -> Result<Frame, Error> {
+        Frame::parse_nested(src, 0)
+    }
+
+    fn parse_nested(src: &mut Cursor<&[u8]>, depth: usize) -> Result<Frame, Error> {
         match get_u8(src)? {
             // Simple string
             b'+' => {
@@ -36,21 +47,29 @@ impl Frame {
             }
             // Integer
             b':' => {
-                let val = get_decimal(src)?;
+                let val = get_int(src)?;
                 Ok(Frame::Integer(val))
             }
+            // Boolean
+            b'#' => match get_line(src)? {
+                b"t" => Ok(Frame::Boolean(true)),
+                b"f" => Ok(Frame::Boolean(false)),
+                _ => Err("Protocol error: invalid frame format".into()),
+            },
             // Bulk string
             b'$' => {
                 if b'-' == peek_u8(src)? {
                     let line = get_line(src)?;
-                    if line != b"-1\r\n" {
+                    if line != b"-1" {
                         return Err("Protocol error: invalid frame format".into());
                     }
 
                     Ok(Frame::Null)
                 } else {
                     let len = get_decimal(src)? as usize;
-                    let n = len + 2;
+                    let n = len
+                        .checked_add(2)
+                        .ok_or("Protocol error: invalid bulk length")?;
 
                     if src.remaining() < n {
                         return Err(Error::Incomplete);
@@ -65,19 +84,37 @@ impl Frame {
             }
             // Array
             b'*' => {
+                check_nesting(depth)?;
                 let len = get_decimal(src)? as usize;
-                let mut vec = Vec::with_capacity(len);
+                // Don't trust the length for the allocation, each frame takes at least a byte
+                let mut vec = Vec::with_capacity(len.min(src.remaining()));
 
                 for _ in 0..len {
-                    vec.push(Frame::parse(src)?)
+                    vec.push(Frame::parse_nested(src, depth + 1)?)
                 }
                 Ok(Frame::Array(vec))
             }
+            // Push
+            b'>' => {
+                check_nesting(depth)?;
+                let len = get_decimal(src)? as usize;
+                let mut vec = Vec::with_capacity(len.min(src.remaining()));
+
+                for _ in 0..len {
+                    vec.push(Frame::parse_nested(src, depth + 1)?)
+                }
+                Ok(Frame::Push(vec))
+            }
             actual => Err(format!("Protocol error: invalid frame type byte `{}`", actual).into()),
         }
     }
+
     /// Checks if an entire message can be decoded from `src`
     pub fn check(src: &mut Cursor<&[u8]>) -> Result<(), Error> {
+        Frame::check_nested(src, 0)
+    }
+
+    fn check_nested(src: &mut Cursor<&[u8]>, depth: usize) -> Result<(), Error> {
         match get_u8(src)? {
             // Simple string
             b'+' => {
@@ -91,7 +128,12 @@ impl Frame {
             }
             // Integer
             b':' => {
-                get_decimal(src)?;
+                get_int(src)?;
+                Ok(())
+            }
+            // Boolean
+            b'#' => {
+                get_line(src)?;
                 Ok(())
             }
             // Bulk string
@@ -102,16 +144,17 @@ impl Frame {
                 } else {
                     let len = get_decimal(src)? as usize;
                     // skip len + "\r\n"
-                    skip(src, len + 2)
+                    skip(src, len.saturating_add(2))
                 }
             }
-            // Array
-            b'*' => {
+            // Array or push
+            b'*' | b'>' => {
+                check_nesting(depth)?;
                 let len = get_decimal(src)?;
 
                 // check each frame in range
                 for _ in 0..len {
-                    Frame::check(src)?
+                    Frame::check_nested(src, depth + 1)?
                 }
                 Ok(())
             }
@@ -175,26 +218,30 @@ impl Frame {
         Bytes::from(self.encode())
     }
 
-    pub fn encode(&self) -> String {
-        return match self {
-            Frame::Simple(string) => encode_simple_string(string),
-            Frame::Error(error) => encode_simple_error(error),
-            Frame::Integer(integer) => encode_integer(*integer as i64),
-            Frame::Bulk(bytes) => encode_bulk_string(Some(std::str::from_utf8(bytes).unwrap())),
+    /// Encodes the frame, bulk payloads are written as is, they don't have to be valid UTF-8
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Frame::Simple(string) => encode_simple_string(string).into_bytes(),
+            Frame::Error(error) => encode_simple_error(error).into_bytes(),
+            Frame::Integer(integer) => encode_integer(*integer).into_bytes(),
+            Frame::Boolean(boolean) => encode_boolean(*boolean).into_bytes(),
+            Frame::Bulk(bytes) => encode_bulk_string(Some(bytes)),
             Frame::Array(array) => encode_array(array),
-            Frame::Null => encode_null(),
+            Frame::Push(push) => encode_push(push),
+            Frame::Null => encode_null().into_bytes(),
             Frame::Rdb(string, bytes) => {
-                let rdb_string = encode_simple_string(string);
-                let rdb_bytes = encode_bulk_string(Some(std::str::from_utf8(bytes).unwrap()));
-                rdb_string + &rdb_bytes
+                let mut rdb = encode_simple_string(string).into_bytes();
+                rdb.extend(encode_bulk_string(Some(bytes)));
+                rdb
             }
             Frame::RawBytes(bytes) => {
                 let length = bytes.len();
-                let bytes = std::str::from_utf8(bytes).unwrap();
-                format!("${length}\r\n{bytes}")
+                let mut raw = format!("${length}\r\n").into_bytes();
+                raw.extend_from_slice(bytes);
+                raw
             }
-            Frame::NoSend => "".to_string(),
-        };
+            Frame::NoSend => Vec::new(),
+        }
     }
 }
 
@@ -239,6 +286,15 @@ impl fmt::Display for Error {
     }
 }
 
+/// Rejects an array or a push nested deeper than [`MAX_NESTING`],
+/// so a malicious frame can't overflow the stack
+fn check_nesting(depth: usize) -> Result<(), Error> {
+    match depth < MAX_NESTING {
+        true => Ok(()),
+        false => Err("Protocol error: too deeply nested frame".into()),
+    }
+}
+
 fn get_u8(src: &mut Cursor<&[u8]>) -> Result<u8, Error> {
     if !src.has_remaining() {
         Err(Error::Incomplete)
@@ -266,7 +322,7 @@ fn skip(src: &mut Cursor<&[u8]>, n: usize) -> Result<(), Error> {
 
 fn get_line<'a>(src: &mut Cursor<&'a [u8]>) -> Result<&'a [u8], Error> {
     let start = src.position() as usize;
-    let end = src.get_ref().len() - 1;
+    let end = src.get_ref().len().saturating_sub(1);
 
     for i in start..end {
         if src.get_ref()[i] == b'\r' && src.get_ref()[i + 1] == b'\n' {
@@ -286,6 +342,14 @@ fn get_decimal(src: &mut Cursor<&[u8]>) -> Result<u64, Error> {
         .map_err(|e| format!("Invalid frame format: failed to get_decimal: {}", e).into())
 }
 
+/// Integer frames can be negative, unlike the lengths read with [`get_decimal`]
+fn get_int(src: &mut Cursor<&[u8]>) -> Result<i64, Error> {
+    let line = get_line(src)?.to_vec();
+    String::from_utf8(line)?
+        .parse()
+        .map_err(|e| format!("Invalid frame format: failed to get_int: {}", e).into())
+}
+
 pub fn encode_simple_string(string: &str) -> String {
     format!("+{string}\r\n")
 }
@@ -298,21 +362,25 @@ pub fn encode_integer(integer: i64) -> String {
     format!(":{integer}\r\n")
 }
 
-pub fn encode_bulk_string(string_option: Option<&str>) -> String {
-    if string_option.is_none() {
-        return "$-1\r\n".to_owned(); // null bulk string
-    }
-    let string = string_option.unwrap();
-    let length = string.len();
-    format!("${length}\r\n{string}\r\n")
+pub fn encode_bulk_string(bytes_option: Option<&[u8]>) -> Vec<u8> {
+    let bytes = match bytes_option {
+        Some(bytes) => bytes,
+        None => return b"$-1\r\n".to_vec(), // null bulk string
+    };
+    let length = bytes.len();
+
+    let mut result = format!("${length}\r\n").into_bytes();
+    result.extend_from_slice(bytes);
+    result.extend_from_slice(b"\r\n");
+    result
 }
 
-pub fn encode_array(array: &Vec<Frame>) -> String {
+pub fn encode_array(array: &Vec<Frame>) -> Vec<u8> {
     let length = array.len();
-    let mut result = format!("*{length}\r\n");
+    let mut result = format!("*{length}\r\n").into_bytes();
 
     for item in array {
-        result += &item.encode();
+        result.extend(item.encode());
     }
 
     result
@@ -322,10 +390,10 @@ pub fn encode_null() -> String {
     "_\r\n".to_string()
 }
 
-// pub fn encode_boolean(boolean: bool) -> String {
-//     let boolean_char = if boolean { "t" } else { "f" };
-//     return format!("#{boolean_char}\r\n");
-// }
+pub fn encode_boolean(boolean: bool) -> String {
+    let boolean_char = if boolean { "t" } else { "f" };
+    format!("#{boolean_char}\r\n")
+}
 
 // pub fn encode_double(double: f64) -> String {
 //     return format!(",{double}\r\n");
@@ -341,11 +409,48 @@ pub fn encode_null() -> String {
 //     return format!("={length}\r\n{string}\r\n");
 // }
 
-// pub fn encode_push(push: &Vec<Frame>) -> String {
-//     let length = push.len();
-//     let mut result = format!("*{length}\r\n");
-//     for item in push {
-//         result += &item.encode();
-//     }
-//     return result;
-// }
+pub fn encode_push(push: &Vec<Frame>) -> Vec<u8> {
+    let length = push.len();
+    let mut result = format!(">{length}\r\n").into_bytes();
+    for item in push {
+        result.extend(item.encode());
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nested(depth: usize) -> Vec<u8> {
+        let mut src = b"*1\r\n".repeat(depth);
+        src.extend_from_slice(b":1\r\n");
+        src
+    }
+
+    #[test]
+    fn deeply_nested_frame_is_rejected() {
+        let src = nested(200_000);
+
+        let err = Frame::check(&mut Cursor::new(&src[..])).unwrap_err();
+        assert_eq!(err.to_string(), "Protocol error: too deeply nested frame");
+        let err = Frame::parse(&mut Cursor::new(&src[..])).unwrap_err();
+        assert_eq!(err.to_string(), "Protocol error: too deeply nested frame");
+    }
+
+    #[test]
+    fn nesting_up_to_the_limit_is_parsed() {
+        let src = nested(MAX_NESTING);
+
+        Frame::check(&mut Cursor::new(&src[..])).unwrap();
+        let mut frame = Frame::parse(&mut Cursor::new(&src[..])).unwrap();
+        for _ in 0..MAX_NESTING {
+            frame = frame.into_array().unwrap().remove(0);
+        }
+        assert_eq!(frame, Frame::Integer(1));
+
+        let src = nested(MAX_NESTING + 1);
+        assert!(Frame::check(&mut Cursor::new(&src[..])).is_err());
+    }
+}
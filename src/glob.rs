@@ -0,0 +1,160 @@
+/// Returns true if the string matches the glob-style pattern
+///
+/// Supported patterns:
+/// - `*` matches any sequence of characters, including an empty one
+/// - `?` matches exactly one character
+/// - `[abc]` matches one of the characters, `[a-z]` one in the range
+/// - `[^abc]` matches one character not in the class
+/// - `\x` matches the character `x` literally, also inside a class
+pub fn matches(pattern: &str, string: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let string = string.chars().collect::<Vec<_>>();
+
+    let (mut p, mut s) = (0, 0);
+    // Position of the last `*` in the pattern and of the string when it was reached,
+    // used to backtrack when the rest of the pattern does not match
+    let mut backtrack = None;
+
+    while s < string.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, s));
+                p += 1;
+                continue;
+            }
+            Some('?') => {
+                p += 1;
+                s += 1;
+                continue;
+            }
+            Some('[') => {
+                if let Some(end) = match_class(&pattern, p + 1, string[s]) {
+                    p = end;
+                    s += 1;
+                    continue;
+                }
+            }
+            Some('\\') if p + 1 < pattern.len() && pattern[p + 1] == string[s] => {
+                p += 2;
+                s += 1;
+                continue;
+            }
+            Some(c) if *c != '\\' && *c == string[s] => {
+                p += 1;
+                s += 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        // Mismatch, let the last `*` consume one more character
+        match backtrack {
+            Some((star, star_s)) => {
+                backtrack = Some((star, star_s + 1));
+                p = star + 1;
+                s = star_s + 1;
+            }
+            None => return false,
+        }
+    }
+
+    // The rest of the pattern has to match the empty string
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Matches the character against the class starting after its `[`.
+/// Returns the position after the closing `]` if the character is in the class,
+/// an unterminated class extending to the end of the pattern
+fn match_class(pattern: &[char], start: usize, c: char) -> Option<usize> {
+    let mut p = start;
+    let negated = pattern.get(p) == Some(&'^');
+    if negated {
+        p += 1;
+    }
+
+    let mut matched = false;
+    while p < pattern.len() && pattern[p] != ']' {
+        match pattern[p] {
+            '\\' if p + 1 < pattern.len() => {
+                matched |= pattern[p + 1] == c;
+                p += 2;
+            }
+            low if p + 2 < pattern.len() && pattern[p + 1] == '-' && pattern[p + 2] != ']' => {
+                let high = pattern[p + 2];
+                let (low, high) = if low <= high {
+                    (low, high)
+                } else {
+                    (high, low)
+                };
+                matched |= (low..=high).contains(&c);
+                p += 3;
+            }
+            class_c => {
+                matched |= class_c == c;
+                p += 1;
+            }
+        }
+    }
+
+    match matched != negated {
+        true => Some((p + 1).min(pattern.len())),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn class_matches_listed_chars() {
+        assert!(matches("h[ae]llo", "hello"));
+        assert!(matches("h[ae]llo", "hallo"));
+        assert!(!matches("h[ae]llo", "hillo"));
+    }
+
+    #[test]
+    fn negated_class_matches_other_chars() {
+        assert!(matches("h[^ae]llo", "hillo"));
+        assert!(!matches("h[^ae]llo", "hello"));
+        assert!(!matches("h[^ae]llo", "hallo"));
+    }
+
+    #[test]
+    fn range_matches_chars_between_bounds() {
+        assert!(matches("h[a-c]llo", "hbllo"));
+        assert!(!matches("h[a-c]llo", "hdllo"));
+        // Reversed bounds are swapped
+        assert!(matches("h[c-a]llo", "hbllo"));
+        // A trailing `-` is a literal
+        assert!(matches("h[a-]llo", "h-llo"));
+    }
+
+    #[test]
+    fn escape_matches_literally() {
+        assert!(matches("h\\*llo", "h*llo"));
+        assert!(!matches("h\\*llo", "hello"));
+        assert!(matches("h\\?llo", "h?llo"));
+        assert!(!matches("h\\?llo", "hello"));
+        assert!(matches("[\\]]", "]"));
+        assert!(matches("[\\^a]", "^"));
+    }
+
+    #[test]
+    fn question_mark_matches_one_char() {
+        assert!(matches("h?llo", "hello"));
+        assert!(!matches("h?llo", "hllo"));
+        assert!(!matches("h?llo", "heello"));
+    }
+
+    #[test]
+    fn star_backtracks() {
+        assert!(matches("*", ""));
+        assert!(matches("*abc", "ababc"));
+        assert!(matches("a*b*c", "aXbYbZc"));
+        assert!(matches("a*b", "abab"));
+        assert!(!matches("a*b", "abac"));
+        assert!(matches("h*llo**", "hello"));
+        assert!(!matches("a*", "ba"));
+    }
+}
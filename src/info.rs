@@ -1,22 +1,57 @@
 use std::{
+    collections::{HashMap, HashSet},
+    fmt,
     net::SocketAddr,
     sync::{
-        mpsc::{self, Receiver, Sender},
-        Arc, Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
     },
-    time::Duration,
+    time::{Duration, SystemTime},
 };
 
-use tokio::task::JoinSet;
+use tokio::{
+    sync::{
+        mpsc::{self, UnboundedReceiver, UnboundedSender},
+        Mutex,
+    },
+    task::JoinSet,
+    time::{self, Instant},
+};
 
-use crate::{command::replconf::ReplConf, Config, Connection, Frame};
+use crate::{
+    command::replconf::ReplConf, random, replicaiton::backlog::ReplicationBacklog, Config,
+    Connection, Frame,
+};
 
 #[derive(Clone, Debug)]
 pub struct Info {
     role: Role,
+    /// Offset of the replication stream processed by a replica,
+    /// the offset of a master is the one of its backlog, shared by all the connections
     offset: u64,
     dir: String,
     dbfilename: String,
+    /// Whether the CRC64 checksum is written at the end of the RDB files
+    rdbchecksum: bool,
+    port: u16,
+    /// Random node id generated at startup, reported by CLUSTER MYID
+    node_id: String,
+    /// Time the server started at
+    started_at: SystemTime,
+    /// Whether a replica rejects writes from its clients
+    replica_read_only: bool,
+    /// Per command counters reported by INFO commandstats,
+    /// shared by all the connections
+    command_stats: Arc<std::sync::Mutex<HashMap<String, Arc<CommandStat>>>>,
+    /// Number of connected clients, shared by all the connections
+    connected_clients: Arc<AtomicUsize>,
+    max_clients: usize,
+    /// Commands per second allowed on a client connection, 0 for unlimited
+    max_commands_per_sec: u64,
+    /// Whether the test only DEBUG subcommands are allowed
+    enable_debug_commands: bool,
+    /// Parameters reported by CONFIG GET, they can't change at runtime
+    config_params: Arc<Vec<(String, String)>>,
 }
 
 impl Info {
@@ -26,10 +61,24 @@ impl Info {
         let master_replid = "8371b4fb1155b71f4a04d3e1bc3e18c4a990aeeb".to_string();
         let dir = config.dir.clone();
         let dbfilename = config.db_filename.clone();
+        let port = config.port;
+        let node_id = random::hex_id(40);
+        let started_at = SystemTime::now();
+        let replica_read_only = config.replica_read_only;
+        let max_clients = config.max_clients;
+        let max_commands_per_sec = config.max_commands_per_sec;
+        let enable_debug_commands = config.enable_debug_commands;
+        let config_params = Arc::new(config.params());
 
         let role = match master {
             Some(master) => Role::Slave(Slave::new(master)),
-            None => Role::Master(Master::new(master_replid)),
+            None => Role::Master(Master::new(
+                master_replid,
+                ReplicationBacklog::new(
+                    config.repl_backlog_size,
+                    Duration::from_secs(config.repl_backlog_ttl),
+                ),
+            )),
         };
 
         Self {
@@ -37,6 +86,17 @@ impl Info {
             offset: 0,
             dir,
             dbfilename,
+            rdbchecksum: config.rdbchecksum,
+            port,
+            node_id,
+            started_at,
+            replica_read_only,
+            command_stats: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            connected_clients: Arc::new(AtomicUsize::new(0)),
+            max_clients,
+            max_commands_per_sec,
+            enable_debug_commands,
+            config_params,
         }
     }
 
@@ -44,9 +104,26 @@ impl Info {
         self.role.get_master()
     }
 
-    pub fn master_replid(&self) -> Option<&str> {
+    pub fn master_replid(&self) -> Option<String> {
         match &self.role {
-            Role::Master(master) => Some(&master.master_replid),
+            Role::Master(master) => Some(master.replids.lock().unwrap().replid.clone()),
+            Role::Slave(_) => None,
+        }
+    }
+
+    /// Starts a new replication history, like after a failover,
+    /// the replicas of the previous history can only continue up to where it ended
+    pub fn change_replid(&mut self) {
+        if let Role::Master(master) = &mut self.role {
+            master.change_replid();
+        }
+    }
+
+    /// Bytes a replica needs to continue the replication stream of replid from the offset,
+    /// `None` if it needs a full resync
+    pub fn partial_resync(&self, replid: &str, offset: u64) -> Option<Vec<u8>> {
+        match &self.role {
+            Role::Master(master) => master.partial_resync(replid, offset),
             Role::Slave(_) => None,
         }
     }
@@ -73,9 +150,12 @@ impl Info {
         }
     }
 
-    pub async fn count_sync_repl(&self, count: u64, timeout: Duration) -> u64 {
+    /// Counts the replicas that acked the offset, see [`Master::count_sync_repl`].
+    /// The offset is a snapshot taken by the caller before waiting,
+    /// so the target does not move with the writes made meanwhile
+    pub async fn count_sync_repl(&self, offset: u64, count: u64, timeout: Duration) -> u64 {
         match &self.role {
-            Role::Master(master) => master.count_sync_repl(self.offset, count, timeout).await,
+            Role::Master(master) => master.count_sync_repl(offset, count, timeout).await,
             Role::Slave(_) => 0,
         }
     }
@@ -87,7 +167,7 @@ impl Info {
         }
     }
 
-    pub fn tx_repl_got_ack(&self) -> Option<&Sender<(SocketAddr, u64)>> {
+    pub fn tx_repl_got_ack(&self) -> Option<&UnboundedSender<(SocketAddr, u64)>> {
         match &self.role {
             Role::Master(master) => Some(master.tx_repl_got()),
             Role::Slave(_) => None,
@@ -104,7 +184,10 @@ impl Info {
     }
 
     pub fn offset(&self) -> u64 {
-        self.offset
+        match &self.role {
+            Role::Master(master) => master.offset(),
+            Role::Slave(_) => self.offset,
+        }
     }
 
     pub fn set_offset(&mut self, offset: u64) {
@@ -126,16 +209,316 @@ impl Info {
     pub fn dbfilename(&self) -> &str {
         &self.dbfilename
     }
+
+    pub fn rdbchecksum(&self) -> bool {
+        self.rdbchecksum
+    }
+
+    /// Path of the RDB file, `./dump.rdb` unless dir or dbfilename are configured
+    pub fn rdb_path(&self) -> String {
+        let dir = if self.dir.is_empty() { "." } else { &self.dir };
+        let dbfilename = if self.dbfilename.is_empty() {
+            "dump.rdb"
+        } else {
+            &self.dbfilename
+        };
+
+        format!("{}/{}", dir, dbfilename)
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    pub fn started_at(&self) -> SystemTime {
+        self.started_at
+    }
+
+    pub fn replica_read_only(&self) -> bool {
+        self.replica_read_only
+    }
+
+    pub fn max_clients(&self) -> usize {
+        self.max_clients
+    }
+
+    pub fn max_commands_per_sec(&self) -> u64 {
+        self.max_commands_per_sec
+    }
+
+    /// Name and value of every configuration parameter that can't change at runtime
+    pub fn all_config_params(&self) -> Vec<(String, String)> {
+        self.config_params.as_ref().clone()
+    }
+
+    pub fn enable_debug_commands(&self) -> bool {
+        self.enable_debug_commands
+    }
+
+    pub fn connected_clients(&self) -> usize {
+        self.connected_clients.load(Ordering::Relaxed)
+    }
+
+    /// Registers a new client connection.
+    /// Returns `None` if the max number of clients is reached,
+    /// else a guard that unregisters the client when dropped.
+    pub fn connect_client(&self) -> Option<ClientGuard> {
+        let max_clients = self.max_clients;
+
+        self.connected_clients
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                (count < max_clients).then_some(count + 1)
+            })
+            .ok()
+            .map(|_| ClientGuard(self.connected_clients.clone()))
+    }
+
+    /// Counters of the given command, created on the first call
+    fn command_stat(&self, name: &str) -> Arc<CommandStat> {
+        let mut stats = self.command_stats.lock().unwrap();
+
+        stats.entry(name.to_lowercase()).or_default().clone()
+    }
+
+    /// Record an executed command, `failed` if it replied with an error
+    pub fn record_command_call(&self, name: &str, duration: Duration, failed: bool) {
+        let stat = self.command_stat(name);
+
+        stat.calls.fetch_add(1, Ordering::Relaxed);
+        stat.usec_total
+            .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+        stat.latency.record(duration.as_micros() as u64);
+        if failed {
+            stat.failed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Record a command rejected before execution, e.g. because of bad arguments
+    pub fn record_command_rejected(&self, name: &str) {
+        self.command_stat(name)
+            .rejected
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The server section of INFO
+    pub fn server_section(&self) -> String {
+        let uptime = self.started_at.elapsed().unwrap_or_default().as_secs();
+
+        format!(
+            "# Server\r\nredis_version:7.2.0\r\nredis_mode:standalone\r\nprocess_id:{}\r\nrun_id:{}\r\ntcp_port:{}\r\nuptime_in_seconds:{}\r\nuptime_in_days:{}\r\n",
+            std::process::id(),
+            self.node_id,
+            self.port,
+            uptime,
+            uptime / (24 * 60 * 60),
+        )
+    }
+
+    /// The clients section of INFO
+    pub fn clients_section(&self) -> String {
+        format!(
+            "# Clients\r\nconnected_clients:{}\r\nmaxclients:{}\r\n",
+            self.connected_clients(),
+            self.max_clients
+        )
+    }
+
+    pub fn replication_section(&self) -> String {
+        format!("# Replication\r\n{}", self)
+    }
+
+    /// The commandstats section of INFO, one line per called command sorted by name
+    pub fn command_stats(&self) -> String {
+        let stats = self.command_stats.lock().unwrap();
+
+        let mut names = stats.keys().collect::<Vec<_>>();
+        names.sort();
+
+        let mut section = "# Commandstats\r\n".to_string();
+        for name in names {
+            section.push_str(&format!("cmdstat_{}:{}\r\n", name, stats[name]));
+        }
+
+        section
+    }
+
+    /// The latencystats section of INFO, the latency percentiles of each called command
+    pub fn latency_stats(&self) -> String {
+        let stats = self.command_stats.lock().unwrap();
+
+        let mut names = stats.keys().collect::<Vec<_>>();
+        names.sort();
+
+        let mut section = "# Latencystats\r\n".to_string();
+        for name in names {
+            let latency = &stats[name].latency;
+            if latency.count() == 0 {
+                continue;
+            }
+
+            let percentiles = LATENCY_PERCENTILES
+                .iter()
+                .map(|p| format!("p{}={:.3}", p, latency.percentile(*p)))
+                .collect::<Vec<_>>()
+                .join(",");
+            section.push_str(&format!(
+                "latency_percentiles_usec_{}:{}\r\n",
+                name, percentiles
+            ));
+        }
+
+        section
+    }
+}
+
+/// Percentiles reported by INFO latencystats
+const LATENCY_PERCENTILES: [f64; 3] = [50.0, 99.0, 99.9];
+
+/// Sub-buckets of each power of two of a [`LatencyHistogram`]
+const LATENCY_SUB_BUCKETS: u64 = 8;
+
+/// Histogram of latencies in microseconds, with a bucket per eighth of each power of two,
+/// so the percentiles are within 12.5% of the recorded latencies
+#[derive(Debug)]
+pub struct LatencyHistogram {
+    buckets: Vec<AtomicU64>,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        let buckets = (0..LatencyHistogram::bucket(u64::MAX) + 1)
+            .map(|_| AtomicU64::new(0))
+            .collect();
+
+        Self { buckets }
+    }
+}
+
+impl LatencyHistogram {
+    /// Index of the bucket of the latency, the latencies below 8 have a bucket each
+    fn bucket(usec: u64) -> usize {
+        if usec < LATENCY_SUB_BUCKETS {
+            return usec as usize;
+        }
+
+        let exp = 63 - usec.leading_zeros() as u64;
+        let sub = (usec >> (exp - 3)) & (LATENCY_SUB_BUCKETS - 1);
+
+        ((exp - 2) * LATENCY_SUB_BUCKETS + sub) as usize
+    }
+
+    /// Highest latency counted in the bucket
+    fn bucket_max(index: usize) -> u64 {
+        let index = index as u64;
+        if index < LATENCY_SUB_BUCKETS {
+            return index;
+        }
+
+        let exp = index / LATENCY_SUB_BUCKETS + 2;
+        let sub = index % LATENCY_SUB_BUCKETS;
+
+        ((LATENCY_SUB_BUCKETS + sub + 1) << (exp - 3)).wrapping_sub(1)
+    }
+
+    fn record(&self, usec: u64) {
+        self.buckets[Self::bucket(usec)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn count(&self) -> u64 {
+        self.buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Latency under which the percentage of the recorded latencies falls
+    fn percentile(&self, percentile: f64) -> f64 {
+        let target = ((self.count() as f64 * percentile / 100.0).ceil() as u64).max(1);
+
+        let mut seen = 0;
+        for (index, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket.load(Ordering::Relaxed);
+            if seen >= target {
+                return Self::bucket_max(index) as f64;
+            }
+        }
+
+        0.0
+    }
+}
+
+/// Connected client, unregistered when dropped
+#[derive(Debug)]
+pub struct ClientGuard(Arc<AtomicUsize>);
+
+impl Drop for ClientGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
 }
 
-impl ToString for Info {
-    fn to_string(&self) -> String {
+/// Execution counters of a single command
+#[derive(Debug, Default)]
+pub struct CommandStat {
+    calls: AtomicU64,
+    usec_total: AtomicU64,
+    rejected: AtomicU64,
+    failed: AtomicU64,
+    latency: LatencyHistogram,
+}
+
+impl fmt::Display for CommandStat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let calls = self.calls.load(Ordering::Relaxed);
+        let usec = self.usec_total.load(Ordering::Relaxed);
+        let usec_per_call = if calls == 0 {
+            0.0
+        } else {
+            usec as f64 / calls as f64
+        };
+
+        write!(
+            f,
+            "calls={},usec={},usec_per_call={:.2},rejected_calls={},failed_calls={}",
+            calls,
+            usec,
+            usec_per_call,
+            self.rejected.load(Ordering::Relaxed),
+            self.failed.load(Ordering::Relaxed)
+        )
+    }
+}
+
+impl fmt::Display for Info {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.role {
-            Role::Master(master) => format!(
-                "role:master\r\nmaster_replid:{}\r\nmaster_repl_offset:{}\r\n",
-                master.master_replid, self.offset
-            ),
-            Role::Slave(_) => "role:slave\r\n".to_string(),
+            Role::Master(master) => {
+                let replids = master.replids.lock().unwrap();
+
+                write!(
+                    f,
+                    "role:master\r\nmaster_replid:{}\r\nmaster_replid2:{}\r\nmaster_repl_offset:{}\r\nsecond_repl_offset:{}\r\n",
+                    replids.replid,
+                    replids.replid2,
+                    master.offset(),
+                    replids
+                        .replid2_offset
+                        .map_or(-1, |offset| offset as i64)
+                )?;
+
+                let mut backlog = master.backlog.lock().unwrap();
+                backlog.expire(master.replicas_count());
+
+                write!(
+                    f,
+                    "repl_backlog_active:{}\r\nrepl_backlog_size:{}\r\nrepl_backlog_first_byte_offset:{}\r\nrepl_backlog_histlen:{}\r\n",
+                    backlog.is_active() as u8,
+                    backlog.size(),
+                    backlog.first_byte_offset(),
+                    backlog.histlen()
+                )
+            }
+            Role::Slave(_) => write!(f, "role:slave\r\n"),
         }
     }
 }
@@ -165,24 +548,74 @@ impl Role {
 #[derive(Clone, Debug)]
 pub struct Master {
     replicas: Arc<std::sync::Mutex<Vec<Replica>>>,
-    master_replid: String,
+    /// Replication ids, shared by all the connections
+    replids: Arc<std::sync::Mutex<ReplIds>>,
+    /// Backlog of the replication stream, shared by all the connections
+    backlog: Arc<std::sync::Mutex<ReplicationBacklog>>,
     /// Sender to send acks from replicas
-    tx_repl_got_ack: Sender<(SocketAddr, u64)>,
+    tx_repl_got_ack: UnboundedSender<(SocketAddr, u64)>,
     /// Receiver to receive acks from replicas
-    rx_repl_got_ack: Arc<Mutex<Receiver<(SocketAddr, u64)>>>,
+    /// Async so the connections of the replicas keep being served while WAIT waits
+    rx_repl_got_ack: Arc<Mutex<UnboundedReceiver<(SocketAddr, u64)>>>,
+}
+
+#[derive(Debug)]
+struct ReplIds {
+    replid: String,
+    /// Previous replication id, set when the id is changed
+    replid2: String,
+    /// Offset after which the previous history ended, `None` without previous id
+    replid2_offset: Option<u64>,
 }
 
 impl Master {
-    pub fn new(master_replid: String) -> Self {
-        let (tx, rx) = mpsc::channel();
+    pub fn new(master_replid: String, backlog: ReplicationBacklog) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
         Self {
             replicas: Arc::new(std::sync::Mutex::new(Vec::new())),
-            master_replid,
+            replids: Arc::new(std::sync::Mutex::new(ReplIds {
+                replid: master_replid,
+                replid2: "0".repeat(40),
+                replid2_offset: None,
+            })),
+            backlog: Arc::new(std::sync::Mutex::new(backlog)),
             tx_repl_got_ack: tx,
             rx_repl_got_ack: Arc::new(Mutex::new(rx)),
         }
     }
 
+    /// Offset of the replication stream, after the last byte sent to the replicas
+    pub fn offset(&self) -> u64 {
+        self.backlog.lock().unwrap().offset()
+    }
+
+    /// Generates a new replication id, keeping the current one as the previous id.
+    /// The offset goes on with the new history, so the replicas of the previous one
+    /// can continue from the backlog up to where it ended.
+    pub fn change_replid(&mut self) {
+        let mut replids = self.replids.lock().unwrap();
+
+        let replid = std::mem::replace(&mut replids.replid, random::hex_id(40));
+        replids.replid2 = replid;
+        replids.replid2_offset = Some(self.backlog.lock().unwrap().offset() + 1);
+    }
+
+    /// Bytes of the backlog from the offset, if the replid is the current id,
+    /// or the previous one and the offset is in its history
+    fn partial_resync(&self, replid: &str, offset: u64) -> Option<Vec<u8>> {
+        let replids = self.replids.lock().unwrap();
+
+        let known = replid == replids.replid
+            || (replid == replids.replid2
+                && replids.replid2_offset.is_some_and(|end| offset <= end));
+        if !known {
+            return None;
+        }
+        drop(replids);
+
+        self.backlog.lock().unwrap().bytes_from(offset)
+    }
+
     pub fn add_replica(
         &mut self,
         addr: (String, u16),
@@ -191,6 +624,7 @@ impl Master {
         let replica = Replica::new(addr, connection);
 
         self.replicas.lock().unwrap().push(replica);
+        self.backlog.lock().unwrap().activate();
 
         Ok(())
     }
@@ -209,7 +643,9 @@ impl Master {
     /// Count the number of replicas that have synced with the master
     /// up to the given offset
     /// Function will wait for the timeout duration for the replicas to ack
-    /// the offset
+    /// the offset, the replicas whose last ack already reached it are counted without waiting,
+    /// GETACK is not sent at all if they are enough.
+    /// With a zero timeout, the acks already received are counted without waiting.
     ///
     /// # Returns
     ///
@@ -229,6 +665,11 @@ impl Master {
         let mut synced_replicas = 0;
         let replicas_count = self.replicas_count() as u64;
 
+        // Without replicas there is nothing to wait for, whatever the offset
+        if replicas_count == 0 {
+            return 0;
+        }
+
         let target_count = target_count.min(replicas_count);
 
         // Master has not written any commands
@@ -237,27 +678,58 @@ impl Master {
             return replicas_count;
         }
 
-        // Propagate the GETACK command to all replicas
+        // Replicas that already acked the offset are synced,
+        // only the acks of the lagging ones are waited for
+        let mut lagging = {
+            let replicas = self.replicas.lock().unwrap();
+
+            replicas
+                .iter()
+                .filter(|replica| replica.replication_offset < master_offset)
+                .map(|replica| replica.connection.addr())
+                .collect::<HashSet<_>>()
+        };
+        synced_replicas += replicas_count - lagging.len() as u64;
+
+        if synced_replicas >= target_count {
+            return synced_replicas;
+        }
+
+        // GETACK is part of the replication stream, like in Redis,
+        // so it is added to the backlog and sent to every replica to keep their offsets in step
         let getack = ReplConf::GetAck;
-        let frame = getack.to_frame();
-        self.propagate(frame).await.unwrap();
+        let _ = self.propagate_in_seq(getack.to_frame()).await;
 
-        let rx = self.rx_repl_got_ack.lock().unwrap();
+        let mut rx = self.rx_repl_got_ack.lock().await;
 
-        // Wait for acks from the replicas
+        // Without timeout only the acks already received are counted
+        if timeout.is_zero() {
+            while let Ok((sock_addr, offset)) = rx.try_recv() {
+                if offset >= master_offset && lagging.remove(&sock_addr) {
+                    synced_replicas += 1;
+                }
+            }
+
+            return synced_replicas;
+        }
+
+        let deadline = Instant::now() + timeout;
+
+        // Wait for acks from the lagging replicas
         loop {
-            match rx.recv_timeout(timeout) {
-                Ok((_sock_addr, offset)) => {
+            match time::timeout_at(deadline, rx.recv()).await {
+                Ok(Some((sock_addr, offset))) => {
                     println!("Received ack");
 
-                    if offset >= master_offset {
+                    // Each replica is counted once, late acks of the others are ignored
+                    if offset >= master_offset && lagging.remove(&sock_addr) {
                         synced_replicas += 1;
                     }
                     if synced_replicas >= target_count {
                         break;
                     }
                 }
-                Err(_) => {
+                Ok(None) | Err(_) => {
                     println!("Timeout");
                     break;
                 }
@@ -272,11 +744,21 @@ impl Master {
         synced_replicas
     }
 
+    /// Adds the frame sent to the replicas to the backlog
+    fn append_backlog(&self, frame: &Frame) {
+        let mut backlog = self.backlog.lock().unwrap();
+
+        backlog.expire(self.replicas_count());
+        backlog.append(&frame.encode());
+    }
+
     /// Propagate the given frame to all replicas
     /// This function will send the frame to all replicas
     /// immidiately without waiting for the previous replica to ack
     /// the frame
     pub async fn propagate(&self, frame: Frame) -> crate::Result<()> {
+        self.append_backlog(&frame);
+
         let connections = {
             let mut replicas = self.replicas.lock().unwrap();
 
@@ -306,6 +788,8 @@ impl Master {
 
     /// Propagate the given frame to all replicas in sequence
     pub async fn propagate_in_seq(&self, frame: Frame) -> crate::Result<()> {
+        self.append_backlog(&frame);
+
         let connections = {
             let mut replicas = self.replicas.lock().unwrap();
 
@@ -328,7 +812,7 @@ impl Master {
         self.replicas.lock().unwrap().len()
     }
 
-    pub fn tx_repl_got(&self) -> &mpsc::Sender<(SocketAddr, u64)> {
+    pub fn tx_repl_got(&self) -> &UnboundedSender<(SocketAddr, u64)> {
         &self.tx_repl_got_ack
     }
 }
@@ -368,11 +852,124 @@ impl Slave {
     }
 }
 
-impl ToString for Role {
-    fn to_string(&self) -> String {
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Role::Master(_) => "role:master".into(),
-            Role::Slave(_) => "role:slave".into(),
+            Role::Master(_) => write!(f, "role:master"),
+            Role::Slave(_) => write!(f, "role:slave"),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn master() -> Master {
+        Master::new(
+            "0".repeat(40),
+            ReplicationBacklog::new(1024, Duration::ZERO),
+        )
+    }
+
+    /// A master with a replica connected over a local socket,
+    /// returns the replica end of the socket
+    async fn master_with_replica() -> (Master, tokio::net::TcpStream) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let replica = tokio::net::TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (stream, addr) = listener.accept().await.unwrap();
+
+        let mut master = master();
+        master
+            .add_replica(
+                ("127.0.0.1".to_string(), 6380),
+                Connection::new(stream, addr),
+            )
+            .unwrap();
+
+        (master, replica)
+    }
+
+    #[tokio::test]
+    async fn synced_replica_is_not_sent_getack() {
+        let (mut master, replica) = master_with_replica().await;
+        master.append_backlog(&Frame::Simple("PING".into()));
+        let offset = master.offset();
+        let addr = replica.local_addr().unwrap();
+        master.update_replica_offset(addr, offset);
+
+        let synced = master
+            .count_sync_repl(offset, 1, Duration::from_millis(100))
+            .await;
+
+        assert_eq!(synced, 1);
+        assert_eq!(master.offset(), offset);
+        let mut buf = [0; 64];
+        assert!(replica.try_read(&mut buf).is_err());
+    }
+
+    #[tokio::test]
+    async fn getack_is_part_of_replication_stream() {
+        let (master, mut replica) = master_with_replica().await;
+        master.append_backlog(&Frame::Simple("PING".into()));
+        let offset = master.offset();
+
+        let synced = master.count_sync_repl(offset, 1, Duration::ZERO).await;
+
+        let getack = ReplConf::GetAck.to_frame().encode();
+        assert_eq!(synced, 0);
+        assert_eq!(master.offset(), offset + getack.len() as u64);
+
+        let mut buf = vec![0; getack.len()];
+        tokio::io::AsyncReadExt::read_exact(&mut replica, &mut buf)
+            .await
+            .unwrap();
+        assert_eq!(buf, getack);
+    }
+
+    #[test]
+    fn change_replid_keeps_shared_offset() {
+        let master = master();
+        let mut other_connection = master.clone();
+        master.append_backlog(&Frame::Simple("PING".into()));
+        let offset = master.offset();
+
+        other_connection.change_replid();
+
+        let replids = master.replids.lock().unwrap();
+        assert_eq!(master.offset(), offset);
+        assert_eq!(replids.replid2, "0".repeat(40));
+        assert_ne!(replids.replid, replids.replid2);
+        assert_eq!(replids.replid2_offset, Some(offset + 1));
+    }
+
+    #[test]
+    fn previous_replid_continues_up_to_the_end_of_its_history() {
+        let mut master = master();
+        master.backlog.lock().unwrap().activate();
+        master.append_backlog(&Frame::Simple("PING".into()));
+        let end = master.offset() + 1;
+
+        master.change_replid();
+        let ping = Frame::Simple("PING".into()).encode();
+        master.append_backlog(&Frame::Simple("PING".into()));
+
+        let old_replid = "0".repeat(40);
+        assert_eq!(master.partial_resync(&old_replid, end), Some(ping.to_vec()));
+        assert_eq!(
+            master.partial_resync(&old_replid, 1).unwrap().len(),
+            2 * ping.len()
+        );
+        assert_eq!(master.partial_resync(&old_replid, end + 1), None);
+
+        let replid = master.replids.lock().unwrap().replid.clone();
+        assert_eq!(master.partial_resync(&replid, end), Some(ping.to_vec()));
+        assert_eq!(
+            master.partial_resync(&replid, master.offset() + 1),
+            Some(Vec::new())
+        );
+        assert_eq!(master.partial_resync(&"1".repeat(40), end), None);
+    }
+}
@@ -2,11 +2,16 @@ mod command;
 mod config;
 mod connection;
 mod db;
+mod error;
 mod frame;
+mod glob;
 mod info;
 mod parse;
+mod random;
+mod rate_limit;
 mod replicaiton;
 mod server;
+mod session;
 
 pub type Error = Box<dyn std::error::Error + Send + Sync>;
 pub type Result<T> = std::result::Result<T, Error>;
@@ -14,7 +19,7 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub use command::Command;
 pub use config::Config;
 pub use connection::Connection;
-pub use db::Db;
+pub use db::{Db, EncodingConfig};
 pub use frame::Frame;
 pub use info::Info;
 pub use parse::Parse;
@@ -1,6 +1,6 @@
 use std::env;
 
-use redis_starter_rust::{Config, Db, RedisDB, Server};
+use redis_starter_rust::{Config, Db, EncodingConfig, RedisDB, Server};
 use tokio::io;
 
 #[tokio::main]
@@ -28,11 +28,27 @@ async fn init_db(config: &Config) -> Db {
     let rdb_filename = format!("{}/{}", config.dir, config.db_filename);
     let mut rdb = RedisDB::new(rdb_filename);
 
+    // Encoding limits must be known before loading the values
+    let db = Db::new();
+    db.set_encoding_config(EncodingConfig {
+        hash_max_listpack_entries: config.hash_max_listpack_entries,
+        hash_max_listpack_value: config.hash_max_listpack_value,
+        list_max_listpack_size: config.list_max_listpack_size,
+        set_max_intset_entries: config.set_max_intset_entries,
+        set_max_listpack_entries: config.set_max_listpack_entries,
+        set_max_listpack_value: config.set_max_listpack_value,
+        zset_max_listpack_entries: config.zset_max_listpack_entries,
+        zset_max_listpack_value: config.zset_max_listpack_value,
+    });
+    db.set_lazyfree_server_del(config.lazyfree_lazy_server_del);
+
     match rdb.read_rdb().await {
-        Ok(db_from_file) => Db::from_rdb(db_from_file),
-        Err(err) => {
-            eprintln!("Error reading RDB file: {}", err);
-            Db::new()
+        Ok(db_from_file) => {
+            db.load_rdb(db_from_file);
+            db.load_functions(rdb.functions());
         }
+        Err(err) => eprintln!("Error reading RDB file: {}", err),
     }
+
+    db
 }
@@ -88,7 +88,7 @@ impl Parse {
         match self.next_frame()? {
             Frame::Integer(n) => {
                 self.bytes_read += 8;
-                Ok(n)
+                n.try_into().map_err(|_| ERROR_MSG.into())
             }
             Frame::Simple(s) => {
                 self.bytes_read += 8;
@@ -108,7 +108,7 @@ impl Parse {
         match self.next_frame()? {
             Frame::Integer(n) => {
                 self.bytes_read += 8;
-                Ok(n.try_into().unwrap())
+                Ok(n)
             }
             Frame::Simple(s) => {
                 self.bytes_read += 8;
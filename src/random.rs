@@ -0,0 +1,100 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::SystemTime,
+};
+
+/// Returns a random `u64`
+/// Seeded from the std hasher random keys and the current time,
+/// good enough for ids and sampling, not for cryptography
+pub fn next_u64() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    hasher.write_u128(nanos);
+
+    hasher.finish()
+}
+
+/// Returns a random hex string of the given length
+/// Used for the run ids, like `master_replid` or the cluster node id
+pub fn hex_id(len: usize) -> String {
+    let mut id = String::with_capacity(len);
+
+    while id.len() < len {
+        id.push_str(&format!("{:016x}", next_u64()));
+    }
+
+    id.truncate(len);
+    id
+}
+
+/// Returns a random number in the range `0..upper`
+///
+/// # Panics
+///
+/// Panics if `upper` is 0.
+pub fn gen_range(upper: usize) -> usize {
+    (next_u64() % upper as u64) as usize
+}
+
+/// Returns up to `count` distinct random items of the slice
+pub fn sample<T: Clone>(items: &[T], count: usize) -> Vec<T> {
+    let mut items = items.to_vec();
+    let count = count.min(items.len());
+
+    // Partial Fisher-Yates shuffle: the first `count` items are the sample
+    for i in 0..count {
+        let j = i + gen_range(items.len() - i);
+        items.swap(i, j);
+    }
+
+    items.truncate(count);
+    items
+}
+
+/// Returns exactly `count` random items of the slice, items may repeat
+/// Returns an empty vector if the slice is empty
+pub fn sample_with_replacement<T: Clone>(items: &[T], count: usize) -> Vec<T> {
+    if items.is_empty() {
+        return Vec::new();
+    }
+
+    (0..count)
+        .map(|_| items[gen_range(items.len())].clone())
+        .collect()
+}
+
+/// Random numbers generator with a fixed seed (splitmix64),
+/// for outputs that must be reproducible, like the LOLWUT art
+#[derive(Debug)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random number in the range `0..upper`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `upper` is 0.
+    pub fn gen_range(&mut self, upper: usize) -> usize {
+        (self.next_u64() % upper as u64) as usize
+    }
+}
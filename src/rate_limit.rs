@@ -0,0 +1,61 @@
+use std::time::Instant;
+
+/// Token bucket limiting the commands of a connection,
+/// holding at most a second worth of tokens so short bursts are allowed
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Tokens added per second, 0 for unlimited
+    rate: u64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(rate: u64) -> Self {
+        Self {
+            rate,
+            tokens: rate as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Takes a token for a command.
+    /// Returns false if the bucket is empty and the command must be rejected.
+    pub fn try_acquire(&mut self) -> bool {
+        if self.rate == 0 {
+            return true;
+        }
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate as f64).min(self.rate as f64);
+        self.last_refill = now;
+
+        if self.tokens < 1.0 {
+            return false;
+        }
+
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_past_the_rate_are_rejected() {
+        let mut limiter = RateLimiter::new(5);
+
+        assert!((0..5).all(|_| limiter.try_acquire()));
+        assert!(!limiter.try_acquire());
+    }
+
+    #[test]
+    fn zero_rate_is_unlimited() {
+        let mut limiter = RateLimiter::new(0);
+
+        assert!((0..10_000).all(|_| limiter.try_acquire()));
+    }
+}
@@ -0,0 +1,166 @@
+use std::{collections::VecDeque, time::Duration};
+
+use tokio::time::Instant;
+
+/// Last bytes of the replication stream, kept so a replica can continue
+/// from its offset after a reconnection instead of doing a full resync
+#[derive(Debug)]
+pub struct ReplicationBacklog {
+    /// Bytes of the stream, `None` until the first replica connects
+    /// and after the backlog is freed
+    buf: Option<VecDeque<u8>>,
+    /// Max number of bytes kept
+    size: usize,
+    /// Offset of the replication stream, after its last byte
+    offset: u64,
+    /// Time without replicas after which the backlog is freed, 0 to never free it
+    ttl: Duration,
+    /// Since when there are no replicas connected
+    without_replicas_since: Option<Instant>,
+}
+
+impl ReplicationBacklog {
+    pub fn new(size: usize, ttl: Duration) -> Self {
+        Self {
+            buf: None,
+            size,
+            offset: 0,
+            ttl,
+            without_replicas_since: None,
+        }
+    }
+
+    /// Creates the backlog when a replica connects, if it is not active yet
+    pub fn activate(&mut self) {
+        self.without_replicas_since = None;
+
+        if self.buf.is_none() {
+            self.buf = Some(VecDeque::new());
+        }
+    }
+
+    /// Adds the bytes sent to the replicas, dropping the oldest bytes over the size
+    pub fn append(&mut self, bytes: &[u8]) {
+        self.offset += bytes.len() as u64;
+
+        if let Some(buf) = &mut self.buf {
+            buf.extend(bytes);
+
+            let excess = buf.len().saturating_sub(self.size);
+            buf.drain(..excess);
+        }
+    }
+
+    /// Frees the backlog once there were no replicas for the whole ttl
+    pub fn expire(&mut self, replicas_count: usize) {
+        if replicas_count > 0 || self.buf.is_none() {
+            self.without_replicas_since = None;
+            return;
+        }
+
+        let since = *self.without_replicas_since.get_or_insert_with(Instant::now);
+        if !self.ttl.is_zero() && since.elapsed() >= self.ttl {
+            self.buf = None;
+            self.without_replicas_since = None;
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.buf.is_some()
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Number of bytes of the stream in the backlog
+    pub fn histlen(&self) -> usize {
+        self.buf.as_ref().map_or(0, |buf| buf.len())
+    }
+
+    /// Offset of the first byte in the backlog, the offsets start at 1
+    pub fn first_byte_offset(&self) -> u64 {
+        match self.buf {
+            Some(_) => self.offset - self.histlen() as u64 + 1,
+            None => 0,
+        }
+    }
+
+    /// Offset of the replication stream, after its last byte
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Bytes of the stream from the offset to the end, the offset of the next byte
+    /// a replica needs to continue. `None` if the offset is not in the backlog anymore
+    pub fn bytes_from(&self, offset: u64) -> Option<Vec<u8>> {
+        let buf = self.buf.as_ref()?;
+
+        if offset < self.first_byte_offset() || offset > self.offset + 1 {
+            return None;
+        }
+
+        let start = (offset - self.first_byte_offset()) as usize;
+        Some(buf.range(start..).copied().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backlog(size: usize) -> ReplicationBacklog {
+        let mut backlog = ReplicationBacklog::new(size, Duration::ZERO);
+        backlog.activate();
+        backlog
+    }
+
+    #[test]
+    fn inactive_backlog_only_counts_the_offset() {
+        let mut backlog = ReplicationBacklog::new(16, Duration::ZERO);
+        backlog.append(b"abc");
+
+        assert_eq!(backlog.offset(), 3);
+        assert_eq!(backlog.histlen(), 0);
+        assert_eq!(backlog.first_byte_offset(), 0);
+        assert_eq!(backlog.bytes_from(1), None);
+    }
+
+    #[test]
+    fn bytes_from_the_offset_to_the_end() {
+        let mut backlog = backlog(16);
+        backlog.append(b"abc");
+        backlog.append(b"de");
+
+        assert_eq!(backlog.first_byte_offset(), 1);
+        assert_eq!(backlog.offset(), 5);
+        assert_eq!(backlog.bytes_from(1), Some(b"abcde".to_vec()));
+        assert_eq!(backlog.bytes_from(4), Some(b"de".to_vec()));
+        // A replica that has everything continues with nothing
+        assert_eq!(backlog.bytes_from(6), Some(Vec::new()));
+        assert_eq!(backlog.bytes_from(7), None);
+    }
+
+    #[test]
+    fn oldest_bytes_are_dropped_over_the_size() {
+        let mut backlog = backlog(4);
+        backlog.append(b"abcdef");
+
+        assert_eq!(backlog.histlen(), 4);
+        assert_eq!(backlog.first_byte_offset(), 3);
+        assert_eq!(backlog.bytes_from(3), Some(b"cdef".to_vec()));
+        assert_eq!(backlog.bytes_from(2), None);
+    }
+
+    #[test]
+    fn backlog_is_freed_after_the_ttl_without_replicas() {
+        let mut backlog = ReplicationBacklog::new(16, Duration::from_millis(1));
+        backlog.activate();
+
+        backlog.expire(0);
+        std::thread::sleep(Duration::from_millis(5));
+        backlog.expire(0);
+
+        assert!(!backlog.is_active());
+    }
+}
@@ -0,0 +1,204 @@
+//! Listpack serialization, used by the RDB stream encoding
+//!
+//! A listpack is a `u32` total size, a `u16` number of elements, the elements
+//! and a `0xFF` terminator. Each element is its encoding, its data and the
+//! length of both (the backlen) so the listpack can be walked backwards.
+
+/// Listpack element, integers and strings are encoded differently
+#[derive(Debug, Clone, PartialEq)]
+pub enum Element {
+    Int(i64),
+    Str(Vec<u8>),
+}
+
+impl Element {
+    /// Returns the element as an integer, strings are parsed
+    pub fn as_int(&self) -> crate::Result<i64> {
+        match self {
+            Element::Int(int) => Ok(*int),
+            Element::Str(string) => std::str::from_utf8(string)?
+                .parse()
+                .map_err(|_| "Invalid listpack integer".into()),
+        }
+    }
+
+    /// Returns the element as bytes, integers are formatted
+    pub fn into_bytes(self) -> Vec<u8> {
+        match self {
+            Element::Int(int) => int.to_string().into_bytes(),
+            Element::Str(string) => string,
+        }
+    }
+}
+
+const TERMINATOR: u8 = 0xFF;
+/// Size of the total size and number of elements header
+const HEADER_SIZE: usize = 6;
+
+/// Builds a listpack element by element
+#[derive(Debug, Default)]
+pub struct ListPackWriter {
+    elements: Vec<u8>,
+    count: usize,
+}
+
+impl ListPackWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an integer, always with the 64 bit encoding
+    pub fn push_int(&mut self, int: i64) {
+        let mut element = vec![0xF4];
+        element.extend_from_slice(&int.to_le_bytes());
+
+        self.push_element(element);
+    }
+
+    /// Appends a string with the smallest length encoding that fits
+    pub fn push_str(&mut self, string: &[u8]) {
+        let len = string.len();
+
+        let mut element = if len < 64 {
+            vec![0x80 | len as u8]
+        } else if len < 4096 {
+            vec![0xE0 | (len >> 8) as u8, len as u8]
+        } else {
+            let mut header = vec![0xF0];
+            header.extend_from_slice(&(len as u32).to_le_bytes());
+            header
+        };
+        element.extend_from_slice(string);
+
+        self.push_element(element);
+    }
+
+    fn push_element(&mut self, element: Vec<u8>) {
+        let backlen = encode_backlen(element.len());
+
+        self.elements.extend(element);
+        self.elements.extend(backlen);
+        self.count += 1;
+    }
+
+    /// Returns the serialized listpack
+    pub fn finish(self) -> Vec<u8> {
+        let total_size = HEADER_SIZE + self.elements.len() + 1;
+        // The count saturates, readers have to walk the listpack to know the real one
+        let count = self.count.min(u16::MAX as usize) as u16;
+
+        let mut listpack = Vec::with_capacity(total_size);
+        listpack.extend_from_slice(&(total_size as u32).to_le_bytes());
+        listpack.extend_from_slice(&count.to_le_bytes());
+        listpack.extend(self.elements);
+        listpack.push(TERMINATOR);
+
+        listpack
+    }
+}
+
+/// Returns all the elements of the serialized listpack
+pub fn read(listpack: &[u8]) -> crate::Result<Vec<Element>> {
+    let mut pos = HEADER_SIZE;
+    let mut elements = Vec::new();
+
+    loop {
+        let encoding = *listpack.get(pos).ok_or("Listpack reached end")?;
+        if encoding == TERMINATOR {
+            return Ok(elements);
+        }
+
+        let (element, size) = read_element(&listpack[pos..])?;
+        elements.push(element);
+
+        pos += size + backlen_size(size);
+    }
+}
+
+/// Reads the element at the start of the bytes
+/// Returns the element and the size of its encoding and data, without the backlen
+fn read_element(bytes: &[u8]) -> crate::Result<(Element, usize)> {
+    let get = |range: std::ops::Range<usize>| -> crate::Result<&[u8]> {
+        bytes
+            .get(range)
+            .ok_or_else(|| "Listpack reached end".into())
+    };
+
+    let encoding = bytes[0];
+
+    let (element, size) = match encoding {
+        // 7 bit unsigned integer
+        0x00..=0x7F => (Element::Int(encoding as i64), 1),
+        // String up to 63 bytes
+        0x80..=0xBF => {
+            let len = (encoding & 0x3F) as usize;
+            (Element::Str(get(1..1 + len)?.to_vec()), 1 + len)
+        }
+        // 13 bit signed integer
+        0xC0..=0xDF => {
+            let uint = (((encoding & 0x1F) as u16) << 8) | get(1..2)?[0] as u16;
+            // Sign extend the 13 bits
+            let int = ((uint << 3) as i16 >> 3) as i64;
+            (Element::Int(int), 2)
+        }
+        // String up to 4095 bytes
+        0xE0..=0xEF => {
+            let len = (((encoding & 0x0F) as usize) << 8) | get(1..2)?[0] as usize;
+            (Element::Str(get(2..2 + len)?.to_vec()), 2 + len)
+        }
+        0xF0 => {
+            let len = u32::from_le_bytes(get(1..5)?.try_into()?) as usize;
+            (Element::Str(get(5..5 + len)?.to_vec()), 5 + len)
+        }
+        // 16, 24, 32 and 64 bit signed integers
+        0xF1..=0xF4 => {
+            let width = match encoding {
+                0xF1 => 2,
+                0xF2 => 3,
+                0xF3 => 4,
+                _ => 8,
+            };
+
+            let mut buf = [0; 8];
+            buf[..width].copy_from_slice(get(1..1 + width)?);
+            // Sign extend to 64 bits
+            let shift = 64 - width as u32 * 8;
+            let int = (i64::from_le_bytes(buf) << shift) >> shift;
+
+            (Element::Int(int), 1 + width)
+        }
+        _ => return Err(format!("Invalid listpack encoding {}", encoding).into()),
+    };
+
+    Ok((element, size))
+}
+
+/// Encodes the size of an element, the bytes are read from right to left
+/// and all but the last one read have their high bit set
+fn encode_backlen(size: usize) -> Vec<u8> {
+    let len = backlen_size(size);
+
+    (0..len)
+        .map(|i| {
+            let byte = ((size >> (7 * (len - 1 - i))) & 0x7F) as u8;
+
+            // The first byte holds the most significant bits and is the last one read
+            if i == 0 {
+                byte
+            } else {
+                byte | 0x80
+            }
+        })
+        .collect()
+}
+
+/// Number of bytes of the backlen of an element of the given size
+fn backlen_size(size: usize) -> usize {
+    match size {
+        0..=127 => 1,
+        128..=16382 => 2,
+        16383..=2097150 => 3,
+        2097151..=268435454 => 4,
+        _ => 5,
+    }
+}
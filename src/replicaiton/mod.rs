@@ -1 +1,3 @@
+pub mod backlog;
+pub mod listpack;
 pub mod rdb;
@@ -8,8 +8,41 @@ use base64::{self, Engine};
 use bytes::Bytes;
 use tokio::{fs::File, io::AsyncReadExt};
 
+use super::listpack::{self, ListPackWriter};
+
 const EMPTY_RDB_BASE64: &[u8] = b"UkVESVMwMDEx+glyZWRpcy12ZXIFNy4yLjD6CnJlZGlzLWJpdHPAQPoFY3RpbWXCbQi8ZfoIdXNlZC1tZW3CsMQQAPoIYW9mLWJhc2XAAP/wbjv+wP9aog==";
 
+/// RDB version written by [`RedisDB::write_rdb`]
+const RDB_VERSION: &[u8] = b"0011";
+/// RDB version in the footer of the DUMP payloads, as a number
+const DUMP_RDB_VERSION: u16 = 11;
+
+/// Reflected polynomial of the CRC-64/Jones checksum used by Redis
+const CRC64_POLY: u64 = 0x95ac9329ac4bc9b5;
+/// CRC of every byte value, so the checksum is computed a byte at a time
+const CRC64_TABLE: [u64; 256] = crc64_table();
+
+/// Stream entry flags in the listpack encoding
+const STREAM_ITEM_FLAG_DELETED: i64 = 1;
+const STREAM_ITEM_FLAG_SAMEFIELDS: i64 = 2;
+
+/// Id of a stream entry: milliseconds and sequence number
+pub type RdbStreamId = (u64, u64);
+
+/// Value of a key loaded from or saved to an RDB file
+#[derive(Debug, Clone, PartialEq)]
+pub enum RdbValue {
+    String(Bytes),
+    /// Elements from head to tail
+    List(Vec<Bytes>),
+    Set(Vec<Bytes>),
+    Hash(Vec<(Bytes, Bytes)>),
+    /// Members with their scores
+    ZSet(Vec<(Bytes, f64)>),
+    /// Entries with their field-value pairs, ordered by id
+    Stream(Vec<(RdbStreamId, Vec<(Bytes, Bytes)>)>),
+}
+
 pub fn empty_rdb() -> Bytes {
     let decoded_bytes = base64::prelude::BASE64_STANDARD
         .decode(EMPTY_RDB_BASE64)
@@ -28,6 +61,8 @@ enum RdbOpCode {
     ResizeDB,
     /// Auxiliary fields
     Aux,
+    /// Code of a function library
+    Function2,
 }
 
 impl RdbOpCode {
@@ -39,11 +74,11 @@ impl RdbOpCode {
             0xFC => Ok(RdbOpCode::ExpireTimeMs),
             0xFB => Ok(RdbOpCode::ResizeDB),
             0xFA => Ok(RdbOpCode::Aux),
+            0xF5 => Ok(RdbOpCode::Function2),
             _ => Err(format!("Invalid RDB opcode {}", value).into()),
         }
     }
 
-    #[allow(dead_code)]
     fn to_u8(&self) -> u8 {
         match self {
             RdbOpCode::Eof => 0xFF,
@@ -52,6 +87,7 @@ impl RdbOpCode {
             RdbOpCode::ExpireTimeMs => 0xFC,
             RdbOpCode::ResizeDB => 0xFB,
             RdbOpCode::Aux => 0xFA,
+            RdbOpCode::Function2 => 0xF5,
         }
     }
 }
@@ -77,31 +113,47 @@ impl RdbEncodingLen {
                 Ok(RdbEncodingLen::Bit14(value as u64))
             }
             128 => {
+                // 0x80 is followed by a 32 bit length, 0x81 by a 64 bit one
+                let len_bytes = if first_byte == 0x81 { 8 } else { 4 };
+
                 let mut val: u64 = 0;
-                for _ in 0..4 {
+                for _ in 0..len_bytes {
                     let next_byte = bytes.next().ok_or("Iter reached end")?;
                     val = (val << 8) | next_byte as u64;
                 }
                 Ok(RdbEncodingLen::Bit64(val))
             }
-            192 => {
-                let last_6_bits = first_byte & 63;
-
-                if last_6_bits == 0 {
-                    let next_byte = bytes.next().ok_or("Iter reached end")?;
-                    return Ok(RdbEncodingLen::SpecialEncoding(next_byte as u32));
-                } else if last_6_bits < 3 {
-                    let mut val: u32 = 0;
-                    for _ in 0..last_6_bits {
-                        let next_byte = bytes.next().ok_or("Iter reached end")?;
-                        val = (val << 8) | next_byte as u32;
-                    }
-                    return Ok(RdbEncodingLen::SpecialEncoding(val));
-                }
+            // The string is encoded in a special format, the integer is read by the caller
+            192 => Ok(RdbEncodingLen::SpecialEncoding((first_byte & 63) as u32)),
+            _ => Err("Invalid RDB length encoding".into()),
+        }
+    }
+}
 
-                Err(format!("Special encoding: {}", last_6_bits).into())
+impl RdbEncodingLen {
+    /// Reads a length, special encodings are only valid for strings
+    fn read_len(bytes: &mut impl Iterator<Item = u8>) -> crate::Result<u64> {
+        match RdbEncodingLen::from_u8(bytes)? {
+            RdbEncodingLen::Bit6(len) | RdbEncodingLen::Bit14(len) | RdbEncodingLen::Bit64(len) => {
+                Ok(len)
             }
-            _ => Err("Invalid RDB length encoding".into()),
+            RdbEncodingLen::SpecialEncoding(_) => Err("Invalid RDB length encoding".into()),
+        }
+    }
+
+    /// Writes a length with the smallest encoding that fits
+    fn write_len(buf: &mut Vec<u8>, len: u64) {
+        if len < 1 << 6 {
+            buf.push(len as u8);
+        } else if len < 1 << 14 {
+            buf.push(0x40 | (len >> 8) as u8);
+            buf.push(len as u8);
+        } else if len <= u32::MAX as u64 {
+            buf.push(0x80);
+            buf.extend_from_slice(&(len as u32).to_be_bytes());
+        } else {
+            buf.push(0x81);
+            buf.extend_from_slice(&len.to_be_bytes());
         }
     }
 }
@@ -119,38 +171,67 @@ impl Display for RdbEncodingLen {
 
 enum RdbEncodingType {
     String,
-    // List,
-    // Set,
-    // SortedSet,
-    // Hash,
+    /// List saved as a sequence of strings, before quicklists
+    List,
+    Set,
+    /// Sorted set with the scores as strings
+    SortedSet,
+    Hash,
+    /// Sorted set with the scores as binary doubles
+    SortedSet2,
     // ZipMap,
     // ZipList,
     // IntSet,
     // SortedSetZipList,
     // HashMapZipList,
     // ListQuickList,
+    /// Stream without the v2 metadata (first id, max deleted id, entries added)
+    StreamListPacks,
+    StreamListPacks2,
+    StreamListPacks3,
 }
 
 impl RdbEncodingType {
     fn from_u8(value: &u8) -> crate::Result<RdbEncodingType> {
         match value {
             0 => Ok(RdbEncodingType::String),
+            1 => Ok(RdbEncodingType::List),
+            2 => Ok(RdbEncodingType::Set),
+            3 => Ok(RdbEncodingType::SortedSet),
+            4 => Ok(RdbEncodingType::Hash),
+            5 => Ok(RdbEncodingType::SortedSet2),
+            15 => Ok(RdbEncodingType::StreamListPacks),
+            19 => Ok(RdbEncodingType::StreamListPacks2),
+            21 => Ok(RdbEncodingType::StreamListPacks3),
             e => Err(format!("Invalid RDB value encoding {}", e).into()),
         }
     }
+
+    fn to_u8(&self) -> u8 {
+        match self {
+            RdbEncodingType::String => 0,
+            RdbEncodingType::List => 1,
+            RdbEncodingType::Set => 2,
+            RdbEncodingType::SortedSet => 3,
+            RdbEncodingType::Hash => 4,
+            RdbEncodingType::SortedSet2 => 5,
+            RdbEncodingType::StreamListPacks => 15,
+            RdbEncodingType::StreamListPacks2 => 19,
+            RdbEncodingType::StreamListPacks3 => 21,
+        }
+    }
 }
 
 enum StringEncoding {
-    Int32(u32),
+    Int(i64),
     LenPrefixed(LenPrefixedString),
-    #[allow(dead_code)]
-    Lzf,
+    Lzf(Vec<u8>),
 }
 
 struct LenPrefixedString {
     #[allow(dead_code)]
     len: u32,
-    value: String,
+    value: Vec<u8>,
 }
 
 impl StringEncoding {
@@ -158,39 +239,188 @@ impl StringEncoding {
         let len_encoding = RdbEncodingLen::from_u8(bytes)?;
         match len_encoding {
             RdbEncodingLen::Bit6(num) | RdbEncodingLen::Bit14(num) | RdbEncodingLen::Bit64(num) => {
-                let mut val: Vec<u8> = Vec::new();
-                for _ in 0..num {
-                    let byte = bytes.next().ok_or("Iter reached end")?;
-                    val.push(byte);
-                }
+                let val = take_bytes(bytes, num)?;
                 let lps = LenPrefixedString {
                     len: num as u32,
-                    value: String::from_utf8(val)?,
+                    value: val,
                 };
                 Ok(StringEncoding::LenPrefixed(lps))
             }
-            RdbEncodingLen::SpecialEncoding(num) => Ok(StringEncoding::Int32(num)),
+            // 8, 16 and 32 bit little endian integers
+            RdbEncodingLen::SpecialEncoding(format @ 0..=2) => {
+                let width = 1 << format;
+                let val = take_bytes(bytes, width)?;
+
+                let mut buf = [0; 8];
+                buf[..width as usize].copy_from_slice(&val);
+                // Sign extend to 64 bits
+                let shift = 64 - width as u32 * 8;
+
+                Ok(StringEncoding::Int(
+                    (i64::from_le_bytes(buf) << shift) >> shift,
+                ))
+            }
+            RdbEncodingLen::SpecialEncoding(3) => {
+                let compressed_len = RdbEncodingLen::read_len(bytes)?;
+                let len = RdbEncodingLen::read_len(bytes)?;
+                let compressed = take_bytes(bytes, compressed_len)?;
+
+                Ok(StringEncoding::Lzf(lzf_decompress(
+                    &compressed,
+                    len as usize,
+                )?))
+            }
+            RdbEncodingLen::SpecialEncoding(num) => {
+                Err(format!("Special encoding: {}", num).into())
+            }
         }
     }
+
+    fn into_bytes(self) -> Vec<u8> {
+        match self {
+            StringEncoding::Int(num) => num.to_string().into_bytes(),
+            StringEncoding::LenPrefixed(lps) => lps.value,
+            StringEncoding::Lzf(value) => value,
+        }
+    }
+
+    /// Writes the string length prefixed, without integer encoding or compression
+    fn write(buf: &mut Vec<u8>, value: &[u8]) {
+        RdbEncodingLen::write_len(buf, value.len() as u64);
+        buf.extend_from_slice(value);
+    }
 }
 
 impl Display for StringEncoding {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            StringEncoding::Int32(num) => write!(f, "{}", num),
-            StringEncoding::LenPrefixed(lps) => write!(f, "{}", lps.value.clone()),
-            StringEncoding::Lzf => write!(f, "LZF"),
+            StringEncoding::Int(num) => write!(f, "{}", num),
+            StringEncoding::LenPrefixed(lps) => {
+                write!(f, "{}", String::from_utf8_lossy(&lps.value))
+            }
+            StringEncoding::Lzf(value) => write!(f, "{}", String::from_utf8_lossy(value)),
+        }
+    }
+}
+
+/// Takes the next `len` bytes of the iterator
+fn take_bytes(bytes: &mut impl Iterator<Item = u8>, len: u64) -> crate::Result<Vec<u8>> {
+    let mut val: Vec<u8> = Vec::new();
+    for _ in 0..len {
+        let byte = bytes.next().ok_or("Iter reached end")?;
+        val.push(byte);
+    }
+
+    Ok(val)
+}
+
+/// Decompresses LZF data, used by Redis for strings longer than 20 bytes
+fn lzf_decompress(input: &[u8], len: usize) -> crate::Result<Vec<u8>> {
+    const ERROR_MSG: &str = "Invalid LZF compressed string";
+    // A 3 byte back reference expands to at most 264 bytes
+    const MAX_EXPANSION: usize = 88;
+
+    // The length is untrusted, so reject it before allocating for it
+    if len > input.len().saturating_mul(MAX_EXPANSION) {
+        return Err(ERROR_MSG.into());
+    }
+
+    let mut output = Vec::with_capacity(len);
+    let mut i = 0;
+
+    while i < input.len() {
+        let ctrl = input[i] as usize;
+        i += 1;
+
+        if ctrl < 32 {
+            // Literal run of ctrl + 1 bytes
+            let run = input.get(i..i + ctrl + 1).ok_or(ERROR_MSG)?;
+            output.extend_from_slice(run);
+            i += ctrl + 1;
+        } else {
+            // Back reference of len + 2 bytes
+            let mut ref_len = ctrl >> 5;
+            if ref_len == 7 {
+                ref_len += *input.get(i).ok_or(ERROR_MSG)? as usize;
+                i += 1;
+            }
+            let offset = ((ctrl & 0x1F) << 8) + *input.get(i).ok_or(ERROR_MSG)? as usize + 1;
+            i += 1;
+
+            let start = output.len().checked_sub(offset).ok_or(ERROR_MSG)?;
+            // The reference can overlap the bytes being written, so copy byte by byte
+            for j in 0..ref_len + 2 {
+                output.push(output[start + j]);
+            }
+        }
+
+        if output.len() > len {
+            return Err(ERROR_MSG.into());
+        }
+    }
+
+    if output.len() != len {
+        return Err(ERROR_MSG.into());
+    }
+
+    Ok(output)
+}
+
+/// CRC-64/Jones checksum
+pub fn crc64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |crc, byte| {
+        CRC64_TABLE[((crc ^ *byte as u64) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Computes the CRC of every byte value bit by bit, like the reference implementation
+const fn crc64_table() -> [u64; 256] {
+    let mut table = [0; 256];
+
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u64;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ CRC64_POLY,
+                _ => crc >> 1,
+            };
+            bit += 1;
         }
+        table[i] = crc;
+        i += 1;
     }
+
+    table
 }
 
+/// Key, value and expiry of an RDB entry
+pub type RdbEntry = (String, RdbValue, Option<SystemTime>);
+
 pub struct RedisDB {
     filename: String,
+    /// Code of the function libraries, loaded by [`RedisDB::read_rdb`]
+    functions: Vec<Bytes>,
+    /// Whether the CRC64 of the file is written, zeros are written otherwise
+    checksum: bool,
 }
 
 impl RedisDB {
     pub fn new(filename: String) -> Self {
-        Self { filename }
+        Self {
+            filename,
+            functions: Vec::new(),
+            checksum: true,
+        }
+    }
+
+    pub fn set_checksum(&mut self, checksum: bool) {
+        self.checksum = checksum;
+    }
+
+    pub fn functions(&self) -> &[Bytes] {
+        &self.functions
     }
 
     fn get_next_opcode(&self, bite: &u8) -> crate::Result<RdbOpCode> {
@@ -210,9 +440,26 @@ impl RedisDB {
 
     pub async fn read_rdb(
         &mut self,
-    ) -> crate::Result<HashMap<String, (String, Option<SystemTime>)>> {
+    ) -> crate::Result<HashMap<String, (RdbValue, Option<SystemTime>)>> {
         let mut bytes = self.get_rbd_bytes().await?;
 
+        if bytes.len() < 9 {
+            return Err("Invalid RDB file".into());
+        }
+
+        // A zero checksum means the checksum is not computed
+        if let Some((data, crc)) = bytes.split_last_chunk::<8>() {
+            let crc = u64::from_le_bytes(*crc);
+            if crc != 0 && crc != crc64(data) {
+                return Err(format!(
+                    "Wrong RDB checksum expected: {:016x} got: {:016x}",
+                    crc64(data),
+                    crc
+                )
+                .into());
+            }
+        }
+
         let magic_string = bytes.drain(0..5).collect::<Vec<u8>>();
         if magic_string != b"REDIS" {
             return Err("Invalid RDB file".into());
@@ -253,6 +500,7 @@ impl RedisDB {
                                     RdbOpCode::SelectDB
                                     | RdbOpCode::Aux
                                     | RdbOpCode::ResizeDB
+                                    | RdbOpCode::Function2
                                     | RdbOpCode::Eof => break,
                                     _ => continue,
                                 },
@@ -267,21 +515,26 @@ impl RedisDB {
 
                     let nb = byte_iter.peek().ok_or("Iter reached end")?;
 
-                    // if next opcode is SelectDB, break, so we can process it
-                    if let RdbOpCode::SelectDB = self.get_next_opcode(nb).unwrap_or(RdbOpCode::Aux)
-                    {
-                        break;
-                    }
                     // if next opcode is Aux, continue to next key-val pair
+                    // else break, so we can process it
                     if let RdbOpCode::Aux = self.get_next_opcode(nb).unwrap_or(RdbOpCode::SelectDB)
                     {
                         byte_iter.next().ok_or("Iter reached end")?;
                         continue;
                     }
+
+                    break;
                 },
-                RdbOpCode::ResizeDB => panic!("ResizeDB should come after select DB"),
-                RdbOpCode::ExpireTime => panic!("ExpireTime should come after select DB"),
-                RdbOpCode::ExpireTimeMs => panic!("ExpireTimeMs should come after select DB"),
+                RdbOpCode::Function2 => {
+                    self.functions.push(Self::load_string(&mut byte_iter)?);
+                }
+                RdbOpCode::ResizeDB => return Err("ResizeDB should come after select DB".into()),
+                RdbOpCode::ExpireTime => {
+                    return Err("ExpireTime should come after select DB".into())
+                }
+                RdbOpCode::ExpireTimeMs => {
+                    return Err("ExpireTimeMs should come after select DB".into())
+                }
             };
 
             next_byte = byte_iter.next().ok_or("Iter reached end")?;
@@ -299,15 +552,15 @@ impl RedisDB {
                 RdbOpCode::ExpireTime => {
                     let _ = byte_iter.next().ok_or("Iter reached end")?;
 
-                    let arr = byte_iter.take(4).collect::<Vec<u8>>();
-                    let expiry = u64::from_le_bytes(arr.try_into().unwrap());
+                    let arr = take_bytes(byte_iter, 4)?;
+                    let expiry = u32::from_le_bytes(arr.try_into().unwrap());
 
-                    SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(expiry))
+                    SystemTime::UNIX_EPOCH.checked_add(Duration::from_secs(expiry as u64))
                 }
                 RdbOpCode::ExpireTimeMs => {
                     let _ = byte_iter.next().ok_or("Iter reached end")?;
 
-                    let arr = byte_iter.take(8).collect::<Vec<u8>>();
+                    let arr = take_bytes(byte_iter, 8)?;
                     let expiry = u64::from_le_bytes(arr.try_into().unwrap());
 
                     SystemTime::UNIX_EPOCH.checked_add(Duration::from_millis(expiry))
@@ -322,18 +575,583 @@ impl RedisDB {
     fn load_key_val(
         &mut self,
         bytes: &mut impl Iterator<Item = u8>,
-    ) -> crate::Result<(String, String)> {
+    ) -> crate::Result<(String, RdbValue)> {
         let val_type_byte = bytes.next().ok_or("Iter reached end")?;
         let key = StringEncoding::from_u8(bytes)?.to_string();
 
         let val_encoding = RdbEncodingType::from_u8(&val_type_byte)?;
-        match val_encoding {
-            RdbEncodingType::String => {
-                let val_string_encoding = StringEncoding::from_u8(bytes)?;
-                let val = val_string_encoding.to_string();
+        let val = Self::load_value(bytes, &val_encoding)?;
+
+        Ok((key, val))
+    }
+
+    /// Loads a value of the given type, without its key
+    fn load_value(
+        bytes: &mut impl Iterator<Item = u8>,
+        val_encoding: &RdbEncodingType,
+    ) -> crate::Result<RdbValue> {
+        let val = match val_encoding {
+            RdbEncodingType::String => RdbValue::String(Self::load_string(bytes)?),
+            RdbEncodingType::List => {
+                let len = RdbEncodingLen::read_len(bytes)?;
+                let values = (0..len)
+                    .map(|_| Self::load_string(bytes))
+                    .collect::<crate::Result<_>>()?;
+
+                RdbValue::List(values)
+            }
+            RdbEncodingType::Set => {
+                let len = RdbEncodingLen::read_len(bytes)?;
+                let members = (0..len)
+                    .map(|_| Self::load_string(bytes))
+                    .collect::<crate::Result<_>>()?;
+
+                RdbValue::Set(members)
+            }
+            RdbEncodingType::Hash => {
+                let len = RdbEncodingLen::read_len(bytes)?;
+                let fields = (0..len)
+                    .map(|_| Ok((Self::load_string(bytes)?, Self::load_string(bytes)?)))
+                    .collect::<crate::Result<_>>()?;
+
+                RdbValue::Hash(fields)
+            }
+            RdbEncodingType::SortedSet | RdbEncodingType::SortedSet2 => {
+                let binary_scores = matches!(val_encoding, RdbEncodingType::SortedSet2);
+
+                let len = RdbEncodingLen::read_len(bytes)?;
+                let members = (0..len)
+                    .map(|_| {
+                        let member = Self::load_string(bytes)?;
+                        let score = if binary_scores {
+                            f64::from_le_bytes(take_bytes(bytes, 8)?.try_into().unwrap())
+                        } else {
+                            Self::load_string_double(bytes)?
+                        };
+
+                        Ok((member, score))
+                    })
+                    .collect::<crate::Result<_>>()?;
+
+                RdbValue::ZSet(members)
+            }
+            RdbEncodingType::StreamListPacks
+            | RdbEncodingType::StreamListPacks2
+            | RdbEncodingType::StreamListPacks3 => {
+                RdbValue::Stream(Self::load_stream(bytes, val_encoding)?)
+            }
+        };
+
+        Ok(val)
+    }
+
+    fn load_string(bytes: &mut impl Iterator<Item = u8>) -> crate::Result<Bytes> {
+        Ok(Bytes::from(StringEncoding::from_u8(bytes)?.into_bytes()))
+    }
+
+    /// Loads a double saved as a string, prefixed by its length on one byte
+    fn load_string_double(bytes: &mut impl Iterator<Item = u8>) -> crate::Result<f64> {
+        match bytes.next().ok_or("Iter reached end")? {
+            253 => Ok(f64::NAN),
+            254 => Ok(f64::INFINITY),
+            255 => Ok(f64::NEG_INFINITY),
+            len => {
+                let double = String::from_utf8(take_bytes(bytes, len as u64)?)?;
+                double
+                    .parse()
+                    .map_err(|_| format!("Invalid RDB double {}", double).into())
+            }
+        }
+    }
+
+    /// Loads a stream saved as listpacks, each listpack holding entries
+    /// with ids relative to a master entry
+    #[allow(clippy::type_complexity)]
+    fn load_stream(
+        bytes: &mut impl Iterator<Item = u8>,
+        encoding: &RdbEncodingType,
+    ) -> crate::Result<Vec<(RdbStreamId, Vec<(Bytes, Bytes)>)>> {
+        let mut entries = Vec::new();
+
+        let nodes = RdbEncodingLen::read_len(bytes)?;
+        for _ in 0..nodes {
+            let master_id = Self::load_string(bytes)?;
+            if master_id.len() != 16 {
+                return Err("Invalid RDB stream master id".into());
+            }
+            let master_ms = u64::from_be_bytes(master_id[..8].try_into().unwrap());
+            let master_seq = u64::from_be_bytes(master_id[8..].try_into().unwrap());
+
+            let listpack = Self::load_string(bytes)?;
+            let mut elements = listpack::read(&listpack)?.into_iter();
+            let mut next = || elements.next().ok_or("Stream listpack reached end");
+
+            let count = next()?.as_int()?;
+            let deleted = next()?.as_int()?;
+            let master_fields_count = next()?.as_int()?;
+            let master_fields = (0..master_fields_count)
+                .map(|_| Ok(Bytes::from(next()?.into_bytes())))
+                .collect::<crate::Result<Vec<_>>>()?;
+            // The master entry ends with a 0 lp-count
+            next()?;
+
+            for _ in 0..count + deleted {
+                let flags = next()?.as_int()?;
+                let ms = master_ms.wrapping_add(next()?.as_int()? as u64);
+                let seq = master_seq.wrapping_add(next()?.as_int()? as u64);
+
+                // The values follow the master fields, the other fields are paired with their values
+                let mut field_values = Vec::new();
+                if flags & STREAM_ITEM_FLAG_SAMEFIELDS != 0 {
+                    for field in &master_fields {
+                        let value = Bytes::from(next()?.into_bytes());
+                        field_values.push((field.clone(), value));
+                    }
+                } else {
+                    let fields_count = next()?.as_int()?;
+                    for _ in 0..fields_count {
+                        let field = Bytes::from(next()?.into_bytes());
+                        let value = Bytes::from(next()?.into_bytes());
+                        field_values.push((field, value));
+                    }
+                }
+
+                // lp-count, used to walk the listpack backwards
+                next()?;
+
+                if flags & STREAM_ITEM_FLAG_DELETED == 0 {
+                    entries.push(((ms, seq), field_values));
+                }
+            }
+        }
+
+        // Number of entries and last id, the ids of the entries are enough
+        let _length = RdbEncodingLen::read_len(bytes)?;
+        let _last_ms = RdbEncodingLen::read_len(bytes)?;
+        let _last_seq = RdbEncodingLen::read_len(bytes)?;
+
+        if !matches!(encoding, RdbEncodingType::StreamListPacks) {
+            // First id, max deleted id and number of entries ever added
+            for _ in 0..5 {
+                RdbEncodingLen::read_len(bytes)?;
+            }
+        }
+
+        let groups = RdbEncodingLen::read_len(bytes)?;
+        if groups > 0 {
+            return Err("Stream consumer groups are not supported".into());
+        }
+
+        Ok(entries)
+    }
+
+    /// Saves the entries and the function libraries to the RDB file
+    /// The file is written next to the target and renamed, so it is replaced atomically
+    pub async fn write_rdb(&self, entries: &[RdbEntry], functions: &[Bytes]) -> crate::Result<()> {
+        let tmp_filename = format!("{}.tmp", self.filename);
+
+        let rdb = Self::encode_rdb(entries, functions, self.checksum);
+        tokio::fs::write(&tmp_filename, rdb)
+            .await
+            .map_err(|e| format!("Error writing RDB file: {}", e))?;
+        tokio::fs::rename(&tmp_filename, &self.filename)
+            .await
+            .map_err(|e| format!("Error writing RDB file: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Serializes the function libraries and the entries, all in database 0,
+    /// followed by the CRC64 of the file or zeros without checksum
+    pub fn encode_rdb(entries: &[RdbEntry], functions: &[Bytes], checksum: bool) -> Vec<u8> {
+        let mut buf = Vec::new();
+
+        buf.extend_from_slice(b"REDIS");
+        buf.extend_from_slice(RDB_VERSION);
+
+        buf.push(RdbOpCode::Aux.to_u8());
+        StringEncoding::write(&mut buf, b"redis-ver");
+        StringEncoding::write(&mut buf, b"7.2.0");
+
+        for code in functions {
+            buf.push(RdbOpCode::Function2.to_u8());
+            StringEncoding::write(&mut buf, code);
+        }
+
+        // The select db section is omitted when there are no keys
+        if !entries.is_empty() {
+            let expires = entries.iter().filter(|(_, _, expiry)| expiry.is_some());
+
+            buf.push(RdbOpCode::SelectDB.to_u8());
+            RdbEncodingLen::write_len(&mut buf, 0);
+            buf.push(RdbOpCode::ResizeDB.to_u8());
+            RdbEncodingLen::write_len(&mut buf, entries.len() as u64);
+            RdbEncodingLen::write_len(&mut buf, expires.count() as u64);
+
+            for (key, value, expiry) in entries {
+                if let Some(expiry) = expiry {
+                    let millis = expiry
+                        .duration_since(SystemTime::UNIX_EPOCH)
+                        .map(|d| d.as_millis() as u64)
+                        .unwrap_or_default();
+
+                    buf.push(RdbOpCode::ExpireTimeMs.to_u8());
+                    buf.extend_from_slice(&millis.to_le_bytes());
+                }
+
+                Self::encode_key_val(&mut buf, key, value);
+            }
+        }
+
+        buf.push(RdbOpCode::Eof.to_u8());
+        // A zero checksum means the checksum is not computed
+        let crc = if checksum { crc64(&buf) } else { 0 };
+        buf.extend_from_slice(&crc.to_le_bytes());
+
+        buf
+    }
+
+    /// Serializes the value in the DUMP format: the type and the value like in an RDB file,
+    /// followed by the RDB version and the CRC64 of everything before it
+    pub fn dump_value(value: &RdbValue) -> Vec<u8> {
+        let mut buf = Vec::new();
+
+        buf.push(Self::encoding_type(value).to_u8());
+        Self::encode_value(&mut buf, value);
+        Self::write_dump_footer(&mut buf);
+
+        buf
+    }
+
+    /// Deserializes a value serialized by [`RedisDB::dump_value`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checksum doesn't match, the payload comes from a newer RDB version,
+    /// or the value can't be decoded.
+    pub fn restore_value(payload: &[u8]) -> crate::Result<RdbValue> {
+        let value = Self::check_dump_footer(payload)?;
+
+        let mut bytes = value.iter().copied();
+        let val_encoding = bytes
+            .next()
+            .ok_or("ERR Bad data format")
+            .and_then(|byte| RdbEncodingType::from_u8(&byte).map_err(|_| "ERR Bad data format"))?;
+        let value =
+            Self::load_value(&mut bytes, &val_encoding).map_err(|_| "ERR Bad data format")?;
+
+        if bytes.next().is_some() {
+            return Err("ERR Bad data format".into());
+        }
+
+        Ok(value)
+    }
+
+    /// Serializes the function libraries in the FUNCTION DUMP format:
+    /// the code of each library like in an RDB file, followed by the DUMP footer
+    pub fn dump_functions(functions: &[Bytes]) -> Vec<u8> {
+        let mut buf = Vec::new();
+
+        for code in functions {
+            buf.push(RdbOpCode::Function2.to_u8());
+            StringEncoding::write(&mut buf, code);
+        }
+        Self::write_dump_footer(&mut buf);
+
+        buf
+    }
+
+    /// Deserializes the function libraries serialized by [`RedisDB::dump_functions`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checksum doesn't match, the payload comes from a newer RDB version,
+    /// or the libraries can't be decoded.
+    pub fn restore_functions(payload: &[u8]) -> crate::Result<Vec<Bytes>> {
+        const PAYLOAD_ERR: &str = "ERR given payload is not a valid function dump";
+
+        let data = Self::check_dump_footer(payload)?;
+
+        let mut bytes = data.iter().copied();
+        let mut functions = Vec::new();
+
+        while let Some(opcode) = bytes.next() {
+            match RdbOpCode::from_u8(&opcode) {
+                Ok(RdbOpCode::Function2) => {
+                    functions.push(Self::load_string(&mut bytes).map_err(|_| PAYLOAD_ERR)?)
+                }
+                _ => return Err(PAYLOAD_ERR.into()),
+            }
+        }
+
+        Ok(functions)
+    }
+
+    /// Appends the RDB version and the CRC64 of the payload
+    fn write_dump_footer(buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&DUMP_RDB_VERSION.to_le_bytes());
+
+        let crc = crc64(buf);
+        buf.extend_from_slice(&crc.to_le_bytes());
+    }
+
+    /// Checks the RDB version and the CRC64 at the end of a DUMP payload.
+    /// Returns the payload without them.
+    fn check_dump_footer(payload: &[u8]) -> crate::Result<&[u8]> {
+        const PAYLOAD_ERR: &str = "ERR DUMP payload version or checksum are wrong";
+
+        if payload.len() < 10 {
+            return Err(PAYLOAD_ERR.into());
+        }
+
+        let (data, crc) = payload.split_at(payload.len() - 8);
+        let (value, version) = data.split_at(data.len() - 2);
+
+        let version = u16::from_le_bytes(version.try_into().unwrap());
+        let crc = u64::from_le_bytes(crc.try_into().unwrap());
+
+        if version > DUMP_RDB_VERSION || crc != crc64(data) {
+            return Err(PAYLOAD_ERR.into());
+        }
 
-                Ok((key, val))
+        Ok(value)
+    }
+
+    fn encoding_type(value: &RdbValue) -> RdbEncodingType {
+        match value {
+            RdbValue::String(_) => RdbEncodingType::String,
+            RdbValue::List(_) => RdbEncodingType::List,
+            RdbValue::Set(_) => RdbEncodingType::Set,
+            RdbValue::Hash(_) => RdbEncodingType::Hash,
+            RdbValue::ZSet(_) => RdbEncodingType::SortedSet2,
+            RdbValue::Stream(_) => RdbEncodingType::StreamListPacks,
+        }
+    }
+
+    fn encode_key_val(buf: &mut Vec<u8>, key: &str, value: &RdbValue) {
+        let val_encoding = Self::encoding_type(value);
+
+        buf.push(val_encoding.to_u8());
+        StringEncoding::write(buf, key.as_bytes());
+
+        Self::encode_value(buf, value);
+    }
+
+    /// Saves the value, without its type and key
+    fn encode_value(buf: &mut Vec<u8>, value: &RdbValue) {
+        match value {
+            RdbValue::String(value) => StringEncoding::write(buf, value),
+            RdbValue::List(values) => {
+                RdbEncodingLen::write_len(buf, values.len() as u64);
+                for value in values {
+                    StringEncoding::write(buf, value);
+                }
+            }
+            RdbValue::Set(members) => {
+                RdbEncodingLen::write_len(buf, members.len() as u64);
+                for member in members {
+                    StringEncoding::write(buf, member);
+                }
+            }
+            RdbValue::Hash(fields) => {
+                RdbEncodingLen::write_len(buf, fields.len() as u64);
+                for (field, value) in fields {
+                    StringEncoding::write(buf, field);
+                    StringEncoding::write(buf, value);
+                }
+            }
+            RdbValue::ZSet(members) => {
+                RdbEncodingLen::write_len(buf, members.len() as u64);
+                for (member, score) in members {
+                    StringEncoding::write(buf, member);
+                    buf.extend_from_slice(&score.to_le_bytes());
+                }
+            }
+            RdbValue::Stream(entries) => Self::encode_stream(buf, entries),
+        }
+    }
+
+    /// Saves the stream in a single listpack, the first entry being the master entry
+    fn encode_stream(buf: &mut Vec<u8>, entries: &[(RdbStreamId, Vec<(Bytes, Bytes)>)]) {
+        let ((master_ms, master_seq), master_fields) = match entries.first() {
+            Some((id, field_values)) => (
+                *id,
+                field_values
+                    .iter()
+                    .map(|(field, _)| field.clone())
+                    .collect::<Vec<_>>(),
+            ),
+            None => {
+                // No listpack, no entries, last id 0-0 and no consumer groups
+                RdbEncodingLen::write_len(buf, 0);
+                for _ in 0..4 {
+                    RdbEncodingLen::write_len(buf, 0);
+                }
+                return;
+            }
+        };
+
+        let mut lp = ListPackWriter::new();
+        lp.push_int(entries.len() as i64);
+        lp.push_int(0);
+        lp.push_int(master_fields.len() as i64);
+        for field in &master_fields {
+            lp.push_str(field);
+        }
+        lp.push_int(0);
+
+        for ((ms, seq), field_values) in entries {
+            let same_fields = field_values
+                .iter()
+                .map(|(field, _)| field)
+                .eq(master_fields.iter());
+
+            let flags = if same_fields {
+                STREAM_ITEM_FLAG_SAMEFIELDS
+            } else {
+                0
+            };
+
+            lp.push_int(flags);
+            lp.push_int(ms.wrapping_sub(master_ms) as i64);
+            lp.push_int(seq.wrapping_sub(master_seq) as i64);
+
+            if same_fields {
+                for (_, value) in field_values {
+                    lp.push_str(value);
+                }
+                lp.push_int(field_values.len() as i64 + 3);
+            } else {
+                lp.push_int(field_values.len() as i64);
+                for (field, value) in field_values {
+                    lp.push_str(field);
+                    lp.push_str(value);
+                }
+                lp.push_int(field_values.len() as i64 * 2 + 4);
             }
         }
+
+        let mut master_id = Vec::with_capacity(16);
+        master_id.extend_from_slice(&master_ms.to_be_bytes());
+        master_id.extend_from_slice(&master_seq.to_be_bytes());
+
+        RdbEncodingLen::write_len(buf, 1);
+        StringEncoding::write(buf, &master_id);
+        StringEncoding::write(buf, &lp.finish());
+
+        let (last_ms, last_seq) = entries.last().map(|(id, _)| *id).unwrap_or_default();
+        RdbEncodingLen::write_len(buf, entries.len() as u64);
+        RdbEncodingLen::write_len(buf, last_ms);
+        RdbEncodingLen::write_len(buf, last_seq);
+        // No consumer groups
+        RdbEncodingLen::write_len(buf, 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(value: RdbValue) {
+        let payload = RedisDB::dump_value(&value);
+
+        assert_eq!(RedisDB::restore_value(&payload).unwrap(), value);
+    }
+
+    #[test]
+    fn crc64_matches_jones_check_value() {
+        assert_eq!(crc64(b"123456789"), 0xe9c6d914c4b8d9ca);
+        assert_eq!(crc64(b""), 0);
+    }
+
+    #[test]
+    fn dump_restore_round_trip() {
+        round_trip(RdbValue::String(Bytes::from_static(b"hello")));
+        round_trip(RdbValue::String(Bytes::from_static(b"12345")));
+        round_trip(RdbValue::List(vec![
+            Bytes::from_static(b"a"),
+            Bytes::from_static(b"b"),
+        ]));
+        round_trip(RdbValue::Set(vec![Bytes::from_static(b"m")]));
+        round_trip(RdbValue::Hash(vec![(
+            Bytes::from_static(b"f"),
+            Bytes::from_static(b"v"),
+        )]));
+        round_trip(RdbValue::ZSet(vec![(Bytes::from_static(b"m"), 1.5)]));
+        round_trip(RdbValue::Stream(vec![(
+            (1, 0),
+            vec![(Bytes::from_static(b"f"), Bytes::from_static(b"v"))],
+        )]));
+    }
+
+    #[test]
+    fn dump_ends_with_version_and_checksum() {
+        let payload = RedisDB::dump_value(&RdbValue::String(Bytes::from_static(b"hello")));
+        let (data, crc) = payload.split_at(payload.len() - 8);
+
+        assert_eq!(&data[data.len() - 2..], &DUMP_RDB_VERSION.to_le_bytes());
+        assert_eq!(crc, &crc64(data).to_le_bytes());
+    }
+
+    #[test]
+    fn restore_rejects_corrupted_payload() {
+        let mut payload = RedisDB::dump_value(&RdbValue::String(Bytes::from_static(b"hello")));
+        payload[2] ^= 0xff;
+
+        let err = RedisDB::restore_value(&payload).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "ERR DUMP payload version or checksum are wrong"
+        );
+        assert!(RedisDB::restore_value(b"short").is_err());
+    }
+
+    #[test]
+    fn restore_rejects_zero_checksum() {
+        let value = RdbValue::String(Bytes::from_static(b"hello"));
+        let mut payload = RedisDB::dump_value(&value);
+        let len = payload.len();
+        payload[len - 8..].fill(0);
+
+        assert!(RedisDB::restore_value(&payload).is_err());
+    }
+
+    #[test]
+    fn restore_rejects_forged_lzf_length() {
+        // A 2 byte LZF string claiming to decompress to 2^60 bytes
+        let mut payload = vec![0x00, 0xC3, 0x02, 0x81];
+        payload.extend_from_slice(&(1u64 << 60).to_be_bytes());
+        payload.extend_from_slice(&[0x00, b'a']);
+        payload.extend_from_slice(&DUMP_RDB_VERSION.to_le_bytes());
+        let crc = crc64(&payload);
+        payload.extend_from_slice(&crc.to_le_bytes());
+
+        let err = RedisDB::restore_value(&payload).unwrap_err();
+        assert_eq!(err.to_string(), "ERR Bad data format");
+    }
+
+    #[test]
+    fn lzf_rejects_output_longer_than_declared() {
+        // A literal "a" followed by a back reference repeating it 8 times
+        let input = [0x00, b'a', 0xC0, 0x00];
+
+        assert_eq!(lzf_decompress(&input, 9).unwrap(), vec![b'a'; 9]);
+        assert!(lzf_decompress(&input, 4).is_err());
+    }
+
+    #[test]
+    fn encode_rdb_trailer() {
+        let entries = [(
+            "key".to_string(),
+            RdbValue::String(Bytes::from_static(b"value")),
+            None,
+        )];
+
+        let rdb = RedisDB::encode_rdb(&entries, &[], false);
+        assert_eq!(&rdb[rdb.len() - 8..], &[0; 8]);
+
+        let rdb = RedisDB::encode_rdb(&entries, &[], true);
+        let (data, crc) = rdb.split_at(rdb.len() - 8);
+        assert_eq!(crc, &crc64(data).to_le_bytes());
     }
 }
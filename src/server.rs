@@ -1,14 +1,16 @@
 use std::net::{SocketAddr, ToSocketAddrs};
-use tokio::net::{TcpListener, TcpStream};
+use tokio::net::{TcpListener, TcpSocket, TcpStream};
 
 use crate::{
     command::{
         psync::Psync,
         replconf::{ReplConf, ReplConfListeningPort},
-        Ping,
+        Ping, REPLICATION_COMMANDS,
     },
     connection::Connection,
+    error::{redis_error, RedisErrorKind},
     info::Role,
+    rate_limit::RateLimiter,
     Command, Config, Db, Frame, Info,
 };
 
@@ -34,6 +36,27 @@ impl Server {
     }
 }
 
+/// Binds the listener of the client connections,
+/// the accepted sockets inherit the send and receive buffer sizes set on it,
+/// the OS defaults are kept with a size of 0
+fn bind_listener(socket_addr: SocketAddr, buffer_size: usize) -> crate::Result<TcpListener> {
+    let socket = match socket_addr {
+        SocketAddr::V4(_) => TcpSocket::new_v4()?,
+        SocketAddr::V6(_) => TcpSocket::new_v6()?,
+    };
+    socket.set_reuseaddr(true)?;
+
+    if buffer_size > 0 {
+        let buffer_size = u32::try_from(buffer_size).unwrap_or(u32::MAX);
+        socket.set_recv_buffer_size(buffer_size)?;
+        socket.set_send_buffer_size(buffer_size)?;
+    }
+
+    socket.bind(socket_addr)?;
+
+    Ok(socket.listen(1024)?)
+}
+
 #[derive(Debug)]
 pub struct SlaveServer {
     db: Db,
@@ -44,10 +67,10 @@ pub struct SlaveServer {
 
 impl SlaveServer {
     pub async fn new(socket_addr: SocketAddr, db: Db, config: Config) -> crate::Result<Self> {
-        let info = Info::parse_config(&config);
+        let mut info = Info::parse_config(&config);
 
-        let connection = SlaveServer::handshake(info.clone(), socket_addr.port()).await?;
-        let listener = TcpListener::bind(socket_addr).await.unwrap();
+        let connection = SlaveServer::handshake(&mut info, socket_addr.port()).await?;
+        let listener = bind_listener(socket_addr, config.tcp_socket_buffer_size).unwrap();
 
         Ok(Self {
             db,
@@ -73,7 +96,7 @@ impl SlaveServer {
             "Server is listening on port {}...",
             self.listener.local_addr()?.port()
         );
-        println!("Role: {}", self.info.role().to_string());
+        println!("Role: {}", self.info.role());
 
         loop {
             println!("Waiting for incoming traffic...");
@@ -114,9 +137,15 @@ impl SlaveServer {
 
         // Spawn a task to handle the connection
         tokio::spawn(async move {
+            let _client = match info.connect_client() {
+                Some(client) => client,
+                None => return reject_client(conneciton).await,
+            };
+
             let mut handle = SlaveHandle {
                 connection: conneciton,
                 db,
+                limiter: RateLimiter::new(info.max_commands_per_sec()),
                 info,
             };
 
@@ -130,10 +159,12 @@ impl SlaveServer {
     /// 2. REPLCONF
     /// 3. PSYNC
     ///
+    /// The offset of the replica starts at the one in the FULLRESYNC response.
+    ///
     /// # Panics
     ///
     /// Panics if the master server is not reachable.
-    async fn handshake(info: Info, local_port: u16) -> crate::Result<Connection> {
+    async fn handshake(info: &mut Info, local_port: u16) -> crate::Result<Connection> {
         if info.role().is_master() {
             return Err("Error establishing handshake: not a slave".into());
         }
@@ -180,6 +211,9 @@ impl SlaveServer {
         let response = connection.read_frame().await.unwrap().unwrap();
         println!("GOT: {:?}", response);
 
+        let offset = SlaveServer::parse_full_resync_offset(&response)?;
+        info.set_offset(offset);
+
         let response = connection.read_rdb().await.unwrap().unwrap();
         println!("GOT: {:?}", response);
 
@@ -187,6 +221,21 @@ impl SlaveServer {
 
         Ok(connection)
     }
+
+    /// Parse the replication offset of `+FULLRESYNC <replid> <offset>`
+    fn parse_full_resync_offset(response: &Frame) -> crate::Result<u64> {
+        const ERROR_MSG: &str = "Error establishing handshake: expected FULLRESYNC response";
+
+        let response = match response {
+            Frame::Simple(response) => response,
+            _ => return Err(ERROR_MSG.into()),
+        };
+
+        match response.split(' ').collect::<Vec<_>>()[..] {
+            ["FULLRESYNC", _replid, offset] => Ok(offset.parse().map_err(|_| ERROR_MSG)?),
+            _ => Err(ERROR_MSG.into()),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -200,7 +249,7 @@ impl MasterServer {
     pub async fn new(socket_addr: SocketAddr, db: Db, config: Config) -> Self {
         let info = Info::parse_config(&config);
 
-        let listener = TcpListener::bind(socket_addr).await.unwrap();
+        let listener = bind_listener(socket_addr, config.tcp_socket_buffer_size).unwrap();
 
         Self { db, listener, info }
     }
@@ -210,7 +259,7 @@ impl MasterServer {
             "Server is listening on port {}...",
             self.listener.local_addr()?.port()
         );
-        println!("Role: {}", self.info.role().to_string());
+        println!("Role: {}", self.info.role());
 
         loop {
             println!("Waiting for incoming traffic...");
@@ -233,9 +282,15 @@ impl MasterServer {
 
         // Spawn a task to handle the connection
         tokio::spawn(async move {
+            let _client = match info.connect_client() {
+                Some(client) => client,
+                None => return reject_client(conneciton).await,
+            };
+
             let mut handle = MasterHandle {
                 connection: conneciton,
                 db,
+                limiter: RateLimiter::new(info.max_commands_per_sec()),
                 info,
             };
 
@@ -252,24 +307,41 @@ pub struct SlaveToMasterHandle {
 
 impl SlaveToMasterHandle {
     pub async fn run(&mut self) {
-        while let Some(frame) = self.connection.read_frame().await.unwrap() {
-            println!("GOT: {:?}", frame);
+        // Writes of the transaction propagated by the master, applied together on EXEC
+        let mut transaction: Option<Vec<Frame>> = None;
 
-            let (response, bytes_read) = Command::execute_replica(
-                frame.clone(),
-                &self.db,
-                &mut self.info,
-                self.connection.clone(),
-            );
+        // The offset counts the bytes received from the master, not the frames encoded again
+        while let Ok(Some((frame, bytes_read))) = self.connection.read_frame_with_len().await {
+            println!("GOT: {:?}", frame);
 
-            if response != Frame::Null {
-                self.write_response(response).await;
+            let name = Command::name(&frame).to_uppercase();
+            match (name.as_str(), &mut transaction) {
+                ("MULTI", _) => transaction = Some(Vec::new()),
+                ("EXEC", Some(_)) => {
+                    let db = self.db.clone();
+                    let _transaction = db.lock_transaction().await;
+                    for frame in transaction.take().unwrap_or_default() {
+                        self.execute(frame).await;
+                    }
+                }
+                // The pings and acks of the master are not part of the transaction
+                (name, Some(frames)) if !REPLICATION_COMMANDS.contains(&name) => frames.push(frame),
+                _ => self.execute(frame).await,
             }
 
             self.info.incr_offset(bytes_read as u64);
         }
     }
 
+    async fn execute(&mut self, frame: Frame) {
+        let response =
+            Command::execute_replica(frame, &self.db, &mut self.info, self.connection.clone());
+
+        if response != Frame::Null {
+            self.write_response(response).await;
+        }
+    }
+
     async fn write_response(&mut self, response: Frame) {
         match self.connection.write_frame(response.clone()).await {
             Ok(_) => println!("SENT: {:?}", response),
@@ -277,18 +349,57 @@ impl SlaveToMasterHandle {
         }
     }
 }
+
+const MAX_CLIENTS_ERR: &str = "max number of clients reached";
+
+/// Replies with an error to a client over the max number of clients,
+/// the connection is closed when dropped
+async fn reject_client(connection: Connection) {
+    if let Err(e) = connection
+        .write_frame(redis_error(RedisErrorKind::Err, MAX_CLIENTS_ERR))
+        .await
+    {
+        eprintln!("Error writing frame: {}", e);
+    }
+}
+
+const READONLY_ERR: &str = "You can't write against a read only replica.";
+
+const RATE_LIMIT_ERR: &str = "command rate limit exceeded";
+
 pub struct SlaveHandle {
     connection: Connection,
     db: Db,
     info: Info,
+    limiter: RateLimiter,
 }
 
 impl SlaveHandle {
     pub async fn run(&mut self) {
-        while let Some(frame) = self.connection.read_frame().await.unwrap() {
+        while let Ok(Some(frame)) = self.connection.read_frame().await {
             println!("GOT: {:?}", frame);
 
-            let (response, _bytes_read) = Command::execute(
+            if !self.limiter.try_acquire() {
+                self.info.record_command_rejected(&Command::name(&frame));
+                self.write_response(redis_error(RedisErrorKind::Err, RATE_LIMIT_ERR))
+                    .await;
+                continue;
+            }
+
+            // Replica is read-only for clients, writes only come from the master link,
+            // unless replica-read-only is disabled
+            if self.info.replica_read_only()
+                && Command::is_propagatable(frame.clone()).unwrap_or(false)
+            {
+                self.info.record_command_rejected(&Command::name(&frame));
+                // A rejected write also aborts the transaction it was queued in
+                self.connection.session().flag_queued_error();
+                self.write_response(redis_error(RedisErrorKind::ReadOnly, READONLY_ERR))
+                    .await;
+                continue;
+            }
+
+            let (response, _) = Command::execute(
                 frame.clone(),
                 &self.db,
                 &mut self.info,
@@ -298,6 +409,9 @@ impl SlaveHandle {
 
             self.write_response(response).await;
         }
+
+        // Stop forwarding the messages of the channels to the client
+        self.connection.session().unsubscribe_all();
     }
 
     async fn write_response(&mut self, response: Frame) {
@@ -312,25 +426,40 @@ pub struct MasterHandle {
     connection: Connection,
     db: Db,
     info: Info,
+    limiter: RateLimiter,
 }
 
 impl MasterHandle {
     pub async fn run(&mut self) {
-        while let Some(frame) = self.connection.read_frame().await.unwrap() {
+        while let Ok(Some(frame)) = self.connection.read_frame().await {
             println!("GOT: {:?}", frame);
 
-            let (response, bytes_read) = Command::execute(
-                frame.clone(),
-                &self.db,
-                &mut self.info,
-                self.connection.clone(),
-            )
-            .await;
+            // Commands over the rate limit are dropped,
+            // except on the replication links so the acks are always processed
+            if !self.limiter.try_acquire()
+                && !self
+                    .info
+                    .get_replica_sock_addrs()
+                    .contains(&self.connection.addr())
+            {
+                self.info.record_command_rejected(&Command::name(&frame));
+                self.write_response(redis_error(RedisErrorKind::Err, RATE_LIMIT_ERR))
+                    .await;
+                continue;
+            }
+
+            let (response, propagated) =
+                Command::execute(frame, &self.db, &mut self.info, self.connection.clone()).await;
 
-            self.propagate(frame, bytes_read).await;
+            for frame in propagated {
+                self.propagate(frame).await;
+            }
 
             self.write_response(response).await;
         }
+
+        // Stop forwarding the messages of the channels to the client
+        self.connection.session().unsubscribe_all();
     }
 
     async fn write_response(&mut self, response: Frame) {
@@ -340,20 +469,352 @@ impl MasterHandle {
         }
     }
 
-    async fn propagate(&mut self, frame: Frame, bytes_read: usize) {
-        // immidiately return if the command is not a write command
-        if !Command::is_propagatable(frame.clone()).unwrap() {
-            return;
-        }
-
-        // Command will be propagated to all replicas
-        // So increment the offset by the bytes read
-        self.info.incr_offset(bytes_read as u64);
-
-        // Propagate the command to all replicas
+    async fn propagate(&mut self, frame: Frame) {
+        // Propagate the command to all replicas,
+        // the master offset is the one of the backlog the frame is added to
         match &self.info.role() {
             Role::Master(master) => master.propagate_in_seq(frame).await.unwrap(),
             Role::Slave(_) => {}
         }
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    /// Starts a server on a free local port, configured with the command line arguments
+    pub(crate) async fn start_server(args: &[&str]) -> SocketAddr {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let addr = SocketAddr::from(([127, 0, 0, 1], port));
+
+        let args = ["--port".to_string(), port.to_string()]
+            .into_iter()
+            .chain(args.iter().map(|arg| arg.to_string()));
+        let config = Config::new(args).unwrap();
+
+        let server = Server::new(addr, Db::new(), config).await;
+        tokio::spawn(server.run());
+
+        addr
+    }
+
+    pub(crate) async fn connect(addr: SocketAddr) -> Connection {
+        let stream = TcpStream::connect(addr).await.unwrap();
+
+        Connection::new(stream, addr)
+    }
+
+    /// Sends the command, each argument as a bulk string, and returns the reply
+    pub(crate) async fn send(connection: &Connection, args: &[&str]) -> Frame {
+        let frame = Frame::Array(
+            args.iter()
+                .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                .collect(),
+        );
+
+        connection.write_frame(frame).await.unwrap();
+        connection.read_frame().await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn replica_rejects_client_writes() {
+        let master = start_server(&[]).await;
+        let master_port = master.port().to_string();
+        let replica = start_server(&["--replicaof", "127.0.0.1", &master_port]).await;
+        let client = connect(replica).await;
+
+        assert_eq!(
+            send(&client, &["SET", "key", "value"]).await,
+            Frame::Error("READONLY You can't write against a read only replica.".to_string())
+        );
+        assert_eq!(send(&client, &["GET", "key"]).await, Frame::Null);
+    }
+
+    #[tokio::test]
+    async fn replica_applies_writes_from_master() {
+        let master = start_server(&[]).await;
+        let master_port = master.port().to_string();
+        let replica = start_server(&["--replicaof", "127.0.0.1", &master_port]).await;
+
+        let client = connect(master).await;
+        send(&client, &["SET", "key", "value"]).await;
+        assert_eq!(
+            send(&client, &["WAIT", "1", "1000"]).await,
+            Frame::Integer(1)
+        );
+
+        let client = connect(replica).await;
+        assert_eq!(
+            send(&client, &["GET", "key"]).await,
+            Frame::Bulk("value".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn replica_accepts_client_writes_when_not_read_only() {
+        let master = start_server(&[]).await;
+        let master_port = master.port().to_string();
+        let replica = start_server(&[
+            "--replicaof",
+            "127.0.0.1",
+            &master_port,
+            "--replica-read-only",
+            "no",
+        ])
+        .await;
+        let client = connect(replica).await;
+
+        assert_eq!(
+            send(&client, &["SET", "key", "value"]).await,
+            Frame::Simple("OK".into())
+        );
+        assert_eq!(
+            send(&client, &["GET", "key"]).await,
+            Frame::Bulk("value".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn wait_counts_pipelined_writes() {
+        use tokio::io::AsyncWriteExt;
+
+        let master = start_server(&[]).await;
+        let master_port = master.port().to_string();
+        let replica = start_server(&["--replicaof", "127.0.0.1", &master_port]).await;
+
+        // The commands are sent in a single write, before any reply is read
+        let pipeline = [
+            &["SET", "a", "1"][..],
+            &["SET", "b", "2"],
+            &["SET", "c", "3"],
+            &["WAIT", "1", "1000"],
+        ]
+        .iter()
+        .flat_map(|args| {
+            Frame::Array(
+                args.iter()
+                    .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                    .collect(),
+            )
+            .encode()
+        })
+        .collect::<Vec<_>>();
+        let mut stream = TcpStream::connect(master).await.unwrap();
+        stream.write_all(&pipeline).await.unwrap();
+        let client = Connection::new(stream, master);
+
+        for _ in 0..3 {
+            let reply = client.read_frame().await.unwrap().unwrap();
+            assert_eq!(reply, Frame::Simple("OK".into()));
+        }
+        let reply = client.read_frame().await.unwrap().unwrap();
+        assert_eq!(reply, Frame::Integer(1));
+
+        let client = connect(replica).await;
+        for (key, value) in [("a", "1"), ("b", "2"), ("c", "3")] {
+            assert_eq!(
+                send(&client, &["GET", key]).await,
+                Frame::Bulk(Bytes::from(value))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn clients_past_the_limit_are_rejected() {
+        let addr = start_server(&["--maxclients", "2"]).await;
+
+        let first = connect(addr).await;
+        let second = connect(addr).await;
+        for client in [&first, &second] {
+            assert_eq!(send(client, &["PING"]).await, Frame::Simple("PONG".into()));
+        }
+
+        let third = connect(addr).await;
+        assert_eq!(
+            third.read_frame().await.unwrap(),
+            Some(Frame::Error(format!("ERR {}", MAX_CLIENTS_ERR)))
+        );
+        assert_eq!(third.read_frame().await.unwrap(), None);
+    }
+
+    #[test]
+    fn full_resync_offset_is_parsed() {
+        let response = Frame::Simple(format!("FULLRESYNC {} 1234", "0".repeat(40)));
+
+        assert_eq!(
+            SlaveServer::parse_full_resync_offset(&response).unwrap(),
+            1234
+        );
+        assert!(SlaveServer::parse_full_resync_offset(&Frame::Simple("OK".into())).is_err());
+    }
+
+    #[tokio::test]
+    async fn replica_offset_starts_at_the_full_resync_offset() {
+        let master = start_server(&[]).await;
+        let client = connect(master).await;
+        send(&client, &["SET", "key", "value"]).await;
+
+        let master_offset = match send(&client, &["INFO", "replication"]).await {
+            Frame::Bulk(info) => String::from_utf8(info.to_vec())
+                .unwrap()
+                .lines()
+                .find_map(|line| line.strip_prefix("master_repl_offset:").map(str::to_string))
+                .unwrap(),
+            frame => panic!("expected the info, got {frame:?}"),
+        };
+        assert_ne!(master_offset, "0");
+
+        let master_port = master.port().to_string();
+        let replica = start_server(&["--replicaof", "127.0.0.1", &master_port]).await;
+        let client = connect(replica).await;
+
+        assert_eq!(
+            send(&client, &["REPLCONF", "GETACK", "*"]).await,
+            Frame::Array(vec![
+                Frame::Bulk("REPLCONF".into()),
+                Frame::Bulk("ACK".into()),
+                Frame::Bulk(master_offset.into()),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn replica_acks_the_bytes_received_from_the_master() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let master_port = listener.local_addr().unwrap().port().to_string();
+        // The replica handshakes before it starts
+        tokio::spawn(async move {
+            start_server(&["--replicaof", "127.0.0.1", &master_port]).await;
+        });
+
+        // Stands for the master, replying to PING, the two REPLCONF and PSYNC
+        let (stream, addr) = listener.accept().await.unwrap();
+        let master = Connection::new(stream, addr);
+        for reply in ["PONG", "OK", "OK"] {
+            master.read_frame().await.unwrap();
+            master
+                .write_frame(Frame::Simple(reply.into()))
+                .await
+                .unwrap();
+        }
+        master.read_frame().await.unwrap();
+        let full_resync = format!("FULLRESYNC {} 100", "0".repeat(40));
+        master
+            .write_frame(Frame::Rdb(
+                full_resync,
+                crate::replicaiton::rdb::empty_rdb(),
+            ))
+            .await
+            .unwrap();
+
+        let command = |args: &[&str]| {
+            Frame::Array(
+                args.iter()
+                    .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                    .collect(),
+            )
+        };
+        let set = command(&["SET", "key", "value"]);
+        let getack = command(&["REPLCONF", "GETACK", "*"]);
+        let ack = |offset: usize| command(&["REPLCONF", "ACK", &offset.to_string()]);
+
+        // The GETACK is counted once acknowledged, like in Redis
+        let mut offset = 100 + set.encode().len();
+        master.write_frame(set).await.unwrap();
+        master.write_frame(getack.clone()).await.unwrap();
+        assert_eq!(master.read_frame().await.unwrap(), Some(ack(offset)));
+
+        offset += getack.encode().len();
+        master.write_frame(getack).await.unwrap();
+        assert_eq!(master.read_frame().await.unwrap(), Some(ack(offset)));
+    }
+
+    #[tokio::test]
+    async fn replica_applies_a_propagated_transaction() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let master_port = listener.local_addr().unwrap().port().to_string();
+        // The replica handshakes before it starts
+        let (addr_sender, addr_receiver) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let addr = start_server(&["--replicaof", "127.0.0.1", &master_port]).await;
+            addr_sender.send(addr).unwrap();
+        });
+
+        // Stands for the master, replying to PING, the two REPLCONF and PSYNC
+        let (stream, addr) = listener.accept().await.unwrap();
+        let master = Connection::new(stream, addr);
+        for reply in ["PONG", "OK", "OK"] {
+            master.read_frame().await.unwrap();
+            master
+                .write_frame(Frame::Simple(reply.into()))
+                .await
+                .unwrap();
+        }
+        master.read_frame().await.unwrap();
+        let full_resync = format!("FULLRESYNC {} 0", "0".repeat(40));
+        master
+            .write_frame(Frame::Rdb(
+                full_resync,
+                crate::replicaiton::rdb::empty_rdb(),
+            ))
+            .await
+            .unwrap();
+
+        let command = |args: &[&str]| {
+            Frame::Array(
+                args.iter()
+                    .map(|arg| Frame::Bulk(Bytes::from(arg.to_string())))
+                    .collect(),
+            )
+        };
+        let transaction = [
+            command(&["MULTI"]),
+            command(&["SET", "a", "1"]),
+            command(&["SET", "b", "2"]),
+            command(&["EXEC"]),
+        ];
+        let offset: usize = transaction.iter().map(|frame| frame.encode().len()).sum();
+        for frame in transaction {
+            master.write_frame(frame).await.unwrap();
+        }
+
+        // MULTI and EXEC are not replied to, the first reply is the ack
+        master
+            .write_frame(command(&["REPLCONF", "GETACK", "*"]))
+            .await
+            .unwrap();
+        assert_eq!(
+            master.read_frame().await.unwrap(),
+            Some(command(&["REPLCONF", "ACK", &offset.to_string()]))
+        );
+
+        let client = connect(addr_receiver.await.unwrap()).await;
+        assert_eq!(
+            send(&client, &["MGET", "a", "b"]).await,
+            Frame::Array(vec![Frame::Bulk("1".into()), Frame::Bulk("2".into())])
+        );
+    }
+
+    #[tokio::test]
+    async fn flooding_commands_hits_the_rate_limit() {
+        let client = connect(start_server(&["--max-commands-per-sec", "10"]).await).await;
+
+        let mut replies = Vec::new();
+        for _ in 0..20 {
+            replies.push(send(&client, &["PING"]).await);
+        }
+
+        let rejected = Frame::Error(format!("ERR {}", RATE_LIMIT_ERR));
+        assert!(replies[..10]
+            .iter()
+            .all(|reply| *reply == Frame::Simple("PONG".into())));
+        assert!(replies[10..].contains(&rejected));
+    }
+}
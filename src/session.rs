@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use tokio::task::JoinHandle;
+
+use crate::Frame;
+
+/// Commands a RESP2 client can send while subscribed
+const SUBSCRIBE_MODE_COMMANDS: [&str; 7] = [
+    "SUBSCRIBE",
+    "UNSUBSCRIBE",
+    "PSUBSCRIBE",
+    "PUNSUBSCRIBE",
+    "PING",
+    "QUIT",
+    "RESET",
+];
+
+/// Commands queued by MULTI, until EXEC or DISCARD
+#[derive(Debug, Default)]
+pub struct Transaction {
+    pub commands: Vec<Frame>,
+    /// Whether a command was rejected while queuing, EXEC then discards the transaction
+    pub queued_errors: bool,
+}
+
+/// State of a client connection, shared by the clones of the [`Connection`](crate::Connection)
+#[derive(Debug)]
+pub struct ClientSession {
+    /// RESP protocol version negotiated with HELLO, 2 or 3
+    protocol: u8,
+    /// Subscribed channels, each with the task forwarding its messages to the client
+    subscriptions: HashMap<String, JoinHandle<()>>,
+    /// Transaction started by MULTI, if any
+    transaction: Option<Transaction>,
+}
+
+impl Default for ClientSession {
+    fn default() -> Self {
+        Self {
+            protocol: 2,
+            subscriptions: HashMap::new(),
+            transaction: None,
+        }
+    }
+}
+
+impl ClientSession {
+    pub fn protocol(&self) -> u8 {
+        self.protocol
+    }
+
+    pub fn set_protocol(&mut self, protocol: u8) {
+        self.protocol = protocol;
+    }
+
+    /// Wraps out of band data, like pub/sub messages,
+    /// in a push frame on RESP3 and in an array on RESP2
+    pub fn push(&self, frames: Vec<Frame>) -> Frame {
+        match self.protocol {
+            3 => Frame::Push(frames),
+            _ => Frame::Array(frames),
+        }
+    }
+
+    /// A boolean frame on RESP3, on RESP2 the boolean is sent as 1 or 0
+    pub fn boolean(&self, value: bool) -> Frame {
+        match self.protocol {
+            3 => Frame::Boolean(value),
+            _ => Frame::Integer(value as i64),
+        }
+    }
+
+    pub fn is_subscribed(&self, channel: &str) -> bool {
+        self.subscriptions.contains_key(channel)
+    }
+
+    /// Adds the subscription with the task forwarding its messages.
+    /// Returns the number of subscriptions of the client.
+    pub fn subscribe(&mut self, channel: String, task: JoinHandle<()>) -> usize {
+        if let Some(prev) = self.subscriptions.insert(channel, task) {
+            prev.abort();
+        }
+
+        self.subscriptions.len()
+    }
+
+    /// Removes the subscription and stops forwarding its messages.
+    /// Returns the number of subscriptions of the client.
+    pub fn unsubscribe(&mut self, channel: &str) -> usize {
+        if let Some(task) = self.subscriptions.remove(channel) {
+            task.abort();
+        }
+
+        self.subscriptions.len()
+    }
+
+    /// Removes all the subscriptions, when the client disconnects
+    pub fn unsubscribe_all(&mut self) {
+        for (_, task) in self.subscriptions.drain() {
+            task.abort();
+        }
+    }
+
+    /// Restores the state of a new connection, like the RESET command
+    pub fn reset(&mut self) {
+        self.unsubscribe_all();
+        self.protocol = 2;
+        self.transaction = None;
+    }
+
+    pub fn subscriptions(&self) -> Vec<String> {
+        self.subscriptions.keys().cloned().collect()
+    }
+
+    pub fn subscription_count(&self) -> usize {
+        self.subscriptions.len()
+    }
+
+    /// A client with subscriptions is in subscribe mode,
+    /// until it unsubscribes from everything
+    pub fn is_subscribe_mode(&self) -> bool {
+        self.subscription_count() > 0
+    }
+
+    pub fn is_in_transaction(&self) -> bool {
+        self.transaction.is_some()
+    }
+
+    /// Starts a transaction, returns false if one is already started
+    pub fn multi(&mut self) -> bool {
+        match self.transaction {
+            Some(_) => false,
+            None => {
+                self.transaction = Some(Transaction::default());
+                true
+            }
+        }
+    }
+
+    /// Queues the command, to be executed by EXEC
+    pub fn queue(&mut self, frame: Frame) {
+        if let Some(transaction) = &mut self.transaction {
+            transaction.commands.push(frame);
+        }
+    }
+
+    /// Flags the transaction after a command was rejected while queuing
+    pub fn flag_queued_error(&mut self) {
+        if let Some(transaction) = &mut self.transaction {
+            transaction.queued_errors = true;
+        }
+    }
+
+    /// Ends the transaction, returning it to be executed or discarded
+    pub fn take_transaction(&mut self) -> Option<Transaction> {
+        self.transaction.take()
+    }
+
+    /// Whether the command can be executed in the current mode,
+    /// on RESP2 a subscribed client only receives messages and manages its subscriptions,
+    /// RESP3 clients can mix messages with regular replies
+    pub fn allows_command(&self, name: &str) -> bool {
+        self.protocol == 3
+            || !self.is_subscribe_mode()
+            || SUBSCRIBE_MODE_COMMANDS.contains(&name.to_uppercase().as_str())
+    }
+}